@@ -8,14 +8,17 @@ use sha3::Sha3_512;
 use strobe_rs::{SecParam, Strobe};
 
 use cyclist::keccyak::{
-    KeccakF1600, KeccakP1600_10, KeccakP1600_12, KeccakP1600_14, Keccyak128Hash, Keccyak128Keyed,
-    Keccyak256Hash, Keccyak256Keyed, KeccyakMaxHash, KeccyakMaxKeyed, KeccyakMinHash,
-    KeccyakMinKeyed,
+    KeccakF1600, KeccakP1600_10, KeccakP1600_12, KeccakP1600_14, KeccakP200, KeccakP400,
+    KeccakP800, Keccyak128Hash, Keccyak128Keyed, Keccyak200Hash, Keccyak200Keyed, Keccyak256Hash,
+    Keccyak256Keyed, Keccyak400Hash, Keccyak400Keyed, Keccyak800Hash, Keccyak800Keyed,
+    KeccyakMaxHash, KeccyakMaxKeyed, KeccyakMinHash, KeccyakMinKeyed,
 };
+use cyclist::outboard::encode_outboard;
 use cyclist::xoodyak::{Xoodoo, XoodyakHash, XoodyakKeyed};
 use cyclist::{Cyclist, Permutation};
 
 const INPUT: usize = 1024 * 1024; // 1MiB
+const PACKET: usize = 64;
 
 fn hash_benchmarks(c: &mut Criterion) {
     let mut g = c.benchmark_group("hash");
@@ -113,6 +116,27 @@ fn hash_benchmarks(c: &mut Criterion) {
             st.squeeze(32)
         })
     });
+    g.bench_with_input("Keccyak800", &[0u8; INPUT], |b, block| {
+        b.iter(|| {
+            let mut st = Keccyak800Hash::default();
+            st.absorb(block);
+            st.squeeze(32)
+        })
+    });
+    g.bench_with_input("Keccyak400", &[0u8; INPUT], |b, block| {
+        b.iter(|| {
+            let mut st = Keccyak400Hash::default();
+            st.absorb(block);
+            st.squeeze(32)
+        })
+    });
+    g.bench_with_input("Keccyak200", &[0u8; INPUT], |b, block| {
+        b.iter(|| {
+            let mut st = Keccyak200Hash::default();
+            st.absorb(block);
+            st.squeeze(32)
+        })
+    });
     g.finish();
 }
 
@@ -194,6 +218,74 @@ fn aead_benchmarks(c: &mut Criterion) {
             st.seal(block)
         })
     });
+    g.bench_with_input("Keccyak800", &[0u8; INPUT], |b, block| {
+        b.iter(|| {
+            let mut st = Keccyak800Keyed::new(&[0u8; 32], b"", b"");
+            st.seal(block)
+        })
+    });
+    g.bench_with_input("Keccyak400", &[0u8; INPUT], |b, block| {
+        b.iter(|| {
+            let mut st = Keccyak400Keyed::new(&[0u8; 32], b"", b"");
+            st.seal(block)
+        })
+    });
+    g.bench_with_input("Keccyak200", &[0u8; INPUT], |b, block| {
+        b.iter(|| {
+            let mut st = Keccyak200Keyed::new(&[0u8; 16], b"", b"");
+            st.seal(block)
+        })
+    });
+    g.finish();
+}
+
+fn alloc_benchmarks(c: &mut Criterion) {
+    let mut g = c.benchmark_group("alloc");
+    g.sample_size(1_000);
+    g.throughput(Throughput::Bytes(PACKET as u64));
+    g.bench_with_input("Xoodyak/seal", &[0u8; PACKET], |b, block| {
+        b.iter(|| {
+            let mut st = XoodyakKeyed::new(&[0u8; 32], b"", b"");
+            st.seal(block)
+        })
+    });
+    g.bench_with_input("Xoodyak/seal_into", &[0u8; PACKET], |b, block| {
+        let mut out = Vec::new();
+        b.iter(|| {
+            let mut st = XoodyakKeyed::new(&[0u8; 32], b"", b"");
+            st.seal_into(block, &mut out);
+        })
+    });
+    g.bench_with_input("Xoodyak/seal_mut", &[0u8; PACKET], |b, block| {
+        let mut out = [0u8; PACKET + 16];
+        b.iter(|| {
+            let mut st = XoodyakKeyed::new(&[0u8; 32], b"", b"");
+            out[..PACKET].copy_from_slice(block);
+            st.seal_mut(&mut out);
+        })
+    });
+    let ciphertext = XoodyakKeyed::new(&[0u8; 32], b"", b"").seal(&[0u8; PACKET]);
+    g.bench_with_input("Xoodyak/open", &ciphertext, |b, block| {
+        b.iter(|| {
+            let mut st = XoodyakKeyed::new(&[0u8; 32], b"", b"");
+            st.open(block)
+        })
+    });
+    g.bench_with_input("Xoodyak/open_into", &ciphertext, |b, block| {
+        let mut out = Vec::new();
+        b.iter(|| {
+            let mut st = XoodyakKeyed::new(&[0u8; 32], b"", b"");
+            st.open_into(block, &mut out)
+        })
+    });
+    g.bench_with_input("Xoodyak/open_mut", &ciphertext, |b, block| {
+        let mut out = [0u8; PACKET + 16];
+        b.iter(|| {
+            let mut st = XoodyakKeyed::new(&[0u8; 32], b"", b"");
+            out.copy_from_slice(block);
+            st.open_mut(&mut out)
+        })
+    });
     g.finish();
 }
 
@@ -229,6 +321,26 @@ fn permutation_benchmarks(c: &mut Criterion) {
         let mut state = KeccakP1600_10::default();
         b.iter(|| state.permute())
     });
+    g.throughput(Throughput::Bytes(4 * 200));
+    g.bench_function("Keccak-p1600-12x4", |b| {
+        let mut states: [KeccakP1600_12; 4] = Default::default();
+        b.iter(|| KeccakP1600_12::permute_many(&mut states))
+    });
+    g.throughput(Throughput::Bytes(100));
+    g.bench_function("Keccak-f800", |b| {
+        let mut state = KeccakP800::default();
+        b.iter(|| state.permute())
+    });
+    g.throughput(Throughput::Bytes(50));
+    g.bench_function("Keccak-f400", |b| {
+        let mut state = KeccakP400::default();
+        b.iter(|| state.permute())
+    });
+    g.throughput(Throughput::Bytes(25));
+    g.bench_function("Keccak-f200", |b| {
+        let mut state = KeccakP200::default();
+        b.iter(|| state.permute())
+    });
     g.throughput(Throughput::Bytes(48));
     g.bench_function("Xoodoo", |b| {
         let mut state = Xoodoo::default();
@@ -237,5 +349,29 @@ fn permutation_benchmarks(c: &mut Criterion) {
     g.finish();
 }
 
-criterion_group!(benches, hash_benchmarks, aead_benchmarks, permutation_benchmarks);
+fn bulk_benchmarks(c: &mut Criterion) {
+    let mut g = c.benchmark_group("bulk");
+    g.throughput(Throughput::Bytes(INPUT as u64));
+    g.bench_with_input("Keccyak128/encode_outboard", &[0u8; INPUT], |b, block| {
+        b.iter(|| encode_outboard::<Keccyak128Hash>(block))
+    });
+    g.bench_with_input("Keccyak128/bulk_seal_mut", &[0u8; INPUT], |b, block| {
+        let mut out = vec![0u8; INPUT + 16];
+        b.iter(|| {
+            let mut st = Keccyak128Keyed::new(&[0u8; 32], b"", b"");
+            out[..INPUT].copy_from_slice(block);
+            st.bulk_seal_mut(&mut out, 16 * 1024)
+        })
+    });
+    g.finish();
+}
+
+criterion_group!(
+    benches,
+    hash_benchmarks,
+    aead_benchmarks,
+    alloc_benchmarks,
+    permutation_benchmarks,
+    bulk_benchmarks
+);
 criterion_main!(benches);
@@ -0,0 +1,701 @@
+//! A C API for Xoodyak, built on the [cyclist] crate.
+//!
+//! This crate exposes a flat `extern "C"` API for hashing, sealing, and opening with Xoodyak, both
+//! as one-shot functions and as operations on opaque, heap-allocated contexts, as well as the
+//! caller-allocated streaming contexts of [`cyclist::abi`]. It builds as a shared library and a
+//! static library, and `cargo xtask headers` generates the matching C header,
+//! `ffi/include/cyclist.h`, with [cbindgen](https://github.com/mozilla/cbindgen).
+//!
+//! ```c
+//! uint8_t digest[32];
+//! cyclist_xoodyak_hash(message, message_len, digest, sizeof(digest));
+//!
+//! CyclistXoodyakKeyed *ctx = cyclist_xoodyak_keyed_new(key, key_len, NULL, 0, NULL, 0);
+//! cyclist_xoodyak_keyed_absorb(ctx, ad, ad_len);
+//! cyclist_xoodyak_keyed_seal(ctx, plaintext, plaintext_len, ciphertext, plaintext_len + 16);
+//! cyclist_xoodyak_keyed_free(ctx);
+//!
+//! CyclistContext mac = {0};
+//! cyclist_context_init_keyed(&mac, CYCLIST_ABI_VERSION, key, key_len, NULL, 0);
+//! cyclist_context_update(&mac, message, message_len);
+//! cyclist_context_finalize(&mac, tag, sizeof(tag));
+//! ```
+//!
+//! Every function which takes a pointer and a length accepts a null pointer if the length is zero.
+//! Functions which can fail return a [`Status`], the same status codes as [`cyclist::abi`]'s, so
+//! each failure has exactly one code across the whole API.
+//!
+//! The [cyclist] crate itself forbids `unsafe` code, so the raw pointer handling required by a C
+//! API lives in this separate crate.
+
+use core::mem::{align_of, size_of};
+
+use cyclist::abi::{self, Context, Status};
+use cyclist::xoodyak::{XoodyakHash, XoodyakKeyed};
+use cyclist::{Cyclist, Error};
+
+/// The length of a Xoodyak authentication tag in bytes.
+pub const CYCLIST_XOODYAK_TAG_LEN: usize = 16;
+
+/// An opaque Xoodyak hash context.
+pub struct CyclistXoodyakHash(XoodyakHash);
+
+/// An opaque Xoodyak keyed context.
+pub struct CyclistXoodyakKeyed(XoodyakKeyed);
+
+/// Hashes `in_len` bytes at `in`, writing an `out_len`-byte digest to `out`.
+///
+/// # Safety
+///
+/// `in` must be valid for reads of `in_len` bytes, and `out` must be valid for writes of `out_len`
+/// bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cyclist_xoodyak_hash(
+    r#in: *const u8,
+    in_len: usize,
+    out: *mut u8,
+    out_len: usize,
+) -> Status {
+    let (Some(input), Some(out)) = (slice(r#in, in_len), slice_mut(out, out_len)) else {
+        return Status::NullPointer;
+    };
+
+    let mut hash = XoodyakHash::default();
+    hash.absorb(input);
+    hash.squeeze_mut(out);
+    Status::Ok
+}
+
+/// Seals `in_len` bytes at `in` with the given key and nonce, authenticating the given associated
+/// data, and writes the ciphertext and tag to `out`, which must be `in_len + 16` bytes long.
+///
+/// # Safety
+///
+/// Each pointer must be valid for reads (or, for `out`, writes) of its corresponding length, and
+/// `in` may be equal to `out` but must not otherwise overlap it.
+#[no_mangle]
+pub unsafe extern "C" fn cyclist_xoodyak_seal(
+    key: *const u8,
+    key_len: usize,
+    nonce: *const u8,
+    nonce_len: usize,
+    ad: *const u8,
+    ad_len: usize,
+    r#in: *const u8,
+    in_len: usize,
+    out: *mut u8,
+    out_len: usize,
+) -> Status {
+    let mut ctx = match new_keyed(key, key_len, nonce, nonce_len) {
+        Ok(ctx) => ctx,
+        Err(status) => return status,
+    };
+    match cyclist_xoodyak_keyed_absorb(&mut ctx, ad, ad_len) {
+        Status::Ok => cyclist_xoodyak_keyed_seal(&mut ctx, r#in, in_len, out, out_len),
+        status => status,
+    }
+}
+
+/// Opens `in_len` bytes of ciphertext and tag at `in` with the given key and nonce, verifying the
+/// given associated data, and writes the plaintext to `out`, which must be `in_len - 16` bytes
+/// long. If the ciphertext can't be authenticated, `out` is zeroed.
+///
+/// # Safety
+///
+/// Each pointer must be valid for reads (or, for `out`, writes) of its corresponding length, and
+/// `in` may be equal to `out` but must not otherwise overlap it.
+#[no_mangle]
+pub unsafe extern "C" fn cyclist_xoodyak_open(
+    key: *const u8,
+    key_len: usize,
+    nonce: *const u8,
+    nonce_len: usize,
+    ad: *const u8,
+    ad_len: usize,
+    r#in: *const u8,
+    in_len: usize,
+    out: *mut u8,
+    out_len: usize,
+) -> Status {
+    let mut ctx = match new_keyed(key, key_len, nonce, nonce_len) {
+        Ok(ctx) => ctx,
+        Err(status) => return status,
+    };
+    match cyclist_xoodyak_keyed_absorb(&mut ctx, ad, ad_len) {
+        Status::Ok => cyclist_xoodyak_keyed_open(&mut ctx, r#in, in_len, out, out_len),
+        status => status,
+    }
+}
+
+/// Returns a new Xoodyak hash context, which must be freed with [`cyclist_xoodyak_hash_free`].
+#[no_mangle]
+pub extern "C" fn cyclist_xoodyak_hash_new() -> *mut CyclistXoodyakHash {
+    Box::into_raw(Box::new(CyclistXoodyakHash(XoodyakHash::default())))
+}
+
+/// Absorbs `in_len` bytes at `in` into the given hash context.
+///
+/// # Safety
+///
+/// `ctx` must have been returned by [`cyclist_xoodyak_hash_new`] and not yet freed, and `in` must
+/// be valid for reads of `in_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cyclist_xoodyak_hash_absorb(
+    ctx: *mut CyclistXoodyakHash,
+    r#in: *const u8,
+    in_len: usize,
+) -> Status {
+    let (Some(ctx), Some(input)) = (ctx.as_mut(), slice(r#in, in_len)) else {
+        return Status::NullPointer;
+    };
+    ctx.0.absorb(input);
+    Status::Ok
+}
+
+/// Squeezes `out_len` bytes from the given hash context into `out`.
+///
+/// # Safety
+///
+/// `ctx` must have been returned by [`cyclist_xoodyak_hash_new`] and not yet freed, and `out` must
+/// be valid for writes of `out_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cyclist_xoodyak_hash_squeeze(
+    ctx: *mut CyclistXoodyakHash,
+    out: *mut u8,
+    out_len: usize,
+) -> Status {
+    let (Some(ctx), Some(out)) = (ctx.as_mut(), slice_mut(out, out_len)) else {
+        return Status::NullPointer;
+    };
+    ctx.0.squeeze_mut(out);
+    Status::Ok
+}
+
+/// Frees the given hash context, wiping its state. Does nothing if `ctx` is null.
+///
+/// # Safety
+///
+/// `ctx` must be null or have been returned by [`cyclist_xoodyak_hash_new`] and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn cyclist_xoodyak_hash_free(ctx: *mut CyclistXoodyakHash) {
+    if !ctx.is_null() {
+        drop(Box::from_raw(ctx));
+    }
+}
+
+/// Returns a new Xoodyak keyed context with the given key, key ID (or nonce), and counter, or null
+/// if the key is empty or the key and key ID are too long. The context must be freed with
+/// [`cyclist_xoodyak_keyed_free`].
+///
+/// # Safety
+///
+/// Each pointer must be valid for reads of its corresponding length.
+#[no_mangle]
+pub unsafe extern "C" fn cyclist_xoodyak_keyed_new(
+    key: *const u8,
+    key_len: usize,
+    key_id: *const u8,
+    key_id_len: usize,
+    counter: *const u8,
+    counter_len: usize,
+) -> *mut CyclistXoodyakKeyed {
+    let (Some(key), Some(key_id), Some(counter)) =
+        (slice(key, key_len), slice(key_id, key_id_len), slice(counter, counter_len))
+    else {
+        return core::ptr::null_mut();
+    };
+    match XoodyakKeyed::try_new(key, key_id, counter) {
+        Ok(keyed) => Box::into_raw(Box::new(CyclistXoodyakKeyed(keyed))),
+        Err(_) => core::ptr::null_mut(),
+    }
+}
+
+/// Absorbs `in_len` bytes at `in` into the given keyed context.
+///
+/// # Safety
+///
+/// `ctx` must have been returned by [`cyclist_xoodyak_keyed_new`] and not yet freed, and `in` must
+/// be valid for reads of `in_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cyclist_xoodyak_keyed_absorb(
+    ctx: *mut CyclistXoodyakKeyed,
+    r#in: *const u8,
+    in_len: usize,
+) -> Status {
+    let (Some(ctx), Some(input)) = (ctx.as_mut(), slice(r#in, in_len)) else {
+        return Status::NullPointer;
+    };
+    status(ctx.0.try_absorb(input))
+}
+
+/// Squeezes `out_len` bytes from the given keyed context into `out`.
+///
+/// # Safety
+///
+/// `ctx` must have been returned by [`cyclist_xoodyak_keyed_new`] and not yet freed, and `out` must
+/// be valid for writes of `out_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cyclist_xoodyak_keyed_squeeze(
+    ctx: *mut CyclistXoodyakKeyed,
+    out: *mut u8,
+    out_len: usize,
+) -> Status {
+    let (Some(ctx), Some(out)) = (ctx.as_mut(), slice_mut(out, out_len)) else {
+        return Status::NullPointer;
+    };
+    status(ctx.0.try_squeeze_mut(out))
+}
+
+/// Seals `in_len` bytes at `in` with the given keyed context, writing the ciphertext and tag to
+/// `out`, which must be `in_len + 16` bytes long. If the context is poisoned, `out` holds a copy
+/// of the plaintext.
+///
+/// # Safety
+///
+/// `ctx` must have been returned by [`cyclist_xoodyak_keyed_new`] and not yet freed, `in` must be
+/// valid for reads of `in_len` bytes, `out` must be valid for writes of `out_len` bytes, and `in`
+/// may be equal to `out` but must not otherwise overlap it.
+#[no_mangle]
+pub unsafe extern "C" fn cyclist_xoodyak_keyed_seal(
+    ctx: *mut CyclistXoodyakKeyed,
+    r#in: *const u8,
+    in_len: usize,
+    out: *mut u8,
+    out_len: usize,
+) -> Status {
+    if in_len.checked_add(CYCLIST_XOODYAK_TAG_LEN) != Some(out_len) {
+        return Status::BufferLength;
+    }
+    let Some(ctx) = ctx.as_mut() else {
+        return Status::NullPointer;
+    };
+    if out.is_null() || !copy(r#in, out, in_len) {
+        return Status::NullPointer;
+    }
+
+    status(ctx.0.try_seal_mut(core::slice::from_raw_parts_mut(out, out_len)))
+}
+
+/// Opens `in_len` bytes of ciphertext and tag at `in` with the given keyed context, writing the
+/// plaintext to `out`, which must be `in_len - 16` bytes long. If the ciphertext can't be
+/// authenticated, `out` is zeroed.
+///
+/// # Safety
+///
+/// `ctx` must have been returned by [`cyclist_xoodyak_keyed_new`] and not yet freed, `in` must be
+/// valid for reads of `in_len` bytes, `out` must be valid for writes of `out_len` bytes, and `in`
+/// may be equal to `out` but must not otherwise overlap it.
+#[no_mangle]
+pub unsafe extern "C" fn cyclist_xoodyak_keyed_open(
+    ctx: *mut CyclistXoodyakKeyed,
+    r#in: *const u8,
+    in_len: usize,
+    out: *mut u8,
+    out_len: usize,
+) -> Status {
+    if out_len.checked_add(CYCLIST_XOODYAK_TAG_LEN) != Some(in_len) {
+        return Status::BufferLength;
+    }
+    let Some(ctx) = ctx.as_mut() else {
+        return Status::NullPointer;
+    };
+    if r#in.is_null() || !copy(r#in, out, out_len) {
+        return Status::NullPointer;
+    }
+
+    // Copy the tag before the ciphertext is decrypted, in case `in` and `out` are the same.
+    let mut tag = [0u8; CYCLIST_XOODYAK_TAG_LEN];
+    core::ptr::copy_nonoverlapping(r#in.add(out_len), tag.as_mut_ptr(), tag.len());
+
+    let Some(out) = slice_mut(out, out_len) else {
+        return Status::NullPointer;
+    };
+    status(ctx.0.open_detached(out, &tag.into()))
+}
+
+/// Returns the status for the result of a keyed operation.
+fn status(result: Result<(), Error>) -> Status {
+    result.map_or_else(Status::from, |()| Status::Ok)
+}
+
+/// Frees the given keyed context, wiping its state. Does nothing if `ctx` is null.
+///
+/// # Safety
+///
+/// `ctx` must be null or have been returned by [`cyclist_xoodyak_keyed_new`] and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn cyclist_xoodyak_keyed_free(ctx: *mut CyclistXoodyakKeyed) {
+    if !ctx.is_null() {
+        drop(Box::from_raw(ctx));
+    }
+}
+
+/// The version of the [`CyclistContext`] layout, which must be passed to
+/// [`cyclist_context_init_hash`] and [`cyclist_context_init_keyed`].
+pub const CYCLIST_ABI_VERSION: u32 = 1;
+
+/// The size of a [`CyclistContext`] in bytes.
+pub const CYCLIST_CONTEXT_SIZE: usize = 112;
+
+/// A caller-allocated streaming hash or MAC context (see [`abi::Context`]). A zeroed context is
+/// uninitialized.
+#[repr(C, align(4))]
+pub struct CyclistContext {
+    opaque: [u8; CYCLIST_CONTEXT_SIZE],
+}
+
+// The opaque context must have exactly the layout of the context it holds.
+const _: () = {
+    assert!(CYCLIST_ABI_VERSION == abi::ABI_VERSION);
+    assert!(CYCLIST_CONTEXT_SIZE == abi::CONTEXT_SIZE);
+    assert!(size_of::<CyclistContext>() == size_of::<Context>());
+    assert!(align_of::<CyclistContext>() == align_of::<Context>());
+};
+
+/// Initializes the given context for hashing.
+///
+/// # Safety
+///
+/// `ctx` must be null or valid for reads and writes of a [`CyclistContext`].
+#[no_mangle]
+pub unsafe extern "C" fn cyclist_context_init_hash(
+    ctx: *mut CyclistContext,
+    abi_version: u32,
+) -> Status {
+    let Some(ctx) = context(ctx) else {
+        return Status::NullPointer;
+    };
+    ctx.init_hash(abi_version)
+}
+
+/// Initializes the given context for calculating a MAC with the given key and key ID.
+///
+/// # Safety
+///
+/// `ctx` must be null or valid for reads and writes of a [`CyclistContext`], and each other pointer
+/// must be valid for reads of its corresponding length.
+#[no_mangle]
+pub unsafe extern "C" fn cyclist_context_init_keyed(
+    ctx: *mut CyclistContext,
+    abi_version: u32,
+    key: *const u8,
+    key_len: usize,
+    key_id: *const u8,
+    key_id_len: usize,
+) -> Status {
+    let (Some(ctx), Some(key), Some(key_id)) =
+        (context(ctx), slice(key, key_len), slice(key_id, key_id_len))
+    else {
+        return Status::NullPointer;
+    };
+    ctx.init_keyed(abi_version, key, key_id)
+}
+
+/// Absorbs `in_len` bytes at `in` into the given context.
+///
+/// # Safety
+///
+/// `ctx` must be null or valid for reads and writes of a [`CyclistContext`], and `in` must be valid
+/// for reads of `in_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cyclist_context_update(
+    ctx: *mut CyclistContext,
+    r#in: *const u8,
+    in_len: usize,
+) -> Status {
+    let (Some(ctx), Some(input)) = (context(ctx), slice(r#in, in_len)) else {
+        return Status::NullPointer;
+    };
+    ctx.update(input)
+}
+
+/// Writes an `out_len`-byte digest or tag to `out` and wipes the given context.
+///
+/// # Safety
+///
+/// `ctx` must be null or valid for reads and writes of a [`CyclistContext`], and `out` must be
+/// valid for writes of `out_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cyclist_context_finalize(
+    ctx: *mut CyclistContext,
+    out: *mut u8,
+    out_len: usize,
+) -> Status {
+    let (Some(ctx), Some(out)) = (context(ctx), slice_mut(out, out_len)) else {
+        return Status::NullPointer;
+    };
+    ctx.finalize(out)
+}
+
+/// Returns the context held by the given opaque context, or `None` if it's null.
+unsafe fn context<'a>(ctx: *mut CyclistContext) -> Option<&'a mut Context> {
+    // Every bit pattern is a valid context, and invalid contents are reported as corrupted.
+    ctx.cast::<Context>().as_mut()
+}
+
+/// Returns a keyed context with the given key and nonce, or the status for the error.
+unsafe fn new_keyed(
+    key: *const u8,
+    key_len: usize,
+    nonce: *const u8,
+    nonce_len: usize,
+) -> Result<CyclistXoodyakKeyed, Status> {
+    let (Some(key), Some(nonce)) = (slice(key, key_len), slice(nonce, nonce_len)) else {
+        return Err(Status::NullPointer);
+    };
+    XoodyakKeyed::try_new(key, nonce, b"").map(CyclistXoodyakKeyed).map_err(Status::from)
+}
+
+/// Returns the given pointer and length as a slice, or `None` if the pointer is null and the length
+/// isn't zero.
+unsafe fn slice<'a>(ptr: *const u8, len: usize) -> Option<&'a [u8]> {
+    match (ptr.is_null(), len) {
+        (_, 0) => Some(&[]),
+        (true, _) => None,
+        (false, _) => Some(core::slice::from_raw_parts(ptr, len)),
+    }
+}
+
+/// Returns the given pointer and length as a mutable slice, or `None` if the pointer is null and
+/// the length isn't zero.
+unsafe fn slice_mut<'a>(ptr: *mut u8, len: usize) -> Option<&'a mut [u8]> {
+    match (ptr.is_null(), len) {
+        (_, 0) => Some(&mut []),
+        (true, _) => None,
+        (false, _) => Some(core::slice::from_raw_parts_mut(ptr, len)),
+    }
+}
+
+/// Copies `len` bytes from `src` to `dst` unless they're the same, returning `false` if either is
+/// null and the length isn't zero.
+unsafe fn copy(src: *const u8, dst: *mut u8, len: usize) -> bool {
+    if len == 0 {
+        return true;
+    }
+    if src.is_null() || dst.is_null() {
+        return false;
+    }
+    if src != dst.cast_const() {
+        core::ptr::copy_nonoverlapping(src, dst, len);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use core::ptr;
+
+    use super::*;
+
+    const KEY: &[u8] = b"This is a secret key!";
+    const NONCE: &[u8] = b"This is a nonce!";
+    const AD: &[u8] = b"This is authenticated data!";
+    const PLAINTEXT: &[u8] = b"This is the plaintext!";
+
+    #[test]
+    fn hash() {
+        let mut digest = [0u8; 16];
+        let status = unsafe {
+            cyclist_xoodyak_hash(b"This is an input message!".as_ptr(), 25, digest.as_mut_ptr(), 16)
+        };
+        assert_eq!(Status::Ok, status);
+        assert_eq!([24, 79, 57, 49, 133, 57, 228, 222, 11, 95, 145, 57, 76, 16, 16, 122], digest);
+
+        let mut digest_p = [0u8; 16];
+        unsafe {
+            let ctx = cyclist_xoodyak_hash_new();
+            assert_eq!(
+                Status::Ok,
+                cyclist_xoodyak_hash_absorb(ctx, b"This is an input message!".as_ptr(), 25)
+            );
+            assert_eq!(Status::Ok, cyclist_xoodyak_hash_squeeze(ctx, digest_p.as_mut_ptr(), 16));
+            cyclist_xoodyak_hash_free(ctx);
+        }
+        assert_eq!(digest, digest_p);
+    }
+
+    #[test]
+    fn seal_and_open() {
+        let mut c = [0u8; 22 + CYCLIST_XOODYAK_TAG_LEN];
+        let status = unsafe {
+            cyclist_xoodyak_seal(
+                KEY.as_ptr(),
+                KEY.len(),
+                NONCE.as_ptr(),
+                NONCE.len(),
+                AD.as_ptr(),
+                AD.len(),
+                PLAINTEXT.as_ptr(),
+                PLAINTEXT.len(),
+                c.as_mut_ptr(),
+                c.len(),
+            )
+        };
+        assert_eq!(Status::Ok, status);
+
+        let mut aead = XoodyakKeyed::new(KEY, NONCE, b"");
+        aead.absorb(AD);
+        let mut c_p = [0u8; 22 + CYCLIST_XOODYAK_TAG_LEN];
+        c_p[..22].copy_from_slice(PLAINTEXT);
+        aead.seal_mut(&mut c_p);
+        assert_eq!(c_p, c);
+
+        let open = |c: &[u8], p: &mut [u8]| unsafe {
+            cyclist_xoodyak_open(
+                KEY.as_ptr(),
+                KEY.len(),
+                NONCE.as_ptr(),
+                NONCE.len(),
+                AD.as_ptr(),
+                AD.len(),
+                c.as_ptr(),
+                c.len(),
+                p.as_mut_ptr(),
+                p.len(),
+            )
+        };
+
+        let mut p = [0u8; 22];
+        assert_eq!(Status::Ok, open(&c, &mut p));
+        assert_eq!(PLAINTEXT, p);
+
+        c[0] ^= 1;
+        assert_eq!(Status::InvalidTag, open(&c, &mut p));
+        assert_eq!([0u8; 22], p);
+        assert_eq!(Status::BufferLength, open(&c, &mut p[..21]));
+
+        let status = unsafe {
+            cyclist_xoodyak_seal(
+                ptr::null(),
+                0,
+                ptr::null(),
+                0,
+                ptr::null(),
+                0,
+                ptr::null(),
+                0,
+                c.as_mut_ptr(),
+                CYCLIST_XOODYAK_TAG_LEN,
+            )
+        };
+        assert_eq!(Status::InvalidKey, status);
+    }
+
+    #[test]
+    fn in_place() {
+        let mut buf = [0u8; 22 + CYCLIST_XOODYAK_TAG_LEN];
+        buf[..22].copy_from_slice(PLAINTEXT);
+        unsafe {
+            let ctx =
+                cyclist_xoodyak_keyed_new(KEY.as_ptr(), KEY.len(), ptr::null(), 0, ptr::null(), 0);
+            assert!(!ctx.is_null());
+            let status =
+                cyclist_xoodyak_keyed_seal(ctx, buf.as_ptr(), 22, buf.as_mut_ptr(), buf.len());
+            assert_eq!(Status::Ok, status);
+            cyclist_xoodyak_keyed_free(ctx);
+
+            let ctx =
+                cyclist_xoodyak_keyed_new(KEY.as_ptr(), KEY.len(), ptr::null(), 0, ptr::null(), 0);
+            let status =
+                cyclist_xoodyak_keyed_open(ctx, buf.as_ptr(), buf.len(), buf.as_mut_ptr(), 22);
+            assert_eq!(Status::Ok, status);
+            cyclist_xoodyak_keyed_free(ctx);
+        }
+        assert_eq!(PLAINTEXT, &buf[..22]);
+    }
+
+    #[test]
+    fn poisoning() {
+        let mut buf = [0u8; CYCLIST_XOODYAK_TAG_LEN];
+        unsafe {
+            let ctx =
+                cyclist_xoodyak_keyed_new(KEY.as_ptr(), KEY.len(), ptr::null(), 0, ptr::null(), 0);
+            let status =
+                cyclist_xoodyak_keyed_open(ctx, buf.as_ptr(), buf.len(), buf.as_mut_ptr(), 0);
+            assert_eq!(Status::InvalidTag, status);
+            let status =
+                cyclist_xoodyak_keyed_open(ctx, buf.as_ptr(), buf.len(), buf.as_mut_ptr(), 0);
+            assert_eq!(Status::Poisoned, status);
+            assert_eq!(Status::Poisoned, cyclist_xoodyak_keyed_absorb(ctx, ptr::null(), 0));
+            assert_eq!(Status::Poisoned, cyclist_xoodyak_keyed_squeeze(ctx, buf.as_mut_ptr(), 1));
+            let status =
+                cyclist_xoodyak_keyed_seal(ctx, ptr::null(), 0, buf.as_mut_ptr(), buf.len());
+            assert_eq!(Status::Poisoned, status);
+            cyclist_xoodyak_keyed_free(ctx);
+        }
+    }
+
+    #[test]
+    fn contexts() {
+        let mut tag = [0u8; 16];
+        let mut ctx = CyclistContext { opaque: [0u8; CYCLIST_CONTEXT_SIZE] };
+        unsafe {
+            assert_eq!(Status::NotInitialized, cyclist_context_update(&mut ctx, ptr::null(), 0));
+            let status = cyclist_context_init_keyed(
+                &mut ctx,
+                CYCLIST_ABI_VERSION,
+                KEY.as_ptr(),
+                KEY.len(),
+                NONCE.as_ptr(),
+                NONCE.len(),
+            );
+            assert_eq!(Status::Ok, status);
+            for chunk in AD.chunks(5) {
+                assert_eq!(
+                    Status::Ok,
+                    cyclist_context_update(&mut ctx, chunk.as_ptr(), chunk.len())
+                );
+            }
+            assert_eq!(Status::Ok, cyclist_context_finalize(&mut ctx, tag.as_mut_ptr(), tag.len()));
+            assert_eq!(
+                Status::AlreadyFinalized,
+                cyclist_context_finalize(&mut ctx, tag.as_mut_ptr(), tag.len())
+            );
+
+            assert_eq!(Status::VersionMismatch, cyclist_context_init_hash(&mut ctx, 0));
+            assert_eq!(
+                Status::InvalidKey,
+                cyclist_context_init_keyed(
+                    &mut ctx,
+                    CYCLIST_ABI_VERSION,
+                    ptr::null(),
+                    0,
+                    ptr::null(),
+                    0
+                )
+            );
+            assert_eq!(
+                Status::NullPointer,
+                cyclist_context_init_hash(ptr::null_mut(), CYCLIST_ABI_VERSION)
+            );
+            assert_eq!(Status::NullPointer, cyclist_context_update(&mut ctx, ptr::null(), 1));
+        }
+
+        let mut mac = XoodyakKeyed::new(KEY, NONCE, b"");
+        mac.absorb(AD);
+        assert_eq!(mac.squeeze_array::<16>(), tag);
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        unsafe {
+            assert!(
+                cyclist_xoodyak_keyed_new(ptr::null(), 0, ptr::null(), 0, ptr::null(), 0).is_null()
+            );
+            assert!(
+                cyclist_xoodyak_keyed_new(ptr::null(), 1, ptr::null(), 0, ptr::null(), 0).is_null()
+            );
+            assert_eq!(
+                Status::NullPointer,
+                cyclist_xoodyak_hash(ptr::null(), 1, ptr::null_mut(), 0)
+            );
+            assert_eq!(Status::Ok, cyclist_xoodyak_hash(ptr::null(), 0, ptr::null_mut(), 0));
+            assert_eq!(
+                Status::NullPointer,
+                cyclist_xoodyak_hash_absorb(ptr::null_mut(), ptr::null(), 0)
+            );
+            cyclist_xoodyak_hash_free(ptr::null_mut());
+            cyclist_xoodyak_keyed_free(ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,10 @@
+#![no_main]
+
+use cyclist::conformance::{Keccyak128, Xoodyak};
+use cyclist::harness;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    harness::portable::<Xoodyak>(data);
+    harness::portable::<Keccyak128>(data);
+});
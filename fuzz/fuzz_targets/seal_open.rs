@@ -0,0 +1,11 @@
+#![no_main]
+
+use cyclist::harness;
+use cyclist::keccyak::Keccyak128Keyed;
+use cyclist::xoodyak::XoodyakKeyed;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    harness::seal_open::<XoodyakKeyed>(data);
+    harness::seal_open::<Keccyak128Keyed>(data);
+});
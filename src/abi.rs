@@ -0,0 +1,463 @@
+#![cfg(feature = "xoodyak")]
+
+//! ABI-stable streaming contexts for foreign-language bindings.
+//!
+//! A [`Context`] is a fixed-size, `#[repr(C)]` struct which a C caller can allocate (on the stack,
+//! even) without knowing anything about its contents, and which holds the exported state of a
+//! Xoodyak duplex plus a partial block of buffered input. Its lifecycle is an explicit state
+//! machine:
+//!
+//! 1. [`Context::init_hash`] or [`Context::init_keyed`] begins a hash or MAC computation.
+//! 2. [`Context::update`] absorbs input, and may be called any number of times.
+//! 3. [`Context::finalize`] squeezes the digest or tag and wipes the context.
+//!
+//! Unlike the rest of the crate, misuse never panics. Calling [`Context::update`] or
+//! [`Context::finalize`] before initialization or after finalization, passing a context whose
+//! contents have been corrupted, or initializing with a different ABI version all return a
+//! [`Status`] code and leave the context unmodified. A context may be re-initialized at any time.
+//!
+//! ```rust
+//! use cyclist::abi::{Context, Status, ABI_VERSION};
+//!
+//! let mut ctx = Context::new();
+//! assert_eq!(Status::NotInitialized, ctx.update(b"too soon"));
+//!
+//! assert_eq!(Status::Ok, ctx.init_hash(ABI_VERSION));
+//! assert_eq!(Status::Ok, ctx.update(b"This is "));
+//! assert_eq!(Status::Ok, ctx.update(b"the message!"));
+//!
+//! let mut digest = [0u8; 32];
+//! assert_eq!(Status::Ok, ctx.finalize(&mut digest));
+//! assert_eq!(Status::AlreadyFinalized, ctx.finalize(&mut digest));
+//! ```
+//!
+//! # Construction
+//!
+//! Input is buffered until a full absorb rate's worth is available, which is absorbed with
+//! [`Cyclist::absorb`] for the first block and [`Cyclist::absorb_more`] for the rest, so any split
+//! of a message across calls to [`Context::update`] produces the same result as a single
+//! [`Cyclist::absorb`] of the whole message. Finalization absorbs the buffered remainder and
+//! squeezes the output with [`Cyclist::squeeze_mut`].
+//!
+//! # ABI Stability
+//!
+//! The layout of [`Context`] is fixed for a given [`ABI_VERSION`]: it is [`CONTEXT_SIZE`] bytes
+//! with 4-byte alignment, and its first field is the ABI version with which it was initialized.
+//! Any change to the layout or to the meaning of its fields increments [`ABI_VERSION`], and
+//! initializing a context with any other version returns [`Status::VersionMismatch`]. Existing
+//! [`Status`] codes never change value.
+
+use core::fmt;
+use core::mem::{align_of, size_of};
+
+use crate::xoodyak::{XoodyakHash, XoodyakKeyed};
+use crate::{wipe, Cyclist, Error};
+
+/// The version of the [`Context`] layout.
+pub const ABI_VERSION: u32 = 1;
+
+/// The size of a [`Context`] in bytes.
+pub const CONTEXT_SIZE: usize = 112;
+
+/// The length of an exported Xoodyak state.
+const STATE_LEN: usize = 384 / 8 + 1;
+
+/// The absorb rate of [`XoodyakHash`].
+const HASH_RATE: usize = (384 - 256) / 8;
+
+/// The absorb rate of [`XoodyakKeyed`], and the size of the input buffer.
+const KEYED_RATE: usize = (384 - 32) / 8;
+
+const PHASE_UNINITIALIZED: u32 = 0;
+const PHASE_INITIALIZED: u32 = 1;
+const PHASE_UPDATING: u32 = 2;
+const PHASE_FINALIZED: u32 = 3;
+
+const MODE_HASH: u32 = 1;
+const MODE_KEYED: u32 = 2;
+
+// The layout of a context is part of the ABI.
+const _: () = {
+    assert!(size_of::<Context>() == CONTEXT_SIZE);
+    assert!(align_of::<Context>() == 4);
+    assert!(XoodyakHash::state_len() == STATE_LEN);
+    assert!(XoodyakKeyed::state_len() == STATE_LEN);
+};
+
+/// The result of an operation on a [`Context`], or of a call to a foreign-language binding.
+///
+/// The numeric values are part of the ABI and will not change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+#[non_exhaustive]
+pub enum Status {
+    /// The operation succeeded.
+    Ok = 0,
+
+    /// The context has not been initialized.
+    NotInitialized = -1,
+
+    /// The context has already been finalized.
+    AlreadyFinalized = -2,
+
+    /// The context was initialized with an unsupported ABI version.
+    VersionMismatch = -3,
+
+    /// The key was empty or too long.
+    InvalidKey = -4,
+
+    /// The context's contents are invalid.
+    Corrupted = -5,
+
+    /// A pointer was null but its length was not zero, or a context was null.
+    NullPointer = -6,
+
+    /// An output buffer was the wrong length.
+    BufferLength = -7,
+
+    /// The authentication tag did not match.
+    InvalidTag = -8,
+
+    /// The keyed duplex was poisoned by a previous failure to authenticate a ciphertext.
+    Poisoned = -9,
+}
+
+impl Status {
+    /// Returns the status's numeric code.
+    pub const fn code(self) -> i32 {
+        match self {
+            Status::Ok => 0,
+            Status::NotInitialized => -1,
+            Status::AlreadyFinalized => -2,
+            Status::VersionMismatch => -3,
+            Status::InvalidKey => -4,
+            Status::Corrupted => -5,
+            Status::NullPointer => -6,
+            Status::BufferLength => -7,
+            Status::InvalidTag => -8,
+            Status::Poisoned => -9,
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Status::Ok => "ok",
+            Status::NotInitialized => "context not initialized",
+            Status::AlreadyFinalized => "context already finalized",
+            Status::VersionMismatch => "unsupported abi version",
+            Status::InvalidKey => "invalid key",
+            Status::Corrupted => "corrupted context",
+            Status::NullPointer => "null pointer",
+            Status::BufferLength => "wrong buffer length",
+            Status::InvalidTag => "invalid authentication tag",
+            Status::Poisoned => "keyed duplex poisoned by a failed open",
+        })
+    }
+}
+
+impl From<Error> for Status {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::InvalidTag => Status::InvalidTag,
+            Error::EmptyKey | Error::KeyTooLong => Status::InvalidKey,
+            Error::BufferTooSmall => Status::BufferLength,
+            Error::Poisoned => Status::Poisoned,
+        }
+    }
+}
+
+/// A streaming hash or MAC context with a stable, C-compatible layout.
+#[derive(Clone)]
+#[repr(C)]
+pub struct Context {
+    abi_version: u32,
+    phase: u32,
+    mode: u32,
+    buf_len: u32,
+    state: [u8; STATE_LEN],
+    buf: [u8; KEYED_RATE],
+}
+
+impl Context {
+    /// Returns a new, uninitialized context. This is equivalent to a zeroed context.
+    pub const fn new() -> Context {
+        Context {
+            abi_version: 0,
+            phase: PHASE_UNINITIALIZED,
+            mode: 0,
+            buf_len: 0,
+            state: [0u8; STATE_LEN],
+            buf: [0u8; KEYED_RATE],
+        }
+    }
+
+    /// Initializes the context for hashing.
+    ///
+    /// `abi_version` must be the [`ABI_VERSION`] the caller was built against.
+    pub fn init_hash(&mut self, abi_version: u32) -> Status {
+        if abi_version != ABI_VERSION {
+            return Status::VersionMismatch;
+        }
+        self.store(abi_version, MODE_HASH, &Duplex::Hash(XoodyakHash::default()));
+        Status::Ok
+    }
+
+    /// Initializes the context for calculating a MAC with the given key and key ID.
+    ///
+    /// `abi_version` must be the [`ABI_VERSION`] the caller was built against.
+    pub fn init_keyed(&mut self, abi_version: u32, key: &[u8], key_id: &[u8]) -> Status {
+        if abi_version != ABI_VERSION {
+            return Status::VersionMismatch;
+        }
+        match XoodyakKeyed::try_new(key, key_id, b"") {
+            Ok(keyed) => {
+                self.store(abi_version, MODE_KEYED, &Duplex::Keyed(keyed));
+                Status::Ok
+            }
+            Err(e) => e.into(),
+        }
+    }
+
+    /// Absorbs the given slice.
+    pub fn update(&mut self, bin: &[u8]) -> Status {
+        let (mut duplex, buf_len) = match self.load() {
+            Ok(v) => v,
+            Err(status) => return status,
+        };
+        let rate = duplex.rate();
+
+        let mut buf_len = buf_len;
+        let mut bin = bin;
+        while !bin.is_empty() {
+            let n = bin.len().min(rate - buf_len);
+            self.buf[buf_len..buf_len + n].copy_from_slice(&bin[..n]);
+            buf_len += n;
+            bin = &bin[n..];
+
+            // Absorb full blocks as soon as they're available.
+            if buf_len == rate {
+                duplex.absorb_block(&self.buf[..rate], self.phase == PHASE_UPDATING);
+                self.phase = PHASE_UPDATING;
+                buf_len = 0;
+            }
+        }
+
+        duplex.export(&mut self.state);
+        self.buf_len = u32::try_from(buf_len).expect("unexpected overflow");
+        Status::Ok
+    }
+
+    /// Absorbs any buffered input, fills `out` with the digest or tag, and wipes the context.
+    pub fn finalize(&mut self, out: &mut [u8]) -> Status {
+        let (mut duplex, buf_len) = match self.load() {
+            Ok(v) => v,
+            Err(status) => return status,
+        };
+
+        duplex.absorb_block(&self.buf[..buf_len], self.phase == PHASE_UPDATING);
+        duplex.squeeze_mut(out);
+
+        wipe(&mut self.state);
+        wipe(&mut self.buf);
+        self.buf_len = 0;
+        self.phase = PHASE_FINALIZED;
+        Status::Ok
+    }
+
+    /// Validates the context and returns its duplex and the length of its buffered input.
+    fn load(&self) -> Result<(Duplex, usize), Status> {
+        if self.abi_version != ABI_VERSION {
+            return Err(match self.phase {
+                PHASE_UNINITIALIZED => Status::NotInitialized,
+                _ => Status::VersionMismatch,
+            });
+        }
+
+        match self.phase {
+            PHASE_INITIALIZED | PHASE_UPDATING => {}
+            PHASE_UNINITIALIZED => return Err(Status::NotInitialized),
+            PHASE_FINALIZED => return Err(Status::AlreadyFinalized),
+            _ => return Err(Status::Corrupted),
+        }
+
+        let duplex = match self.mode {
+            MODE_HASH => XoodyakHash::from_bytes(&self.state).map(Duplex::Hash),
+            MODE_KEYED => XoodyakKeyed::from_bytes(&self.state).map(Duplex::Keyed),
+            _ => None,
+        }
+        .ok_or(Status::Corrupted)?;
+
+        match usize::try_from(self.buf_len) {
+            Ok(buf_len) if buf_len < duplex.rate() => Ok((duplex, buf_len)),
+            _ => Err(Status::Corrupted),
+        }
+    }
+
+    /// Resets the context to a newly-initialized state with the given duplex.
+    fn store(&mut self, abi_version: u32, mode: u32, duplex: &Duplex) {
+        self.abi_version = abi_version;
+        self.mode = mode;
+        self.phase = PHASE_INITIALIZED;
+        self.buf_len = 0;
+        wipe(&mut self.buf);
+        duplex.export(&mut self.state);
+    }
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Context::new()
+    }
+}
+
+impl fmt::Debug for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Context")
+            .field("abi_version", &self.abi_version)
+            .field("phase", &self.phase)
+            .field("mode", &self.mode)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        wipe(&mut self.state);
+        wipe(&mut self.buf);
+    }
+}
+
+/// The duplex held by a context.
+enum Duplex {
+    Hash(XoodyakHash),
+    Keyed(XoodyakKeyed),
+}
+
+impl Duplex {
+    const fn rate(&self) -> usize {
+        match self {
+            Duplex::Hash(_) => HASH_RATE,
+            Duplex::Keyed(_) => KEYED_RATE,
+        }
+    }
+
+    /// Absorbs a block, extending the previous absorb if there was one.
+    fn absorb_block(&mut self, block: &[u8], more: bool) {
+        match (self, more) {
+            (Duplex::Hash(h), false) => h.absorb(block),
+            (Duplex::Hash(h), true) => h.absorb_more(block),
+            (Duplex::Keyed(k), false) => k.absorb(block),
+            (Duplex::Keyed(k), true) => k.absorb_more(block),
+        }
+    }
+
+    fn squeeze_mut(&mut self, out: &mut [u8]) {
+        match self {
+            Duplex::Hash(h) => h.squeeze_mut(out),
+            Duplex::Keyed(k) => k.squeeze_mut(out),
+        }
+    }
+
+    fn export(&self, out: &mut [u8]) {
+        match self {
+            Duplex::Hash(h) => h.to_bytes_mut(out),
+            Duplex::Keyed(k) => k.to_bytes_mut(out),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(message: &[u8]) -> [u8; 32] {
+        let mut h = XoodyakHash::default();
+        h.absorb(message);
+        let mut digest = [0u8; 32];
+        h.squeeze_mut(&mut digest);
+        digest
+    }
+
+    #[test]
+    fn split_updates() {
+        let message = [0xCAu8; 200];
+        for len in [0, 1, 15, 16, 17, 32, 44, 45, 88, 200] {
+            let expected = hash(&message[..len]);
+            for split in [1, 3, 16, 44, 100] {
+                let mut ctx = Context::new();
+                assert_eq!(Status::Ok, ctx.init_hash(ABI_VERSION));
+                for chunk in message[..len].chunks(split) {
+                    assert_eq!(Status::Ok, ctx.update(chunk));
+                }
+                let mut digest = [0u8; 32];
+                assert_eq!(Status::Ok, ctx.finalize(&mut digest));
+                assert_eq!(expected, digest, "len={} split={}", len, split);
+            }
+        }
+    }
+
+    #[test]
+    fn keyed() {
+        let message = [0xCAu8; 100];
+        let mut keyed = XoodyakKeyed::new(b"ok then", b"key id", b"");
+        keyed.absorb(&message);
+        let mut expected = [0u8; 16];
+        keyed.squeeze_mut(&mut expected);
+
+        let mut ctx = Context::new();
+        assert_eq!(Status::Ok, ctx.init_keyed(ABI_VERSION, b"ok then", b"key id"));
+        for chunk in message.chunks(7) {
+            assert_eq!(Status::Ok, ctx.update(chunk));
+        }
+        let mut tag = [0u8; 16];
+        assert_eq!(Status::Ok, ctx.finalize(&mut tag));
+        assert_eq!(expected, tag);
+
+        assert_eq!(Status::InvalidKey, ctx.init_keyed(ABI_VERSION, b"", b""));
+        assert_eq!(Status::InvalidKey, ctx.init_keyed(ABI_VERSION, &[0u8; 44], b""));
+    }
+
+    #[test]
+    fn misuse() {
+        let mut out = [0u8; 16];
+        let mut ctx = Context::default();
+        assert_eq!(Status::NotInitialized, ctx.update(b"ok"));
+        assert_eq!(Status::NotInitialized, ctx.finalize(&mut out));
+        assert_eq!(Status::VersionMismatch, ctx.init_hash(ABI_VERSION + 1));
+        assert_eq!(Status::NotInitialized, ctx.update(b"ok"));
+
+        assert_eq!(Status::Ok, ctx.init_hash(ABI_VERSION));
+        assert_eq!(Status::Ok, ctx.finalize(&mut out));
+        assert_eq!(Status::AlreadyFinalized, ctx.update(b"ok"));
+        assert_eq!(Status::AlreadyFinalized, ctx.finalize(&mut out));
+
+        // Finalized contexts can be re-initialized.
+        assert_eq!(Status::Ok, ctx.init_hash(ABI_VERSION));
+        assert_eq!(Status::Ok, ctx.update(b"ok"));
+
+        let mut bad = ctx.clone();
+        bad.phase = 42;
+        assert_eq!(Status::Corrupted, bad.update(b"ok"));
+        let mut bad = ctx.clone();
+        bad.mode = 42;
+        assert_eq!(Status::Corrupted, bad.update(b"ok"));
+        let mut bad = ctx.clone();
+        bad.buf_len = 16;
+        assert_eq!(Status::Corrupted, bad.update(b"ok"));
+        let mut bad = ctx.clone();
+        bad.abi_version = 2;
+        assert_eq!(Status::VersionMismatch, bad.finalize(&mut out));
+    }
+
+    #[test]
+    fn status_codes() {
+        assert_eq!(0, Status::Ok.code());
+        assert_eq!(-5, Status::Corrupted.code());
+        assert_eq!(-9, Status::Poisoned.code());
+        assert_eq!("context already finalized", Status::AlreadyFinalized.to_string());
+        assert_eq!(Status::InvalidKey, Error::KeyTooLong.into());
+        assert_eq!(Status::BufferLength, Error::BufferTooSmall.into());
+    }
+}
@@ -0,0 +1,132 @@
+//! Canonical encodings of common containers for absorption.
+//!
+//! Protocols often need to absorb optional fields, fixed-size arrays, or lists of byte strings,
+//! and each ad hoc encoding of them is another thing for a reviewer to check for ambiguity. The
+//! [`Absorb`] trait absorbs these containers with a single canonical encoding:
+//!
+//! ```rust
+//! use cyclist::absorb::Absorb;
+//! use cyclist::xoodyak::XoodyakHash;
+//! use cyclist::Cyclist;
+//!
+//! let recipients: &[&[u8]] = &[b"alice", b"bob"];
+//! let reply_to: Option<&[u8]> = None;
+//!
+//! let mut hash = XoodyakHash::default();
+//! recipients.absorb_into(&mut hash);
+//! reply_to.absorb_into(&mut hash);
+//! let digest = hash.squeeze(32);
+//! ```
+//!
+//! # Construction
+//!
+//! * A byte string or byte array is absorbed with a single absorb operation, which Cyclist frames
+//!   unambiguously.
+//! * A slice or array of values is absorbed as its length, as a little-endian 64-bit integer,
+//!   followed by each of its values.
+//! * An absent value is absorbed as the byte `0x00`, and a present value as the byte `0x01`
+//!   followed by the value.
+
+use crate::Cyclist;
+
+/// A value with a canonical encoding as a sequence of absorb operations.
+pub trait Absorb {
+    /// Absorbs the value into the given Cyclist object.
+    fn absorb_into<C: Cyclist>(&self, cyclist: &mut C);
+}
+
+impl Absorb for [u8] {
+    fn absorb_into<C: Cyclist>(&self, cyclist: &mut C) {
+        cyclist.absorb(self);
+    }
+}
+
+impl<const N: usize> Absorb for [u8; N] {
+    fn absorb_into<C: Cyclist>(&self, cyclist: &mut C) {
+        cyclist.absorb(self);
+    }
+}
+
+impl<T: Absorb> Absorb for [T] {
+    fn absorb_into<C: Cyclist>(&self, cyclist: &mut C) {
+        let len = u64::try_from(self.len()).expect("unexpected overflow");
+        cyclist.absorb(&len.to_le_bytes());
+        for value in self {
+            value.absorb_into(cyclist);
+        }
+    }
+}
+
+impl<T: Absorb, const N: usize> Absorb for [T; N] {
+    fn absorb_into<C: Cyclist>(&self, cyclist: &mut C) {
+        self.as_slice().absorb_into(cyclist);
+    }
+}
+
+impl<T: Absorb> Absorb for Option<T> {
+    fn absorb_into<C: Cyclist>(&self, cyclist: &mut C) {
+        match self {
+            None => cyclist.absorb(&[0x00]),
+            Some(value) => {
+                cyclist.absorb(&[0x01]);
+                value.absorb_into(cyclist);
+            }
+        }
+    }
+}
+
+impl<T: Absorb + ?Sized> Absorb for &T {
+    fn absorb_into<C: Cyclist>(&self, cyclist: &mut C) {
+        (**self).absorb_into(cyclist);
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakHash;
+
+    use super::*;
+
+    fn digest<T: Absorb + ?Sized>(value: &T) -> Vec<u8> {
+        let mut hash = XoodyakHash::default();
+        value.absorb_into(&mut hash);
+        hash.squeeze(16)
+    }
+
+    #[test]
+    fn encodings() {
+        let mut hash = XoodyakHash::default();
+        hash.absorb(&2u64.to_le_bytes());
+        hash.absorb(b"a");
+        hash.absorb(b"bc");
+        assert_eq!(hash.squeeze(16), digest::<[&[u8]]>(&[b"a", b"bc"]));
+        assert_eq!(digest(&[*b"ab", *b"cd"]), digest::<[&[u8]]>(&[b"ab", b"cd"]));
+        assert_eq!(digest(b"abc"), digest(b"abc".as_slice()));
+
+        let mut hash = XoodyakHash::default();
+        hash.absorb(&[0x01]);
+        hash.absorb(b"abc");
+        assert_eq!(hash.squeeze(16), digest(&Some(b"abc".as_slice())));
+    }
+
+    #[test]
+    fn unambiguous() {
+        let digests = [
+            digest::<[&[u8]]>(&[b"ab", b"c"]),
+            digest::<[&[u8]]>(&[b"a", b"bc"]),
+            digest::<[&[u8]]>(&[b"abc"]),
+            digest::<[&[u8]]>(&[b"abc", b""]),
+            digest::<[&[u8]]>(&[]),
+            digest(b"abc"),
+            digest(b""),
+            digest(&None::<&[u8]>),
+            digest(&Some(b"".as_slice())),
+            digest(&Some(None::<&[u8]>)),
+        ];
+        for (i, a) in digests.iter().enumerate() {
+            for b in &digests[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Incremental absorption of arbitrarily-sized chunks.
+//!
+//! [`Cyclist::absorb_more`](crate::Cyclist::absorb_more) only extends a previous absorb operation correctly if the previous
+//! operation absorbed a whole number of blocks. An [`Absorber`] buffers partial blocks so that
+//! callers can feed it chunks of any size, and everything passed to [`Absorber::update`] is
+//! absorbed exactly as if it had been passed to a single [`Cyclist::absorb`](crate::Cyclist::absorb) call:
+//!
+//! ```rust
+//! use cyclist::absorber::Absorber;
+//! use cyclist::xoodyak::XoodyakHash;
+//! use cyclist::Cyclist;
+//!
+//! let mut absorber = Absorber::new(XoodyakHash::default());
+//! absorber.update(b"This is ");
+//! absorber.update(b"an input message!");
+//! let digest = absorber.finish().squeeze(16);
+//!
+//! let mut hash = XoodyakHash::default();
+//! hash.absorb(b"This is an input message!");
+//! assert_eq!(digest, hash.squeeze(16));
+//! ```
+//!
+//! Unlike [`AbsorbWriter`](crate::io::AbsorbWriter), an [`Absorber`] doesn't allocate and doesn't
+//! require the `std` feature.
+
+use crate::Rates;
+
+/// The largest absorb rate of any Cyclist object, in bytes.
+const MAX_RATE: usize = 200;
+
+/// A buffering wrapper which absorbs a sequence of arbitrarily-sized chunks as a single absorb
+/// operation.
+#[derive(Clone, Debug)]
+pub struct Absorber<C> {
+    cyclist: C,
+    buf: [u8; MAX_RATE],
+    buf_len: usize,
+    absorbed: bool,
+}
+
+impl<C> Absorber<C>
+where
+    C: Rates,
+{
+    /// The Cyclist object's absorb rate, which is checked at compile time.
+    const RATE: usize = {
+        assert!(C::ABSORB_RATE > 0 && C::ABSORB_RATE <= MAX_RATE, "unsupported absorb rate");
+        C::ABSORB_RATE
+    };
+
+    /// Creates a new [`Absorber`] which absorbs into the given Cyclist object.
+    ///
+    /// Absorbers for Cyclist objects with absorb rates larger than any of this crate's Cyclist
+    /// states fail to compile.
+    pub const fn new(cyclist: C) -> Self {
+        let _ = Self::RATE;
+        Absorber { cyclist, buf: [0u8; MAX_RATE], buf_len: 0, absorbed: false }
+    }
+
+    /// Absorbs the given chunk as a continuation of all previous chunks.
+    pub fn update(&mut self, mut bin: &[u8]) {
+        while !bin.is_empty() {
+            // Only absorb a full block once more input arrives, leaving the final block for
+            // finishing.
+            if self.buf_len == Self::RATE {
+                Self::absorb(&mut self.cyclist, &mut self.absorbed, &self.buf[..Self::RATE]);
+                self.buf_len = 0;
+            }
+
+            // Absorb whole blocks directly, as long as more input follows them.
+            if self.buf_len == 0 && bin.len() > Self::RATE {
+                let (blocks, rest) = bin.split_at((bin.len() - 1) / Self::RATE * Self::RATE);
+                Self::absorb(&mut self.cyclist, &mut self.absorbed, blocks);
+                bin = rest;
+                continue;
+            }
+
+            let n = (Self::RATE - self.buf_len).min(bin.len());
+            self.buf[self.buf_len..self.buf_len + n].copy_from_slice(&bin[..n]);
+            self.buf_len += n;
+            bin = &bin[n..];
+        }
+    }
+
+    /// Absorbs any buffered data and returns the Cyclist object. Everything passed to
+    /// [`Absorber::update`] will have been absorbed exactly as if it had been passed to a single
+    /// [`Cyclist::absorb`](crate::Cyclist::absorb) call.
+    pub fn finish(mut self) -> C {
+        Self::absorb(&mut self.cyclist, &mut self.absorbed, &self.buf[..self.buf_len]);
+        self.cyclist
+    }
+
+    /// Starts or extends the absorb operation.
+    fn absorb(cyclist: &mut C, absorbed: &mut bool, bin: &[u8]) {
+        if *absorbed {
+            cyclist.absorb_more(bin);
+        } else {
+            cyclist.absorb(bin);
+            *absorbed = true;
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::{XoodyakHash, XoodyakKeyed};
+    use crate::Cyclist;
+
+    use super::*;
+
+    fn check<C: Rates + Clone>(cyclist: C) {
+        for len in [0, 1, 15, 16, 17, 43, 44, 45, 100, 1000] {
+            let input = (0..len).map(|i| u8::try_from(i % 251).expect("ok")).collect::<Vec<_>>();
+
+            let mut expected = cyclist.clone();
+            expected.absorb(&input);
+            let expected = expected.squeeze(32);
+
+            for chunk_len in [1, 7, 16, 44, 50, 333] {
+                let mut absorber = Absorber::new(cyclist.clone());
+                for chunk in input.chunks(chunk_len) {
+                    absorber.update(chunk);
+                }
+                assert_eq!(
+                    expected,
+                    absorber.finish().squeeze(32),
+                    "len={}, chunk_len={}",
+                    len,
+                    chunk_len
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn equivalent_to_single_absorb() {
+        check(XoodyakHash::default());
+        check(XoodyakKeyed::new(b"key", b"", b""));
+    }
+
+    #[test]
+    #[cfg(feature = "keccyak")]
+    fn largest_rate() {
+        check(crate::keccyak::KeccyakMaxKeyed::new(b"key", b"", b""));
+    }
+
+    #[test]
+    fn empty_updates() {
+        let mut absorber = Absorber::new(XoodyakHash::default());
+        absorber.update(b"");
+        absorber.update(b"ok");
+        absorber.update(b"");
+
+        let mut expected = XoodyakHash::default();
+        expected.absorb(b"ok");
+        assert_eq!(expected.squeeze(16), absorber.finish().squeeze(16));
+    }
+}
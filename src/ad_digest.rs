@@ -0,0 +1,121 @@
+//! Pre-hashed associated data.
+//!
+//! Absorbing associated data costs a permutation per block, which adds up when the same large
+//! associated data (e.g. a header shared by millions of records) is absorbed for every message. An
+//! [`AdDigest`] hashes the associated data once, and can then be absorbed in its place for a
+//! constant per-message cost:
+//!
+//! ```rust
+//! use cyclist::ad_digest::AdDigest;
+//! use cyclist::xoodyak::{XoodyakHash, XoodyakKeyed};
+//! use cyclist::Cyclist;
+//!
+//! let header = [0xCAu8; 4096];
+//! let digest = AdDigest::<32>::new::<XoodyakHash>(&header);
+//!
+//! let mut sealer = XoodyakKeyed::new(b"This is a secret key!", b"This is a nonce!", b"");
+//! digest.absorb_into(&mut sealer);
+//! let ciphertext = sealer.seal(b"This is the plaintext!");
+//!
+//! let mut opener = XoodyakKeyed::new(b"This is a secret key!", b"This is a nonce!", b"");
+//! digest.absorb_into(&mut opener);
+//! assert_eq!(Ok(b"This is the plaintext!".to_vec()), opener.open(&ciphertext));
+//! ```
+//!
+//! Absorbing a digest is domain-separated from absorbing the associated data itself, so both sides
+//! must agree on whether associated data is pre-hashed. The security of the AEAD then also depends
+//! on the collision resistance of the hash, so `N` should be at least twice the desired security
+//! level in bytes.
+//!
+//! # Construction
+//!
+//! The digest is squeezed from a hash which has absorbed a domain separation label and the
+//! associated data. To absorb the digest, a keyed duplex absorbs a second domain separation label
+//! and the digest.
+
+use crate::Cyclist;
+
+/// The domain separation label absorbed by the hash before the associated data.
+const DIGEST_LABEL: &[u8] = b"cyclist.ad-digest";
+
+/// The domain separation label absorbed by a keyed duplex before a digest.
+const ABSORB_LABEL: &[u8] = b"cyclist.ad-digest.absorb";
+
+/// An `N`-byte digest of associated data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AdDigest<const N: usize>([u8; N]);
+
+impl<const N: usize> AdDigest<N> {
+    /// Hashes the given associated data with the given hash.
+    pub fn new<H: Cyclist + Default>(ad: &[u8]) -> Self {
+        let mut hash = H::default();
+        hash.absorb(DIGEST_LABEL);
+        hash.absorb(ad);
+        let mut digest = [0u8; N];
+        hash.squeeze_mut(&mut digest);
+        AdDigest(digest)
+    }
+
+    /// Absorbs the digest into the given Cyclist object in place of the associated data.
+    pub fn absorb_into<C: Cyclist>(&self, cyclist: &mut C) {
+        cyclist.absorb(ABSORB_LABEL);
+        cyclist.absorb(&self.0);
+    }
+
+    /// Returns the digest's bytes.
+    pub const fn to_bytes(&self) -> [u8; N] {
+        self.0
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for AdDigest<N> {
+    fn from(bytes: [u8; N]) -> Self {
+        AdDigest(bytes)
+    }
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::{XoodyakHash, XoodyakKeyed};
+
+    use super::*;
+
+    fn seal_with(ad: impl FnOnce(&mut XoodyakKeyed)) -> Vec<u8> {
+        let mut keyed = XoodyakKeyed::new(b"key", b"nonce", b"");
+        ad(&mut keyed);
+        keyed.seal(b"message")
+    }
+
+    #[test]
+    fn equivalence() {
+        let ad = [0xCAu8; 1000];
+        let digest = AdDigest::<32>::new::<XoodyakHash>(&ad);
+
+        // A precomputed digest is equivalent to hashing the raw AD for each message.
+        let expected = seal_with(|k| {
+            let mut hash = XoodyakHash::default();
+            hash.absorb(b"cyclist.ad-digest");
+            hash.absorb(&ad);
+            k.absorb(b"cyclist.ad-digest.absorb");
+            k.absorb(&hash.squeeze(32));
+        });
+        assert_eq!(expected, seal_with(|k| digest.absorb_into(k)));
+        assert_eq!(expected, seal_with(|k| AdDigest::from(digest.to_bytes()).absorb_into(k)));
+
+        // But it's domain-separated from absorbing either the raw AD or the digest itself.
+        assert_ne!(expected, seal_with(|k| k.absorb(&ad)));
+        assert_ne!(expected, seal_with(|k| k.absorb(&digest.to_bytes())));
+    }
+
+    #[test]
+    fn distinct_ads() {
+        let a = AdDigest::<32>::new::<XoodyakHash>(b"header one");
+        let b = AdDigest::<32>::new::<XoodyakHash>(b"header two");
+        assert_ne!(a, b);
+
+        let c = seal_with(|k| a.absorb_into(k));
+        let mut opener = XoodyakKeyed::new(b"key", b"nonce", b"");
+        b.absorb_into(&mut opener);
+        assert!(opener.open(&c).is_err());
+    }
+}
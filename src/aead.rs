@@ -0,0 +1,327 @@
+#![cfg(feature = "aead")]
+
+//! Implementations of the [`aead`](::aead) traits for keyed schemes.
+//!
+//! A [`CyclistAead`] holds a [`KEY_LEN`]-byte key, and encrypts each message with a new keyed
+//! duplex, which absorbs the [`NONCE_LEN`]-byte nonce and the associated data before sealing the
+//! message. This allows the keyed schemes to be used with frameworks which are generic over the
+//! `aead` traits:
+//!
+//! ```rust
+//! use aead::{Aead, KeyInit, Payload};
+//! use cyclist::xoodyak::XoodyakAead;
+//!
+//! let aead = XoodyakAead::new(&[0u8; 32].into());
+//! let nonce = [1u8; 16].into();
+//! let ciphertext = aead
+//!     .encrypt(&nonce, Payload { msg: b"this is a message", aad: b"this is data" })
+//!     .expect("should encrypt");
+//! let plaintext = aead
+//!     .decrypt(&nonce, Payload { msg: &ciphertext, aad: b"this is data" })
+//!     .expect("should decrypt");
+//! assert_eq!(b"this is a message", plaintext.as_slice());
+//! ```
+//!
+//! [`CyclistAead`] implements [`AeadInPlace`], and so the [`Aead`](::aead::Aead) trait (with the
+//! `std` feature) as well. [`AeadInPlace::encrypt_in_place_detached`] and
+//! [`AeadInPlace::decrypt_in_place_detached`] operate on caller-owned buffers without allocating,
+//! and are available without the `std` feature.
+//!
+//! As with AES-GCM and ChaCha20Poly1305, a nonce must never be used to encrypt more than one
+//! message with the same key.
+//!
+//! Only schemes with an absorb rate greater than [`KEY_LEN`] bytes can absorb the key, so there is
+//! no AEAD alias for schemes with smaller states, like
+//! [`Keccyak200Keyed`](crate::keccyak::Keccyak200Keyed), and a [`CyclistAead`] with such a scheme's
+//! parameters fails to compile.
+
+use core::fmt;
+
+use ::aead::consts::{U0, U16, U32};
+use ::aead::generic_array::ArrayLength;
+use ::aead::{AeadCore, AeadInPlace, Key, KeyInit, KeySizeUser, Nonce, Tag};
+use constant_time_eq::constant_time_eq;
+use typenum::{Const, ToUInt, U};
+
+use crate::{wipe, Cyclist, CyclistKeyed, Permutation};
+
+/// The length of a [`CyclistAead`] key, in bytes.
+pub const KEY_LEN: usize = 32;
+
+/// The length of a [`CyclistAead`] nonce, in bytes.
+pub const NONCE_LEN: usize = 16;
+
+/// A keyed Cyclist scheme which implements the [`aead`](::aead) traits. Parameterized with the
+/// permutation algorithm, the permutation width, the absorb rate, the squeeze rate, the ratchet
+/// rate, and the length of authentication tags.
+#[derive(Clone)]
+pub struct CyclistAead<
+    P,
+    const WIDTH: usize,
+    const ABSORB_RATE: usize,
+    const SQUEEZE_RATE: usize,
+    const RATCHET_RATE: usize,
+    const TAG_LEN: usize,
+> where
+    P: Permutation<WIDTH>,
+{
+    key: [u8; KEY_LEN],
+    _permutation: core::marker::PhantomData<P>,
+}
+
+impl<
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > CyclistAead<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+{
+    /// Returns a keyed duplex which has absorbed the given nonce and associated data.
+    fn keyed(
+        &self,
+        nonce: &[u8],
+        ad: &[u8],
+    ) -> CyclistKeyed<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN> {
+        let mut keyed = CyclistKeyed::new(&self.key, b"", b"");
+        keyed.absorb(nonce);
+        keyed.absorb(ad);
+        keyed
+    }
+}
+
+impl<
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > KeySizeUser for CyclistAead<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+{
+    type KeySize = U32;
+}
+
+impl<
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > KeyInit for CyclistAead<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+{
+    fn new(key: &Key<Self>) -> Self {
+        const { assert!(KEY_LEN < ABSORB_RATE, "absorb rate too small for a key") };
+        CyclistAead { key: (*key).into(), _permutation: core::marker::PhantomData }
+    }
+}
+
+impl<
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > AeadCore for CyclistAead<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+    Const<TAG_LEN>: ToUInt,
+    U<TAG_LEN>: ArrayLength<u8>,
+{
+    type NonceSize = U16;
+    type TagSize = U<TAG_LEN>;
+    type CiphertextOverhead = U0;
+}
+
+impl<
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > AeadInPlace for CyclistAead<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+    Const<TAG_LEN>: ToUInt,
+    U<TAG_LEN>: ArrayLength<u8>,
+{
+    fn encrypt_in_place_detached(
+        &self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> ::aead::Result<Tag<Self>> {
+        let mut keyed = self.keyed(nonce, associated_data);
+        keyed.encrypt_mut(buffer);
+        let mut tag = Tag::<Self>::default();
+        keyed.squeeze_mut(&mut tag);
+        Ok(tag)
+    }
+
+    fn decrypt_in_place_detached(
+        &self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &Tag<Self>,
+    ) -> ::aead::Result<()> {
+        let mut keyed = self.keyed(nonce, associated_data);
+        keyed.decrypt_mut(buffer);
+
+        // Squeeze a counterfactual tag, compare it in constant time, and wipe it.
+        let mut tag_p = Tag::<Self>::default();
+        keyed.squeeze_mut(&mut tag_p);
+        let authentic = constant_time_eq(tag, &tag_p);
+        wipe(&mut tag_p);
+
+        // As with open_mut, never leave inauthentic plaintext in the buffer.
+        if authentic {
+            Ok(())
+        } else {
+            buffer.fill(0);
+            Err(::aead::Error)
+        }
+    }
+}
+
+impl<
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > Drop for CyclistAead<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+{
+    fn drop(&mut self) {
+        wipe(&mut self.key);
+    }
+}
+
+impl<
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > fmt::Debug for CyclistAead<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CyclistAead").field("algorithm", &P::NAME).finish_non_exhaustive()
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "xoodyak"))]
+mod tests {
+    use ::aead::{Aead, Payload};
+
+    use crate::xoodyak::{XoodyakAead, XoodyakKeyed};
+    use crate::Keyed;
+
+    use super::*;
+
+    fn aead() -> XoodyakAead {
+        XoodyakAead::new(&[7u8; KEY_LEN].into())
+    }
+
+    #[test]
+    fn round_trip() {
+        let nonce = [1u8; NONCE_LEN].into();
+        let (msg, aad) = (b"this is a message", b"this is data");
+        let ciphertext = aead().encrypt(&nonce, Payload { msg, aad }).expect("should encrypt");
+        assert_eq!(msg.len() + XoodyakKeyed::TAG_LEN, ciphertext.len());
+
+        let plaintext =
+            aead().decrypt(&nonce, Payload { msg: &ciphertext, aad }).expect("should decrypt");
+        assert_eq!(msg, plaintext.as_slice());
+    }
+
+    #[test]
+    fn equivalent_to_keyed() {
+        let nonce = [1u8; NONCE_LEN];
+        let mut keyed = XoodyakKeyed::new(&[7u8; KEY_LEN], b"", b"");
+        keyed.absorb(&nonce);
+        keyed.absorb(b"ad");
+        assert_eq!(
+            keyed.seal(b"message"),
+            aead().encrypt(&nonce.into(), Payload { msg: b"message", aad: b"ad" }).expect("ok"),
+        );
+    }
+
+    #[test]
+    fn inauthentic_inputs() {
+        let nonce = [1u8; NONCE_LEN].into();
+        let ciphertext = aead().encrypt(&nonce, b"message".as_slice()).expect("should encrypt");
+
+        let other_nonce = [2u8; NONCE_LEN].into();
+        assert!(aead().decrypt(&other_nonce, ciphertext.as_slice()).is_err());
+        assert!(aead().decrypt(&nonce, Payload { msg: &ciphertext, aad: b"ad" }).is_err());
+        assert!(aead().decrypt(&nonce, &ciphertext[1..]).is_err());
+        assert!(aead().decrypt(&nonce, &ciphertext[..3]).is_err());
+
+        let other_key = XoodyakAead::new(&[8u8; KEY_LEN].into());
+        assert!(other_key.decrypt(&nonce, ciphertext.as_slice()).is_err());
+    }
+
+    #[test]
+    fn detached_tags() {
+        let nonce = [1u8; NONCE_LEN].into();
+        let mut buffer = *b"this is a message";
+        let tag = aead().encrypt_in_place_detached(&nonce, b"ad", &mut buffer).expect("ok");
+
+        let attached = aead()
+            .encrypt(&nonce, Payload { msg: b"this is a message", aad: b"ad" })
+            .expect("should encrypt");
+        assert_eq!(&buffer[..], &attached[..buffer.len()]);
+        assert_eq!(tag.as_slice(), &attached[buffer.len()..]);
+
+        let mut bad = buffer;
+        bad[0] ^= 1;
+        assert!(aead().decrypt_in_place_detached(&nonce, b"ad", &mut bad, &tag).is_err());
+        assert_eq!([0u8; 17], bad);
+
+        aead().decrypt_in_place_detached(&nonce, b"ad", &mut buffer, &tag).expect("should decrypt");
+        assert_eq!(b"this is a message", &buffer);
+    }
+
+    fn round_trip_with<A: Aead + KeyInit>() {
+        let aead = A::new(&Key::<A>::default());
+        let nonce = Nonce::<A>::default();
+        let ciphertext = aead.encrypt(&nonce, b"message".as_slice()).expect("should encrypt");
+        assert_eq!(Ok(b"message".to_vec()), aead.decrypt(&nonce, ciphertext.as_slice()));
+    }
+
+    #[test]
+    fn aliases() {
+        round_trip_with::<XoodyakAead>();
+
+        #[cfg(feature = "keccyak")]
+        {
+            use crate::keccyak::*;
+            round_trip_with::<KeccyakMaxAead>();
+            round_trip_with::<Keccyak256Aead>();
+            round_trip_with::<Keccyak128Aead>();
+            round_trip_with::<KeccyakMinAead>();
+            round_trip_with::<Keccyak800Aead>();
+            round_trip_with::<Keccyak400Aead>();
+        }
+
+        #[cfg(feature = "ascon")]
+        round_trip_with::<crate::ascon::AsconCyclistAead>();
+    }
+}
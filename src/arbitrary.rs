@@ -0,0 +1,157 @@
+#![cfg(feature = "arbitrary")]
+
+//! Implementations of the [`arbitrary`](::arbitrary) traits for the crate's public types.
+//!
+//! With the `arbitrary` feature, structure-aware fuzzers (e.g. `cargo fuzz` targets taking typed
+//! input) can generate transcripts of Cyclist operations, hash and keyed duplexes, tags, and the
+//! envelope types of the higher-level constructions directly, rather than decoding them from raw
+//! bytes:
+//!
+//! ```rust
+//! use arbitrary::{Arbitrary, Unstructured};
+//! use cyclist::fuzzing::KeyedTranscript;
+//! use cyclist::xoodyak::XoodyakKeyed;
+//!
+//! let mut u = Unstructured::new(b"some fuzzer input, of whatever length the fuzzer likes");
+//! let t = KeyedTranscript::arbitrary(&mut u).expect("should generate a transcript");
+//! assert!(cyclist::fuzzing::keyed_transcript_symmetry::<XoodyakKeyed>(&t));
+//! ```
+//!
+//! Generated keyed duplexes and transcripts have keys of 1 to 16 bytes and key IDs and counters of
+//! up to 16 bytes, which are valid for every scheme in the crate except the Keccyak\[200\] schemes,
+//! for which generating a keyed duplex may fail. The `arbitrary` feature enables the `fuzzing`
+//! feature.
+
+use ::arbitrary::{Arbitrary, Error, Result, Unstructured};
+
+use crate::fuzzing::{HashOp, HashTranscript, KeyedOp, KeyedTranscript};
+use crate::sealed_sender::Opened;
+use crate::stream::MessageTag;
+use crate::{Cyclist, CyclistHash, CyclistKeyed, Permutation, Tag};
+
+/// The maximum length of generated keys, key IDs, and counters.
+const MAX_KEY_LEN: usize = 16;
+
+impl<'a, const N: usize> Arbitrary<'a> for Tag<N> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Tag(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for MessageTag {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.choose(&[MessageTag::Message, MessageTag::Push, MessageTag::Rekey, MessageTag::Final])
+            .copied()
+    }
+}
+
+impl<'a> Arbitrary<'a> for Opened {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Opened { sender_id: u.arbitrary()?, payload: u.arbitrary()? })
+    }
+}
+
+impl<'a> Arbitrary<'a> for HashOp {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(if u.arbitrary()? {
+            HashOp::Absorb(u.arbitrary()?)
+        } else {
+            HashOp::Squeeze(u.int_in_range(1..=255)?)
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for HashTranscript {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(HashTranscript { ops: u.arbitrary()? })
+    }
+}
+
+impl<'a> Arbitrary<'a> for KeyedOp {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=4)? {
+            0 => KeyedOp::Absorb(u.arbitrary()?),
+            1 => KeyedOp::Squeeze(u.int_in_range(1..=255)?),
+            2 => KeyedOp::Encrypt(u.arbitrary()?),
+            3 => KeyedOp::Decrypt(u.arbitrary()?),
+            _ => KeyedOp::Ratchet,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for KeyedTranscript {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let (key, key_id, counter) = key_material(u)?;
+        Ok(KeyedTranscript { key, key_id, counter, ops: u.arbitrary()? })
+    }
+}
+
+impl<'a, P, const WIDTH: usize, const HASH_RATE: usize> Arbitrary<'a>
+    for CyclistHash<P, WIDTH, HASH_RATE>
+where
+    P: Permutation<WIDTH>,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut hash = CyclistHash::default();
+        hash.absorb(u.arbitrary()?);
+        Ok(hash)
+    }
+}
+
+impl<
+        'a,
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > Arbitrary<'a> for CyclistKeyed<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let (key, key_id, counter) = key_material(u)?;
+        CyclistKeyed::try_new(&key, &key_id, &counter).map_err(|_| Error::IncorrectFormat)
+    }
+}
+
+/// Returns a non-empty key, a key ID, and a counter.
+fn key_material(u: &mut Unstructured<'_>) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+    let mut bytes = |min| -> Result<Vec<u8>> {
+        let len = u.int_in_range(min..=MAX_KEY_LEN)?;
+        Ok(u.bytes(len)?.to_vec())
+    };
+    Ok((bytes(1)?, bytes(0)?, bytes(0)?))
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use super::*;
+    use crate::xoodyak::{XoodyakHash, XoodyakKeyed};
+
+    #[test]
+    fn generated_values_are_valid() {
+        let mut hash = XoodyakHash::default();
+        for len in 0..100 {
+            let input = hash.squeeze(len);
+            let mut u = Unstructured::new(&input);
+            if let Ok(t) = KeyedTranscript::arbitrary(&mut u) {
+                assert!(!t.key.is_empty());
+                assert!(crate::fuzzing::keyed_transcript_symmetry::<XoodyakKeyed>(&t));
+            }
+
+            let mut u = Unstructured::new(&input);
+            if let Ok(mut keyed) = XoodyakKeyed::arbitrary(&mut u) {
+                let c = keyed.clone().seal(b"message");
+                assert_eq!(Ok(b"message".to_vec()), keyed.open(&c));
+            }
+        }
+    }
+
+    #[test]
+    fn tags() {
+        let mut u = Unstructured::new(&[7; 16]);
+        assert_eq!(Tag::from([7; 16]), Tag::<16>::arbitrary(&mut u).expect("should generate"));
+    }
+}
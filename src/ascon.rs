@@ -0,0 +1,187 @@
+#![cfg(feature = "ascon")]
+
+//! Cyclist schemes using the Ascon permutation.
+//!
+//! Ascon, standardized by NIST in SP 800-232, has a 320-bit state, which makes it attractive for
+//! constrained targets where even Xoodoo's 384-bit state is a squeeze. The two schemes,
+//! [`AsconCyclistHash`] and [`AsconCyclistKeyed`], use the 12-round [`AsconP12`] permutation and
+//! are parameterized to offer ~128-bit security, using the same rules as the Keccyak schemes: the
+//! hash rate is `b-2k` for `k` bits of security, the keyed absorb rate is `b-W` with `W` of 32 bits,
+//! and the keyed squeeze rate is `b-c` with the same 192-bit capacity as Xoodyak.
+//!
+//! The 8-round [`AsconP8`] permutation, which Ascon itself uses for processing data, is provided
+//! for custom parameterizations.
+//!
+//! ```rust
+//! use cyclist::ascon::AsconCyclistKeyed;
+//! use cyclist::Cyclist;
+//!
+//! let mut aead = AsconCyclistKeyed::new(b"a secret key", b"a nonce", b"");
+//! aead.absorb(b"This is authenticated data!");
+//! let ciphertext = aead.seal(b"This is the plaintext!");
+//! ```
+//!
+//! **N.B:** This is not a published configuration for Cyclist and there are no official security
+//! analyses or specifications.
+
+use crate::macros::lane_accessors;
+use crate::{CyclistHash, CyclistKeyed, Permutation};
+
+/// A Cyclist hash using Ascon-p\[12\] and `r=64`, offering 128-bit security.
+pub type AsconCyclistHash = CyclistHash<AsconP12, { 320 / 8 }, { (320 - 256) / 8 }>;
+
+/// A keyed Cyclist using Ascon-p\[12\] and `r_absorb=288`/`r_squeeze=128`, offering 128-bit
+/// security.
+pub type AsconCyclistKeyed = CyclistKeyed<
+    AsconP12,
+    { 320 / 8 },
+    { (320 - 32) / 8 },  // R_absorb=b-W
+    { (320 - 192) / 8 }, // R_squeeze=b-c
+    16,
+    16,
+>;
+
+/// [`AsconCyclistKeyed`] as an [`aead`](::aead) scheme.
+#[cfg(feature = "aead")]
+pub type AsconCyclistAead = crate::aead::CyclistAead<
+    AsconP12,
+    { 320 / 8 },
+    { (320 - 32) / 8 },  // R_absorb=b-W
+    { (320 - 192) / 8 }, // R_squeeze=b-c
+    16,
+    16,
+>;
+
+// All Ascon schemes can be sent and shared between threads.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<AsconCyclistHash>();
+    assert_send_sync::<AsconCyclistKeyed>();
+};
+
+/// The Ascon-p\[12\] permutation.
+#[derive(Clone, Debug, Default)]
+pub struct AsconP12([u64; 5]);
+
+impl Permutation<40> for AsconP12 {
+    const NAME: &'static str = "Ascon-p[12]";
+    const ID: u16 = 0x0201;
+
+    lane_accessors!(u64, 40);
+
+    #[inline(always)]
+    fn permute(&mut self) {
+        ascon_p(&mut self.0, 12);
+    }
+}
+
+/// The Ascon-p\[8\] permutation.
+#[derive(Clone, Debug, Default)]
+pub struct AsconP8([u64; 5]);
+
+impl Permutation<40> for AsconP8 {
+    const NAME: &'static str = "Ascon-p[8]";
+    const ID: u16 = 0x0202;
+
+    lane_accessors!(u64, 40);
+
+    #[inline(always)]
+    fn permute(&mut self) {
+        ascon_p(&mut self.0, 8);
+    }
+}
+
+/// The round constants for Ascon-p\[12\]. Ascon-p\[n\] uses the last `n` constants.
+const ROUND_CONSTANTS: [u64; 12] =
+    [0xf0, 0xe1, 0xd2, 0xc3, 0xb4, 0xa5, 0x96, 0x87, 0x78, 0x69, 0x5a, 0x4b];
+
+/// Applies the last `rounds` rounds of Ascon-p\[12\] to the given lanes.
+#[inline(always)]
+fn ascon_p(x: &mut [u64; 5], rounds: usize) {
+    for &c in &ROUND_CONSTANTS[ROUND_CONSTANTS.len() - rounds..] {
+        // Add the round constant.
+        x[2] ^= c;
+
+        // Apply the substitution layer.
+        x[0] ^= x[4];
+        x[4] ^= x[3];
+        x[2] ^= x[1];
+        let t = [!x[0] & x[1], !x[1] & x[2], !x[2] & x[3], !x[3] & x[4], !x[4] & x[0]];
+        x[0] ^= t[1];
+        x[1] ^= t[2];
+        x[2] ^= t[3];
+        x[3] ^= t[4];
+        x[4] ^= t[0];
+        x[1] ^= x[0];
+        x[0] ^= x[4];
+        x[3] ^= x[2];
+        x[2] = !x[2];
+
+        // Apply the linear diffusion layer.
+        x[0] ^= x[0].rotate_right(19) ^ x[0].rotate_right(28);
+        x[1] ^= x[1].rotate_right(61) ^ x[1].rotate_right(39);
+        x[2] ^= x[2].rotate_right(1) ^ x[2].rotate_right(6);
+        x[3] ^= x[3].rotate_right(10) ^ x[3].rotate_right(17);
+        x[4] ^= x[4].rotate_right(7) ^ x[4].rotate_right(41);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascon_hash_initial_state() {
+        // The precomputed initial state of Ascon-Hash v1.2, from the reference implementation.
+        let mut x = [0x00400c0000000100, 0, 0, 0, 0];
+        ascon_p(&mut x, 12);
+        assert_eq!(
+            [
+                0xee9398aadb67f03d,
+                0x8bb21831c60f1002,
+                0xb48a92db98d5da62,
+                0x43189921b8f8e3e8,
+                0x348fa5c9d525e140
+            ],
+            x
+        );
+    }
+
+    #[test]
+    fn ascon_hash_empty_message() {
+        // Ascon-Hash v1.2 of the empty message, using big-endian lanes and a 64-bit rate.
+        let mut x = [
+            0xee9398aadb67f03d,
+            0x8bb21831c60f1002,
+            0xb48a92db98d5da62,
+            0x43189921b8f8e3e8,
+            0x348fa5c9d525e140,
+        ];
+        x[0] ^= 0x80 << 56;
+        let mut digest = [0u8; 32];
+        for block in digest.chunks_mut(8) {
+            ascon_p(&mut x, 12);
+            block.copy_from_slice(&x[0].to_be_bytes());
+        }
+        assert_eq!(
+            [
+                0x73, 0x46, 0xbc, 0x14, 0xf0, 0x36, 0xe8, 0x7a, 0xe0, 0x3d, 0x09, 0x97, 0x91, 0x30,
+                0x88, 0xf5, 0xf6, 0x84, 0x11, 0x43, 0x4b, 0x3c, 0xf8, 0xb5, 0x4f, 0xa7, 0x96, 0xa8,
+                0x0d, 0x25, 0x1f, 0x91,
+            ],
+            digest
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut d = AsconCyclistKeyed::new(b"ok then", b"", b"");
+        let m = b"it's a deal".to_vec();
+        let c = d.seal(&m);
+
+        let mut d = AsconCyclistKeyed::new(b"ok then", b"", b"");
+        let p = d.open(&c);
+
+        assert_eq!(Ok(m), p);
+    }
+}
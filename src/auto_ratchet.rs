@@ -0,0 +1,294 @@
+//! Keyed duplexes which ratchet themselves at a configured cadence.
+//!
+//! Ratcheting a keyed duplex irreversibly overwrites part of its state, so that compromising the
+//! state later doesn't reveal anything processed before the ratchet. Long-lived sessions should
+//! ratchet regularly, but it's easy for a call site to forget. An [`AutoRatchet`] wraps a keyed
+//! duplex and ratchets it after every `n` messages sealed or opened, or every `n` bytes encrypted
+//! or decrypted, whichever comes first:
+//!
+//! ```rust
+//! use cyclist::auto_ratchet::AutoRatchet;
+//! use cyclist::xoodyak::XoodyakKeyed;
+//! use cyclist::Keyed;
+//!
+//! let key = XoodyakKeyed::new(b"a session key", b"", b"");
+//! let mut sender = AutoRatchet::new(key.clone()).ratchet_every_n_messages(2);
+//! let mut receiver = AutoRatchet::new(key).ratchet_every_n_messages(2);
+//!
+//! for _ in 0..5 {
+//!     let mut message = b"a message\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0".to_vec();
+//!     sender.seal_mut(&mut message).expect("sender should be usable");
+//!     assert_eq!(Ok(()), receiver.open_mut(&mut message));
+//!     assert_eq!(b"a message", &message[..9]);
+//! }
+//!
+//! assert_eq!(2, sender.ratchets());
+//! ```
+//!
+//! Both ends of a session must use the same policy, and perform the same operations in the same
+//! order, for their states to remain in sync. Messages which fail to open aren't counted, since the
+//! duplex must be discarded after a failed open (see
+//! [`CyclistKeyed::is_poisoned`](crate::CyclistKeyed::is_poisoned)), and every later operation
+//! returns [`Error::Poisoned`].
+//!
+//! # Construction
+//!
+//! The wrapper counts the messages and bytes processed since the last ratchet. After each
+//! operation which encrypts or decrypts data, it checks those counts against the policy, and if
+//! either limit has been reached it ratchets the duplex and resets both counts. Ratchets never
+//! happen in the middle of an operation, so the ciphertexts are those of the wrapped duplex with
+//! explicit calls to [`Keyed::ratchet`] between operations.
+
+use crate::{Error, Keyed};
+
+/// A keyed duplex which ratchets itself after a configured number of messages or bytes.
+#[derive(Clone, Debug)]
+pub struct AutoRatchet<K> {
+    keyed: K,
+    every_n_bytes: Option<u64>,
+    every_n_messages: Option<u64>,
+    bytes: u64,
+    messages: u64,
+    ratchets: u64,
+}
+
+impl<K> AutoRatchet<K>
+where
+    K: Keyed,
+{
+    /// Creates a new [`AutoRatchet`] which wraps the given keyed duplex. Until a policy is
+    /// configured, it never ratchets automatically.
+    pub const fn new(keyed: K) -> Self {
+        AutoRatchet {
+            keyed,
+            every_n_bytes: None,
+            every_n_messages: None,
+            bytes: 0,
+            messages: 0,
+            ratchets: 0,
+        }
+    }
+
+    /// Ratchets the duplex once at least `n` bytes have been encrypted or decrypted since the last
+    /// ratchet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    #[must_use]
+    pub const fn ratchet_every_n_bytes(mut self, n: u64) -> Self {
+        assert!(n > 0, "ratchet interval must be > 0");
+        self.every_n_bytes = Some(n);
+        self
+    }
+
+    /// Ratchets the duplex once `n` messages have been sealed or opened since the last ratchet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    #[must_use]
+    pub const fn ratchet_every_n_messages(mut self, n: u64) -> Self {
+        assert!(n > 0, "ratchet interval must be > 0");
+        self.every_n_messages = Some(n);
+        self
+    }
+
+    /// Returns the number of times the duplex has been ratcheted, automatically or otherwise.
+    pub const fn ratchets(&self) -> u64 {
+        self.ratchets
+    }
+
+    /// Returns a reference to the wrapped duplex.
+    pub const fn get_ref(&self) -> &K {
+        &self.keyed
+    }
+
+    /// Returns the wrapped duplex.
+    pub fn into_inner(self) -> K {
+        self.keyed
+    }
+
+    /// Returns `true` if the wrapped duplex has been poisoned by a failed open, after which every
+    /// operation returns [`Error::Poisoned`].
+    pub fn is_poisoned(&self) -> bool {
+        self.keyed.is_poisoned()
+    }
+
+    /// Absorbs the given slice of data. See [`Cyclist::absorb`](crate::Cyclist::absorb).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the wrapped duplex is poisoned.
+    pub fn absorb(&mut self, bin: &[u8]) -> Result<(), Error> {
+        self.keyed.try_absorb(bin)
+    }
+
+    /// Fills the given mutable slice with squeezed data. See [`Cyclist::squeeze_mut`](crate::Cyclist::squeeze_mut).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the wrapped duplex is poisoned.
+    pub fn squeeze_mut(&mut self, out: &mut [u8]) -> Result<(), Error> {
+        self.keyed.try_squeeze_mut(out)
+    }
+
+    /// Encrypts the given mutable slice in place, then ratchets if the policy requires it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the wrapped duplex is poisoned.
+    pub fn encrypt_mut(&mut self, in_out: &mut [u8]) -> Result<(), Error> {
+        self.keyed.try_encrypt_mut(in_out)?;
+        self.update(in_out.len(), 0);
+        Ok(())
+    }
+
+    /// Decrypts the given mutable slice in place, then ratchets if the policy requires it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the wrapped duplex is poisoned.
+    pub fn decrypt_mut(&mut self, in_out: &mut [u8]) -> Result<(), Error> {
+        self.keyed.try_decrypt_mut(in_out)?;
+        self.update(in_out.len(), 0);
+        Ok(())
+    }
+
+    /// Seals the given mutable slice in place, then ratchets if the policy requires it. See
+    /// [`Keyed::seal_mut`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Keyed::try_seal_mut`].
+    pub fn seal_mut(&mut self, in_out: &mut [u8]) -> Result<(), Error> {
+        self.keyed.try_seal_mut(in_out)?;
+        self.update(K::opened_len(in_out.len()).unwrap_or(0), 1);
+        Ok(())
+    }
+
+    /// Opens the given mutable slice in place, then ratchets if the policy requires it and the
+    /// input was authentic. See [`Keyed::open_mut`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Keyed::open_mut`].
+    pub fn open_mut(&mut self, in_out: &mut [u8]) -> Result<(), Error> {
+        self.keyed.open_mut(in_out)?;
+        self.update(K::opened_len(in_out.len()).unwrap_or(0), 1);
+        Ok(())
+    }
+
+    /// Ratchets the duplex, and resets the counts of messages and bytes since the last ratchet.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the wrapped duplex is poisoned.
+    pub fn ratchet(&mut self) -> Result<(), Error> {
+        self.keyed.try_ratchet()?;
+        self.bytes = 0;
+        self.messages = 0;
+        self.ratchets += 1;
+        Ok(())
+    }
+
+    /// Counts the given bytes and messages, and ratchets if either limit has been reached.
+    fn update(&mut self, bytes: usize, messages: u64) {
+        self.bytes = self.bytes.saturating_add(u64::try_from(bytes).unwrap_or(u64::MAX));
+        self.messages += messages;
+        if self.every_n_bytes.is_some_and(|n| self.bytes >= n)
+            || self.every_n_messages.is_some_and(|n| self.messages >= n)
+        {
+            self.keyed.ratchet();
+            self.bytes = 0;
+            self.messages = 0;
+            self.ratchets += 1;
+        }
+    }
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakKeyed;
+    use crate::Cyclist;
+
+    use super::*;
+
+    fn keyed() -> XoodyakKeyed {
+        XoodyakKeyed::new(b"key", b"", b"")
+    }
+
+    #[test]
+    fn message_policy() {
+        let mut auto = AutoRatchet::new(keyed()).ratchet_every_n_messages(2);
+        let mut manual = keyed();
+        for i in 0..5 {
+            let mut a = [0u8; 32];
+            let mut b = [0u8; 32];
+            auto.seal_mut(&mut a).expect("should be unpoisoned");
+            manual.seal_mut(&mut b);
+            if i % 2 == 1 {
+                manual.ratchet();
+            }
+            assert_eq!(a, b);
+        }
+        assert_eq!(2, auto.ratchets());
+        let mut out = [0u8; 16];
+        auto.squeeze_mut(&mut out).expect("should be unpoisoned");
+        assert_eq!(manual.squeeze(16), out);
+    }
+
+    #[test]
+    fn byte_policy() {
+        let mut auto = AutoRatchet::new(keyed()).ratchet_every_n_bytes(100);
+        let mut manual = keyed();
+        for i in 0..3 {
+            let mut a = [0u8; 60];
+            let mut b = [0u8; 60];
+            auto.encrypt_mut(&mut a).expect("should be unpoisoned");
+            manual.encrypt_mut(&mut b);
+            if i == 1 {
+                manual.ratchet();
+            }
+            assert_eq!(a, b);
+        }
+        assert_eq!(1, auto.ratchets());
+        let mut out = [0u8; 16];
+        auto.squeeze_mut(&mut out).expect("should be unpoisoned");
+        assert_eq!(manual.squeeze(16), out);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut sender = AutoRatchet::new(keyed()).ratchet_every_n_bytes(10);
+        let mut receiver = AutoRatchet::new(keyed()).ratchet_every_n_bytes(10);
+        for len in 0..40 {
+            let mut m = vec![7u8; XoodyakKeyed::sealed_len(len)];
+            sender.seal_mut(&mut m).expect("should be unpoisoned");
+            assert_eq!(Ok(()), receiver.open_mut(&mut m));
+            assert_eq!(vec![7u8; len], m[..len]);
+        }
+        assert_eq!(sender.ratchets(), receiver.ratchets());
+        assert_eq!(Err(Error::BufferTooSmall), receiver.open_mut(&mut [0u8; 4]));
+    }
+
+    #[test]
+    fn poisoning() {
+        let mut receiver = AutoRatchet::new(keyed()).ratchet_every_n_messages(1);
+        let mut forged = [0u8; 32];
+        assert_eq!(Err(Error::InvalidTag), receiver.open_mut(&mut forged));
+        assert!(receiver.is_poisoned());
+        assert_eq!(Err(Error::Poisoned), receiver.open_mut(&mut forged));
+        assert_eq!(Err(Error::Poisoned), receiver.absorb(b"ad"));
+        assert_eq!(Err(Error::Poisoned), receiver.squeeze_mut(&mut [0u8; 16]));
+        assert_eq!(Err(Error::Poisoned), receiver.decrypt_mut(&mut [0u8; 16]));
+        assert_eq!(Err(Error::Poisoned), receiver.ratchet());
+        assert_eq!(0, receiver.ratchets());
+    }
+
+    #[test]
+    #[should_panic(expected = "ratchet interval must be > 0")]
+    fn zero_interval() {
+        let _ = AutoRatchet::new(keyed()).ratchet_every_n_messages(0);
+    }
+}
@@ -0,0 +1,180 @@
+//! Configuration of hashers with an output length, a salt, and a customization string.
+//!
+//! A [`HashBuilder`] collects hash options and produces a [`ConfiguredHash`]:
+//!
+//! ```rust
+//! use cyclist::xoodyak::XoodyakHash;
+//!
+//! let mut hash = XoodyakHash::builder()
+//!     .salt(b"This is a salt!")
+//!     .customization(b"example.com/v1")
+//!     .output_len(32)
+//!     .build();
+//! hash.absorb(b"This is an input message!");
+//! let digest = hash.finalize();
+//!
+//! assert_eq!(digest.len(), 32);
+//! ```
+//!
+//! # Construction
+//!
+//! Before any input is absorbed, the output length (as a little-endian 64-bit integer), the salt,
+//! and the customization string are each absorbed in a separate absorb operation. Because separate
+//! absorb operations are injective, any two distinct configurations yield unrelated hashers, and
+//! digests of different lengths are not prefixes of each other.
+
+use core::marker::PhantomData;
+
+use crate::Cyclist;
+
+/// The default output length of a [`ConfiguredHash`], in bytes.
+pub const DEFAULT_OUTPUT_LEN: usize = 32;
+
+/// A builder for [`ConfiguredHash`] instances.
+#[derive(Clone, Copy, Debug)]
+#[must_use]
+pub struct HashBuilder<'a, H> {
+    salt: &'a [u8],
+    customization: &'a [u8],
+    output_len: usize,
+    _hash: PhantomData<H>,
+}
+
+impl<'a, H> HashBuilder<'a, H>
+where
+    H: Cyclist + Default,
+{
+    /// Creates a new [`HashBuilder`] with an empty salt, an empty customization string, and an
+    /// output length of [`DEFAULT_OUTPUT_LEN`] bytes.
+    pub const fn new() -> Self {
+        HashBuilder {
+            salt: &[],
+            customization: &[],
+            output_len: DEFAULT_OUTPUT_LEN,
+            _hash: PhantomData,
+        }
+    }
+
+    /// Sets the salt.
+    pub const fn salt(mut self, salt: &'a [u8]) -> Self {
+        self.salt = salt;
+        self
+    }
+
+    /// Sets the customization string, which separates the hashes of different applications.
+    pub const fn customization(mut self, customization: &'a [u8]) -> Self {
+        self.customization = customization;
+        self
+    }
+
+    /// Sets the output length in bytes.
+    pub const fn output_len(mut self, output_len: usize) -> Self {
+        self.output_len = output_len;
+        self
+    }
+
+    /// Returns a [`ConfiguredHash`] with the given configuration.
+    pub fn build(self) -> ConfiguredHash<H> {
+        let mut hash = H::default();
+        hash.absorb(&u64::try_from(self.output_len).expect("unexpected overflow").to_le_bytes());
+        hash.absorb(self.salt);
+        hash.absorb(self.customization);
+        ConfiguredHash { hash, output_len: self.output_len }
+    }
+}
+
+impl<'a, H> Default for HashBuilder<'a, H>
+where
+    H: Cyclist + Default,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A hasher produced by a [`HashBuilder`] with a fixed output length.
+#[derive(Clone, Debug)]
+pub struct ConfiguredHash<H> {
+    hash: H,
+    output_len: usize,
+}
+
+impl<H> ConfiguredHash<H>
+where
+    H: Cyclist,
+{
+    /// Absorbs the given slice.
+    pub fn absorb(&mut self, bin: &[u8]) {
+        self.hash.absorb(bin);
+    }
+
+    /// Returns the length of the digest in bytes.
+    pub const fn output_len(&self) -> usize {
+        self.output_len
+    }
+
+    /// Fills the given mutable slice with the digest.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slice is not exactly [`ConfiguredHash::output_len`] bytes long.
+    pub fn finalize_mut(mut self, out: &mut [u8]) {
+        assert_eq!(out.len(), self.output_len, "invalid output length");
+        self.hash.squeeze_mut(out);
+    }
+
+    /// Returns the digest.
+    #[cfg(feature = "std")]
+    pub fn finalize(self) -> Vec<u8> {
+        let mut out = vec![0u8; self.output_len];
+        self.finalize_mut(&mut out);
+        out
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakHash;
+
+    use super::*;
+
+    fn digest(builder: HashBuilder<'_, XoodyakHash>) -> Vec<u8> {
+        let mut hash = builder.build();
+        hash.absorb(b"message");
+        hash.finalize()
+    }
+
+    #[test]
+    fn defaults() {
+        let hash = XoodyakHash::builder().build();
+        assert_eq!(DEFAULT_OUTPUT_LEN, hash.output_len());
+        assert_eq!(digest(HashBuilder::default()), digest(XoodyakHash::builder()));
+    }
+
+    #[test]
+    fn salt_and_customization() {
+        let plain = digest(XoodyakHash::builder());
+        let salted = digest(XoodyakHash::builder().salt(b"salt"));
+        let customized = digest(XoodyakHash::builder().customization(b"salt"));
+
+        assert_ne!(plain, salted);
+        assert_ne!(plain, customized);
+        assert_ne!(salted, customized);
+    }
+
+    #[test]
+    fn output_len() {
+        let short = digest(XoodyakHash::builder().output_len(16));
+        let long = digest(XoodyakHash::builder().output_len(64));
+
+        assert_eq!(16, short.len());
+        assert_eq!(64, long.len());
+        assert_ne!(short, long[..16]);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid output length")]
+    fn wrong_output_len() {
+        XoodyakHash::builder().output_len(16).build().finalize_mut(&mut [0u8; 32]);
+    }
+}
@@ -0,0 +1,507 @@
+//! Segmented bulk encryption, for payloads large enough to benefit from parallelism.
+//!
+//! Cyclist's keyed mode is strictly serial: each block of ciphertext depends on every preceding
+//! block. Bulk mode trades that chaining for parallelism by splitting the payload into fixed-size
+//! segments, each of which is sealed by an independent duplex. Segments can be processed in any
+//! order, and on any number of threads.
+//!
+//! # Construction
+//!
+//! 1. The session absorbs a header containing the segment length and the payload length.
+//! 2. The session is cloned to produce a base state, and then ratcheted. Ratcheting uses a distinct
+//!    domain separator, so the session's state diverges irreversibly from the base state.
+//! 3. For each segment `i`, the base state is cloned and absorbs `i` as a little-endian 64-bit
+//!    integer. The segment is then encrypted with that per-segment duplex, and a segment tag is
+//!    squeezed from it. Because each segment duplex absorbs a unique index, their keystreams are
+//!    independent of each other.
+//! 4. The session absorbs each segment tag in order, and then squeezes the final tag.
+//!
+//! # Security Model
+//!
+//! Bulk mode requires the same uniqueness of the session's key and nonce as [`CyclistKeyed::seal`].
+//! Segment tags are never transmitted; the final tag authenticates every segment, the order of the
+//! segments, the segment length, and the payload length, so the payload is authenticated as a
+//! whole. Unlike [`CyclistKeyed::open_mut`], however, segments are not chained together, and a
+//! payload's plaintext can be recovered segment-by-segment by anyone with the session state. The
+//! output of bulk mode is not interchangeable with the output of [`CyclistKeyed::seal_mut`].
+//!
+//! # Parallelism
+//!
+//! [`CyclistKeyed::bulk_seal_mut`] and [`CyclistKeyed::bulk_open_mut`] process segments serially.
+//! Their `_with` counterparts accept an [`Executor`], which determines how segments are processed.
+//! Executors are always passed in by the caller rather than drawn from a global pool, so that
+//! applications remain in control of their CPU budget.
+//!
+//! Either way, segments are processed in groups of four, and the duplexes of a group's segments are
+//! run in lockstep, so a permutation which evaluates several instances in parallel (see
+//! [`Permutation::permute_many`]) permutes their states together.
+
+#[cfg(feature = "std")]
+use std::num::NonZeroUsize;
+#[cfg(feature = "std")]
+use std::thread;
+
+use constant_time_eq::constant_time_eq;
+
+use crate::{wipe, Cyclist, CyclistCore, CyclistKeyed, Error, Permutation, LOCKSTEP};
+
+/// A strategy for processing bulk mode segments.
+#[cfg(feature = "std")]
+pub trait Executor {
+    /// Applies `f` to each group of segments and its index, returning the results in order.
+    fn map<T, F>(&self, segments: Vec<&mut [u8]>, f: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(usize, &mut [u8]) -> T + Sync;
+}
+
+/// An [`Executor`] which processes each group of segments in turn on the calling thread.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Serial;
+
+#[cfg(feature = "std")]
+impl Executor for Serial {
+    fn map<T, F>(&self, segments: Vec<&mut [u8]>, f: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(usize, &mut [u8]) -> T + Sync,
+    {
+        segments.into_iter().enumerate().map(|(i, segment)| f(i, segment)).collect()
+    }
+}
+
+/// An [`Executor`] which divides groups of segments evenly between a fixed number of scoped
+/// threads.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug)]
+pub struct ScopedThreads {
+    threads: NonZeroUsize,
+}
+
+#[cfg(feature = "std")]
+impl ScopedThreads {
+    /// Creates a new [`ScopedThreads`] which will spawn at most `threads` threads per operation.
+    pub const fn new(threads: NonZeroUsize) -> Self {
+        ScopedThreads { threads }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Executor for ScopedThreads {
+    fn map<T, F>(&self, segments: Vec<&mut [u8]>, f: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(usize, &mut [u8]) -> T + Sync,
+    {
+        let per_thread = segments.len().div_ceil(self.threads.get()).max(1);
+        let mut segments = segments.into_iter().enumerate().collect::<Vec<_>>();
+        let f = &f;
+        thread::scope(|s| {
+            let workers = segments
+                .chunks_mut(per_thread)
+                .map(|group| {
+                    s.spawn(move || {
+                        group.iter_mut().map(|(i, segment)| f(*i, segment)).collect::<Vec<T>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            workers.into_iter().flat_map(|w| w.join().expect("bulk mode worker panicked")).collect()
+        })
+    }
+}
+
+impl<
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > CyclistKeyed<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+{
+    /// Seals the given mutable slice in place using bulk mode with segments of `segment_len`
+    /// bytes.
+    ///
+    /// The last `TAG_LEN` bytes of the slice will be overwritten with the authentication tag.
+    pub fn bulk_seal_mut(&mut self, in_out: &mut [u8], segment_len: usize) {
+        let (plaintext, tag) = in_out.split_at_mut(in_out.len() - TAG_LEN);
+        let base = self.bulk_base(plaintext.len(), segment_len);
+        for (i, group) in plaintext.chunks_mut(segment_len.saturating_mul(LOCKSTEP)).enumerate() {
+            base.crypt_bulk_group(i, group, segment_len, false, |s| self.absorb_bulk_segment(s));
+        }
+        self.squeeze_mut(tag);
+        self.core.stats.seals += 1;
+    }
+
+    /// Returns a copy of the given slice sealed using bulk mode with segments of `segment_len`
+    /// bytes.
+    ///
+    /// The returned [Vec] will be `TAG_LEN` bytes longer than `bin`.
+    #[cfg(feature = "std")]
+    pub fn bulk_seal(&mut self, bin: &[u8], segment_len: usize) -> Vec<u8> {
+        let mut c = vec![0u8; bin.len() + TAG_LEN];
+        c[..bin.len()].copy_from_slice(bin);
+        self.bulk_seal_mut(&mut c, segment_len);
+        c
+    }
+
+    /// Opens the given mutable slice in place using bulk mode with segments of `segment_len`
+    /// bytes. The last `TAG_LEN` bytes of the slice will be unmodified.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the duplex is poisoned, [`Error::BufferTooSmall`] if the slice
+    /// is shorter than `TAG_LEN` bytes, and [`Error::InvalidTag`] if the input could not be
+    /// authenticated.
+    pub fn bulk_open_mut(&mut self, in_out: &mut [u8], segment_len: usize) -> Result<(), Error> {
+        self.check_unpoisoned()?;
+        if in_out.len() < TAG_LEN {
+            return self.reject_short_bulk();
+        }
+        let (ciphertext, tag) = in_out.split_at_mut(in_out.len() - TAG_LEN);
+        let base = self.bulk_base(ciphertext.len(), segment_len);
+        for (i, group) in ciphertext.chunks_mut(segment_len.saturating_mul(LOCKSTEP)).enumerate() {
+            base.crypt_bulk_group(i, group, segment_len, true, |s| self.absorb_bulk_segment(s));
+        }
+        self.verify_bulk_tag(ciphertext, tag)
+    }
+
+    /// Returns an unsealed copy of the given slice, which was sealed using bulk mode with segments
+    /// of `segment_len` bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the duplex is poisoned, [`Error::BufferTooSmall`] if the slice
+    /// is shorter than `TAG_LEN` bytes, and [`Error::InvalidTag`] if the ciphertext cannot be
+    /// authenticated.
+    #[cfg(feature = "std")]
+    pub fn bulk_open(&mut self, bin: &[u8], segment_len: usize) -> Result<Vec<u8>, Error> {
+        let mut c = bin.to_vec();
+        self.bulk_open_mut(&mut c, segment_len)?;
+        c.truncate(c.len() - TAG_LEN);
+        Ok(c)
+    }
+
+    /// Seals the given mutable slice in place using bulk mode with segments of `segment_len`
+    /// bytes, processing segments with the given [`Executor`].
+    ///
+    /// The output is identical to that of [`CyclistKeyed::bulk_seal_mut`].
+    #[cfg(feature = "std")]
+    pub fn bulk_seal_mut_with<E>(&mut self, in_out: &mut [u8], segment_len: usize, executor: &E)
+    where
+        P: Sync,
+        E: Executor,
+    {
+        let (plaintext, tag) = in_out.split_at_mut(in_out.len() - TAG_LEN);
+        let base = self.bulk_base(plaintext.len(), segment_len);
+        let groups = plaintext.chunks_mut(segment_len.saturating_mul(LOCKSTEP)).collect();
+        for segment in executor
+            .map(groups, |i, group| base.collect_bulk_group(i, group, segment_len, false))
+            .into_iter()
+            .flatten()
+        {
+            self.absorb_bulk_segment(segment);
+        }
+        self.squeeze_mut(tag);
+        self.core.stats.seals += 1;
+    }
+
+    /// Opens the given mutable slice in place using bulk mode with segments of `segment_len`
+    /// bytes, processing segments with the given [`Executor`]. The last `TAG_LEN` bytes of the
+    /// slice will be unmodified.
+    ///
+    /// The output is identical to that of [`CyclistKeyed::bulk_open_mut`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the duplex is poisoned, [`Error::BufferTooSmall`] if the slice
+    /// is shorter than `TAG_LEN` bytes, and [`Error::InvalidTag`] if the input could not be
+    /// authenticated.
+    #[cfg(feature = "std")]
+    pub fn bulk_open_mut_with<E>(
+        &mut self,
+        in_out: &mut [u8],
+        segment_len: usize,
+        executor: &E,
+    ) -> Result<(), Error>
+    where
+        P: Sync,
+        E: Executor,
+    {
+        self.check_unpoisoned()?;
+        if in_out.len() < TAG_LEN {
+            return self.reject_short_bulk();
+        }
+        let (ciphertext, tag) = in_out.split_at_mut(in_out.len() - TAG_LEN);
+        let base = self.bulk_base(ciphertext.len(), segment_len);
+        let groups = ciphertext.chunks_mut(segment_len.saturating_mul(LOCKSTEP)).collect();
+        for segment in executor
+            .map(groups, |i, group| base.collect_bulk_group(i, group, segment_len, true))
+            .into_iter()
+            .flatten()
+        {
+            self.absorb_bulk_segment(segment);
+        }
+        self.verify_bulk_tag(ciphertext, tag)
+    }
+
+    /// Absorbs the bulk mode header, returning the base state for segments and ratcheting the
+    /// session.
+    fn bulk_base(&mut self, len: usize, segment_len: usize) -> Self {
+        assert!(segment_len > 0, "segment length must be > 0");
+
+        let mut header = [0u8; 16];
+        header[..8]
+            .copy_from_slice(&u64::try_from(segment_len).expect("invalid length").to_le_bytes());
+        header[8..].copy_from_slice(&u64::try_from(len).expect("invalid length").to_le_bytes());
+        self.absorb(&header);
+
+        let base = self.clone();
+        self.ratchet();
+        base
+    }
+
+    /// Encrypts (or decrypts) the `group`-th group of segments in place, passing each segment's
+    /// tag and the number of permutations it took to `f` in segment order.
+    fn crypt_bulk_group(
+        &self,
+        group: usize,
+        buf: &mut [u8],
+        segment_len: usize,
+        decrypt: bool,
+        mut f: impl FnMut(([u8; TAG_LEN], u64)),
+    ) {
+        let mut segments: [&mut [u8]; LOCKSTEP] = Default::default();
+        let mut n = 0;
+        for (segment, chunk) in segments.iter_mut().zip(buf.chunks_mut(segment_len)) {
+            *segment = chunk;
+            n += 1;
+        }
+
+        // Only the last segment can be shorter than the others, so it's processed on its own.
+        let full = segments[..n].iter().take_while(|s| s.len() == segment_len).count();
+        let (full, short) = segments[..n].split_at_mut(full);
+        let first = group * LOCKSTEP;
+        self.crypt_bulk_segments(first, full, decrypt, &mut f);
+        self.crypt_bulk_segments(first + full.len(), short, decrypt, &mut f);
+    }
+
+    /// Encrypts (or decrypts) the `group`-th group of segments in place, returning each segment's
+    /// tag and the number of permutations it took in segment order.
+    #[cfg(feature = "std")]
+    fn collect_bulk_group(
+        &self,
+        group: usize,
+        buf: &mut [u8],
+        segment_len: usize,
+        decrypt: bool,
+    ) -> Vec<([u8; TAG_LEN], u64)> {
+        let mut segments = Vec::with_capacity(LOCKSTEP);
+        self.crypt_bulk_group(group, buf, segment_len, decrypt, |s| segments.push(s));
+        segments
+    }
+
+    /// Encrypts (or decrypts) at most [`LOCKSTEP`] consecutive segments of the same length in
+    /// place, the first of which has the given index, running their duplexes in lockstep.
+    fn crypt_bulk_segments(
+        &self,
+        first: usize,
+        segments: &mut [&mut [u8]],
+        decrypt: bool,
+        f: &mut impl FnMut(([u8; TAG_LEN], u64)),
+    ) {
+        let n = segments.len();
+        if n == 0 {
+            return;
+        }
+
+        // Derive each segment's duplex by absorbing its index into a copy of the base state.
+        let mut keyeds: [Self; LOCKSTEP] = core::array::from_fn(|_| self.clone());
+        let keyeds = &mut keyeds[..n];
+        let indices: [[u8; 8]; LOCKSTEP] = core::array::from_fn(|i| {
+            u64::try_from(first + i).expect("invalid index").to_le_bytes()
+        });
+        let indices = indices.each_ref().map(|index| &index[..]);
+        CyclistCore::absorb_many(keyeds, |keyed| &mut keyed.core, &indices[..n]);
+
+        // Encrypt each segment with its duplex, and then squeeze its segment tag.
+        CyclistCore::crypt_many(keyeds, |keyed| &mut keyed.core, segments, decrypt);
+        let mut tags = [[0u8; TAG_LEN]; LOCKSTEP];
+        let mut outs = tags.each_mut().map(|tag| &mut tag[..]);
+        CyclistCore::squeeze_many(keyeds, |keyed| &mut keyed.core, &mut outs[..n]);
+
+        for (&tag, keyed) in tags.iter().zip(keyeds.iter()) {
+            f((tag, keyed.core.stats.permutations - self.core.stats.permutations));
+        }
+    }
+
+    /// Absorbs a segment tag into the session and accounts for the segment's permutations.
+    fn absorb_bulk_segment(&mut self, (tag, permutations): ([u8; TAG_LEN], u64)) {
+        self.absorb(&tag);
+        self.core.stats.permutations += permutations;
+    }
+
+    /// Records the rejection of an input too short to contain a tag.
+    const fn reject_short_bulk(&mut self) -> Result<(), Error> {
+        self.core.stats.opens += 1;
+        self.core.stats.failures += 1;
+        Err(Error::BufferTooSmall)
+    }
+
+    /// Squeezes a counterfactual final tag and compares it to the given tag, zeroing the
+    /// plaintext if they don't match.
+    fn verify_bulk_tag(&mut self, plaintext: &mut [u8], tag: &[u8]) -> Result<(), Error> {
+        let mut tag_p = [0u8; TAG_LEN];
+        self.squeeze_mut(&mut tag_p);
+        let authentic = constant_time_eq(tag, &tag_p);
+        wipe(&mut tag_p);
+        self.core.stats.opens += 1;
+        if !authentic {
+            self.reject(plaintext);
+            return Err(Error::InvalidTag);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakKeyed;
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for len in [0, 1, 63, 64, 65, 1000] {
+            let m = vec![0xCA; len];
+            let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+            let c = d.bulk_seal(&m, 64);
+
+            let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+            let p = d.bulk_open(&c, 64);
+
+            assert_eq!(Ok(m), p);
+        }
+    }
+
+    #[test]
+    fn reordered_segments() {
+        let mut m = vec![0xCA; 64];
+        m.extend([0xFE; 64]);
+        let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+        let mut c = d.bulk_seal(&m, 64);
+        c[..128].rotate_left(64);
+
+        let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+        assert_eq!(Err(Error::InvalidTag), d.bulk_open(&c, 64));
+    }
+
+    #[test]
+    fn different_segment_lengths() {
+        let m = vec![0xCA; 1000];
+        let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+        let c = d.bulk_seal(&m, 64);
+
+        let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+        assert_eq!(Err(Error::InvalidTag), d.bulk_open(&c, 128));
+    }
+
+    #[test]
+    fn distinct_from_seal() {
+        let m = vec![0xCA; 1000];
+        let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+        let one = d.bulk_seal(&m, 64);
+
+        let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+        let two = d.seal(&m);
+
+        assert_ne!(one, two);
+    }
+
+    #[test]
+    fn construction() {
+        let m = (0..=255u8).cycle().take(1000).collect::<Vec<_>>();
+        for segment_len in [1, 64, 100, 250, 999, 1000, 2000] {
+            let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+            let c = d.bulk_seal(&m, segment_len);
+
+            let mut session = XoodyakKeyed::new(b"ok then", b"", b"");
+            let mut header = u64::try_from(segment_len).unwrap().to_le_bytes().to_vec();
+            header.extend(u64::try_from(m.len()).unwrap().to_le_bytes());
+            session.absorb(&header);
+            let base = session.clone();
+            session.ratchet();
+
+            let mut expected = Vec::new();
+            for (i, segment) in m.chunks(segment_len).enumerate() {
+                let mut keyed = base.clone();
+                keyed.absorb(&u64::try_from(i).unwrap().to_le_bytes());
+                expected.extend(keyed.encrypt(segment));
+                session.absorb(&keyed.squeeze(16));
+            }
+            expected.extend(session.squeeze(16));
+            assert_eq!(expected, c, "segment_len={segment_len}");
+        }
+    }
+
+    #[test]
+    fn executors() {
+        let m = vec![0xCA; 1000];
+        let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+        let c = d.bulk_seal(&m, 64);
+
+        let threads = ScopedThreads::new(NonZeroUsize::new(3).unwrap());
+        let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+        let mut c_p = vec![0u8; m.len() + XoodyakKeyed::tag_len()];
+        c_p[..m.len()].copy_from_slice(&m);
+        d.bulk_seal_mut_with(&mut c_p, 64, &threads);
+        assert_eq!(c, c_p);
+
+        let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+        assert_eq!(Ok(()), d.bulk_open_mut_with(&mut c_p, 64, &Serial));
+        assert_eq!(m, c_p[..m.len()]);
+    }
+
+    #[test]
+    #[cfg(feature = "keccyak")]
+    fn keccyak() {
+        use crate::keccyak::*;
+
+        fn check<
+            P,
+            const WIDTH: usize,
+            const ABSORB_RATE: usize,
+            const SQUEEZE_RATE: usize,
+            const RATCHET_RATE: usize,
+            const TAG_LEN: usize,
+        >(
+            new: impl Fn() -> CyclistKeyed<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>,
+        ) where
+            P: Permutation<WIDTH>,
+        {
+            for len in [0, 1, ABSORB_RATE - 1, ABSORB_RATE, ABSORB_RATE + 1, 2 * ABSORB_RATE + 1] {
+                let m = vec![0xCA; len];
+                let mut c = new().bulk_seal(&m, ABSORB_RATE);
+                assert_eq!(Ok(m), new().bulk_open(&c, ABSORB_RATE), "len={len}");
+
+                c[len] ^= 1;
+                assert_eq!(Err(Error::InvalidTag), new().bulk_open(&c, ABSORB_RATE), "len={len}");
+            }
+
+            let mut short = [0u8; TAG_LEN];
+            let short = &mut short[1..];
+            assert_eq!(Err(Error::BufferTooSmall), new().bulk_open_mut(short, ABSORB_RATE));
+        }
+
+        check(|| KeccyakMaxKeyed::new(b"ok then", b"", b""));
+        check(|| Keccyak256Keyed::new(b"ok then", b"", b""));
+        check(|| Keccyak128Keyed::new(b"ok then", b"", b""));
+        check(|| KeccyakMinKeyed::new(b"ok then", b"", b""));
+        check(|| Keccyak800Keyed::new(b"ok then", b"", b""));
+        check(|| Keccyak400Keyed::new(b"ok then", b"", b""));
+        check(|| Keccyak200Keyed::new(b"ok then", b"", b""));
+    }
+}
@@ -0,0 +1,339 @@
+//! Message authentication for automotive and industrial buses (e.g. CAN or CAN FD).
+//!
+//! Bus frames have little room for security overhead: classic CAN carries at most 8 bytes of data
+//! and CAN FD at most 64. Modeled on AUTOSAR SecOC, this profile appends [`OVERHEAD`] bytes to each
+//! frame's payload: the [`FRESHNESS_LEN`] least significant bytes of a 64-bit freshness counter and
+//! a [`TAG_LEN`]-byte truncated MAC over the frame's CAN ID, the full freshness counter, and the
+//! payload. Payloads are authenticated but not encrypted.
+//!
+//! ```rust
+//! use cyclist::can::{Receiver, Transmitter, OVERHEAD};
+//! use cyclist::xoodyak::XoodyakKeyed;
+//! use cyclist::Keyed;
+//!
+//! let key = XoodyakKeyed::new(b"a bus key", b"", b"");
+//! let mut transmitter = Transmitter::new(key.clone());
+//! let mut receiver = Receiver::new(key);
+//!
+//! // Write a payload into a frame and authenticate it.
+//! let mut frame = [0u8; 2 + OVERHEAD];
+//! frame[..2].copy_from_slice(&[0x12, 0x34]);
+//! transmitter.authenticate(0x123, &mut frame);
+//!
+//! // Verify the frame on receipt.
+//! assert_eq!(Ok(&[0x12, 0x34][..]), receiver.verify(0x123, &frame));
+//! ```
+//!
+//! # Freshness and Resynchronization
+//!
+//! A receiver reconstructs each frame's full freshness counter as the smallest value greater than
+//! that of the last frame it verified which has the same least significant bytes, so it rejects
+//! replayed frames and tolerates up to 65,535 consecutive lost frames. If a receiver loses its
+//! state (e.g. after a reset) or falls further behind, it can be resynchronized with a sync message
+//! from [`Transmitter::sync_message`], which carries the full freshness counter and a tag. A
+//! receiver only accepts sync messages which move its counter forward.
+//!
+//! # Construction
+//!
+//! A frame's tag is squeezed from a copy of the keyed duplex which has absorbed a domain separation
+//! label, the CAN ID and the freshness counter as little-endian integers, and the payload. A sync
+//! message's tag is constructed the same way, with a distinct label and no payload.
+
+use core::fmt;
+
+use constant_time_eq::constant_time_eq;
+
+use crate::Keyed;
+
+/// The length of a frame's truncated freshness counter, in bytes.
+pub const FRESHNESS_LEN: usize = 2;
+
+/// The length of a truncated MAC, in bytes.
+pub const TAG_LEN: usize = 4;
+
+/// The number of bytes added to each frame's payload.
+pub const OVERHEAD: usize = FRESHNESS_LEN + TAG_LEN;
+
+/// The length of a sync message, in bytes.
+pub const SYNC_LEN: usize = 8 + TAG_LEN;
+
+/// The domain separation label for frames.
+const FRAME_LABEL: &[u8] = b"cyclist.can.frame";
+
+/// The domain separation label for sync messages.
+const SYNC_LABEL: &[u8] = b"cyclist.can.sync";
+
+/// An error verifying a frame or a sync message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The frame was too short to contain a freshness counter and a tag, or the sync message was
+    /// not [`SYNC_LEN`] bytes long.
+    Truncated,
+
+    /// The frame or sync message could not be authenticated, indicating it was modified, replayed,
+    /// or sent with a different key or CAN ID, or that the receiver needs to be resynchronized.
+    Inauthentic,
+
+    /// The sync message was authentic, but would have moved the receiver's counter backwards.
+    Stale,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Truncated => write!(f, "truncated frame"),
+            Error::Inauthentic => write!(f, "inauthentic frame"),
+            Error::Stale => write!(f, "stale sync message"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// The transmitting end of an authenticated bus channel.
+#[derive(Clone, Debug)]
+pub struct Transmitter<K> {
+    keyed: K,
+    freshness: u64,
+}
+
+impl<K> Transmitter<K>
+where
+    K: Keyed,
+{
+    /// Creates a new [`Transmitter`] with the given keyed duplex and a freshness counter of zero.
+    pub const fn new(keyed: K) -> Self {
+        Transmitter::with_freshness(keyed, 0)
+    }
+
+    /// Creates a new [`Transmitter`] with the given keyed duplex and freshness counter (e.g. one
+    /// restored from non-volatile storage).
+    pub const fn with_freshness(keyed: K, freshness: u64) -> Self {
+        Transmitter { keyed, freshness }
+    }
+
+    /// Returns the freshness counter of the next frame.
+    pub const fn freshness(&self) -> u64 {
+        self.freshness
+    }
+
+    /// Authenticates a frame in place, returning its freshness counter. The payload must be in
+    /// `frame[..frame.len() - OVERHEAD]`; the truncated freshness counter and tag will be
+    /// overwritten.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the frame is shorter than [`OVERHEAD`].
+    pub fn authenticate(&mut self, can_id: u32, frame: &mut [u8]) -> u64 {
+        assert!(frame.len() >= OVERHEAD, "frame too short");
+
+        let freshness = self.freshness;
+        self.freshness += 1;
+
+        let (payload, trailer) = frame.split_at_mut(frame.len() - OVERHEAD);
+        let (truncated, tag) = trailer.split_at_mut(FRESHNESS_LEN);
+        truncated.copy_from_slice(&freshness.to_le_bytes()[..FRESHNESS_LEN]);
+        tag.copy_from_slice(&frame_tag(&self.keyed, can_id, freshness, payload));
+        freshness
+    }
+
+    /// Returns a sync message carrying the freshness counter of the next frame, which allows a
+    /// receiver to resynchronize with [`Receiver::resync`].
+    pub fn sync_message(&self, can_id: u32) -> [u8; SYNC_LEN] {
+        let mut msg = [0u8; SYNC_LEN];
+        msg[..8].copy_from_slice(&self.freshness.to_le_bytes());
+        msg[8..].copy_from_slice(&sync_tag(&self.keyed, can_id, self.freshness));
+        msg
+    }
+}
+
+/// The receiving end of an authenticated bus channel.
+#[derive(Clone, Debug)]
+pub struct Receiver<K> {
+    keyed: K,
+    next_freshness: u64,
+}
+
+impl<K> Receiver<K>
+where
+    K: Keyed,
+{
+    /// Creates a new [`Receiver`] with the given keyed duplex, which accepts frames with freshness
+    /// counters of zero or greater.
+    pub const fn new(keyed: K) -> Self {
+        Receiver { keyed, next_freshness: 0 }
+    }
+
+    /// Returns the smallest freshness counter the receiver will accept.
+    pub const fn next_freshness(&self) -> u64 {
+        self.next_freshness
+    }
+
+    /// Verifies a frame with the given CAN ID, returning its payload.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Truncated`] if the frame is shorter than [`OVERHEAD`] and
+    /// [`Error::Inauthentic`] if it cannot be authenticated.
+    pub fn verify<'a>(&mut self, can_id: u32, frame: &'a [u8]) -> Result<&'a [u8], Error> {
+        if frame.len() < OVERHEAD {
+            return Err(Error::Truncated);
+        }
+
+        let (payload, trailer) = frame.split_at(frame.len() - OVERHEAD);
+        let (truncated, tag) = trailer.split_at(FRESHNESS_LEN);
+
+        // Find the smallest acceptable counter with the frame's least significant bytes.
+        let mask = (1 << (8 * FRESHNESS_LEN)) - 1;
+        let truncated = u64::from(u16::from_le_bytes([truncated[0], truncated[1]]));
+        let mut freshness = (self.next_freshness & !mask) | truncated;
+        if freshness < self.next_freshness {
+            freshness += mask + 1;
+        }
+
+        if !constant_time_eq(tag, &frame_tag(&self.keyed, can_id, freshness, payload)) {
+            return Err(Error::Inauthentic);
+        }
+
+        self.next_freshness = freshness + 1;
+        Ok(payload)
+    }
+
+    /// Resynchronizes the receiver with a sync message from [`Transmitter::sync_message`],
+    /// returning the new freshness counter.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Truncated`] if the message isn't [`SYNC_LEN`] bytes long,
+    /// [`Error::Inauthentic`] if it cannot be authenticated, and [`Error::Stale`] if its counter is
+    /// less than the receiver's.
+    pub fn resync(&mut self, can_id: u32, msg: &[u8]) -> Result<u64, Error> {
+        if msg.len() != SYNC_LEN {
+            return Err(Error::Truncated);
+        }
+
+        let (freshness, tag) = msg.split_at(8);
+        let freshness = u64::from_le_bytes(freshness.try_into().expect("invalid counter length"));
+        if !constant_time_eq(tag, &sync_tag(&self.keyed, can_id, freshness)) {
+            return Err(Error::Inauthentic);
+        }
+
+        if freshness < self.next_freshness {
+            return Err(Error::Stale);
+        }
+
+        self.next_freshness = freshness;
+        Ok(freshness)
+    }
+}
+
+/// Returns the tag of a frame.
+fn frame_tag<K: Keyed>(keyed: &K, can_id: u32, freshness: u64, payload: &[u8]) -> [u8; TAG_LEN] {
+    let mut keyed = keyed.clone();
+    keyed.absorb(FRAME_LABEL);
+    keyed.absorb(&can_id.to_le_bytes());
+    keyed.absorb(&freshness.to_le_bytes());
+    keyed.absorb(payload);
+    let mut tag = [0u8; TAG_LEN];
+    keyed.squeeze_mut(&mut tag);
+    tag
+}
+
+/// Returns the tag of a sync message.
+fn sync_tag<K: Keyed>(keyed: &K, can_id: u32, freshness: u64) -> [u8; TAG_LEN] {
+    let mut keyed = keyed.clone();
+    keyed.absorb(SYNC_LABEL);
+    keyed.absorb(&can_id.to_le_bytes());
+    keyed.absorb(&freshness.to_le_bytes());
+    let mut tag = [0u8; TAG_LEN];
+    keyed.squeeze_mut(&mut tag);
+    tag
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakKeyed;
+
+    use super::*;
+
+    const CAN_ID: u32 = 0x123;
+
+    fn keyed() -> XoodyakKeyed {
+        XoodyakKeyed::new(b"a bus key", b"", b"")
+    }
+
+    fn frame(transmitter: &mut Transmitter<XoodyakKeyed>) -> [u8; 8] {
+        let mut frame = [0x12, 0x34, 0, 0, 0, 0, 0, 0];
+        transmitter.authenticate(CAN_ID, &mut frame);
+        frame
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut transmitter = Transmitter::new(keyed());
+        let mut receiver = Receiver::new(keyed());
+        for i in 0..3 {
+            let frame = frame(&mut transmitter);
+            assert_eq!(Ok(&[0x12, 0x34][..]), receiver.verify(CAN_ID, &frame));
+            assert_eq!(i + 1, receiver.next_freshness());
+        }
+
+        let mut frame = [0u8; 64];
+        transmitter.authenticate(CAN_ID, &mut frame);
+        assert_eq!(Ok(&[0u8; 64 - OVERHEAD][..]), receiver.verify(CAN_ID, &frame));
+    }
+
+    #[test]
+    fn replayed_and_modified_frames() {
+        let mut transmitter = Transmitter::new(keyed());
+        let mut receiver = Receiver::new(keyed());
+        let frame = frame(&mut transmitter);
+        assert!(receiver.verify(CAN_ID, &frame).is_ok());
+        assert_eq!(Err(Error::Inauthentic), receiver.verify(CAN_ID, &frame));
+
+        let mut frame_2 = self::frame(&mut transmitter);
+        assert_eq!(Err(Error::Inauthentic), receiver.verify(0x124, &frame_2));
+        frame_2[0] ^= 1;
+        assert_eq!(Err(Error::Inauthentic), receiver.verify(CAN_ID, &frame_2));
+        assert_eq!(Err(Error::Truncated), receiver.verify(CAN_ID, &frame[..OVERHEAD - 1]));
+    }
+
+    #[test]
+    fn lost_frames() {
+        let mut transmitter = Transmitter::with_freshness(keyed(), 0x3_FFF0);
+        let mut receiver = Receiver::new(keyed());
+
+        // The receiver is too far behind to reconstruct the counter.
+        assert_eq!(Err(Error::Inauthentic), receiver.verify(CAN_ID, &frame(&mut transmitter)));
+
+        // Resynchronize, then lose frames across a wrap of the truncated counter.
+        let sync = transmitter.sync_message(CAN_ID);
+        assert_eq!(Ok(0x3_FFF1), receiver.resync(CAN_ID, &sync));
+        for _ in 0..100 {
+            frame(&mut transmitter);
+        }
+        assert!(receiver.verify(CAN_ID, &frame(&mut transmitter)).is_ok());
+        assert_eq!(0x3_FFF1 + 101, receiver.next_freshness());
+    }
+
+    #[test]
+    fn sync_messages() {
+        let mut transmitter = Transmitter::new(keyed());
+        let mut receiver = Receiver::new(keyed());
+        let old_sync = transmitter.sync_message(CAN_ID);
+        frame(&mut transmitter);
+        let sync = transmitter.sync_message(CAN_ID);
+
+        assert_eq!(Err(Error::Inauthentic), receiver.resync(0x124, &sync));
+        assert_eq!(Ok(1), receiver.resync(CAN_ID, &sync));
+        assert_eq!(Err(Error::Stale), receiver.resync(CAN_ID, &old_sync));
+        assert_eq!(Err(Error::Truncated), receiver.resync(CAN_ID, &sync[1..]));
+
+        let mut forged = sync;
+        forged[0] = 0xFF;
+        assert_eq!(Err(Error::Inauthentic), receiver.resync(CAN_ID, &forged));
+        assert!(receiver.verify(CAN_ID, &frame(&mut transmitter)).is_ok());
+    }
+}
@@ -0,0 +1,235 @@
+//! Content-defined chunking with Cyclist chunk digests and manifests.
+//!
+//! A [`Chunker`] splits data into chunks at boundaries determined by the data itself, so that an
+//! insertion or deletion only changes the chunks around it. This makes it suitable for
+//! deduplicating backup and sync tools. Each chunk is identified by its [`chunk_digest`], and the
+//! whole sequence of chunks by a [`Manifest`] digest.
+//!
+//! ```rust
+//! use cyclist::cdc::{chunk_digest, Chunker, Manifest};
+//! use cyclist::xoodyak::XoodyakHash;
+//!
+//! let data = vec![0xCAu8; 100_000];
+//! let chunker = Chunker::new::<XoodyakHash>(1024, 4096, 16384);
+//!
+//! let mut manifest = Manifest::<XoodyakHash>::default();
+//! for chunk in chunker.chunks(&data) {
+//!     manifest.add(&chunk_digest::<XoodyakHash>(chunk), chunk.len());
+//! }
+//! let digest = manifest.finalize();
+//! ```
+//!
+//! # Construction
+//!
+//! Boundaries are found with a gear rolling hash, which shifts its 64-bit state left by one bit and
+//! adds a table entry for each byte. The table is squeezed from a hash which has absorbed a
+//! domain separation label, so the entire pipeline depends only on the chosen Cyclist hash. A
+//! boundary is placed after the first byte past the minimum chunk length at which the top
+//! `log2(avg_len)` bits of the rolling hash are zero, or at the maximum chunk length.
+//!
+//! Chunk digests and manifests are computed with hashes which have absorbed distinct domain
+//! separation labels. A manifest absorbs each chunk's digest and length, then squeezes a digest.
+
+use core::iter::FusedIterator;
+
+use crate::Cyclist;
+
+/// The length of chunk and manifest digests, in bytes.
+pub const DIGEST_LEN: usize = 32;
+
+/// The domain separation label for the gear table.
+pub(crate) const GEAR_LABEL: &[u8] = b"cyclist.cdc.gear";
+
+/// The domain separation label for chunk digests.
+pub(crate) const CHUNK_LABEL: &[u8] = b"cyclist.cdc.chunk";
+
+/// The domain separation label for manifest digests.
+pub(crate) const MANIFEST_LABEL: &[u8] = b"cyclist.cdc.manifest";
+
+/// A content-defined chunker.
+#[derive(Clone, Debug)]
+pub struct Chunker {
+    gear: [u64; 256],
+    mask: u64,
+    min_len: usize,
+    max_len: usize,
+}
+
+impl Chunker {
+    /// Creates a new [`Chunker`] with a gear table derived from the given hash, which produces
+    /// chunks of between `min_len` and `max_len` bytes, averaging roughly `min_len + avg_len`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `avg_len` is not a power of two greater than one, or if `min_len` is zero or
+    /// greater than `max_len`.
+    pub fn new<H: Cyclist + Default>(min_len: usize, avg_len: usize, max_len: usize) -> Self {
+        assert!(avg_len > 1 && avg_len.is_power_of_two(), "average length must be a power of two");
+        assert!(0 < min_len && min_len <= max_len, "invalid minimum or maximum length");
+
+        let mut hash = H::default();
+        hash.absorb(GEAR_LABEL);
+        let mut table = [0u8; 256 * 8];
+        hash.squeeze_mut(&mut table);
+
+        let mut gear = [0u64; 256];
+        for (g, bytes) in gear.iter_mut().zip(table.chunks_exact(8)) {
+            *g = u64::from_le_bytes(bytes.try_into().expect("invalid chunk"));
+        }
+
+        Chunker { gear, mask: !(u64::MAX >> avg_len.trailing_zeros()), min_len, max_len }
+    }
+
+    /// Returns the length of the first chunk of `data`.
+    pub fn next_boundary(&self, data: &[u8]) -> usize {
+        if data.len() <= self.min_len {
+            return data.len();
+        }
+
+        let mut hash = 0u64;
+        let end = data.len().min(self.max_len);
+        for (i, &b) in data.iter().enumerate().take(end).skip(self.min_len) {
+            hash = (hash << 1).wrapping_add(self.gear[usize::from(b)]);
+            if hash & self.mask == 0 {
+                return i + 1;
+            }
+        }
+        end
+    }
+
+    /// Returns an iterator over the chunks of `data`.
+    pub const fn chunks<'a>(&'a self, data: &'a [u8]) -> Chunks<'a> {
+        Chunks { chunker: self, data }
+    }
+}
+
+/// An iterator over the content-defined chunks of a slice.
+#[derive(Clone, Debug)]
+pub struct Chunks<'a> {
+    chunker: &'a Chunker,
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let (chunk, rest) = self.data.split_at(self.chunker.next_boundary(self.data));
+        self.data = rest;
+        Some(chunk)
+    }
+}
+
+impl FusedIterator for Chunks<'_> {}
+
+/// Returns the digest of a chunk.
+pub fn chunk_digest<H: Cyclist + Default>(chunk: &[u8]) -> [u8; DIGEST_LEN] {
+    let mut hash = H::default();
+    hash.absorb(CHUNK_LABEL);
+    hash.absorb(chunk);
+    let mut digest = [0u8; DIGEST_LEN];
+    hash.squeeze_mut(&mut digest);
+    digest
+}
+
+/// A digest of a sequence of chunks.
+#[derive(Clone, Debug)]
+pub struct Manifest<H> {
+    hash: H,
+}
+
+impl<H> Manifest<H>
+where
+    H: Cyclist + Default,
+{
+    /// Adds a chunk with the given digest and length to the manifest.
+    pub fn add(&mut self, digest: &[u8; DIGEST_LEN], len: usize) {
+        self.hash.absorb(digest);
+        self.hash.absorb(&u64::try_from(len).expect("unexpected overflow").to_le_bytes());
+    }
+
+    /// Returns the digest of the manifest.
+    pub fn finalize(mut self) -> [u8; DIGEST_LEN] {
+        let mut digest = [0u8; DIGEST_LEN];
+        self.hash.squeeze_mut(&mut digest);
+        digest
+    }
+}
+
+impl<H> Default for Manifest<H>
+where
+    H: Cyclist + Default,
+{
+    fn default() -> Self {
+        let mut hash = H::default();
+        hash.absorb(MANIFEST_LABEL);
+        Manifest { hash }
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakHash;
+
+    use super::*;
+
+    /// Returns pseudorandom test data.
+    fn data(len: usize) -> Vec<u8> {
+        let mut hash = XoodyakHash::default();
+        hash.absorb(b"test data");
+        hash.squeeze(len)
+    }
+
+    fn chunker() -> Chunker {
+        Chunker::new::<XoodyakHash>(256, 1024, 4096)
+    }
+
+    #[test]
+    fn chunk_lengths() {
+        let data = data(100_000);
+        let chunker = chunker();
+        let chunks = chunker.chunks(&data).collect::<Vec<_>>();
+
+        assert_eq!(data, chunks.concat());
+        assert!(chunks.len() > 10, "too few chunks: {}", chunks.len());
+        for chunk in &chunks[..chunks.len() - 1] {
+            assert!((256..=4096).contains(&chunk.len()), "invalid chunk length: {}", chunk.len());
+        }
+    }
+
+    #[test]
+    fn boundaries_resynchronize() {
+        let data = data(100_000);
+        let mut shifted = b"an insertion".to_vec();
+        shifted.extend(&data);
+
+        let chunker = chunker();
+        let one = chunker.chunks(&data).map(chunk_digest::<XoodyakHash>).collect::<Vec<_>>();
+        let two = chunker.chunks(&shifted).map(chunk_digest::<XoodyakHash>).collect::<Vec<_>>();
+
+        let shared = one.iter().filter(|d| two.contains(d)).count();
+        assert!(shared >= one.len() - 2, "only {} of {} chunks shared", shared, one.len());
+    }
+
+    #[test]
+    fn manifests() {
+        let digest = chunk_digest::<XoodyakHash>(b"chunk");
+
+        let mut one = Manifest::<XoodyakHash>::default();
+        one.add(&digest, 5);
+
+        let mut two = Manifest::<XoodyakHash>::default();
+        two.add(&digest, 6);
+
+        assert_ne!(one.finalize(), two.finalize());
+        assert_ne!(digest, Manifest::<XoodyakHash>::default().finalize());
+    }
+
+    #[test]
+    fn empty_data() {
+        assert_eq!(None, chunker().chunks(&[]).next());
+    }
+}
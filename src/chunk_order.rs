@@ -0,0 +1,168 @@
+#![cfg(feature = "std")]
+
+//! Keyed permutations of chunk transmission order.
+//!
+//! **Research use only.** This module exists for prototyping metadata defenses against traffic
+//! analysis of chunked transfers (e.g. hiding which chunks of a file are sent first). It has not
+//! been analyzed as a defense, reordering alone doesn't hide chunk sizes or timing, and its API may
+//! change or be removed without notice.
+//!
+//! A [`ChunkOrder`] is a permutation of `0..n` derived from a keyed duplex, so the sender and
+//! receiver derive the same order from their shared session, and anyone without the key sees an
+//! unpredictable order:
+//!
+//! ```rust
+//! use cyclist::chunk_order::ChunkOrder;
+//! use cyclist::xoodyak::XoodyakKeyed;
+//!
+//! let session = XoodyakKeyed::new(b"This is a secret key!", b"This is a nonce!", b"");
+//! let order = ChunkOrder::new(&session, 4);
+//!
+//! let sent = order.shuffle(vec!["a", "b", "c", "d"]);
+//! assert_eq!(vec!["a", "b", "c", "d"], order.unshuffle(sent));
+//! ```
+//!
+//! # Construction
+//!
+//! A copy of the duplex absorbs a domain separation label and the number of chunks as a
+//! little-endian 64-bit integer, and the order is a Fisher–Yates shuffle of `0..n` using indexes
+//! squeezed from it in the same way as [`shuffle`](crate::shuffle), so every order is equally
+//! likely. The duplex itself is unmodified.
+
+use crate::shuffle::Indexes;
+use crate::Keyed;
+
+/// The domain separation label for chunk orders.
+const CHUNK_ORDER_LABEL: &[u8] = b"cyclist.chunk-order";
+
+/// A keyed permutation of the transmission order of `n` chunks, and its inverse.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkOrder {
+    order: Vec<usize>,
+    positions: Vec<usize>,
+}
+
+impl ChunkOrder {
+    /// Derives the transmission order of `n` chunks from the given keyed duplex.
+    pub fn new<K: Keyed>(keyed: &K, n: usize) -> ChunkOrder {
+        let mut keyed = keyed.clone();
+        keyed.absorb(CHUNK_ORDER_LABEL);
+        keyed.absorb(&u64::try_from(n).expect("unexpected overflow").to_le_bytes());
+
+        let mut indexes = Indexes::from_duplex(keyed);
+        let mut order = (0..n).collect::<Vec<_>>();
+        for i in (1..n).rev() {
+            order.swap(i, indexes.below(i + 1));
+        }
+
+        let mut positions = vec![0; n];
+        for (position, &chunk) in order.iter().enumerate() {
+            positions[chunk] = position;
+        }
+        ChunkOrder { order, positions }
+    }
+
+    /// Returns the number of chunks.
+    pub const fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Returns `true` if there are no chunks.
+    pub const fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// Returns the indexes of the chunks in transmission order.
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+
+    /// Returns the index of the chunk transmitted at the given position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `position` is out of bounds.
+    pub fn chunk_at(&self, position: usize) -> usize {
+        self.order[position]
+    }
+
+    /// Returns the position at which the given chunk is transmitted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk` is out of bounds.
+    pub fn position_of(&self, chunk: usize) -> usize {
+        self.positions[chunk]
+    }
+
+    /// Reorders the given chunks into transmission order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of chunks doesn't match.
+    pub fn shuffle<T>(&self, chunks: Vec<T>) -> Vec<T> {
+        self.apply(&self.order, chunks)
+    }
+
+    /// Reorders the given chunks from transmission order into their original order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of chunks doesn't match.
+    pub fn unshuffle<T>(&self, chunks: Vec<T>) -> Vec<T> {
+        self.apply(&self.positions, chunks)
+    }
+
+    /// Returns a vec of the given items such that the `i`th element is `items[indexes[i]]`.
+    fn apply<T>(&self, indexes: &[usize], items: Vec<T>) -> Vec<T> {
+        assert_eq!(self.len(), items.len(), "chunk count mismatch");
+        let mut items = items.into_iter().map(Some).collect::<Vec<_>>();
+        indexes.iter().map(|&i| items[i].take().expect("invalid permutation")).collect()
+    }
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakKeyed;
+    use crate::Cyclist;
+
+    use super::*;
+
+    fn keyed(key: &[u8]) -> XoodyakKeyed {
+        XoodyakKeyed::new(key, b"nonce", b"")
+    }
+
+    #[test]
+    fn round_trip() {
+        for n in [0, 1, 2, 10, 100] {
+            let order = ChunkOrder::new(&keyed(b"ok then"), n);
+            assert_eq!(n, order.len());
+
+            let chunks = (0..n).map(|i| format!("chunk {}", i)).collect::<Vec<_>>();
+            let sent = order.shuffle(chunks.clone());
+            for (position, chunk) in sent.iter().enumerate() {
+                assert_eq!(&chunks[order.chunk_at(position)], chunk);
+                assert_eq!(position, order.position_of(order.chunk_at(position)));
+            }
+            assert_eq!(chunks, order.unshuffle(sent));
+        }
+    }
+
+    #[test]
+    fn keyed_orders() {
+        let session = keyed(b"ok then");
+        let order = ChunkOrder::new(&session, 100);
+        assert_ne!((0..100).collect::<Vec<_>>(), order.order());
+        assert_eq!(order, ChunkOrder::new(&session, 100));
+        assert_ne!(order, ChunkOrder::new(&keyed(b"other key"), 100));
+
+        // The duplex is unmodified.
+        assert_eq!(keyed(b"ok then").squeeze(16), session.clone().squeeze(16));
+    }
+
+    #[test]
+    #[should_panic(expected = "chunk count mismatch")]
+    fn mismatched_counts() {
+        ChunkOrder::new(&keyed(b"ok then"), 3).shuffle(vec![1, 2]);
+    }
+}
@@ -0,0 +1,497 @@
+#![cfg(feature = "std")]
+
+//! A conformance suite for Cyclist schemes.
+//!
+//! Crates which add new permutations can check that a scheme built on them behaves like a Cyclist
+//! scheme by implementing [`Scheme`] for a marker type and calling [`run_all`], which runs any
+//! known-answer tests the scheme provides along with a fixed set of property and edge-case checks
+//! and returns a [`Report`] of the results:
+//!
+//! ```rust
+//! use cyclist::conformance::{self, Xoodyak};
+//!
+//! let report = conformance::run_all::<Xoodyak>();
+//! assert!(report.passed(), "{:?}", report.failures().collect::<Vec<_>>());
+//! ```
+//!
+//! Each of the crate's own schemes has a marker type here (e.g. [`Xoodyak`], [`Keccyak128`]),
+//! which the `kat` module and the fuzzing `harness` accept as well.
+//!
+//! The property checks are deterministic, and cover far fewer inputs than the crate's own
+//! property-based tests. Passing them doesn't make a scheme secure, only consistent.
+
+use crate::{Cyclist, Error, Keyed, Rates};
+
+/// A Cyclist scheme to be checked for conformance.
+pub trait Scheme {
+    /// The scheme's name, as it appears in reports.
+    const NAME: &'static str;
+
+    /// The scheme's hash mode.
+    type Hash: Rates + Default;
+
+    /// The scheme's keyed mode.
+    type Keyed: Keyed;
+
+    /// Returns the scheme's known-answer tests, if any.
+    fn known_answers() -> &'static [KnownAnswer] {
+        &[]
+    }
+}
+
+/// A known-answer test.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KnownAnswer {
+    /// A message hashed in hash mode.
+    Hash {
+        /// The name of the test.
+        name: &'static str,
+        /// The message.
+        message: &'static [u8],
+        /// The expected digest.
+        digest: &'static [u8],
+    },
+
+    /// Authenticated data and a plaintext sealed in keyed mode.
+    Aead {
+        /// The name of the test.
+        name: &'static str,
+        /// The key.
+        key: &'static [u8],
+        /// The key ID (i.e. the nonce).
+        key_id: &'static [u8],
+        /// The authenticated data.
+        ad: &'static [u8],
+        /// The plaintext.
+        plaintext: &'static [u8],
+        /// The expected ciphertext, including the tag.
+        ciphertext: &'static [u8],
+    },
+}
+
+impl KnownAnswer {
+    /// Returns the name of the test.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            KnownAnswer::Hash { name, .. } | KnownAnswer::Aead { name, .. } => name,
+        }
+    }
+
+    fn check<S: Scheme>(&self) -> bool {
+        match *self {
+            KnownAnswer::Hash { message, digest, .. } => {
+                let mut hash = S::Hash::default();
+                hash.absorb(message);
+                hash.squeeze(digest.len()) == digest
+            }
+            KnownAnswer::Aead { key, key_id, ad, plaintext, ciphertext, .. } => {
+                let mut keyed = S::Keyed::new(key, key_id, b"");
+                keyed.absorb(ad);
+                let mut out = plaintext.to_vec();
+                out.resize(S::Keyed::sealed_len(plaintext.len()), 0);
+                keyed.seal_mut(&mut out);
+                if out != ciphertext {
+                    return false;
+                }
+
+                let mut keyed = S::Keyed::new(key, key_id, b"");
+                keyed.absorb(ad);
+                keyed.open_mut(&mut out).is_ok() && out[..plaintext.len()] == *plaintext
+            }
+        }
+    }
+}
+
+/// The result of a single conformance check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Check {
+    /// The name of the check.
+    pub name: &'static str,
+
+    /// Whether or not the scheme passed the check.
+    pub passed: bool,
+}
+
+/// The results of running the conformance suite against a scheme.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    /// The name of the scheme.
+    pub scheme: &'static str,
+
+    /// The results of each check, in the order they were run.
+    pub checks: Vec<Check>,
+}
+
+impl Report {
+    /// Returns `true` if the scheme passed every check.
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|c| c.passed)
+    }
+
+    /// Returns the checks the scheme failed.
+    pub fn failures(&self) -> impl Iterator<Item = &Check> + '_ {
+        self.checks.iter().filter(|c| !c.passed)
+    }
+}
+
+/// Runs the scheme's known-answer tests and every property and edge-case check against it.
+pub fn run_all<S: Scheme>() -> Report {
+    let mut checks: Vec<Check> = S::known_answers()
+        .iter()
+        .map(|kat| Check { name: kat.name(), passed: kat.check::<S>() })
+        .collect();
+
+    let properties: [Property; 10] = [
+        ("hash determinism", hash_determinism::<S>),
+        ("hash absorb framing", hash_absorb_framing::<S>),
+        ("hash absorb_more", hash_absorb_more::<S>),
+        ("hash squeeze_more", hash_squeeze_more::<S>),
+        ("keyed input separation", keyed_input_separation::<S>),
+        ("encrypt/decrypt symmetry", encrypt_decrypt_symmetry::<S>),
+        ("seal/open round trip", seal_open_round_trip::<S>),
+        ("tampering detection", tampering_detection::<S>),
+        ("short ciphertexts", short_ciphertexts::<S>),
+        ("ratchet", ratchet::<S>),
+    ];
+    checks.extend(properties.into_iter().map(|(name, check)| Check { name, passed: check() }));
+
+    Report { scheme: S::NAME, checks }
+}
+
+/// A named property check.
+type Property = (&'static str, fn() -> bool);
+
+/// Message lengths around the edges of the scheme's rates.
+fn lengths<C: Rates>() -> Vec<usize> {
+    let mut lengths = vec![0, 1];
+    for rate in [C::ABSORB_RATE, C::SQUEEZE_RATE] {
+        lengths.extend([rate - 1, rate, rate + 1, 2 * rate, 3 * rate + 1]);
+    }
+    lengths.sort_unstable();
+    lengths.dedup();
+    lengths
+}
+
+/// A deterministic, non-repeating message.
+fn message(len: usize) -> Vec<u8> {
+    (0..len).map(|i| u8::try_from(i % 251).expect("unexpected overflow")).collect()
+}
+
+fn hash<S: Scheme>(inputs: &[&[u8]], n: usize) -> Vec<u8> {
+    let mut hash = S::Hash::default();
+    for input in inputs {
+        hash.absorb(input);
+    }
+    hash.squeeze(n)
+}
+
+fn hash_determinism<S: Scheme>() -> bool {
+    lengths::<S::Hash>().into_iter().all(|len| {
+        let m = message(len);
+        let mut m_p = m.clone();
+        m_p.push(0);
+        hash::<S>(&[&m], 32) == hash::<S>(&[&m], 32)
+            && hash::<S>(&[&m], 32) != hash::<S>(&[&m_p], 32)
+    })
+}
+
+fn hash_absorb_framing<S: Scheme>() -> bool {
+    let m = message(100);
+    hash::<S>(&[&m[..50], &m[50..]], 32) != hash::<S>(&[&m], 32)
+        && hash::<S>(&[&m[..50], &m[50..]], 32) != hash::<S>(&[&m[..51], &m[51..]], 32)
+        && hash::<S>(&[b""], 32) != hash::<S>(&[], 32)
+}
+
+fn hash_absorb_more<S: Scheme>() -> bool {
+    let rate = S::Hash::ABSORB_RATE;
+    let m = message(3 * rate + 1);
+
+    let mut a = S::Hash::default();
+    a.absorb(&m);
+
+    let mut b = S::Hash::default();
+    b.absorb(&m[..rate]);
+    b.absorb_more(&m[rate..2 * rate]);
+    b.absorb_more(&m[2 * rate..]);
+
+    a.squeeze(32) == b.squeeze(32)
+}
+
+fn hash_squeeze_more<S: Scheme>() -> bool {
+    let rate = S::Hash::SQUEEZE_RATE;
+
+    let mut a = S::Hash::default();
+    let out = a.squeeze(3 * rate + 1);
+
+    let mut b = S::Hash::default();
+    let mut out_p = b.squeeze(rate);
+    out_p.extend(b.squeeze_more(2 * rate));
+    out_p.extend(b.squeeze_more(1));
+
+    out == out_p && out[..rate] != out[rate..2 * rate]
+}
+
+fn keyed_input_separation<S: Scheme>() -> bool {
+    let outputs = [
+        S::Keyed::new(b"key", b"", b"").squeeze(32),
+        S::Keyed::new(b"kez", b"", b"").squeeze(32),
+        S::Keyed::new(b"key", b"id", b"").squeeze(32),
+        S::Keyed::new(b"key", b"", b"counter").squeeze(32),
+        S::Keyed::new(b"keyi", b"d", b"").squeeze(32),
+        S::Hash::default().squeeze(32),
+    ];
+    outputs.iter().enumerate().all(|(i, a)| outputs[i + 1..].iter().all(|b| a != b))
+}
+
+fn encrypt_decrypt_symmetry<S: Scheme>() -> bool {
+    lengths::<S::Keyed>().into_iter().all(|len| {
+        let m = message(len);
+
+        let mut a = S::Keyed::new(b"key", b"", b"");
+        let mut c = m.clone();
+        a.encrypt_mut(&mut c);
+
+        let mut b = S::Keyed::new(b"key", b"", b"");
+        let mut p = c.clone();
+        b.decrypt_mut(&mut p);
+
+        p == m && (len < 16 || c != m) && a.squeeze(16) == b.squeeze(16)
+    })
+}
+
+fn seal<S: Scheme>(m: &[u8]) -> Vec<u8> {
+    let mut out = m.to_vec();
+    out.resize(S::Keyed::sealed_len(m.len()), 0);
+    S::Keyed::new(b"key", b"", b"").seal_mut(&mut out);
+    out
+}
+
+fn open<S: Scheme>(c: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut out = c.to_vec();
+    S::Keyed::new(b"key", b"", b"").open_mut(&mut out)?;
+    out.truncate(S::Keyed::opened_len(c.len()).ok_or(Error::BufferTooSmall)?);
+    Ok(out)
+}
+
+fn seal_open_round_trip<S: Scheme>() -> bool {
+    lengths::<S::Keyed>().into_iter().all(|len| {
+        let m = message(len);
+        let c = seal::<S>(&m);
+        c.len() == len + S::Keyed::OVERHEAD && open::<S>(&c) == Ok(m)
+    })
+}
+
+fn tampering_detection<S: Scheme>() -> bool {
+    lengths::<S::Keyed>().into_iter().all(|len| {
+        let c = seal::<S>(&message(len));
+        (0..c.len()).all(|i| {
+            let mut c_p = c.clone();
+            c_p[i] ^= 1;
+            open::<S>(&c_p) == Err(Error::InvalidTag)
+        })
+    })
+}
+
+fn short_ciphertexts<S: Scheme>() -> bool {
+    (0..S::Keyed::OVERHEAD).all(|len| open::<S>(&message(len)) == Err(Error::BufferTooSmall))
+}
+
+fn ratchet<S: Scheme>() -> bool {
+    let mut a = S::Keyed::new(b"key", b"", b"");
+    let mut b = a.clone();
+    let mut c = a.clone();
+    b.ratchet();
+    c.ratchet();
+
+    let (a, b, c) = (a.squeeze(32), b.squeeze(32), c.squeeze(32));
+    a != b && b == c
+}
+
+/// Xoodyak, with the XKCP known-answer tests.
+#[cfg(feature = "xoodyak")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Xoodyak;
+
+#[cfg(feature = "xoodyak")]
+impl Scheme for Xoodyak {
+    const NAME: &'static str = "Xoodyak";
+
+    type Hash = crate::xoodyak::XoodyakHash;
+
+    type Keyed = crate::xoodyak::XoodyakKeyed;
+
+    fn known_answers() -> &'static [KnownAnswer] {
+        &[
+            // from https://github.com/XKCP/XKCP/blob/2a8d2311a830ab3037f8c7ef2511e5c7cc032127/tests/SUPERCOP/Xoodyak_hash/selftest.c
+            KnownAnswer::Hash {
+                name: "XKCP SUPERCOP hash",
+                message: &[0x11, 0x97, 0x13, 0xCC, 0x83, 0xEE, 0xEF],
+                digest: &[
+                    0x99, 0x9d, 0x58, 0x65, 0xb0, 0xdd, 0x9f, 0xa3, 0x09, 0x73, 0x36, 0x5f, 0xec,
+                    0xf0, 0x41, 0x77, 0x8d, 0x04, 0x49, 0xa1, 0xb0, 0xc5, 0x5b, 0x74, 0x36, 0x60,
+                    0x83, 0x1a, 0x7d, 0x50, 0x25, 0xee,
+                ],
+            },
+            // from https://github.com/XKCP/XKCP/blob/2a8d2311a830ab3037f8c7ef2511e5c7cc032127/tests/SUPERCOP/Xoodyak_aead_round3/selftest.c
+            KnownAnswer::Aead {
+                name: "XKCP SUPERCOP AEAD",
+                key: &[
+                    0x5a, 0x4b, 0x3c, 0x2d, 0x1e, 0x0f, 0x00, 0xf1, 0xe2, 0xd3, 0xc4, 0xb5, 0xa6,
+                    0x97, 0x88, 0x79,
+                ],
+                key_id: &[
+                    0x6b, 0x4c, 0x2d, 0x0e, 0xef, 0xd0, 0xb1, 0x92, 0x72, 0x53, 0x34, 0x15, 0xf6,
+                    0xd7, 0xb8, 0x99,
+                ],
+                ad: &[0x32, 0xf3, 0xb4, 0x75, 0x35, 0xf6],
+                plaintext: &[0xe4, 0x65, 0xe5, 0x66, 0xe6, 0x67, 0xe7],
+                ciphertext: &[
+                    0x6e, 0x68, 0x08, 0x1c, 0x7e, 0xac, 0xbf, 0x72, 0xe2, 0xa6, 0x77, 0xa6, 0x0e,
+                    0x44, 0x27, 0x48, 0xd7, 0xa8, 0x6e, 0x78, 0x8e, 0xb9, 0xd4,
+                ],
+            },
+        ]
+    }
+}
+
+/// Declares a marker type for one of the crate's schemes which has no known-answer tests.
+macro_rules! scheme {
+    ($(#[$attr:meta])* $feature:literal, $name:ident, $hash:ty, $keyed:ty) => {
+        $(#[$attr])*
+        #[cfg(feature = $feature)]
+        #[derive(Clone, Copy, Debug, Default)]
+        pub struct $name;
+
+        #[cfg(feature = $feature)]
+        impl Scheme for $name {
+            const NAME: &'static str = stringify!($name);
+
+            type Hash = $hash;
+
+            type Keyed = $keyed;
+        }
+    };
+}
+
+scheme!(
+    /// KeccyakMax, with Keccak-f\[1600\].
+    "keccyak",
+    KeccyakMax,
+    crate::keccyak::KeccyakMaxHash,
+    crate::keccyak::KeccyakMaxKeyed
+);
+
+scheme!(
+    /// Keccyak256, with Keccak-p\[1600, 14\].
+    "keccyak",
+    Keccyak256,
+    crate::keccyak::Keccyak256Hash,
+    crate::keccyak::Keccyak256Keyed
+);
+
+scheme!(
+    /// Keccyak128, with Keccak-p\[1600, 12\].
+    "keccyak",
+    Keccyak128,
+    crate::keccyak::Keccyak128Hash,
+    crate::keccyak::Keccyak128Keyed
+);
+
+scheme!(
+    /// KeccyakMin, with Keccak-p\[1600, 10\].
+    "keccyak",
+    KeccyakMin,
+    crate::keccyak::KeccyakMinHash,
+    crate::keccyak::KeccyakMinKeyed
+);
+
+scheme!(
+    /// Keccyak800, with Keccak-p\[800\].
+    "keccyak",
+    Keccyak800,
+    crate::keccyak::Keccyak800Hash,
+    crate::keccyak::Keccyak800Keyed
+);
+
+scheme!(
+    /// Keccyak400, with Keccak-p\[400\].
+    "keccyak",
+    Keccyak400,
+    crate::keccyak::Keccyak400Hash,
+    crate::keccyak::Keccyak400Keyed
+);
+
+scheme!(
+    /// Keccyak200, with Keccak-p\[200\].
+    "keccyak",
+    Keccyak200,
+    crate::keccyak::Keccyak200Hash,
+    crate::keccyak::Keccyak200Keyed
+);
+
+scheme!(
+    /// Cyclist with the Ascon permutation.
+    "ascon",
+    Ascon,
+    crate::ascon::AsconCyclistHash,
+    crate::ascon::AsconCyclistKeyed
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_conforms<S: Scheme>() {
+        let report = run_all::<S>();
+        assert_eq!(S::NAME, report.scheme);
+        assert!(report.passed(), "{}: {:?}", S::NAME, report.failures().collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[cfg(feature = "xoodyak")]
+    fn xoodyak() {
+        assert_conforms::<Xoodyak>();
+        assert_eq!(12, run_all::<Xoodyak>().checks.len());
+    }
+
+    #[test]
+    #[cfg(feature = "keccyak")]
+    fn keccyak() {
+        assert_conforms::<KeccyakMax>();
+        assert_conforms::<Keccyak256>();
+        assert_conforms::<Keccyak128>();
+        assert_conforms::<KeccyakMin>();
+        assert_conforms::<Keccyak800>();
+        assert_conforms::<Keccyak400>();
+        assert_conforms::<Keccyak200>();
+    }
+
+    #[test]
+    #[cfg(feature = "ascon")]
+    fn ascon() {
+        assert_conforms::<Ascon>();
+    }
+
+    #[test]
+    #[cfg(feature = "xoodyak")]
+    fn failing_known_answer() {
+        struct Broken;
+
+        impl Scheme for Broken {
+            const NAME: &'static str = "Broken";
+            type Hash = crate::xoodyak::XoodyakHash;
+            type Keyed = crate::xoodyak::XoodyakKeyed;
+
+            fn known_answers() -> &'static [KnownAnswer] {
+                &[KnownAnswer::Hash { name: "wrong digest", message: b"", digest: &[0; 16] }]
+            }
+        }
+
+        let report = run_all::<Broken>();
+        assert!(!report.passed());
+        assert_eq!(
+            vec![&Check { name: "wrong digest", passed: false }],
+            report.failures().collect::<Vec<_>>()
+        );
+    }
+}
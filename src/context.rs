@@ -0,0 +1,681 @@
+//! Compact security contexts for constrained request/response protocols (e.g. CoAP).
+//!
+//! Modeled on [OSCORE][oscore], a [`SecurityContext`] derives a sender key, a recipient key, and a
+//! common IV from a master secret, an optional master salt and ID context, and the sender and
+//! recipient IDs of the two endpoints. Requests are sealed with the sender's next sequence number,
+//! whose compact encoding (its [`PartialIv`]) is carried in the message along with the sender's ID,
+//! and responses are sealed with the nonce of the request they answer. Recipients reject replayed
+//! requests with a sliding window of recently seen sequence numbers, or with any other
+//! [`ReplayCache`] passed to [`SecurityContext::with_replay_cache`].
+//!
+//! [oscore]: https://www.rfc-editor.org/rfc/rfc8613
+//!
+//! ```rust
+//! use cyclist::context::{decode_option, encode_option, SecurityContext, MAX_OPTION_LEN};
+//! use cyclist::xoodyak::XoodyakKeyed;
+//! use cyclist::Keyed;
+//!
+//! let secret = b"a master secret";
+//! let mut client = SecurityContext::<XoodyakKeyed>::new(secret, b"", b"", b"", b"\x01")
+//!     .expect("should be valid");
+//! let mut server = SecurityContext::<XoodyakKeyed>::new(secret, b"", b"", b"\x01", b"")
+//!     .expect("should be valid");
+//!
+//! // The client seals a request and encodes its partial IV and ID as an option.
+//! let mut request = [0u8; 16 + 16];
+//! request[..16].copy_from_slice(b"GET /temperature");
+//! let piv = client.seal_request(b"", &mut request).expect("should seal");
+//! let mut option = [0u8; MAX_OPTION_LEN];
+//! let n = encode_option(&piv, Some(client.sender_id()), None, &mut option);
+//!
+//! // The server decodes the option and opens the request.
+//! let option = decode_option(&option[..n]).expect("should decode");
+//! assert_eq!(Some(server.recipient_id()), option.kid);
+//! server.open_request(option.partial_iv, b"", &mut request).expect("should open");
+//!
+//! // The server seals a response with the request's nonce, which the client opens.
+//! let mut response = [0u8; 3 + 16];
+//! response[..3].copy_from_slice(b"21C");
+//! server.seal_response(option.partial_iv, b"", &mut response).expect("should seal");
+//! client.open_response(&piv, b"", &mut response).expect("should open");
+//! assert_eq!(b"21C", &response[..3]);
+//! ```
+//!
+//! # Construction
+//!
+//! A keyed duplex is initialized with the master secret and absorbs a domain separation label, the
+//! master salt, and the ID context. For each of the sender and recipient keys, a copy absorbs the
+//! endpoint's ID and squeezes a [`KEY_LEN`]-byte key (or one byte less than the absorb rate, for
+//! schemes which can't absorb a key that long); the common IV is squeezed after absorbing an
+//! empty ID. As in OSCORE, a nonce is the common IV XORed with the length of an endpoint's ID, the
+//! ID, and a partial IV, each left-padded with zeros. A message is sealed with a keyed duplex which
+//! is initialized with the appropriate key and absorbs the nonce and the associated data.
+
+use core::fmt;
+
+use crate::replay::{BitmapWindow, ReplayCache};
+use crate::{wipe, Keyed};
+
+/// The length of derived keys, in bytes.
+pub const KEY_LEN: usize = 32;
+
+/// The length of nonces and the common IV, in bytes.
+pub const NONCE_LEN: usize = 13;
+
+/// The maximum length of a partial IV, in bytes.
+pub const MAX_PARTIAL_IV_LEN: usize = 5;
+
+/// The maximum length of a sender or recipient ID, in bytes.
+pub const MAX_ID_LEN: usize = NONCE_LEN - MAX_PARTIAL_IV_LEN - 1;
+
+/// The maximum length of an ID context, in bytes.
+pub const MAX_ID_CONTEXT_LEN: usize = 255;
+
+/// The maximum length of an encoded option with a sender ID and no ID context, in bytes.
+pub const MAX_OPTION_LEN: usize = 1 + MAX_PARTIAL_IV_LEN + MAX_ID_LEN;
+
+/// The largest sequence number which can be encoded in a partial IV.
+pub const MAX_SEQUENCE_NUMBER: u64 = (1 << (8 * MAX_PARTIAL_IV_LEN)) - 1;
+
+/// The number of sequence numbers below the highest one seen which recipients keep track of by
+/// default.
+pub const REPLAY_WINDOW_LEN: u64 = 64;
+
+/// The domain separation label for security contexts.
+const CONTEXT_LABEL: &[u8] = b"cyclist.context";
+
+/// An error creating a security context or sealing or opening a message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// A sender or recipient ID was longer than [`MAX_ID_LEN`], or the sender and recipient IDs
+    /// were the same.
+    InvalidId,
+
+    /// The sender's sequence numbers have been exhausted, and the context must be rederived.
+    SequenceExhausted,
+
+    /// A partial IV was empty or longer than [`MAX_PARTIAL_IV_LEN`].
+    InvalidPartialIv,
+
+    /// The request's sequence number had already been seen, or was too old to tell.
+    Replayed,
+
+    /// The message could not be authenticated.
+    Inauthentic,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidId => write!(f, "invalid sender or recipient id"),
+            Error::SequenceExhausted => write!(f, "sequence numbers exhausted"),
+            Error::InvalidPartialIv => write!(f, "invalid partial iv"),
+            Error::Replayed => write!(f, "replayed request"),
+            Error::Inauthentic => write!(f, "inauthentic message"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// A sequence number in its compact, big-endian encoding without leading zeros.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PartialIv {
+    buf: [u8; MAX_PARTIAL_IV_LEN],
+    len: usize,
+}
+
+impl PartialIv {
+    /// Returns the partial IV of the given sequence number.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sequence_number` is greater than [`MAX_SEQUENCE_NUMBER`].
+    pub fn new(sequence_number: u64) -> Self {
+        assert!(sequence_number <= MAX_SEQUENCE_NUMBER, "sequence number out of range");
+        let mut buf = [0u8; MAX_PARTIAL_IV_LEN];
+        buf.copy_from_slice(&sequence_number.to_be_bytes()[8 - MAX_PARTIAL_IV_LEN..]);
+        let zeros = buf.iter().take_while(|&&b| b == 0).count().min(MAX_PARTIAL_IV_LEN - 1);
+        buf.copy_within(zeros.., 0);
+        PartialIv { buf, len: MAX_PARTIAL_IV_LEN - zeros }
+    }
+
+    /// Returns the sequence number encoded in the given partial IV, or `None` if it is empty or
+    /// longer than [`MAX_PARTIAL_IV_LEN`].
+    pub fn sequence_number(piv: &[u8]) -> Option<u64> {
+        if piv.is_empty() || piv.len() > MAX_PARTIAL_IV_LEN {
+            return None;
+        }
+        let mut buf = [0u8; 8];
+        buf[8 - piv.len()..].copy_from_slice(piv);
+        Some(u64::from_be_bytes(buf))
+    }
+}
+
+impl AsRef<[u8]> for PartialIv {
+    fn as_ref(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl core::ops::Deref for PartialIv {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_ref()
+    }
+}
+
+/// The fields of a decoded option.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OptionValue<'a> {
+    /// The partial IV, which is empty in most responses.
+    pub partial_iv: &'a [u8],
+    /// The ID of the sender, if present.
+    pub kid: Option<&'a [u8]>,
+    /// The ID context, if present.
+    pub kid_context: Option<&'a [u8]>,
+}
+
+/// Encodes a partial IV, an optional sender ID, and an optional ID context into `out` in the OSCORE
+/// option format, returning the encoded length. If all three are empty or absent, the encoded
+/// option is empty.
+///
+/// # Panics
+///
+/// Panics if the partial IV is longer than [`MAX_PARTIAL_IV_LEN`], the ID context is longer than
+/// [`MAX_ID_CONTEXT_LEN`], or `out` is too short.
+pub fn encode_option(
+    partial_iv: &[u8],
+    kid: Option<&[u8]>,
+    kid_context: Option<&[u8]>,
+    out: &mut [u8],
+) -> usize {
+    assert!(partial_iv.len() <= MAX_PARTIAL_IV_LEN, "partial iv too long");
+    if partial_iv.is_empty() && kid.is_none() && kid_context.is_none() {
+        return 0;
+    }
+
+    let mut n = 1;
+    let mut push = |b: &[u8]| {
+        out[n..n + b.len()].copy_from_slice(b);
+        n += b.len();
+    };
+    push(partial_iv);
+    if let Some(kid_context) = kid_context {
+        push(&[u8::try_from(kid_context.len()).expect("id context too long")]);
+        push(kid_context);
+    }
+    if let Some(kid) = kid {
+        push(kid);
+    }
+
+    let n_bits = u8::try_from(partial_iv.len()).expect("unexpected overflow");
+    out[0] = n_bits | (u8::from(kid.is_some()) << 3) | (u8::from(kid_context.is_some()) << 4);
+    n
+}
+
+/// Decodes an option in the OSCORE option format, or returns `None` if it is malformed.
+pub fn decode_option(option: &[u8]) -> Option<OptionValue<'_>> {
+    let (&flags, rest) = match option.split_first() {
+        Some(split) => split,
+        None => return Some(OptionValue { partial_iv: b"", kid: None, kid_context: None }),
+    };
+
+    let n = usize::from(flags & 0x07);
+    if flags & 0xE0 != 0 || n > MAX_PARTIAL_IV_LEN || rest.len() < n {
+        return None;
+    }
+    let (partial_iv, mut rest) = rest.split_at(n);
+
+    let mut kid_context = None;
+    if flags & 0x10 != 0 {
+        let (&s, tail) = rest.split_first()?;
+        if tail.len() < usize::from(s) {
+            return None;
+        }
+        let (ctx, tail) = tail.split_at(usize::from(s));
+        kid_context = Some(ctx);
+        rest = tail;
+    }
+
+    let kid = if flags & 0x08 != 0 {
+        Some(rest)
+    } else if rest.is_empty() {
+        None
+    } else {
+        return None;
+    };
+
+    Some(OptionValue { partial_iv, kid, kid_context })
+}
+
+/// An endpoint's view of a security context shared with another endpoint.
+#[derive(Clone, Debug)]
+pub struct SecurityContext<K, R = BitmapWindow<1>> {
+    sender: K,
+    recipient: K,
+    sender_id: ([u8; MAX_ID_LEN], usize),
+    recipient_id: ([u8; MAX_ID_LEN], usize),
+    common_iv: [u8; NONCE_LEN],
+    sequence_number: u64,
+    replay_cache: R,
+}
+
+impl<K> SecurityContext<K>
+where
+    K: Keyed,
+{
+    /// Derives a security context from the given master secret, master salt, ID context, and the
+    /// IDs of this endpoint (the sender) and the other endpoint (the recipient), which rejects
+    /// replayed requests with a window of [`REPLAY_WINDOW_LEN`] sequence numbers.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidId`] if either ID is longer than [`MAX_ID_LEN`], or if they are the
+    /// same.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the master secret is empty or too long to initialize a keyed duplex.
+    pub fn new(
+        master_secret: &[u8],
+        master_salt: &[u8],
+        id_context: &[u8],
+        sender_id: &[u8],
+        recipient_id: &[u8],
+    ) -> Result<Self, Error> {
+        Self::with_replay_cache(
+            master_secret,
+            master_salt,
+            id_context,
+            sender_id,
+            recipient_id,
+            BitmapWindow::default(),
+        )
+    }
+}
+
+impl<K, R> SecurityContext<K, R>
+where
+    K: Keyed,
+    R: ReplayCache,
+{
+    /// Derives a security context like [`SecurityContext::new`], but which rejects replayed
+    /// requests with the given replay cache.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidId`] if either ID is longer than [`MAX_ID_LEN`], or if they are the
+    /// same.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the master secret is empty or too long to initialize a keyed duplex.
+    pub fn with_replay_cache(
+        master_secret: &[u8],
+        master_salt: &[u8],
+        id_context: &[u8],
+        sender_id: &[u8],
+        recipient_id: &[u8],
+        replay_cache: R,
+    ) -> Result<Self, Error> {
+        if sender_id.len() > MAX_ID_LEN || recipient_id.len() > MAX_ID_LEN {
+            return Err(Error::InvalidId);
+        }
+        if sender_id == recipient_id {
+            return Err(Error::InvalidId);
+        }
+
+        let mut kdf = K::new(master_secret, b"", b"");
+        kdf.absorb(CONTEXT_LABEL);
+        kdf.absorb(master_salt);
+        kdf.absorb(id_context);
+
+        let derive_key = |id: &[u8]| {
+            let mut kdf = kdf.clone();
+            kdf.absorb(id);
+            kdf.absorb(b"key");
+            let mut key = [0u8; KEY_LEN];
+            let key = &mut key[..KEY_LEN.min(K::ABSORB_RATE - 1)];
+            kdf.squeeze_key_mut(key);
+            let keyed = K::new(key, b"", b"");
+            wipe(key);
+            keyed
+        };
+        let (sender, recipient) = (derive_key(sender_id), derive_key(recipient_id));
+
+        let mut common_iv = [0u8; NONCE_LEN];
+        kdf.absorb(b"");
+        kdf.absorb(b"iv");
+        kdf.squeeze_mut(&mut common_iv);
+
+        let id = |id: &[u8]| {
+            let mut buf = [0u8; MAX_ID_LEN];
+            buf[..id.len()].copy_from_slice(id);
+            (buf, id.len())
+        };
+
+        Ok(SecurityContext {
+            sender,
+            recipient,
+            sender_id: id(sender_id),
+            recipient_id: id(recipient_id),
+            common_iv,
+            sequence_number: 0,
+            replay_cache,
+        })
+    }
+
+    /// Returns this endpoint's ID.
+    pub fn sender_id(&self) -> &[u8] {
+        &self.sender_id.0[..self.sender_id.1]
+    }
+
+    /// Returns the other endpoint's ID.
+    pub fn recipient_id(&self) -> &[u8] {
+        &self.recipient_id.0[..self.recipient_id.1]
+    }
+
+    /// Seals a request in place with the sender's next sequence number, and returns the sequence
+    /// number's partial IV. The last `K::TAG_LEN` bytes of `in_out` will be overwritten with the
+    /// authentication tag.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::SequenceExhausted`] if the sender's sequence numbers have been exhausted.
+    pub fn seal_request(&mut self, ad: &[u8], in_out: &mut [u8]) -> Result<PartialIv, Error> {
+        if self.sequence_number > MAX_SEQUENCE_NUMBER {
+            return Err(Error::SequenceExhausted);
+        }
+
+        let piv = PartialIv::new(self.sequence_number);
+        self.sequence_number += 1;
+        let nonce = self.nonce(self.sender_id(), &piv);
+        self.sealer(&nonce, ad).seal_mut(in_out);
+        Ok(piv)
+    }
+
+    /// Opens a request from the other endpoint in place, given the partial IV from its option. The
+    /// last `K::TAG_LEN` bytes of `in_out` will be unmodified.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidPartialIv`] if the partial IV is malformed, [`Error::Replayed`] if
+    /// its sequence number has already been seen or is too old to tell, and [`Error::Inauthentic`]
+    /// if the request cannot be authenticated.
+    pub fn open_request(
+        &mut self,
+        partial_iv: &[u8],
+        ad: &[u8],
+        in_out: &mut [u8],
+    ) -> Result<(), Error> {
+        let seq = PartialIv::sequence_number(partial_iv).ok_or(Error::InvalidPartialIv)?;
+        if !self.replay_cache.is_fresh(seq) {
+            return Err(Error::Replayed);
+        }
+
+        let nonce = self.nonce(self.recipient_id(), partial_iv);
+        if self.opener(&nonce, ad).open_mut(in_out).is_err() {
+            return Err(Error::Inauthentic);
+        }
+
+        self.replay_cache.mark_seen(seq);
+        Ok(())
+    }
+
+    /// Seals a response in place to the other endpoint's request, given the request's partial IV.
+    /// The last `K::TAG_LEN` bytes of `in_out` will be overwritten with the authentication tag.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidPartialIv`] if the partial IV is malformed.
+    pub fn seal_response(
+        &self,
+        request_partial_iv: &[u8],
+        ad: &[u8],
+        in_out: &mut [u8],
+    ) -> Result<(), Error> {
+        PartialIv::sequence_number(request_partial_iv).ok_or(Error::InvalidPartialIv)?;
+        let nonce = self.nonce(self.recipient_id(), request_partial_iv);
+        self.sealer(&nonce, ad).seal_mut(in_out);
+        Ok(())
+    }
+
+    /// Opens a response in place to this endpoint's request, given the request's partial IV. The
+    /// last `K::TAG_LEN` bytes of `in_out` will be unmodified.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidPartialIv`] if the partial IV is malformed and
+    /// [`Error::Inauthentic`] if the response cannot be authenticated.
+    pub fn open_response(
+        &self,
+        request_partial_iv: &[u8],
+        ad: &[u8],
+        in_out: &mut [u8],
+    ) -> Result<(), Error> {
+        PartialIv::sequence_number(request_partial_iv).ok_or(Error::InvalidPartialIv)?;
+        let nonce = self.nonce(self.sender_id(), request_partial_iv);
+        if self.opener(&nonce, ad).open_mut(in_out).is_err() {
+            return Err(Error::Inauthentic);
+        }
+        Ok(())
+    }
+
+    /// Returns the nonce for the given endpoint ID and partial IV.
+    fn nonce(&self, id: &[u8], partial_iv: &[u8]) -> [u8; NONCE_LEN] {
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce[0] = u8::try_from(id.len()).expect("unexpected overflow");
+        nonce[NONCE_LEN - MAX_PARTIAL_IV_LEN - id.len()..NONCE_LEN - MAX_PARTIAL_IV_LEN]
+            .copy_from_slice(id);
+        nonce[NONCE_LEN - partial_iv.len()..].copy_from_slice(partial_iv);
+        for (n, iv) in nonce.iter_mut().zip(&self.common_iv) {
+            *n ^= iv;
+        }
+        nonce
+    }
+
+    /// Returns a copy of the sender's keyed duplex which has absorbed the nonce and the associated
+    /// data.
+    fn sealer(&self, nonce: &[u8], ad: &[u8]) -> K {
+        let mut keyed = self.sender.clone();
+        keyed.absorb(nonce);
+        keyed.absorb(ad);
+        keyed
+    }
+
+    /// Returns a copy of the recipient's keyed duplex which has absorbed the nonce and the
+    /// associated data.
+    fn opener(&self, nonce: &[u8], ad: &[u8]) -> K {
+        let mut keyed = self.recipient.clone();
+        keyed.absorb(nonce);
+        keyed.absorb(ad);
+        keyed
+    }
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::replay::FixedCache;
+    use crate::xoodyak::XoodyakKeyed;
+
+    use super::*;
+
+    type Context = SecurityContext<XoodyakKeyed>;
+
+    fn contexts() -> (Context, Context) {
+        let client = Context::new(b"secret", b"salt", b"ctx", b"", b"\x01").expect("should derive");
+        let server = Context::new(b"secret", b"salt", b"ctx", b"\x01", b"").expect("should derive");
+        (client, server)
+    }
+
+    #[cfg(feature = "keccyak")]
+    #[test]
+    fn small_states() {
+        type Context = SecurityContext<crate::keccyak::Keccyak200Keyed>;
+        let mut client = Context::new(b"secret", b"salt", b"ctx", b"", b"\x01").expect("ok");
+        let mut server = Context::new(b"secret", b"salt", b"ctx", b"\x01", b"").expect("ok");
+        let mut request = *b"ping\0\0\0\0\0\0\0\0";
+        let piv = client.seal_request(b"ad", &mut request).expect("should seal");
+        assert_eq!(Ok(()), server.open_request(&piv, b"ad", &mut request));
+        assert_eq!(b"ping", &request[..4]);
+    }
+
+    #[test]
+    fn partial_ivs() {
+        assert_eq!(&[0x00], PartialIv::new(0).as_ref());
+        assert_eq!(&[0x01], PartialIv::new(1).as_ref());
+        assert_eq!(&[0x01, 0x00], PartialIv::new(256).as_ref());
+        assert_eq!(&[0xFF; 5], PartialIv::new(MAX_SEQUENCE_NUMBER).as_ref());
+
+        assert_eq!(Some(256), PartialIv::sequence_number(&[0x01, 0x00]));
+        assert_eq!(None, PartialIv::sequence_number(&[]));
+        assert_eq!(None, PartialIv::sequence_number(&[0x01; 6]));
+    }
+
+    #[test]
+    fn options() {
+        let mut out = [0u8; 32];
+        assert_eq!(0, encode_option(b"", None, None, &mut out));
+        assert_eq!(
+            Some(OptionValue { partial_iv: b"", kid: None, kid_context: None }),
+            decode_option(b"")
+        );
+
+        // The example from RFC 8613, appendix C.4.
+        let n = encode_option(&[0x14], Some(b""), None, &mut out);
+        assert_eq!(&[0x09, 0x14], &out[..n]);
+
+        let n = encode_option(&[0x05], Some(b"\x42"), Some(b"\x37\xcb\xf3"), &mut out);
+        assert_eq!(&[0x19, 0x05, 0x03, 0x37, 0xcb, 0xf3, 0x42], &out[..n]);
+        assert_eq!(
+            Some(OptionValue {
+                partial_iv: &[0x05],
+                kid: Some(b"\x42"),
+                kid_context: Some(b"\x37\xcb\xf3")
+            }),
+            decode_option(&out[..n])
+        );
+
+        assert_eq!(None, decode_option(&[0x02, 0x05]));
+        assert_eq!(None, decode_option(&[0x01, 0x05, 0x42]));
+        assert_eq!(None, decode_option(&[0x10, 0x03, 0x37]));
+        assert_eq!(None, decode_option(&[0x20]));
+    }
+
+    #[test]
+    fn requests_and_responses() {
+        let (mut client, mut server) = contexts();
+        for i in 0..3 {
+            let mut request = [0u8; 20];
+            request[..4].copy_from_slice(b"ping");
+            let piv = client.seal_request(b"ad", &mut request).expect("should seal");
+            assert_eq!(PartialIv::new(i), piv);
+            assert_ne!(b"ping", &request[..4]);
+
+            assert_eq!(Ok(()), server.open_request(&piv, b"ad", &mut request));
+            assert_eq!(b"ping", &request[..4]);
+
+            let mut response = [0u8; 20];
+            response[..4].copy_from_slice(b"pong");
+            assert_eq!(Ok(()), server.seal_response(&piv, b"ad", &mut response));
+            assert_eq!(Ok(()), client.open_response(&piv, b"ad", &mut response));
+            assert_eq!(b"pong", &response[..4]);
+        }
+    }
+
+    #[test]
+    fn replayed_requests() {
+        let (mut client, mut server) = contexts();
+        let mut requests = (0..100)
+            .map(|_| {
+                let mut request = [0u8; 16];
+                let piv = client.seal_request(b"", &mut request).expect("should seal");
+                (piv, request)
+            })
+            .collect::<Vec<_>>();
+
+        let (piv, mut request) = requests[50];
+        assert_eq!(Ok(()), server.open_request(&piv, b"", &mut request));
+        let (piv, mut request) = requests[50];
+        assert_eq!(Err(Error::Replayed), server.open_request(&piv, b"", &mut request));
+
+        // Requests within the window can arrive out of order, but older ones are rejected.
+        let (piv, request) = &mut requests[20];
+        assert_eq!(Ok(()), server.open_request(piv, b"", request));
+        let (piv, request) = &mut requests[99];
+        assert_eq!(Ok(()), server.open_request(piv, b"", request));
+        let (piv, request) = &mut requests[30];
+        assert_eq!(Err(Error::Replayed), server.open_request(piv, b"", request));
+        let (piv, request) = &mut requests[36];
+        assert_eq!(Ok(()), server.open_request(piv, b"", request));
+    }
+
+    #[test]
+    fn custom_replay_caches() {
+        let (mut client, _) = contexts();
+        let mut server = SecurityContext::<XoodyakKeyed, _>::with_replay_cache(
+            b"secret",
+            b"salt",
+            b"ctx",
+            b"\x01",
+            b"",
+            FixedCache::<2>::default(),
+        )
+        .expect("should derive");
+
+        let requests = (0..3)
+            .map(|_| {
+                let mut request = [0u8; 16];
+                let piv = client.seal_request(b"", &mut request).expect("should seal");
+                (piv, request)
+            })
+            .collect::<Vec<_>>();
+
+        for &(piv, mut request) in &requests {
+            assert_eq!(Ok(()), server.open_request(&piv, b"", &mut request));
+        }
+
+        // The first request was forgotten, but is still rejected.
+        let (piv, mut request) = requests[0];
+        assert_eq!(Err(Error::Replayed), server.open_request(&piv, b"", &mut request));
+    }
+
+    #[test]
+    fn inauthentic_messages() {
+        let (mut client, mut server) = contexts();
+        let mut request = [0u8; 16];
+        let piv = client.seal_request(b"", &mut request).expect("should seal");
+        assert_eq!(Err(Error::Inauthentic), server.open_request(&piv, b"other", &mut request));
+        assert_eq!(Err(Error::Inauthentic), server.open_request(&[0x01], b"", &mut request));
+
+        // Responses are bound to their requests.
+        let mut response = [0u8; 16];
+        assert_eq!(Ok(()), server.seal_response(&piv, b"", &mut response));
+        assert_eq!(Err(Error::Inauthentic), client.open_response(&[0x01], b"", &mut response));
+        assert_eq!(Err(Error::InvalidPartialIv), client.open_response(&[], b"", &mut response));
+
+        // A request can't be replayed as a response.
+        let mut request = [0u8; 16];
+        let piv = client.seal_request(b"", &mut request).expect("should seal");
+        assert_eq!(Err(Error::Inauthentic), client.open_response(&piv, b"", &mut request));
+    }
+
+    #[test]
+    fn invalid_contexts() {
+        assert_eq!(Err(Error::InvalidId), Context::new(b"s", b"", b"", b"\x01", b"\x01").map(drop));
+        assert_eq!(Err(Error::InvalidId), Context::new(b"s", b"", b"", &[0; 8], b"").map(drop));
+    }
+
+    #[test]
+    fn sequence_exhaustion() {
+        let (mut client, _) = contexts();
+        client.sequence_number = MAX_SEQUENCE_NUMBER;
+        let mut request = [0u8; 16];
+        assert_eq!(Ok(PartialIv::new(MAX_SEQUENCE_NUMBER)), client.seal_request(b"", &mut request));
+        assert_eq!(Err(Error::SequenceExhausted), client.seal_request(b"", &mut request));
+    }
+}
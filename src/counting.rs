@@ -0,0 +1,130 @@
+#![cfg(all(test, feature = "std", feature = "xoodyak"))]
+
+//! Tests of the number of permutations each operation costs.
+//!
+//! The permutation is the dominant cost of every Cyclist operation, so a refactor which adds an
+//! extra permutation per block (or per message) is a significant performance regression, but one
+//! which no other test would catch. These tests count the permutations Xoodyak makes for various
+//! operations and input sizes, and compare them to the counts the Cyclist specification implies.
+
+use std::cell::Cell;
+
+use crate::xoodyak::Xoodoo;
+use crate::{Cyclist, CyclistHash, CyclistKeyed, Permutation};
+
+thread_local! {
+    /// The number of permutations made by the current thread.
+    static PERMUTATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A permutation which counts its invocations.
+#[derive(Clone, Debug, Default)]
+struct Counting<P>(P);
+
+impl<P, const WIDTH: usize> Permutation<WIDTH> for Counting<P>
+where
+    P: Permutation<WIDTH>,
+{
+    fn add_byte(&mut self, byte: u8, offset: usize) {
+        self.0.add_byte(byte, offset);
+    }
+
+    fn add_bytes(&mut self, bytes: &[u8]) {
+        self.0.add_bytes(bytes);
+    }
+
+    fn extract_bytes(&mut self, out: &mut [u8]) {
+        self.0.extract_bytes(out);
+    }
+
+    fn to_bytes(&self) -> [u8; WIDTH] {
+        self.0.to_bytes()
+    }
+
+    fn from_bytes(bytes: &[u8; WIDTH]) -> Self {
+        Counting(P::from_bytes(bytes))
+    }
+
+    fn wipe(&mut self) {
+        self.0.wipe();
+    }
+
+    fn permute(&mut self) {
+        PERMUTATIONS.with(|n| n.set(n.get() + 1));
+        self.0.permute();
+    }
+}
+
+/// Xoodyak's hash mode, counting permutations.
+type CountingHash = CyclistHash<Counting<Xoodoo>, 48, 16>;
+
+/// Xoodyak's keyed mode, counting permutations.
+type CountingKeyed = CyclistKeyed<Counting<Xoodoo>, 48, 44, 24, 16, 16>;
+
+/// Returns the number of permutations made by the given function.
+fn count(f: impl FnOnce()) -> usize {
+    let before = PERMUTATIONS.with(Cell::get);
+    f();
+    PERMUTATIONS.with(Cell::get) - before
+}
+
+/// Returns the number of blocks an operation on `len` bytes processes, which is at least one.
+const fn blocks(len: usize, rate: usize) -> usize {
+    if len == 0 {
+        1
+    } else {
+        len.div_ceil(rate)
+    }
+}
+
+const LENS: [usize; 12] = [0, 1, 15, 16, 17, 23, 24, 25, 43, 44, 45, 1000];
+
+#[test]
+fn hash() {
+    for len in LENS {
+        for n in [16, 32, 100] {
+            let mut hash = CountingHash::default();
+
+            // The first block is absorbed without a permutation, and every squeezed block requires
+            // one.
+            assert_eq!(blocks(len, 16) - 1, count(|| hash.absorb(&vec![0u8; len])), "len={len}");
+            assert_eq!(blocks(n, 16), count(|| drop(hash.squeeze(n))), "len={len} n={n}");
+        }
+    }
+}
+
+#[test]
+fn keyed_initialization() {
+    assert_eq!(0, count(|| drop(CountingKeyed::new(b"key", b"id", b""))));
+    assert_eq!(3, count(|| drop(CountingKeyed::new(b"key", b"id", b"123"))));
+}
+
+#[test]
+fn keyed_absorb() {
+    for len in LENS {
+        let mut keyed = CountingKeyed::new(b"key", b"", b"");
+        assert_eq!(blocks(len, 44), count(|| keyed.absorb(&vec![0u8; len])), "len={len}");
+    }
+}
+
+#[test]
+fn seal_and_open() {
+    for len in LENS {
+        let mut sealer = CountingKeyed::new(b"key", b"", b"");
+        let mut c = Vec::new();
+
+        // Crypt permutes once per block, and the tag requires one more. An empty plaintext has no
+        // blocks, so sealing it costs only the tag's permutation.
+        let expected = len.div_ceil(24) + 1;
+        assert_eq!(expected, count(|| c = sealer.seal(&vec![0u8; len])), "len={len}");
+
+        let mut opener = CountingKeyed::new(b"key", b"", b"");
+        assert_eq!(expected, count(|| assert!(opener.open(&c).is_ok())), "len={len}");
+    }
+}
+
+#[test]
+fn ratchet() {
+    let mut keyed = CountingKeyed::new(b"key", b"", b"");
+    assert_eq!(1, count(|| keyed.ratchet()));
+}
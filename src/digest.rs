@@ -0,0 +1,273 @@
+#![cfg(feature = "digest")]
+
+//! Implementations of the [`digest`](::digest) traits for fixed-output and extendable-output hashes.
+//!
+//! Because the [`Update`] trait requires that a sequence of updates be equivalent to a single
+//! update with their concatenation, these traits are implemented for [`FixedHash`] types (e.g.
+//! [`Xoodyak256`](crate::xoodyak::Xoodyak256)) rather than for [`CyclistHash`](crate::CyclistHash),
+//! whose separate absorb operations are distinct. A [`FixedHash`]'s digest is identical to that of
+//! the corresponding [`CyclistHash`](crate::CyclistHash) absorbing the entire input at once.
+//!
+//! ```rust
+//! use cyclist::xoodyak::Xoodyak256;
+//! use digest::Digest;
+//!
+//! let digest = Xoodyak256::digest(b"This is an input message!");
+//! assert_eq!(digest.len(), 32);
+//! ```
+//!
+//! [`FixedHash`] types also implement [`ExtendableOutput`], and any [`CyclistHash`] can be turned
+//! into an [`XofReader`] with [`CyclistXofReader::new`]. Either way, the reader's output is
+//! identical to that of a single squeeze operation of the same total length:
+//!
+//! ```rust
+//! use cyclist::digest::CyclistXofReader;
+//! use cyclist::xoodyak::XoodyakHash;
+//! use cyclist::Cyclist;
+//! use digest::XofReader;
+//!
+//! let mut hash = XoodyakHash::default();
+//! hash.absorb(b"This is an input message!");
+//!
+//! let mut reader: Box<dyn XofReader> = Box::new(CyclistXofReader::new(hash));
+//! let mut out = [0u8; 100];
+//! reader.read(&mut out);
+//! ```
+
+use ::digest::generic_array::ArrayLength;
+use ::digest::{
+    ExtendableOutput, ExtendableOutputReset, FixedOutput, FixedOutputReset, HashMarker, Output,
+    OutputSizeUser, Reset, Update, XofReader,
+};
+use typenum::{Const, ToUInt, U};
+
+use crate::fixed::FixedHash;
+use crate::{Cyclist, CyclistHash, Permutation};
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize, const N: usize> HashMarker
+    for FixedHash<P, WIDTH, HASH_RATE, N>
+where
+    P: Permutation<WIDTH>,
+{
+}
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize, const N: usize> OutputSizeUser
+    for FixedHash<P, WIDTH, HASH_RATE, N>
+where
+    P: Permutation<WIDTH>,
+    Const<N>: ToUInt,
+    U<N>: ArrayLength<u8> + 'static,
+{
+    type OutputSize = U<N>;
+}
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize, const N: usize> Update
+    for FixedHash<P, WIDTH, HASH_RATE, N>
+where
+    P: Permutation<WIDTH>,
+{
+    fn update(&mut self, data: &[u8]) {
+        FixedHash::update(self, data);
+    }
+}
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize, const N: usize> FixedOutput
+    for FixedHash<P, WIDTH, HASH_RATE, N>
+where
+    P: Permutation<WIDTH>,
+    Const<N>: ToUInt,
+    U<N>: ArrayLength<u8> + 'static,
+{
+    fn finalize_into(self, out: &mut Output<Self>) {
+        out.copy_from_slice(&self.finalize());
+    }
+}
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize, const N: usize> FixedOutputReset
+    for FixedHash<P, WIDTH, HASH_RATE, N>
+where
+    P: Permutation<WIDTH>,
+    Const<N>: ToUInt,
+    U<N>: ArrayLength<u8> + 'static,
+{
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        out.copy_from_slice(&core::mem::take(self).finalize());
+    }
+}
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize, const N: usize> Reset
+    for FixedHash<P, WIDTH, HASH_RATE, N>
+where
+    P: Permutation<WIDTH>,
+{
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize, const N: usize> ExtendableOutput
+    for FixedHash<P, WIDTH, HASH_RATE, N>
+where
+    P: Permutation<WIDTH>,
+{
+    type Reader = CyclistXofReader<P, WIDTH, HASH_RATE>;
+
+    fn finalize_xof(self) -> Self::Reader {
+        CyclistXofReader::new(self.into_hash())
+    }
+}
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize, const N: usize> ExtendableOutputReset
+    for FixedHash<P, WIDTH, HASH_RATE, N>
+where
+    P: Permutation<WIDTH>,
+{
+    fn finalize_xof_reset(&mut self) -> Self::Reader {
+        core::mem::take(self).finalize_xof()
+    }
+}
+
+/// An [`XofReader`] which squeezes output from a [`CyclistHash`].
+///
+/// Output is squeezed a block at a time and buffered, so any sequence of reads returns the same
+/// bytes as a single squeeze operation of their total length.
+#[derive(Clone, Debug)]
+pub struct CyclistXofReader<P, const WIDTH: usize, const HASH_RATE: usize>
+where
+    P: Permutation<WIDTH>,
+{
+    hash: CyclistHash<P, WIDTH, HASH_RATE>,
+    buf: [u8; HASH_RATE],
+    buf_pos: usize,
+    squeezed: bool,
+}
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize> CyclistXofReader<P, WIDTH, HASH_RATE>
+where
+    P: Permutation<WIDTH>,
+{
+    /// Creates a new [`CyclistXofReader`] which squeezes output from the given hash.
+    pub const fn new(hash: CyclistHash<P, WIDTH, HASH_RATE>) -> Self {
+        CyclistXofReader { hash, buf: [0u8; HASH_RATE], buf_pos: HASH_RATE, squeezed: false }
+    }
+}
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize> From<CyclistHash<P, WIDTH, HASH_RATE>>
+    for CyclistXofReader<P, WIDTH, HASH_RATE>
+where
+    P: Permutation<WIDTH>,
+{
+    fn from(hash: CyclistHash<P, WIDTH, HASH_RATE>) -> Self {
+        CyclistXofReader::new(hash)
+    }
+}
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize> XofReader
+    for CyclistXofReader<P, WIDTH, HASH_RATE>
+where
+    P: Permutation<WIDTH>,
+{
+    fn read(&mut self, mut buffer: &mut [u8]) {
+        while !buffer.is_empty() {
+            if self.buf_pos == HASH_RATE {
+                // The first block starts a squeeze operation, and each subsequent block extends it.
+                if self.squeezed {
+                    self.hash.squeeze_more_mut(&mut self.buf);
+                } else {
+                    self.hash.squeeze_mut(&mut self.buf);
+                    self.squeezed = true;
+                }
+                self.buf_pos = 0;
+            }
+
+            let n = (HASH_RATE - self.buf_pos).min(buffer.len());
+            buffer[..n].copy_from_slice(&self.buf[self.buf_pos..self.buf_pos + n]);
+            self.buf_pos += n;
+            buffer = &mut buffer[n..];
+        }
+    }
+}
+
+#[cfg(all(test, feature = "xoodyak", feature = "keccyak"))]
+mod tests {
+    use ::digest::{Digest, ExtendableOutput, ExtendableOutputReset, Update, XofReader};
+
+    use crate::keccyak::{Keccyak512, KeccyakMaxHash};
+    use crate::xoodyak::{Xoodyak256, XoodyakHash};
+    use crate::Cyclist;
+
+    use super::CyclistXofReader;
+
+    /// Hashes the given input with any [`Digest`] implementation.
+    fn hash<D: Digest>(chunks: &[&[u8]]) -> Vec<u8> {
+        let mut d = D::new();
+        for chunk in chunks {
+            d.update(chunk);
+        }
+        d.finalize().to_vec()
+    }
+
+    #[test]
+    fn equivalent_to_cyclist_hash() {
+        let mut h = XoodyakHash::default();
+        h.absorb(b"it's a deal");
+        assert_eq!(h.squeeze(32), hash::<Xoodyak256>(&[b"it's ", b"a deal"]));
+
+        let mut h = KeccyakMaxHash::default();
+        h.absorb(b"it's a deal");
+        assert_eq!(h.squeeze(64), hash::<Keccyak512>(&[b"it's a", b" deal"]));
+    }
+
+    #[test]
+    fn reset() {
+        let mut d = Xoodyak256::new();
+        Digest::update(&mut d, b"garbage");
+        let garbage = d.finalize_reset();
+        Digest::update(&mut d, b"it's a deal");
+        assert_ne!(garbage.as_slice(), Digest::finalize(d).as_slice());
+    }
+
+    #[test]
+    fn xof_reads_are_concatenated() {
+        let mut h = XoodyakHash::default();
+        h.absorb(b"it's a deal");
+        let expected = h.clone().squeeze(200);
+
+        for chunk_len in [1, 7, 16, 17, 100] {
+            let mut reader = CyclistXofReader::new(h.clone());
+            let mut out = vec![0u8; 200];
+            for chunk in out.chunks_mut(chunk_len) {
+                reader.read(chunk);
+            }
+            assert_eq!(expected, out, "chunk_len={}", chunk_len);
+        }
+    }
+
+    #[test]
+    fn xof_extends_digest() {
+        let mut d = Xoodyak256::default();
+        Update::update(&mut d, b"it's a deal");
+        let digest = Digest::finalize(d.clone());
+
+        let mut reader: Box<dyn XofReader> = Box::new(d.finalize_xof());
+        let mut out = [0u8; 64];
+        reader.read(&mut out);
+        assert_eq!(digest.as_slice(), &out[..32]);
+
+        let mut d = Keccyak512::default();
+        Update::update(&mut d, b"it's a deal");
+        let mut h = KeccyakMaxHash::default();
+        h.absorb(b"it's a deal");
+        assert_eq!(h.squeeze(300), d.finalize_boxed(300).to_vec());
+    }
+
+    #[test]
+    fn xof_reset() {
+        let mut d = Xoodyak256::default();
+        Update::update(&mut d, b"garbage");
+        let mut garbage = [0u8; 32];
+        d.finalize_xof_reset().read(&mut garbage);
+        Update::update(&mut d, b"it's a deal");
+        assert_ne!(garbage.as_slice(), Digest::finalize(d).as_slice());
+    }
+}
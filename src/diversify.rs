@@ -0,0 +1,108 @@
+//! Deterministic per-device key diversification.
+//!
+//! When provisioning a fleet of devices, each device should hold its own key rather than a key
+//! shared with the whole fleet, so that extracting the key from one device compromises only that
+//! device. [`diversify`] derives a device key from a master key and a device identifier (e.g. a
+//! serial number), so a back end holding only the master key can re-derive any device's key on
+//! demand:
+//!
+//! ```rust
+//! use cyclist::diversify::{diversify, KeyUsage};
+//! use cyclist::xoodyak::XoodyakKeyed;
+//!
+//! let master = b"a master key";
+//!
+//! // Derive a device key at provisioning time.
+//! let mut device_key = [0u8; 32];
+//! diversify::<XoodyakKeyed>(master, b"SN-0001", None, &mut device_key);
+//!
+//! // Derive separate encryption and MAC keys for the same device.
+//! let (mut enc_key, mut mac_key) = ([0u8; 32], [0u8; 32]);
+//! diversify::<XoodyakKeyed>(master, b"SN-0001", Some(KeyUsage::Encryption), &mut enc_key);
+//! diversify::<XoodyakKeyed>(master, b"SN-0001", Some(KeyUsage::Authentication), &mut mac_key);
+//! assert_ne!(enc_key, mac_key);
+//! ```
+//!
+//! Device identifiers need not be secret, but each device must have a unique identifier.
+//!
+//! # Construction
+//!
+//! A keyed duplex is initialized with the master key, and absorbs a domain separation label, the
+//! device identifier, and a one-byte key usage code (`0x00` for none, `0x01` for encryption, and
+//! `0x02` for authentication). The device key is then squeezed as key data.
+
+use crate::Keyed;
+
+/// The domain separation label absorbed by every derivation.
+const DIVERSIFY_LABEL: &[u8] = b"cyclist.diversify";
+
+/// The intended use of a diversified key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum KeyUsage {
+    /// A key used only for encryption.
+    Encryption,
+
+    /// A key used only for message authentication.
+    Authentication,
+}
+
+impl KeyUsage {
+    /// Returns the code absorbed for this usage.
+    const fn code(usage: Option<Self>) -> u8 {
+        match usage {
+            None => 0x00,
+            Some(KeyUsage::Encryption) => 0x01,
+            Some(KeyUsage::Authentication) => 0x02,
+        }
+    }
+}
+
+/// Fills `out` with the key for the device with the given identifier, derived from the master key
+/// and optionally separated by key usage.
+pub fn diversify<K: Keyed>(
+    master: &[u8],
+    device_id: &[u8],
+    usage: Option<KeyUsage>,
+    out: &mut [u8],
+) {
+    let mut keyed = K::new(master, b"", b"");
+    keyed.absorb(DIVERSIFY_LABEL);
+    keyed.absorb(device_id);
+    keyed.absorb(&[KeyUsage::code(usage)]);
+    keyed.squeeze_key_mut(out);
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakKeyed;
+
+    use super::*;
+
+    fn key(master: &[u8], device_id: &[u8], usage: Option<KeyUsage>) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        diversify::<XoodyakKeyed>(master, device_id, usage, &mut out);
+        out
+    }
+
+    #[test]
+    fn deterministic() {
+        assert_eq!(key(b"master", b"device", None), key(b"master", b"device", None));
+    }
+
+    #[test]
+    fn separation() {
+        let keys = [
+            key(b"master", b"device", None),
+            key(b"master", b"device", Some(KeyUsage::Encryption)),
+            key(b"master", b"device", Some(KeyUsage::Authentication)),
+            key(b"master", b"device2", None),
+            key(b"master2", b"device", None),
+        ];
+        for (i, a) in keys.iter().enumerate() {
+            for b in &keys[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+}
@@ -0,0 +1,397 @@
+//! Key epochs derived from a root key, with authenticated rollover records.
+//!
+//! Long-lived systems need to rotate the keys they use for data without coordinating anything more
+//! than a counter. [`Epochs`] derives a key for each epoch from a root key and the epoch number,
+//! and moves to the next epoch by sealing a [`RolloverRecord`] which authenticates the transition.
+//! Records can be sent to peers, which apply them to follow along, and persisted, so the current
+//! epoch can be restored after a restart:
+//!
+//! ```rust
+//! use cyclist::epochs::{Epochs, ROLLOVER_RECORD_LEN};
+//! use cyclist::xoodyak::XoodyakKeyed;
+//! use cyclist::Cyclist;
+//!
+//! let root = b"a long-lived root key";
+//! let mut writer = Epochs::<XoodyakKeyed>::new(root, 0);
+//! let mut reader = Epochs::<XoodyakKeyed>::new(root, 0);
+//!
+//! // The writer moves to the next epoch, and the reader follows.
+//! let record = writer.rollover().expect("should have epochs left");
+//! assert_eq!(Ok(1), reader.apply(&record));
+//!
+//! // Both ends now use the same key.
+//! assert_eq!(writer.keyed().squeeze(16), reader.keyed().squeeze(16));
+//!
+//! // The writer persists the record and restores its epoch after a restart.
+//! let persisted: [u8; ROLLOVER_RECORD_LEN] = record.to_bytes();
+//! let restored = Epochs::<XoodyakKeyed>::restore(root, &persisted).expect("should be authentic");
+//! assert_eq!(1, restored.epoch());
+//! ```
+//!
+//! # Recovering From Gaps
+//!
+//! A peer which misses some records can apply any later one directly, skipping the epochs in
+//! between, as long as the gap is no more than the maximum set with [`Epochs::with_max_gap`] (by
+//! default, [`DEFAULT_MAX_GAP`]). This bounds how far a single record can move a peer forward,
+//! which limits the damage of a stolen root key being used to push peers to far-future epochs.
+//! Records for the current or earlier epochs are rejected as stale, so epochs never move
+//! backwards.
+//!
+//! Every epoch key can be derived from the root key, so rotating epochs limits the data protected
+//! by any single epoch key but provides no forward secrecy if the root key is compromised.
+//!
+//! # Construction
+//!
+//! A keyed duplex is initialized with the root key and absorbs a domain separation label. An epoch
+//! key is derived by a copy which absorbs the epoch number as a little-endian 64-bit integer and
+//! the string `key`, and squeezes [`EPOCH_KEY_LEN`] bytes of key data (or one byte less than the
+//! absorb rate, for schemes which can't absorb a key that long). A rollover record is the
+//! previous and new epoch numbers as little-endian 64-bit integers, followed by a
+//! [`ROLLOVER_TAG_LEN`]-byte tag squeezed by a copy which absorbs the string `rollover` and the two
+//! epoch numbers.
+
+use core::fmt;
+
+use constant_time_eq::constant_time_eq;
+
+use crate::{wipe, Keyed};
+
+/// The length of epoch keys, in bytes.
+pub const EPOCH_KEY_LEN: usize = 32;
+
+/// The length of a rollover record's tag, in bytes.
+pub const ROLLOVER_TAG_LEN: usize = 16;
+
+/// The length of an encoded rollover record, in bytes.
+pub const ROLLOVER_RECORD_LEN: usize = 8 + 8 + ROLLOVER_TAG_LEN;
+
+/// The default maximum number of epochs a single rollover record can move forward.
+pub const DEFAULT_MAX_GAP: u64 = 1024;
+
+/// The domain separation label for epochs.
+const EPOCHS_LABEL: &[u8] = b"cyclist.epochs";
+
+/// An error applying or restoring from a rollover record.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The record was not [`ROLLOVER_RECORD_LEN`] bytes long, or its new epoch didn't follow its
+    /// previous epoch.
+    Malformed,
+
+    /// The record could not be authenticated.
+    Inauthentic,
+
+    /// The record's new epoch isn't after the current epoch.
+    Stale,
+
+    /// The record's new epoch is further ahead of the current epoch than the maximum gap.
+    GapTooLarge,
+
+    /// The epochs have been exhausted.
+    Exhausted,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Malformed => write!(f, "malformed rollover record"),
+            Error::Inauthentic => write!(f, "inauthentic rollover record"),
+            Error::Stale => write!(f, "stale rollover record"),
+            Error::GapTooLarge => write!(f, "epoch gap too large"),
+            Error::Exhausted => write!(f, "epochs exhausted"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// An authenticated record of a transition between two epochs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RolloverRecord {
+    from: u64,
+    to: u64,
+    tag: [u8; ROLLOVER_TAG_LEN],
+}
+
+impl RolloverRecord {
+    /// Returns the previous epoch.
+    pub const fn from(&self) -> u64 {
+        self.from
+    }
+
+    /// Returns the new epoch.
+    pub const fn to(&self) -> u64 {
+        self.to
+    }
+
+    /// Returns the record's encoding.
+    pub fn to_bytes(&self) -> [u8; ROLLOVER_RECORD_LEN] {
+        let mut out = [0u8; ROLLOVER_RECORD_LEN];
+        out[..8].copy_from_slice(&self.from.to_le_bytes());
+        out[8..16].copy_from_slice(&self.to.to_le_bytes());
+        out[16..].copy_from_slice(&self.tag);
+        out
+    }
+
+    /// Decodes a record, without authenticating it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Malformed`] if the record is the wrong length or its new epoch doesn't
+    /// follow its previous epoch.
+    pub fn from_bytes(bytes: &[u8]) -> Result<RolloverRecord, Error> {
+        if bytes.len() != ROLLOVER_RECORD_LEN {
+            return Err(Error::Malformed);
+        }
+
+        let (from, rest) = bytes.split_at(8);
+        let (to, tag) = rest.split_at(8);
+        let record = RolloverRecord {
+            from: u64::from_le_bytes(from.try_into().expect("invalid length")),
+            to: u64::from_le_bytes(to.try_into().expect("invalid length")),
+            tag: tag.try_into().expect("invalid length"),
+        };
+        if record.to <= record.from {
+            return Err(Error::Malformed);
+        }
+        Ok(record)
+    }
+}
+
+/// A sequence of epoch keys derived from a root key.
+#[derive(Clone, Debug)]
+pub struct Epochs<K> {
+    root: K,
+    epoch: u64,
+    max_gap: u64,
+}
+
+impl<K> Epochs<K>
+where
+    K: Keyed,
+{
+    /// Creates a new [`Epochs`] with the given root key, starting at the given epoch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the root key is empty or too long to initialize a keyed duplex.
+    pub fn new(root: &[u8], epoch: u64) -> Self {
+        let mut keyed = K::new(root, b"", b"");
+        keyed.absorb(EPOCHS_LABEL);
+        Epochs { root: keyed, epoch, max_gap: DEFAULT_MAX_GAP }
+    }
+
+    /// Restores an [`Epochs`] with the given root key from a persisted rollover record, starting at
+    /// the record's new epoch.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Malformed`] if the record is malformed and [`Error::Inauthentic`] if it
+    /// cannot be authenticated with the root key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the root key is empty or too long to initialize a keyed duplex.
+    pub fn restore(root: &[u8], record: &[u8]) -> Result<Self, Error> {
+        let mut epochs = Self::new(root, 0);
+        let record = epochs.verify(record)?;
+        epochs.epoch = record.to;
+        Ok(epochs)
+    }
+
+    /// Sets the maximum number of epochs a single rollover record can move forward.
+    #[must_use]
+    pub const fn with_max_gap(mut self, max_gap: u64) -> Self {
+        self.max_gap = max_gap;
+        self
+    }
+
+    /// Returns the current epoch.
+    pub const fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Fills `out` with the key for the given epoch.
+    pub fn epoch_key_mut(&self, epoch: u64, out: &mut [u8]) {
+        let mut keyed = self.root.clone();
+        keyed.absorb(&epoch.to_le_bytes());
+        keyed.absorb(b"key");
+        keyed.squeeze_key_mut(out);
+    }
+
+    /// Returns a keyed duplex initialized with the current epoch's key.
+    pub fn keyed(&self) -> K {
+        self.keyed_for(self.epoch)
+    }
+
+    /// Returns a keyed duplex initialized with the given epoch's key, e.g. to open data sealed
+    /// before a rollover.
+    pub fn keyed_for(&self, epoch: u64) -> K {
+        let mut key = [0u8; EPOCH_KEY_LEN];
+        let key = &mut key[..EPOCH_KEY_LEN.min(K::ABSORB_RATE - 1)];
+        self.epoch_key_mut(epoch, key);
+        let keyed = K::new(key, b"", b"");
+        wipe(key);
+        keyed
+    }
+
+    /// Moves to the next epoch, returning a record of the transition.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Exhausted`] if the current epoch is the last.
+    pub fn rollover(&mut self) -> Result<RolloverRecord, Error> {
+        let to = self.epoch.checked_add(1).ok_or(Error::Exhausted)?;
+        let record = RolloverRecord { from: self.epoch, to, tag: self.tag(self.epoch, to) };
+        self.epoch = to;
+        Ok(record)
+    }
+
+    /// Authenticates and applies an encoded rollover record, moving to its new epoch and returning
+    /// it. Any epochs between the current epoch and the record's previous epoch are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Malformed`] if the record is malformed, [`Error::Inauthentic`] if it cannot
+    /// be authenticated, [`Error::Stale`] if its new epoch isn't after the current epoch, and
+    /// [`Error::GapTooLarge`] if its new epoch is more than the maximum gap ahead of the current
+    /// epoch.
+    pub fn apply(&mut self, record: &RolloverRecord) -> Result<u64, Error> {
+        self.apply_bytes(&record.to_bytes())
+    }
+
+    /// Authenticates and applies an encoded rollover record, like [`Epochs::apply`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Epochs::apply`].
+    pub fn apply_bytes(&mut self, record: &[u8]) -> Result<u64, Error> {
+        let record = self.verify(record)?;
+        if record.to <= self.epoch {
+            return Err(Error::Stale);
+        }
+        if record.to - self.epoch > self.max_gap {
+            return Err(Error::GapTooLarge);
+        }
+        self.epoch = record.to;
+        Ok(record.to)
+    }
+
+    /// Decodes and authenticates an encoded rollover record.
+    fn verify(&self, record: &[u8]) -> Result<RolloverRecord, Error> {
+        let record = RolloverRecord::from_bytes(record)?;
+        if !constant_time_eq(&record.tag, &self.tag(record.from, record.to)) {
+            return Err(Error::Inauthentic);
+        }
+        Ok(record)
+    }
+
+    /// Returns the tag for a transition between the given epochs.
+    fn tag(&self, from: u64, to: u64) -> [u8; ROLLOVER_TAG_LEN] {
+        let mut keyed = self.root.clone();
+        keyed.absorb(b"rollover");
+        keyed.absorb(&from.to_le_bytes());
+        keyed.absorb(&to.to_le_bytes());
+        let mut tag = [0u8; ROLLOVER_TAG_LEN];
+        keyed.squeeze_mut(&mut tag);
+        tag
+    }
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakKeyed;
+    use crate::Cyclist;
+
+    use super::*;
+
+    type XoodyakEpochs = Epochs<XoodyakKeyed>;
+
+    #[test]
+    fn epoch_keys() {
+        let epochs = XoodyakEpochs::new(b"root", 0);
+        let key = |epoch| {
+            let mut out = [0u8; EPOCH_KEY_LEN];
+            epochs.epoch_key_mut(epoch, &mut out);
+            out
+        };
+        assert_eq!(key(3), key(3));
+        assert_ne!(key(3), key(4));
+
+        let mut other = [0u8; EPOCH_KEY_LEN];
+        XoodyakEpochs::new(b"other root", 0).epoch_key_mut(3, &mut other);
+        assert_ne!(key(3), other);
+    }
+
+    #[test]
+    fn rollovers() {
+        let mut writer = XoodyakEpochs::new(b"root", 5);
+        let mut reader = XoodyakEpochs::new(b"root", 5);
+        let old = writer.keyed().squeeze(16);
+
+        let record = writer.rollover().expect("should roll over");
+        assert_eq!((5, 6), (record.from(), record.to()));
+        assert_eq!(Ok(6), reader.apply(&record));
+        assert_eq!(writer.keyed().squeeze(16), reader.keyed().squeeze(16));
+        assert_ne!(old, reader.keyed().squeeze(16));
+        assert_eq!(old, reader.keyed_for(5).squeeze(16));
+
+        // Records can't be applied twice.
+        assert_eq!(Err(Error::Stale), reader.apply(&record));
+    }
+
+    #[test]
+    fn gaps() {
+        let mut writer = XoodyakEpochs::new(b"root", 0);
+        let mut reader = XoodyakEpochs::new(b"root", 0).with_max_gap(3);
+
+        let records =
+            (0..6).map(|_| writer.rollover().expect("should roll over")).collect::<Vec<_>>();
+        assert_eq!(Ok(3), reader.apply(&records[2]));
+        assert_eq!(Err(Error::Stale), reader.apply(&records[1]));
+        let mut behind = XoodyakEpochs::new(b"root", 0).with_max_gap(3);
+        assert_eq!(Err(Error::GapTooLarge), behind.apply(&records[5]));
+        assert_eq!(Ok(6), reader.apply(&records[5]));
+    }
+
+    #[test]
+    fn persisted_records() {
+        let mut writer = XoodyakEpochs::new(b"root", 0);
+        writer.rollover().expect("should roll over");
+        let record = writer.rollover().expect("should roll over").to_bytes();
+
+        let restored = XoodyakEpochs::restore(b"root", &record).expect("should restore");
+        assert_eq!(2, restored.epoch());
+        assert_eq!(writer.keyed().squeeze(16), restored.keyed().squeeze(16));
+
+        assert_eq!(Err(Error::Inauthentic), XoodyakEpochs::restore(b"other", &record).map(|_| ()));
+        assert_eq!(
+            Err(Error::Malformed),
+            XoodyakEpochs::restore(b"root", &record[1..]).map(|_| ())
+        );
+
+        let mut forged = record;
+        forged[8] ^= 1;
+        assert_eq!(Err(Error::Inauthentic), XoodyakEpochs::restore(b"root", &forged).map(|_| ()));
+
+        let mut backwards = record;
+        backwards[8..16].copy_from_slice(&0u64.to_le_bytes());
+        assert_eq!(Err(Error::Malformed), XoodyakEpochs::restore(b"root", &backwards).map(|_| ()));
+    }
+
+    #[test]
+    fn exhaustion() {
+        let mut epochs = XoodyakEpochs::new(b"root", u64::MAX);
+        assert_eq!(Err(Error::Exhausted), epochs.rollover());
+    }
+
+    #[cfg(feature = "keccyak")]
+    #[test]
+    fn small_states() {
+        let epochs = Epochs::<crate::keccyak::Keccyak200Keyed>::new(b"root", 3);
+        let ciphertext = epochs.keyed().seal(b"message");
+        assert_eq!(Ok(b"message".to_vec()), epochs.keyed_for(3).open(&ciphertext));
+    }
+}
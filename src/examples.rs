@@ -0,0 +1,179 @@
+#![cfg(test)]
+
+//! Golden outputs for the crate-level examples, for every scheme.
+//!
+//! The examples in the crate documentation advertise specific digests, MACs, and ciphertexts, and
+//! those outputs must stay stable as new backends and fast paths land. These tests run the same
+//! examples against every scheme enabled in the current build, using only APIs which are available
+//! without the `std` feature, and compare them to golden outputs. Adding a scheme to the
+//! [`examples!`] invocations below covers it with every example.
+
+use crate::{Cyclist, Keyed};
+
+const MESSAGE: &[u8] = b"This is an input message!";
+const KEY: &[u8] = b"This is a secret key!";
+const NONCE: &[u8] = b"This is a nonce!";
+const AD: &[u8] = b"This is authenticated data!";
+const PLAINTEXT: &[u8] = b"This is the plaintext!";
+
+/// Asserts that the given output matches the given lowercase hexadecimal golden output.
+fn check(name: &str, actual: &[u8], expected: &str) {
+    let digit = |c: u8| match c {
+        b'0'..=b'9' => c - b'0',
+        b'a'..=b'f' => c - b'a' + 10,
+        _ => panic!("invalid golden output for {name}"),
+    };
+
+    assert_eq!(expected.len(), 2 * actual.len(), "wrong output length for {name}");
+    for (i, (&a, e)) in actual.iter().zip(expected.as_bytes().chunks_exact(2)).enumerate() {
+        assert_eq!((digit(e[0]) << 4) | digit(e[1]), a, "wrong output for {name} at byte {i}");
+    }
+}
+
+/// The message digest example.
+fn digest<H: Cyclist + Default>() -> [u8; 16] {
+    let mut hash = H::default();
+    hash.absorb(MESSAGE);
+    hash.squeeze_array()
+}
+
+/// The message authentication code example.
+fn mac<K: Keyed>() -> [u8; 16] {
+    let mut mac = K::new(KEY, b"", b"");
+    mac.absorb(MESSAGE);
+    mac.squeeze_array()
+}
+
+/// The authenticated encryption example, writing the ciphertext to `out` and returning its length.
+fn aead<K: Keyed>(out: &mut [u8; 64]) -> usize {
+    let len = K::sealed_len(PLAINTEXT.len());
+    out[..PLAINTEXT.len()].copy_from_slice(PLAINTEXT);
+    let mut aead = K::new(KEY, NONCE, b"");
+    aead.absorb(AD);
+    aead.seal_mut(&mut out[..len]);
+    len
+}
+
+/// Defines a module of tests which run every example against a scheme and compare the outputs to
+/// the given golden outputs.
+///
+/// The keyed examples are optional, since the example key and nonce don't fit in the absorb rate of
+/// the smallest schemes.
+macro_rules! examples {
+    ($(#[$attr:meta])* $name:ident: $hash:ty, $keyed:ty {
+        digest: $digest:literal
+        $(, mac: $mac:literal)?
+        $(, aead: $aead:literal)? $(,)?
+    }) => {
+        $(#[$attr])*
+        mod $name {
+            use super::*;
+
+            #[test]
+            fn digest() {
+                check(stringify!($hash), &super::digest::<$hash>(), $digest);
+            }
+
+            $(
+                #[test]
+                fn mac() {
+                    check(stringify!($keyed), &super::mac::<$keyed>(), $mac);
+                }
+            )?
+
+            $(
+                #[test]
+                fn aead() {
+                    let mut out = [0u8; 64];
+                    let len = super::aead::<$keyed>(&mut out);
+                    check(stringify!($keyed), &out[..len], $aead);
+                }
+            )?
+        }
+    };
+}
+
+examples!(
+    #[cfg(feature = "xoodyak")]
+    xoodyak: crate::xoodyak::XoodyakHash, crate::xoodyak::XoodyakKeyed {
+        digest: "184f39318539e4de0b5f91394c10107a",
+        mac: "c2a656504a3eac737a6bbad5fc52efba",
+        aead: "64b69831db94207c11229fa90cf6e00d17732faf959f91eebe354deb62ff34303\
+               6db941bd03a",
+    }
+);
+
+examples!(
+    #[cfg(feature = "keccyak")]
+    keccyak_max: crate::keccyak::KeccyakMaxHash, crate::keccyak::KeccyakMaxKeyed {
+        digest: "b282995345adfee97b3f5b674d6f4999",
+        mac: "f5d95c0506bcd5fa71b26e0c87e7bcca",
+        aead: "c3c95d8f81d7ce0ba389f242b2ef4db9703287c51faca1602b808593caabda1ad\
+               9581b43574fdc2cbdf14b662c70f773bb52ce9a9d96",
+    }
+);
+
+examples!(
+    #[cfg(feature = "keccyak")]
+    keccyak_256: crate::keccyak::Keccyak256Hash, crate::keccyak::Keccyak256Keyed {
+        digest: "1df1793be89371f61f68042c99435117",
+        mac: "4e287380a5200dc2542942d9a5b8ed49",
+        aead: "3d02366b8d8ed48528db366a3ac5567d217f1e5d4ee4162d57af7c01286873d11\
+               da1b32a92ca44c42786d3e7902edd197aea128befcb",
+    }
+);
+
+examples!(
+    #[cfg(feature = "keccyak")]
+    keccyak_128: crate::keccyak::Keccyak128Hash, crate::keccyak::Keccyak128Keyed {
+        digest: "cd233624840ea6cf11ef24304d2621f3",
+        mac: "f69ff8f132f785cc62ee957ecd332be9",
+        aead: "05f846349fd7654793c341f981140bf6060f6fdc5306c889be22702d31e9b0d89\
+               84684bf24b0",
+    }
+);
+
+examples!(
+    #[cfg(feature = "keccyak")]
+    keccyak_min: crate::keccyak::KeccyakMinHash, crate::keccyak::KeccyakMinKeyed {
+        digest: "de148d13921eb81ff0fec0aefad4d0c7",
+        mac: "87a206c44bbeef3b55620469442891ec",
+        aead: "bbf44912971bdd7d58150080d5a6a0aa04a6879e36c05d021886b72dd9c43d292\
+               df41d62b8dc",
+    }
+);
+
+examples!(
+    #[cfg(feature = "keccyak")]
+    keccyak_800: crate::keccyak::Keccyak800Hash, crate::keccyak::Keccyak800Keyed {
+        digest: "7a5511dc87c4fd64775a114d76dc40f8",
+        mac: "e6d17957f1a0bf5798d2753f74fab628",
+        aead: "42bddb64e83413e770f7014d449ed7dd99aeb7fb42022291ff0631cd13b0cf952\
+               363aa6cec3b",
+    }
+);
+
+examples!(
+    #[cfg(feature = "keccyak")]
+    keccyak_400: crate::keccyak::Keccyak400Hash, crate::keccyak::Keccyak400Keyed {
+        digest: "5872898929bfa459174003438f4afe0f",
+        mac: "194a75f1dcfec31184d28f4c3cba221f",
+        aead: "6862dac1138cb7db589b706f9173a919427d4ba30799400b5fad96bb5ddf44fa5\
+               bb1190e6b24",
+    }
+);
+
+examples!(
+    #[cfg(feature = "keccyak")]
+    keccyak_200: crate::keccyak::Keccyak200Hash, crate::keccyak::Keccyak200Keyed {
+        digest: "f4bb2afd1fa5f5a96a2ea1b36375f764",
+    }
+);
+
+examples!(
+    #[cfg(feature = "ascon")]
+    ascon: crate::ascon::AsconCyclistHash, crate::ascon::AsconCyclistKeyed {
+        digest: "677ef2ab120329b8513f832bf0151d5b",
+        mac: "5021a08025c7d116a82e6d9cfa29d9f2",
+    }
+);
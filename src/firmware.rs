@@ -0,0 +1,260 @@
+//! Verification of segmented firmware images against a signed manifest.
+//!
+//! A firmware image is a sequence of segments (e.g. a bootloader stage, an application, and its
+//! data), each of which is described in a manifest by its length and a [`segment_digest`]. Segment
+//! digests are chained, so each one depends on all of the segments before it. The manifest itself
+//! is identified by its [`manifest_digest`], which is what a release tool signs and what a
+//! bootloader verifies the signature of with whatever signature scheme it uses.
+//!
+//! ```rust
+//! use cyclist::firmware::{manifest, manifest_digest, verify_image};
+//! use cyclist::xoodyak::XoodyakHash;
+//!
+//! let segments: [&[u8]; 2] = [b"stage one", b"stage two"];
+//!
+//! // At release time, build the manifest and sign its digest.
+//! let manifest = manifest::<XoodyakHash, _>(segments);
+//! let signed = manifest_digest::<XoodyakHash>(&manifest);
+//!
+//! // At boot time, verify the manifest's signature and then the image.
+//! verify_image::<XoodyakHash, _>(segments, &manifest, |digest| digest == &signed)
+//!     .expect("image should be valid");
+//! ```
+//!
+//! # Construction
+//!
+//! A segment's digest is squeezed from a hash which has absorbed a domain separation label, the
+//! previous segment's digest (or all zeros, for the first segment), the segment's index and length
+//! as little-endian 64-bit integers, and the segment. The manifest's digest is squeezed from a hash
+//! which has absorbed a distinct label, the number of segments, and each segment's length and
+//! digest.
+
+use core::fmt;
+
+use crate::Cyclist;
+
+/// The length of segment and manifest digests, in bytes.
+pub const DIGEST_LEN: usize = 32;
+
+/// A segment or manifest digest.
+pub type Digest = [u8; DIGEST_LEN];
+
+/// The domain separation label for segment digests.
+const SEGMENT_LABEL: &[u8] = b"cyclist.firmware.segment";
+
+/// The domain separation label for manifest digests.
+const MANIFEST_LABEL: &[u8] = b"cyclist.firmware.manifest";
+
+/// A manifest entry describing a segment of a firmware image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Segment {
+    /// The length of the segment in bytes.
+    pub len: u64,
+    /// The chained digest of the segment.
+    pub digest: Digest,
+}
+
+/// An error verifying a firmware image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The manifest's signature was not valid.
+    Signature,
+
+    /// The image had a different number of segments than the manifest.
+    SegmentCount {
+        /// The number of segments in the manifest.
+        expected: usize,
+    },
+
+    /// A segment had a different length than its manifest entry.
+    SegmentLength {
+        /// The index of the segment.
+        index: usize,
+    },
+
+    /// A segment had a different digest than its manifest entry.
+    SegmentDigest {
+        /// The index of the segment.
+        index: usize,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Signature => write!(f, "invalid manifest signature"),
+            Error::SegmentCount { expected } => {
+                write!(f, "invalid segment count (expected {})", expected)
+            }
+            Error::SegmentLength { index } => write!(f, "invalid length of segment {}", index),
+            Error::SegmentDigest { index } => write!(f, "invalid digest of segment {}", index),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Returns the chained digest of the segment at the given index, given the previous segment's
+/// digest (or all zeros, for the first segment).
+pub fn segment_digest<H: Cyclist + Default>(prev: &Digest, index: usize, segment: &[u8]) -> Digest {
+    let mut hash = H::default();
+    hash.absorb(SEGMENT_LABEL);
+    hash.absorb(prev);
+    hash.absorb(&u64::try_from(index).expect("unexpected overflow").to_le_bytes());
+    hash.absorb(&u64::try_from(segment.len()).expect("unexpected overflow").to_le_bytes());
+    hash.absorb(segment);
+    let mut digest = [0u8; DIGEST_LEN];
+    hash.squeeze_mut(&mut digest);
+    digest
+}
+
+/// Returns the digest of the given manifest.
+pub fn manifest_digest<H: Cyclist + Default>(manifest: &[Segment]) -> Digest {
+    let mut hash = H::default();
+    hash.absorb(MANIFEST_LABEL);
+    hash.absorb(&u64::try_from(manifest.len()).expect("unexpected overflow").to_le_bytes());
+    for segment in manifest {
+        hash.absorb(&segment.len.to_le_bytes());
+        hash.absorb(&segment.digest);
+    }
+    let mut digest = [0u8; DIGEST_LEN];
+    hash.squeeze_mut(&mut digest);
+    digest
+}
+
+/// Returns the manifest of the given segments.
+#[cfg(feature = "std")]
+pub fn manifest<'a, H, I>(segments: I) -> Vec<Segment>
+where
+    H: Cyclist + Default,
+    I: IntoIterator<Item = &'a [u8]>,
+{
+    let mut prev = [0u8; DIGEST_LEN];
+    segments
+        .into_iter()
+        .enumerate()
+        .map(|(index, segment)| {
+            prev = segment_digest::<H>(&prev, index, segment);
+            Segment {
+                len: u64::try_from(segment.len()).expect("unexpected overflow"),
+                digest: prev,
+            }
+        })
+        .collect()
+}
+
+/// Verifies a firmware image against a manifest. First, `verify_signature` is called with the
+/// manifest's digest, and must return `true` if the manifest's signature is valid. Then, each
+/// segment's length and digest is checked against the manifest.
+///
+/// # Errors
+///
+/// Returns [`Error::Signature`] if `verify_signature` returns `false`, [`Error::SegmentCount`] if
+/// the image and the manifest have different numbers of segments, and [`Error::SegmentLength`] or
+/// [`Error::SegmentDigest`] for the first segment which doesn't match its manifest entry.
+pub fn verify_image<'a, H, I>(
+    segments: I,
+    manifest: &[Segment],
+    verify_signature: impl FnOnce(&Digest) -> bool,
+) -> Result<(), Error>
+where
+    H: Cyclist + Default,
+    I: IntoIterator<Item = &'a [u8]>,
+{
+    if !verify_signature(&manifest_digest::<H>(manifest)) {
+        return Err(Error::Signature);
+    }
+
+    let mut prev = [0u8; DIGEST_LEN];
+    let mut count = 0;
+    for (index, segment) in segments.into_iter().enumerate() {
+        let entry = manifest.get(index).ok_or(Error::SegmentCount { expected: manifest.len() })?;
+        if u64::try_from(segment.len()).ok() != Some(entry.len) {
+            return Err(Error::SegmentLength { index });
+        }
+
+        // The digests aren't secret, so they needn't be compared in constant time.
+        prev = segment_digest::<H>(&prev, index, segment);
+        if prev != entry.digest {
+            return Err(Error::SegmentDigest { index });
+        }
+        count += 1;
+    }
+
+    if count != manifest.len() {
+        return Err(Error::SegmentCount { expected: manifest.len() });
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, feature = "std", feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakHash;
+
+    use super::*;
+
+    const SEGMENTS: [&[u8]; 3] = [b"stage one", b"stage two", b"data"];
+
+    fn verify(segments: &[&[u8]], manifest: &[Segment]) -> Result<(), Error> {
+        verify_image::<XoodyakHash, _>(segments.iter().copied(), manifest, |_| true)
+    }
+
+    #[test]
+    fn valid_image() {
+        let manifest = manifest::<XoodyakHash, _>(SEGMENTS);
+        assert_eq!(3, manifest.len());
+        assert_eq!(Ok(()), verify(&SEGMENTS, &manifest));
+    }
+
+    #[test]
+    fn signature_hook() {
+        let manifest = manifest::<XoodyakHash, _>(SEGMENTS);
+        let signed = manifest_digest::<XoodyakHash>(&manifest);
+        assert_eq!(
+            Err(Error::Signature),
+            verify_image::<XoodyakHash, _>(SEGMENTS, &manifest, |d| d != &signed)
+        );
+
+        let mut modified = manifest.clone();
+        modified[2].len += 1;
+        assert_ne!(signed, manifest_digest::<XoodyakHash>(&modified));
+    }
+
+    #[test]
+    fn modified_segments() {
+        let manifest = manifest::<XoodyakHash, _>(SEGMENTS);
+        assert_eq!(
+            Err(Error::SegmentLength { index: 1 }),
+            verify(&[b"stage one", b"stage 2", b"data"], &manifest)
+        );
+        assert_eq!(
+            Err(Error::SegmentDigest { index: 1 }),
+            verify(&[b"stage one", b"stage 2!!", b"data"], &manifest)
+        );
+    }
+
+    #[test]
+    fn reordered_segments() {
+        let manifest = manifest::<XoodyakHash, _>([b"same".as_slice(), b"same", b"diff"]);
+        assert_eq!(
+            Err(Error::SegmentDigest { index: 2 }),
+            verify(&[b"same", b"same", b"ffid"], &manifest)
+        );
+
+        // Chaining makes each digest depend on its predecessors.
+        assert_ne!(manifest[0].digest, manifest[1].digest);
+    }
+
+    #[test]
+    fn segment_counts() {
+        let manifest = manifest::<XoodyakHash, _>(SEGMENTS);
+        assert_eq!(Err(Error::SegmentCount { expected: 3 }), verify(&SEGMENTS[..2], &manifest));
+        assert_eq!(
+            Err(Error::SegmentCount { expected: 3 }),
+            verify(&[SEGMENTS[0], SEGMENTS[1], SEGMENTS[2], b"extra"], &manifest)
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! Hashes with fixed-length digests.
+//!
+//! A [`FixedHash`] absorbs its input incrementally via [`FixedHash::update`] and returns a digest of
+//! exactly `N` bytes. Its digest is identical to that of the underlying [`CyclistHash`] absorbing
+//! the concatenation of all updates in a single absorb operation and then squeezing `N` bytes.
+
+use crate::{Cyclist, CyclistHash, Permutation};
+
+/// A Cyclist hash with an `N`-byte digest. Parameterized with the permutation algorithm, the
+/// permutation width, the hash rate, and the digest length.
+#[derive(Clone, Debug)]
+pub struct FixedHash<P, const WIDTH: usize, const HASH_RATE: usize, const N: usize>
+where
+    P: Permutation<WIDTH>,
+{
+    hash: CyclistHash<P, WIDTH, HASH_RATE>,
+    buf: [u8; HASH_RATE],
+    buf_len: usize,
+    absorbed: bool,
+}
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize, const N: usize>
+    FixedHash<P, WIDTH, HASH_RATE, N>
+where
+    P: Permutation<WIDTH>,
+{
+    /// Returns the length of the digest in bytes.
+    pub const fn output_len() -> usize {
+        N
+    }
+
+    /// Absorbs the given slice as a continuation of all previous updates.
+    pub fn update(&mut self, mut bin: &[u8]) {
+        while !bin.is_empty() {
+            // Only flush a full block once more input arrives, leaving the final block for
+            // finalization.
+            if self.buf_len == HASH_RATE {
+                self.flush();
+            }
+
+            let n = (HASH_RATE - self.buf_len).min(bin.len());
+            self.buf[self.buf_len..self.buf_len + n].copy_from_slice(&bin[..n]);
+            self.buf_len += n;
+            bin = &bin[n..];
+        }
+    }
+
+    /// Returns the digest of all updates.
+    pub fn finalize(self) -> [u8; N] {
+        let mut out = [0u8; N];
+        self.into_hash().squeeze_mut(&mut out);
+        out
+    }
+
+    /// Returns the underlying [`CyclistHash`] after absorbing all updates.
+    pub(crate) fn into_hash(mut self) -> CyclistHash<P, WIDTH, HASH_RATE> {
+        self.flush();
+        self.hash
+    }
+
+    /// Absorbs the buffered input, starting an absorb operation if none has been started.
+    fn flush(&mut self) {
+        if self.absorbed {
+            self.hash.absorb_more(&self.buf[..self.buf_len]);
+        } else {
+            self.hash.absorb(&self.buf[..self.buf_len]);
+            self.absorbed = true;
+        }
+        self.buf_len = 0;
+    }
+}
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize, const N: usize> Default
+    for FixedHash<P, WIDTH, HASH_RATE, N>
+where
+    P: Permutation<WIDTH>,
+{
+    fn default() -> Self {
+        FixedHash {
+            hash: CyclistHash::default(),
+            buf: [0u8; HASH_RATE],
+            buf_len: 0,
+            absorbed: false,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::{Xoodyak256, XoodyakHash};
+
+    use super::*;
+
+    #[test]
+    fn equivalent_to_single_absorb() {
+        for len in [0, 1, 15, 16, 17, 32, 33, 100] {
+            let input = vec![0xCA; len];
+
+            let mut hash = XoodyakHash::default();
+            hash.absorb(&input);
+            let expected = hash.squeeze(32);
+
+            for chunk_len in [1, 7, 16, 50] {
+                let mut hash = Xoodyak256::default();
+                for chunk in input.chunks(chunk_len) {
+                    hash.update(chunk);
+                }
+                assert_eq!(expected, hash.finalize(), "len={}, chunk_len={}", len, chunk_len);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_updates() {
+        let mut hash = Xoodyak256::default();
+        hash.update(&[]);
+        hash.update(b"ok");
+        hash.update(&[]);
+        let one = hash.finalize();
+
+        let mut hash = Xoodyak256::default();
+        hash.update(b"ok");
+        let two = hash.finalize();
+
+        assert_eq!(one, two);
+    }
+}
@@ -0,0 +1,244 @@
+#![cfg(feature = "fuzzing")]
+
+//! Oracles for fuzzing Cyclist schemes with unstructured input.
+//!
+//! Each function here decodes an arbitrary byte string into a sequence of operations, runs them
+//! against a scheme, and panics if the scheme's outputs violate one of its invariants. They
+//! accept any input without panicking otherwise, so they can be used directly as the bodies of
+//! libFuzzer (or AFL, or honggfuzz) targets:
+//!
+//! ```rust,ignore
+//! #![no_main]
+//!
+//! use cyclist::xoodyak::XoodyakKeyed;
+//! use libfuzzer_sys::fuzz_target;
+//!
+//! fuzz_target!(|data: &[u8]| cyclist::harness::seal_open::<XoodyakKeyed>(data));
+//! ```
+//!
+//! The crate's own targets, in `fuzz/`, run these against Xoodyak and Keccyak with `cargo fuzz`.
+//! Crates which embed cyclist or add new permutations can reuse the same oracles for their own
+//! schemes:
+//!
+//! * [`seal_open`] checks that sealed messages open, and that tampered or truncated ones don't.
+//! * [`transcript`] checks that a transcript of operations produces the same state when inputs are
+//!   absorbed in arbitrary chunks and squeezed in rate-sized pieces, and that encrypting and
+//!   decrypting are inverses.
+//! * [`portable`] checks that any state which imports from the portable format exports to an
+//!   equivalent state.
+
+use crate::absorber::Absorber;
+use crate::conformance::Scheme;
+use crate::portable::Portable;
+use crate::{Cyclist, Error, Keyed, Rates};
+
+/// Seals a message with a keyed duplex and checks that it opens, and that tampering with or
+/// truncating the ciphertext causes opening to fail.
+///
+/// # Panics
+///
+/// Panics if the scheme violates any of those properties.
+pub fn seal_open<K: Keyed>(data: &[u8]) {
+    let mut input = Input(data);
+    let Some(mut keyed) = keyed::<K>(&mut input) else {
+        return;
+    };
+    keyed.absorb(input.chunk());
+    let (flip, bit) = (usize::from(input.byte()), input.byte() % 8);
+    let m = input.rest();
+
+    let mut c = m.to_vec();
+    c.resize(K::sealed_len(m.len()), 0);
+    keyed.clone().seal_mut(&mut c);
+
+    let mut p = c.clone();
+    assert_eq!(Ok(()), keyed.clone().open_mut(&mut p), "sealed message didn't open");
+    assert_eq!(m, &p[..m.len()], "opened message didn't match");
+
+    let mut c_p = c.clone();
+    let i = flip % c_p.len();
+    c_p[i] ^= 1 << bit;
+    assert_eq!(Err(Error::InvalidTag), keyed.clone().open_mut(&mut c_p), "tampered message opened");
+
+    let mut c_p = c[..K::OVERHEAD - 1].to_vec();
+    assert_eq!(Err(Error::BufferTooSmall), keyed.open_mut(&mut c_p), "truncated message opened");
+}
+
+/// Runs a transcript of operations against two copies of the scheme's hash and keyed modes, one
+/// of which absorbs its inputs in arbitrary chunks and squeezes its outputs in rate-sized pieces,
+/// and decrypts what the other encrypts, and checks that the copies end in the same state.
+///
+/// # Panics
+///
+/// Panics if the copies' outputs or final states differ.
+pub fn transcript<S: Scheme>(data: &[u8]) {
+    let mut input = Input(data);
+    let Some(keyed) = keyed::<S::Keyed>(&mut input) else {
+        return;
+    };
+    let (mut a, mut b) = (keyed.clone(), keyed);
+    let (mut h_a, mut h_b) = (S::Hash::default(), S::Hash::default());
+
+    while !input.is_empty() {
+        match input.byte() % 5 {
+            0 => {
+                let split = usize::from(input.byte());
+                let data = input.chunk();
+                absorb(&mut a, &mut b, data, split);
+                absorb(&mut h_a, &mut h_b, data, split);
+            }
+            1 => {
+                let n = usize::from(input.byte()) * 4 + 1;
+                squeeze(&mut a, &mut b, n);
+                squeeze(&mut h_a, &mut h_b, n);
+            }
+            2 => crypt(&mut a, &mut b, input.chunk()),
+            3 => crypt(&mut b, &mut a, input.chunk()),
+            _ => {
+                a.ratchet();
+                b.ratchet();
+            }
+        }
+    }
+
+    assert_eq!(a.squeeze(16), b.squeeze(16), "keyed states diverged");
+    assert_eq!(h_a.squeeze(16), h_b.squeeze(16), "hash states diverged");
+}
+
+/// Imports the scheme's keyed mode from the portable format and, if it's valid, checks that it
+/// exports to a state which imports as an equivalent duplex.
+///
+/// # Panics
+///
+/// Panics if the re-exported state doesn't import, or behaves differently.
+pub fn portable<S: Scheme>(data: &[u8])
+where
+    S::Keyed: Portable,
+{
+    let export = |keyed: &S::Keyed| {
+        let mut out = vec![0u8; S::Keyed::portable_len()];
+        keyed.write_portable_bytes(&mut out);
+        out
+    };
+
+    let Ok(mut imported) = S::Keyed::from_portable_bytes(data) else {
+        return;
+    };
+
+    let exported = export(&imported);
+    let mut reimported =
+        S::Keyed::from_portable_bytes(&exported).expect("exported state didn't import");
+    assert_eq!(exported, export(&reimported), "re-exported state differs");
+    assert_eq!(imported.squeeze(16), reimported.squeeze(16), "imported states diverged");
+}
+
+/// Creates a keyed duplex with a key and key ID from the input, or returns `None` if they're
+/// invalid for the scheme.
+fn keyed<K: Keyed>(input: &mut Input<'_>) -> Option<K> {
+    let (key, key_id) = (input.chunk(), input.chunk());
+    (!key.is_empty() && key.len() + key_id.len() < K::ABSORB_RATE).then(|| K::new(key, key_id, b""))
+}
+
+/// Absorbs the given data into `a` in one piece and into `b` in two pieces split at the given
+/// index.
+fn absorb<C: Rates>(a: &mut C, b: &mut C, data: &[u8], split: usize) {
+    a.absorb(data);
+
+    let split = split % (data.len() + 1);
+    let mut absorber = Absorber::new(b);
+    absorber.update(&data[..split]);
+    absorber.update(&data[split..]);
+    absorber.finish();
+}
+
+/// Squeezes `n` bytes from `a` in one piece and from `b` in a whole number of blocks followed by
+/// the remainder, and checks that they match.
+fn squeeze<C: Rates>(a: &mut C, b: &mut C, n: usize) {
+    let out = a.squeeze(n);
+
+    let blocks = n / C::SQUEEZE_RATE * C::SQUEEZE_RATE;
+    let mut out_p = b.squeeze(if blocks == 0 { n } else { blocks });
+    if blocks != 0 && blocks != n {
+        out_p.extend(b.squeeze_more(n - blocks));
+    }
+    assert_eq!(out, out_p, "squeezed outputs differ");
+}
+
+/// Encrypts the given data with `a` and decrypts the result with `b`, and checks that it matches.
+fn crypt<K: Keyed>(a: &mut K, b: &mut K, data: &[u8]) {
+    let mut out = data.to_vec();
+    a.encrypt_mut(&mut out);
+    b.decrypt_mut(&mut out);
+    assert_eq!(data, out, "decrypted output differs");
+}
+
+/// A cursor over fuzzer input, which returns zeros once the input is exhausted.
+struct Input<'a>(&'a [u8]);
+
+impl<'a> Input<'a> {
+    const fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn byte(&mut self) -> u8 {
+        self.bytes(1).first().copied().unwrap_or(0)
+    }
+
+    fn bytes(&mut self, n: usize) -> &'a [u8] {
+        let (head, tail) = self.0.split_at(n.min(self.0.len()));
+        self.0 = tail;
+        head
+    }
+
+    /// Returns a length-prefixed chunk of input.
+    fn chunk(&mut self) -> &'a [u8] {
+        let n = self.byte();
+        self.bytes(n.into())
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        self.bytes(self.0.len())
+    }
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::conformance::Xoodyak;
+    use crate::xoodyak::{XoodyakHash, XoodyakKeyed};
+
+    use super::*;
+
+    /// Deterministic pseudo-random inputs of various lengths, with a valid key and key ID.
+    fn inputs() -> impl Iterator<Item = Vec<u8>> {
+        let mut hash = XoodyakHash::default();
+        (0..200).map(move |len| {
+            let mut input = vec![3, b'k', b'e', b'y', 2, b'i', b'd'];
+            input.extend(hash.squeeze(len));
+            input
+        })
+    }
+
+    #[test]
+    fn seal_open_oracle() {
+        seal_open::<XoodyakKeyed>(&[4, b'k', b'e', b'y', b'!', 0, 2, b'a', b'd', 7, 3, b'm']);
+        inputs().for_each(|input| seal_open::<XoodyakKeyed>(&input));
+    }
+
+    #[test]
+    fn transcript_oracle() {
+        inputs().for_each(|input| transcript::<Xoodyak>(&input));
+    }
+
+    #[test]
+    fn portable_oracle() {
+        let mut keyed = XoodyakKeyed::new(b"key", b"", b"");
+        keyed.absorb(b"ad");
+        let mut exported = keyed.to_portable_bytes();
+        portable::<Xoodyak>(&exported);
+        for i in 0..exported.len() {
+            exported[i] ^= 0x80;
+            portable::<Xoodyak>(&exported);
+        }
+        inputs().for_each(|input| portable::<Xoodyak>(&input));
+    }
+}
@@ -0,0 +1,159 @@
+//! Adapters for using Cyclist objects as [`core::hash::Hasher`]s.
+//!
+//! A [`CyclistBuildHasher`] created with a secret seed produces keyed hashers, which makes hash
+//! tables resistant to hash-flooding attacks in environments without SipHash:
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//!
+//! use cyclist::hasher::CyclistBuildHasher;
+//! use cyclist::xoodyak::XoodyakKeyed;
+//!
+//! let mut map = HashMap::with_hasher(CyclistBuildHasher::<XoodyakKeyed>::with_seed(b"seed"));
+//! map.insert("key", "value");
+//!
+//! assert_eq!(map.get("key"), Some(&"value"));
+//! ```
+//!
+//! Writes are buffered, so a hash depends only on the concatenation of all written bytes.
+
+use core::hash::{BuildHasher, Hasher};
+
+use crate::{Cyclist, Keyed};
+
+/// The number of bytes buffered before being absorbed.
+const BUF_LEN: usize = 64;
+
+/// The domain separation label absorbed by seeded hashers.
+pub(crate) const HASHER_LABEL: &[u8] = b"cyclist.hasher";
+
+/// A [`Hasher`] which absorbs written bytes into a Cyclist object and squeezes 64-bit hashes.
+#[derive(Clone, Debug)]
+pub struct CyclistHasher<H> {
+    hash: H,
+    buf: [u8; BUF_LEN],
+    buf_len: usize,
+}
+
+impl<H> CyclistHasher<H>
+where
+    H: Cyclist + Clone,
+{
+    /// Creates a new [`CyclistHasher`] which absorbs into the given Cyclist object.
+    pub const fn new(hash: H) -> Self {
+        CyclistHasher { hash, buf: [0u8; BUF_LEN], buf_len: 0 }
+    }
+}
+
+impl<H> Hasher for CyclistHasher<H>
+where
+    H: Cyclist + Clone,
+{
+    fn finish(&self) -> u64 {
+        let mut hash = self.hash.clone();
+        hash.absorb(&self.buf[..self.buf_len]);
+        let mut out = [0u8; 8];
+        hash.squeeze_mut(&mut out);
+        u64::from_le_bytes(out)
+    }
+
+    fn write(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            // Only absorb a full buffer once more input arrives, leaving the final block for
+            // finishing.
+            if self.buf_len == BUF_LEN {
+                self.hash.absorb(&self.buf);
+                self.buf_len = 0;
+            }
+
+            let n = (BUF_LEN - self.buf_len).min(bytes.len());
+            self.buf[self.buf_len..self.buf_len + n].copy_from_slice(&bytes[..n]);
+            self.buf_len += n;
+            bytes = &bytes[n..];
+        }
+    }
+}
+
+/// A [`BuildHasher`] which creates [`CyclistHasher`]s from an initial Cyclist object.
+#[derive(Clone, Debug)]
+pub struct CyclistBuildHasher<H> {
+    hash: H,
+}
+
+impl<H> CyclistBuildHasher<H>
+where
+    H: Cyclist + Clone,
+{
+    /// Creates a new [`CyclistBuildHasher`] whose hashers start from copies of the given Cyclist
+    /// object.
+    pub const fn new(hash: H) -> Self {
+        CyclistBuildHasher { hash }
+    }
+}
+
+impl<K> CyclistBuildHasher<K>
+where
+    K: Keyed,
+{
+    /// Creates a new [`CyclistBuildHasher`] whose hashers are keyed with the given secret seed.
+    pub fn with_seed(seed: &[u8]) -> Self {
+        let mut keyed = K::new(seed, b"", b"");
+        keyed.absorb(HASHER_LABEL);
+        CyclistBuildHasher { hash: keyed }
+    }
+}
+
+impl<H> BuildHasher for CyclistBuildHasher<H>
+where
+    H: Cyclist + Clone,
+{
+    type Hasher = CyclistHasher<H>;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        CyclistHasher::new(self.hash.clone())
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::{XoodyakHash, XoodyakKeyed};
+
+    use super::*;
+
+    #[test]
+    fn concatenation() {
+        let build = CyclistBuildHasher::<XoodyakKeyed>::with_seed(b"seed");
+        let input = [0xCAu8; 200];
+
+        let mut one = build.build_hasher();
+        one.write(&input);
+
+        for chunk_len in [1, 7, 64, 65] {
+            let mut two = build.build_hasher();
+            for chunk in input.chunks(chunk_len) {
+                two.write(chunk);
+            }
+            assert_eq!(one.finish(), two.finish(), "chunk_len={}", chunk_len);
+        }
+    }
+
+    #[test]
+    fn seeds() {
+        let one = CyclistBuildHasher::<XoodyakKeyed>::with_seed(b"one");
+        let two = CyclistBuildHasher::<XoodyakKeyed>::with_seed(b"two");
+        assert_eq!(one.hash_one("ok"), one.hash_one("ok"));
+        assert_ne!(one.hash_one("ok"), two.hash_one("ok"));
+        assert_ne!(one.hash_one("ok"), one.hash_one("ko"));
+    }
+
+    #[test]
+    fn finishing_is_idempotent() {
+        let mut hasher = CyclistHasher::new(XoodyakHash::default());
+        hasher.write(&[0u8; 64]);
+        assert_eq!(hasher.finish(), hasher.finish());
+        hasher.write(&[]);
+        let one = hasher.finish();
+        hasher.write(&[0]);
+        assert_ne!(one, hasher.finish());
+    }
+}
@@ -0,0 +1,138 @@
+//! Unique identifiers squeezed from a Cyclist object.
+//!
+//! Systems which already keep a Cyclist object as a random number generator (e.g. a keyed duplex
+//! seeded from a hardware entropy source) can mint identifiers from it without additional crates.
+//! [`generate_id`] returns a random identifier laid out as a version 4 UUID, and [`generate_ulid`]
+//! returns a ULID, whose 48-bit millisecond timestamp prefix makes identifiers sort by creation
+//! time.
+//!
+//! ```rust
+//! use cyclist::id::{format_ulid, format_uuid, generate_id, generate_ulid};
+//! use cyclist::xoodyak::XoodyakKeyed;
+//! use cyclist::Keyed;
+//!
+//! let mut rng = XoodyakKeyed::new(b"a seed from an entropy source", b"", b"");
+//!
+//! let id = generate_id(&mut rng);
+//! println!("{}", std::str::from_utf8(&format_uuid(&id)).unwrap());
+//!
+//! let ulid = generate_ulid(&mut rng, 1_469_918_176_385);
+//! assert!(format_ulid(&ulid).starts_with(b"01ARYZ6S41"));
+//! ```
+//!
+//! The uniqueness of identifiers depends entirely on the uniqueness of the Cyclist object's
+//! state: two objects in the same state will generate the same identifiers.
+
+use crate::Cyclist;
+
+/// The length of a formatted UUID, in bytes.
+pub const UUID_LEN: usize = 36;
+
+/// The length of a formatted ULID, in bytes.
+pub const ULID_LEN: usize = 26;
+
+/// The largest timestamp which can be encoded in a ULID.
+pub const MAX_ULID_TIMESTAMP: u64 = (1 << 48) - 1;
+
+/// Crockford's base32 alphabet, as used by ULIDs.
+const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Returns a random 128-bit identifier squeezed from the given Cyclist object, with the version and
+/// variant bits of a version 4 UUID.
+pub fn generate_id<C: Cyclist + ?Sized>(rng: &mut C) -> [u8; 16] {
+    let mut id = [0u8; 16];
+    rng.squeeze_mut(&mut id);
+    id[6] = (id[6] & 0x0F) | 0x40;
+    id[8] = (id[8] & 0x3F) | 0x80;
+    id
+}
+
+/// Returns a ULID with the given millisecond timestamp and 80 random bits squeezed from the given
+/// Cyclist object.
+///
+/// # Panics
+///
+/// Panics if `timestamp_ms` is greater than [`MAX_ULID_TIMESTAMP`].
+pub fn generate_ulid<C: Cyclist + ?Sized>(rng: &mut C, timestamp_ms: u64) -> [u8; 16] {
+    assert!(timestamp_ms <= MAX_ULID_TIMESTAMP, "timestamp out of range");
+
+    let mut id = [0u8; 16];
+    id[..6].copy_from_slice(&timestamp_ms.to_be_bytes()[2..]);
+    rng.squeeze_mut(&mut id[6..]);
+    id
+}
+
+/// Formats the given identifier as a hyphenated, lowercase hexadecimal UUID string.
+pub fn format_uuid(id: &[u8; 16]) -> [u8; UUID_LEN] {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+
+    let mut out = [b'-'; UUID_LEN];
+    let mut pos = 0;
+    for (i, &b) in id.iter().enumerate() {
+        if matches!(i, 4 | 6 | 8 | 10) {
+            pos += 1;
+        }
+        out[pos] = HEX[usize::from(b >> 4)];
+        out[pos + 1] = HEX[usize::from(b & 0x0F)];
+        pos += 2;
+    }
+    out
+}
+
+/// Formats the given identifier as a ULID string in Crockford's base32.
+pub fn format_ulid(id: &[u8; 16]) -> [u8; ULID_LEN] {
+    let n = u128::from_be_bytes(*id);
+    let mut out = [0u8; ULID_LEN];
+    for (i, c) in out.iter_mut().enumerate() {
+        let digit = (n >> (5 * (ULID_LEN - 1 - i))) & 0x1F;
+        *c = CROCKFORD[usize::try_from(digit).expect("unexpected overflow")];
+    }
+    out
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::{XoodyakHash, XoodyakKeyed};
+
+    use super::*;
+
+    #[test]
+    fn uuids() {
+        let mut rng = XoodyakKeyed::new(b"seed", b"", b"");
+        let one = generate_id(&mut rng);
+        let two = generate_id(&mut rng);
+        assert_ne!(one, two);
+
+        for id in [one, two] {
+            let s = format_uuid(&id);
+            assert_eq!(b'4', s[14]);
+            assert!(matches!(s[19], b'8' | b'9' | b'a' | b'b'));
+        }
+
+        let mut id = [0u8; 16];
+        id.iter_mut().enumerate().for_each(|(i, b)| *b = u8::try_from(i * 17).expect("ok"));
+        assert_eq!(b"00112233-4455-6677-8899-aabbccddeeff", &format_uuid(&id));
+    }
+
+    #[test]
+    fn ulids() {
+        assert_eq!(b"00000000000000000000000000", &format_ulid(&[0u8; 16]));
+        assert_eq!(b"7ZZZZZZZZZZZZZZZZZZZZZZZZZ", &format_ulid(&[0xFFu8; 16]));
+
+        // The timestamp from the ULID specification's example.
+        let mut rng = XoodyakHash::default();
+        let one = generate_ulid(&mut rng, 1_469_918_176_385);
+        assert_eq!(b"01ARYZ6S41", &format_ulid(&one)[..10]);
+
+        // ULIDs sort by timestamp.
+        let two = generate_ulid(&mut rng, 1_469_918_176_386);
+        assert!(format_ulid(&one) < format_ulid(&two));
+        assert!(one < two);
+    }
+
+    #[test]
+    #[should_panic(expected = "timestamp out of range")]
+    fn ulid_timestamp_overflow() {
+        generate_ulid(&mut XoodyakHash::default(), MAX_ULID_TIMESTAMP + 1);
+    }
+}
@@ -0,0 +1,198 @@
+#![cfg(feature = "std")]
+
+//! Adapters between Cyclist objects and [`std::io`] traits.
+//!
+//! An [`AbsorbWriter`] implements [`Write`], buffering partial blocks so that everything written
+//! to it is absorbed as a single absorb operation. This allows for hashing readers with
+//! [`io::copy`]:
+//!
+//! ```rust
+//! use std::io;
+//!
+//! use cyclist::io::AbsorbWriter;
+//! use cyclist::xoodyak::XoodyakHash;
+//! use cyclist::Cyclist;
+//!
+//! let mut file = io::Cursor::new(vec![0xCAu8; 100_000]);
+//! let mut writer = AbsorbWriter::new(XoodyakHash::default());
+//! io::copy(&mut file, &mut writer).expect("should copy");
+//!
+//! let mut hash = writer.finish();
+//! let digest = hash.squeeze(32);
+//! ```
+//!
+//! A [`SqueezeReader`] implements [`Read`], yielding an unbounded stream of output from a single
+//! squeeze operation:
+//!
+//! ```rust
+//! use std::io::Read;
+//!
+//! use cyclist::io::SqueezeReader;
+//! use cyclist::xoodyak::XoodyakHash;
+//! use cyclist::Cyclist;
+//!
+//! let mut hash = XoodyakHash::default();
+//! hash.absorb(b"a seed");
+//!
+//! let mut reader = SqueezeReader::new(hash);
+//! let mut out = [0u8; 100];
+//! reader.read_exact(&mut out).expect("should read");
+//! ```
+
+use std::io::{self, Read, Write};
+
+use crate::absorber::Absorber;
+use crate::Rates;
+
+/// A [`Write`] implementation which absorbs everything written to it as a single absorb operation.
+///
+/// See [`Absorber`] for details and limitations.
+#[derive(Clone, Debug)]
+pub struct AbsorbWriter<C> {
+    absorber: Absorber<C>,
+}
+
+impl<C> AbsorbWriter<C>
+where
+    C: Rates,
+{
+    /// Creates a new [`AbsorbWriter`] which absorbs into the given Cyclist object.
+    pub const fn new(cyclist: C) -> Self {
+        AbsorbWriter { absorber: Absorber::new(cyclist) }
+    }
+
+    /// Absorbs any buffered data and returns the Cyclist object. Everything written will have been
+    /// absorbed exactly as if it had been passed to a single [`Cyclist::absorb`](crate::Cyclist::absorb) call.
+    pub fn finish(self) -> C {
+        self.absorber.finish()
+    }
+}
+
+impl<C> Write for AbsorbWriter<C>
+where
+    C: Rates,
+{
+    fn write(&mut self, bin: &[u8]) -> io::Result<usize> {
+        self.absorber.update(bin);
+        Ok(bin.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A [`Read`] implementation which yields an unbounded stream of squeezed output.
+///
+/// Output is squeezed a block at a time and buffered, so any sequence of reads returns the same
+/// bytes as a single [`Cyclist::squeeze_mut`](crate::Cyclist::squeeze_mut) call of their total length.
+#[derive(Clone, Debug)]
+pub struct SqueezeReader<C> {
+    cyclist: C,
+    buf: Vec<u8>,
+    buf_pos: usize,
+    squeezed: bool,
+}
+
+impl<C> SqueezeReader<C>
+where
+    C: Rates,
+{
+    /// Creates a new [`SqueezeReader`] which squeezes output from the given Cyclist object.
+    pub fn new(cyclist: C) -> Self {
+        let rate = C::SQUEEZE_RATE;
+        SqueezeReader { cyclist, buf: vec![0u8; rate], buf_pos: rate, squeezed: false }
+    }
+}
+
+impl<C> Read for SqueezeReader<C>
+where
+    C: Rates,
+{
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let mut pos = 0;
+        while pos < out.len() {
+            if self.buf_pos == self.buf.len() {
+                // The first block starts a squeeze operation, and each subsequent block extends it.
+                if self.squeezed {
+                    self.cyclist.squeeze_more_mut(&mut self.buf);
+                } else {
+                    self.cyclist.squeeze_mut(&mut self.buf);
+                    self.squeezed = true;
+                }
+                self.buf_pos = 0;
+            }
+
+            let n = (self.buf.len() - self.buf_pos).min(out.len() - pos);
+            out[pos..pos + n].copy_from_slice(&self.buf[self.buf_pos..self.buf_pos + n]);
+            self.buf_pos += n;
+            pos += n;
+        }
+        Ok(out.len())
+    }
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::{XoodyakHash, XoodyakKeyed};
+    use crate::Cyclist;
+
+    use super::*;
+
+    fn check<C: Rates + Clone>(cyclist: C) {
+        for len in [0, 1, 15, 16, 17, 43, 44, 45, 100, 1000] {
+            let input = (0..len).map(|i| u8::try_from(i % 251).expect("ok")).collect::<Vec<_>>();
+
+            let mut expected = cyclist.clone();
+            expected.absorb(&input);
+            let expected = expected.squeeze(32);
+
+            for chunk_len in [1, 7, 16, 44, 50, 333] {
+                let mut writer = AbsorbWriter::new(cyclist.clone());
+                for chunk in input.chunks(chunk_len) {
+                    writer.write_all(chunk).expect("should write");
+                }
+                assert_eq!(
+                    expected,
+                    writer.finish().squeeze(32),
+                    "len={}, chunk_len={}",
+                    len,
+                    chunk_len
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn equivalent_to_single_absorb() {
+        check(XoodyakHash::default());
+        check(XoodyakKeyed::new(b"key", b"", b""));
+    }
+
+    #[test]
+    fn copying() {
+        let input = vec![0xCAu8; 10_000];
+        let mut writer = AbsorbWriter::new(XoodyakHash::default());
+        io::copy(&mut input.as_slice(), &mut writer).expect("should copy");
+
+        let mut expected = XoodyakHash::default();
+        expected.absorb(&input);
+        assert_eq!(expected.squeeze(32), writer.finish().squeeze(32));
+    }
+
+    #[test]
+    fn squeezing() {
+        let mut cyclist = XoodyakKeyed::new(b"key", b"", b"");
+        cyclist.absorb(b"seed");
+
+        let expected = cyclist.clone().squeeze(1000);
+        for chunk_len in [1, 7, 24, 50, 333] {
+            let mut reader = SqueezeReader::new(cyclist.clone());
+            let mut out = vec![0u8; 1000];
+            for chunk in out.chunks_mut(chunk_len) {
+                reader.read_exact(chunk).expect("should read");
+            }
+            assert_eq!(expected, out, "chunk_len={}", chunk_len);
+        }
+    }
+}
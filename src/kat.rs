@@ -0,0 +1,449 @@
+#![cfg(feature = "std")]
+
+//! A parser and runner for NIST Lightweight Cryptography (LWC) known-answer test files.
+//!
+//! The LWC competition's `genkat` programs produce `LWC_AEAD_KAT_{key}_{nonce}.txt` and
+//! `LWC_HASH_KAT_{digest}.txt` files, which list records of hexadecimal fields separated by blank
+//! lines:
+//!
+//! ```text
+//! Count = 1
+//! Key = 000102030405060708090A0B0C0D0E0F
+//! Nonce = 000102030405060708090A0B0C0D0E0F
+//! PT =
+//! AD =
+//! CT = ...
+//!
+//! Count = 1
+//! Msg =
+//! MD = ...
+//! ```
+//!
+//! [`parse`] reads either kind of file into a list of [`Vector`]s, which can be checked against
+//! any [`Scheme`], whether one of the crate's own or a custom parameterization:
+//!
+//! ```rust,no_run
+//! use cyclist::conformance::Xoodyak;
+//! use cyclist::kat;
+//!
+//! let file = std::fs::read_to_string("LWC_AEAD_KAT_128_128.txt").expect("should read file");
+//! let vectors = kat::parse(&file).expect("should parse file");
+//! let failures = kat::failures::<Xoodyak>(&vectors).map(kat::Vector::count).collect::<Vec<_>>();
+//! assert!(failures.is_empty(), "failed vectors: {failures:?}");
+//! ```
+//!
+//! AEAD vectors are checked as the LWC reference implementations run them: the keyed mode is
+//! initialized with the key and with the nonce as the key ID, absorbs the associated data, and
+//! seals the plaintext, which must produce the ciphertext (including the tag), which in turn must
+//! open to the plaintext. Hash vectors are checked by absorbing the message in hash mode and
+//! squeezing a digest of the expected length.
+//!
+//! # Generation
+//!
+//! [`aead_vectors`] and [`hash_vectors`] generate vectors in the same pattern as `genkat`, for
+//! schemes which have no official vectors, and [`format`] writes them in the same file format. The
+//! crate pins the Keccyak schemes' outputs with files generated this way in `kats/`, which `cargo
+//! xtask kats` regenerates.
+//!
+//! The Xoodyak files in `kats/` have the exact layout of the LWC submission's: a 128-bit key and
+//! nonce of `00 01 02 ...`, every plaintext and associated data length up to 32 bytes, and every
+//! message length up to 1024 bytes. They were generated the same way, from an implementation which
+//! passes XKCP's SUPERCOP vectors, and aren't regenerated.
+
+use core::fmt;
+
+use crate::conformance::Scheme;
+use crate::{hex_digit, Cyclist, Keyed};
+
+/// A known-answer test vector.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Vector {
+    /// Authenticated data and a plaintext sealed with a key and nonce.
+    Aead {
+        /// The vector's number in its file.
+        count: u32,
+        /// The key.
+        key: Vec<u8>,
+        /// The nonce, used as the key ID.
+        nonce: Vec<u8>,
+        /// The plaintext.
+        plaintext: Vec<u8>,
+        /// The authenticated data.
+        ad: Vec<u8>,
+        /// The expected ciphertext, including the tag.
+        ciphertext: Vec<u8>,
+    },
+
+    /// A message hashed in hash mode.
+    Hash {
+        /// The vector's number in its file.
+        count: u32,
+        /// The message.
+        message: Vec<u8>,
+        /// The expected digest.
+        digest: Vec<u8>,
+    },
+}
+
+impl fmt::Display for Vector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Count = {}", self.count())?;
+        let fields: &[(&str, &[u8])] = match self {
+            Vector::Aead { key, nonce, plaintext, ad, ciphertext, .. } => {
+                &[("Key", key), ("Nonce", nonce), ("PT", plaintext), ("AD", ad), ("CT", ciphertext)]
+            }
+            Vector::Hash { message, digest, .. } => &[("Msg", message), ("MD", digest)],
+        };
+        for (name, value) in fields {
+            write!(f, "{name} =")?;
+            if !value.is_empty() {
+                f.write_str(" ")?;
+            }
+            for b in *value {
+                write!(f, "{b:02X}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Vector {
+    /// Returns the vector's number in its file.
+    pub const fn count(&self) -> u32 {
+        match self {
+            Vector::Aead { count, .. } | Vector::Hash { count, .. } => *count,
+        }
+    }
+
+    /// Returns `true` if the given scheme produces the vector's expected output.
+    pub fn check<S: Scheme>(&self) -> bool {
+        match self {
+            Vector::Aead { key, nonce, plaintext, ad, ciphertext, .. } => {
+                let mut keyed = S::Keyed::new(key, nonce, b"");
+                keyed.absorb(ad);
+                let mut out = plaintext.clone();
+                out.resize(S::Keyed::sealed_len(plaintext.len()), 0);
+                keyed.seal_mut(&mut out);
+                if out != *ciphertext {
+                    return false;
+                }
+
+                let mut keyed = S::Keyed::new(key, nonce, b"");
+                keyed.absorb(ad);
+                keyed.open_mut(&mut out).is_ok() && out[..plaintext.len()] == *plaintext
+            }
+            Vector::Hash { message, digest, .. } => {
+                let mut hash = S::Hash::default();
+                hash.absorb(message);
+                hash.squeeze(digest.len()) == *digest
+            }
+        }
+    }
+}
+
+/// An error parsing a known-answer test file. Each variant holds the (1-based) line number at
+/// which the error was found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// A line is not of the form `Name = value`, or names an unknown or repeated field.
+    Syntax(usize),
+
+    /// A value is not valid hexadecimal or, for `Count`, decimal.
+    InvalidValue(usize),
+
+    /// A record is missing a field, or mixes AEAD and hash fields.
+    IncompleteRecord(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Syntax(line) => write!(f, "invalid syntax on line {line}"),
+            Error::InvalidValue(line) => write!(f, "invalid value on line {line}"),
+            Error::IncompleteRecord(line) => write!(f, "incomplete record starting on line {line}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Parses the contents of an LWC AEAD or hash known-answer test file.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the file is malformed.
+pub fn parse(input: &str) -> Result<Vec<Vector>, Error> {
+    let mut vectors = Vec::new();
+    let mut record = Record::default();
+    for (i, line) in input.lines().enumerate() {
+        let line_no = i + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            if let Some(vector) = record.finish()? {
+                vectors.push(vector);
+            }
+            continue;
+        }
+
+        let (name, value) = line.split_once('=').ok_or(Error::Syntax(line_no))?;
+        record.set(line_no, name.trim(), value.trim())?;
+    }
+
+    vectors.extend(record.finish()?);
+    Ok(vectors)
+}
+
+/// Returns the vectors which the given scheme fails.
+pub fn failures<'a, S: Scheme>(vectors: &'a [Vector]) -> impl Iterator<Item = &'a Vector> + 'a {
+    vectors.iter().filter(|v| !v.check::<S>())
+}
+
+/// Returns the given vectors in the LWC known-answer test file format.
+pub fn format(vectors: &[Vector]) -> String {
+    vectors.iter().map(|v| format!("{v}\n")).collect()
+}
+
+/// Generates AEAD vectors for the given scheme, key, and nonce, with every combination of the
+/// given plaintext and associated data lengths.
+///
+/// As with `genkat`, the plaintext and associated data are the sequence `00 01 02 ...`, and the
+/// vectors iterate over associated data lengths for each plaintext length.
+///
+/// # Panics
+///
+/// Panics if the key is empty, or the key and nonce are too long for the scheme.
+pub fn aead_vectors<S: Scheme>(key: &[u8], nonce: &[u8], lengths: &[usize]) -> Vec<Vector> {
+    let mut vectors = Vec::with_capacity(lengths.len() * lengths.len());
+    for &pt_len in lengths {
+        for &ad_len in lengths {
+            let (plaintext, ad) = (sequence(pt_len), sequence(ad_len));
+            let mut keyed = S::Keyed::new(key, nonce, b"");
+            keyed.absorb(&ad);
+            let mut ciphertext = plaintext.clone();
+            ciphertext.resize(S::Keyed::sealed_len(pt_len), 0);
+            keyed.seal_mut(&mut ciphertext);
+            vectors.push(Vector::Aead {
+                count: next_count(&vectors),
+                key: key.to_vec(),
+                nonce: nonce.to_vec(),
+                plaintext,
+                ad,
+                ciphertext,
+            });
+        }
+    }
+    vectors
+}
+
+/// Generates hash vectors for the given scheme with messages of the given lengths and digests of
+/// `digest_len` bytes.
+///
+/// As with `genkat`, each message is the sequence `00 01 02 ...`.
+pub fn hash_vectors<S: Scheme>(lengths: &[usize], digest_len: usize) -> Vec<Vector> {
+    let mut vectors = Vec::with_capacity(lengths.len());
+    for &len in lengths {
+        let message = sequence(len);
+        let mut hash = S::Hash::default();
+        hash.absorb(&message);
+        let digest = hash.squeeze(digest_len);
+        vectors.push(Vector::Hash { count: next_count(&vectors), message, digest });
+    }
+    vectors
+}
+
+/// Returns the count of the next vector in the list.
+fn next_count(vectors: &[Vector]) -> u32 {
+    u32::try_from(vectors.len() + 1).expect("too many vectors")
+}
+
+/// Returns the sequence `00 01 02 ...` of the given length, wrapping at `FF`.
+fn sequence(len: usize) -> Vec<u8> {
+    (0..len).map(|i| i.to_le_bytes()[0]).collect()
+}
+
+/// The fields of a record being parsed.
+#[derive(Debug, Default)]
+struct Record {
+    start: usize,
+    count: Option<u32>,
+    key: Option<Vec<u8>>,
+    nonce: Option<Vec<u8>>,
+    plaintext: Option<Vec<u8>>,
+    ad: Option<Vec<u8>>,
+    ciphertext: Option<Vec<u8>>,
+    message: Option<Vec<u8>>,
+    digest: Option<Vec<u8>>,
+}
+
+impl Record {
+    fn set(&mut self, line: usize, name: &str, value: &str) -> Result<(), Error> {
+        if self.start == 0 {
+            self.start = line;
+        }
+
+        if name == "Count" {
+            let count = value.parse().map_err(|_| Error::InvalidValue(line))?;
+            return match self.count.replace(count) {
+                Some(_) => Err(Error::Syntax(line)),
+                None => Ok(()),
+            };
+        }
+
+        let field = match name {
+            "Key" => &mut self.key,
+            "Nonce" => &mut self.nonce,
+            "PT" => &mut self.plaintext,
+            "AD" => &mut self.ad,
+            "CT" => &mut self.ciphertext,
+            "Msg" => &mut self.message,
+            "MD" => &mut self.digest,
+            _ => return Err(Error::Syntax(line)),
+        };
+        match field.replace(decode(value).ok_or(Error::InvalidValue(line))?) {
+            Some(_) => Err(Error::Syntax(line)),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the completed vector, if any fields have been set, and resets the record.
+    fn finish(&mut self) -> Result<Option<Vector>, Error> {
+        let record = core::mem::take(self);
+        let vector = match record {
+            Record { start: 0, .. } => return Ok(None),
+            Record {
+                count: Some(count),
+                key: Some(key),
+                nonce: Some(nonce),
+                plaintext: Some(plaintext),
+                ad: Some(ad),
+                ciphertext: Some(ciphertext),
+                message: None,
+                digest: None,
+                ..
+            } => Vector::Aead { count, key, nonce, plaintext, ad, ciphertext },
+            Record {
+                count: Some(count),
+                key: None,
+                nonce: None,
+                plaintext: None,
+                ad: None,
+                ciphertext: None,
+                message: Some(message),
+                digest: Some(digest),
+                ..
+            } => Vector::Hash { count, message, digest },
+            Record { start, .. } => return Err(Error::IncompleteRecord(start)),
+        };
+        Ok(Some(vector))
+    }
+}
+
+/// Decodes a string of hexadecimal digits in either case.
+fn decode(hex: &str) -> Option<Vec<u8>> {
+    let hex = hex.as_bytes();
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    hex.chunks_exact(2).map(|pair| Some((hex_digit(pair[0])? << 4) | hex_digit(pair[1])?)).collect()
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::conformance::Xoodyak;
+
+    use super::*;
+
+    // The XKCP SUPERCOP Xoodyak vectors, in the LWC file format.
+    const AEAD: &str = "Count = 1
+Key = 5A4B3C2D1E0F00F1E2D3C4B5A6978879
+Nonce = 6B4C2D0EEFD0B19272533415F6D7B899
+PT = E465E566E667E7
+AD = 32F3B47535F6
+CT = 6E68081C7EACBF72E2A677A60E442748D7A86E788EB9D4
+
+";
+
+    const HASH: &str = "Count = 1
+Msg = 119713CC83EEEF
+MD = 999D5865B0DD9FA30973365FECF041778D0449A1B0C55B743660831A7D5025EE
+";
+
+    #[test]
+    fn xoodyak_vectors() {
+        let mut vectors = parse(AEAD).expect("should parse");
+        vectors.extend(parse(HASH).expect("should parse"));
+        assert_eq!(2, vectors.len());
+        assert!(matches!(&vectors[0], Vector::Aead { count: 1, ad, .. } if ad.len() == 6));
+        assert!(matches!(&vectors[1], Vector::Hash { count: 1, digest, .. } if digest.len() == 32));
+        assert_eq!(0, failures::<Xoodyak>(&vectors).count());
+    }
+
+    #[test]
+    fn xoodyak_lwc_vectors() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("kats/v1/Xoodyak");
+        let read = |file: &str| {
+            let path = dir.join(file);
+            let vectors = std::fs::read_to_string(&path).expect("should read vectors");
+            parse(&vectors).expect("should parse vectors")
+        };
+        let seq = (0..16).collect::<Vec<u8>>();
+
+        let aead = read("LWC_AEAD_KAT_128_128.txt");
+        assert_eq!(33 * 33, aead.len());
+        for (i, vector) in aead.iter().enumerate() {
+            let Vector::Aead { key, nonce, plaintext, ad, ciphertext, .. } = vector else {
+                panic!("expected an AEAD vector");
+            };
+            assert_eq!((&seq, &seq), (key, nonce));
+            assert_eq!((i / 33, i % 33), (plaintext.len(), ad.len()));
+            assert_eq!(plaintext.len() + 16, ciphertext.len());
+        }
+        assert_eq!(0, failures::<Xoodyak>(&aead).count());
+
+        let hash = read("LWC_HASH_KAT_256.txt");
+        assert_eq!(1025, hash.len());
+        for (i, vector) in hash.iter().enumerate() {
+            let Vector::Hash { message, digest, .. } = vector else {
+                panic!("expected a hash vector");
+            };
+            assert_eq!((i, 32), (message.len(), digest.len()));
+        }
+        assert_eq!(0, failures::<Xoodyak>(&hash).count());
+    }
+
+    #[test]
+    fn failing_vectors() {
+        let vectors = parse(&HASH.replace("MD = 9", "MD = 8")).expect("should parse");
+        assert_eq!(vec![1], failures::<Xoodyak>(&vectors).map(Vector::count).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn generated_round_trip() {
+        let mut vectors = aead_vectors::<Xoodyak>(&[0x0F; 16], &[0xF0; 16], &[0, 1, 24, 300]);
+        vectors.extend(hash_vectors::<Xoodyak>(&[0, 1, 16, 17], 32));
+        assert_eq!(20, vectors.len());
+        assert_eq!(16, vectors[15].count());
+        assert_eq!(Ok(vectors.clone()), parse(&format(&vectors)));
+        assert_eq!(0, failures::<Xoodyak>(&vectors).count());
+
+        assert_eq!(AEAD, format(&parse(AEAD).expect("should parse")));
+    }
+
+    #[test]
+    fn malformed_files() {
+        assert_eq!(Ok(vec![]), parse("\n\n"));
+        assert_eq!(Err(Error::Syntax(2)), parse("Count = 1\nMsg\n"));
+        assert_eq!(Err(Error::Syntax(2)), parse("Count = 1\nTag = 00\n"));
+        assert_eq!(Err(Error::Syntax(3)), parse("Count = 1\nMsg = 00\nMsg = 00\n"));
+        assert_eq!(Err(Error::InvalidValue(1)), parse("Count = one\n"));
+        assert_eq!(Err(Error::InvalidValue(2)), parse("Count = 1\nMsg = 0\n"));
+        assert_eq!(Err(Error::InvalidValue(2)), parse("Count = 1\nMsg = 0G\n"));
+        assert_eq!(Err(Error::IncompleteRecord(2)), parse("\nCount = 1\nMsg = 00\n"));
+        assert_eq!(
+            Err(Error::IncompleteRecord(1)),
+            parse("Count = 1\nMsg = 00\nMD = 00\nKey = 00\n\n")
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! Labeled key derivation.
+//!
+//! A [`Kdf`] derives any number of independent subkeys from a single input key, each identified by
+//! a label and an optional context (e.g. a session identifier):
+//!
+//! ```rust
+//! use cyclist::kdf::Kdf;
+//! use cyclist::xoodyak::XoodyakKeyed;
+//!
+//! let kdf = Kdf::<XoodyakKeyed>::new(b"an input key");
+//!
+//! let (mut enc_key, mut mac_key) = ([0u8; 32], [0u8; 32]);
+//! kdf.derive("example.com/v1/encryption", b"session 1", &mut enc_key);
+//! kdf.derive("example.com/v1/authentication", b"session 1", &mut mac_key);
+//! assert_ne!(enc_key, mac_key);
+//! ```
+//!
+//! Labels should be fixed strings which identify the subkey's purpose, and contexts should hold
+//! anything variable. Subkeys of different lengths are independent, so a 16-byte subkey is not a
+//! prefix of a 32-byte subkey with the same label and context.
+//!
+//! # Construction
+//!
+//! A keyed duplex is initialized with the input key and absorbs a domain separation label. To
+//! derive a subkey, a copy of the duplex absorbs the label, the context, and the subkey's length as
+//! a little-endian 64-bit integer, each with its own absorb operation, and the subkey is then
+//! squeezed as key data.
+
+use crate::Keyed;
+
+/// The domain separation label absorbed by every KDF.
+const KDF_LABEL: &[u8] = b"cyclist.kdf";
+
+/// A key derivation function which derives labeled subkeys from an input key.
+#[derive(Clone, Debug)]
+pub struct Kdf<K> {
+    keyed: K,
+}
+
+impl<K> Kdf<K>
+where
+    K: Keyed,
+{
+    /// Creates a new [`Kdf`] with the given input key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is empty or not shorter than the keyed duplex's absorb rate.
+    pub fn new(key: &[u8]) -> Self {
+        let mut keyed = K::new(key, b"", b"");
+        keyed.absorb(KDF_LABEL);
+        Kdf { keyed }
+    }
+
+    /// Fills `out` with the subkey for the given label and context.
+    pub fn derive(&self, label: &str, context: &[u8], out: &mut [u8]) {
+        let mut keyed = self.keyed.clone();
+        keyed.absorb(label.as_bytes());
+        keyed.absorb(context);
+        keyed.absorb(&u64::try_from(out.len()).expect("unexpected overflow").to_le_bytes());
+        keyed.squeeze_key_mut(out);
+    }
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakKeyed;
+
+    use super::*;
+
+    fn derive<const N: usize>(key: &[u8], label: &str, context: &[u8]) -> [u8; N] {
+        let mut out = [0u8; N];
+        Kdf::<XoodyakKeyed>::new(key).derive(label, context, &mut out);
+        out
+    }
+
+    #[test]
+    fn determinism() {
+        assert_eq!(derive::<32>(b"key", "label", b"ctx"), derive::<32>(b"key", "label", b"ctx"));
+    }
+
+    #[test]
+    fn separation() {
+        let base = derive::<32>(b"key", "label", b"ctx");
+        assert_ne!(base, derive::<32>(b"kez", "label", b"ctx"));
+        assert_ne!(base, derive::<32>(b"key", "labem", b"ctx"));
+        assert_ne!(base, derive::<32>(b"key", "label", b"cty"));
+
+        // The boundary between label and context is unambiguous.
+        assert_ne!(base, derive::<32>(b"key", "labelc", b"tx"));
+        assert_ne!(base, derive::<32>(b"key", "labe", b"lctx"));
+    }
+
+    #[test]
+    fn lengths_are_independent() {
+        let long = derive::<32>(b"key", "label", b"ctx");
+        let short = derive::<16>(b"key", "label", b"ctx");
+        assert_ne!(&long[..16], &short);
+    }
+}
@@ -2,7 +2,7 @@
 
 //! A collection of Cyclist/Keccak-_p_ (aka Keccyak) schemes.
 //!
-//! The four schemes are:
+//! The seven schemes are:
 //!
 //! 1. [`KeccyakMaxHash`] and [`KeccyakMaxKeyed`], which use the full Keccak-f\[1600\] permutation,
 //!    are parameterized to offer ~256-bit security with a very conservative design.
@@ -13,6 +13,16 @@
 //! 4. [`KeccyakMinHash`] and [`KeccyakMinKeyed`], which use the 10-round Keccak-p\[1600,10\]
 //!    permutation, are parameterized to offer ~128-bit security with a very performance-oriented
 //!    design.
+//! 5. [`Keccyak800Hash`] and [`Keccyak800Keyed`], which use the 22-round Keccak-f\[800\]
+//!    permutation, are parameterized to offer ~128-bit security on 32-bit targets (e.g. Cortex-M),
+//!    where the 64-bit lanes of Keccak-_p_\[1600\] are emulated with pairs of registers.
+//! 6. [`Keccyak400Hash`] and [`Keccyak400Keyed`], which use the 20-round Keccak-f\[400\]
+//!    permutation, are parameterized to offer ~128-bit security on very small microcontrollers and
+//!    smartcard-class devices, with a 50-byte state.
+//! 7. [`Keccyak200Hash`] and [`Keccyak200Keyed`], which use the 18-round Keccak-f\[200\]
+//!    permutation, are parameterized to offer only ~64-bit security, with a 25-byte state which
+//!    fits on 8-bit (e.g. AVR or 8051) devices. A 200-bit state is too small for 128-bit security
+//!    with any rate, so these schemes are suitable only for short-lived keys and low-value data.
 //!
 //! Parameters were chosen based on the discussion of the
 //! [Motorist](https://keccak.team/files/Keyakv2-doc2.2.pdf) construction, of which Cyclist is a
@@ -23,14 +33,28 @@
 //!
 //! **N.B:** This is not a published configuration for Cyclist and there are no official security
 //! analyses or specifications.
+//!
+//! # Test Vectors
+//!
+//! Since there are no official test vectors, the crate pins the outputs of [`Keccyak128Hash`],
+//! [`Keccyak256Hash`], and [`KeccyakMaxHash`] and their keyed modes with known-answer test files in
+//! the NIST LWC format (see the [`kat`](crate::kat) module), in `kats/v1`. Any change to those
+//! files is a breaking change to the schemes, and must come with a new version of the files.
+
+use core::ops::{BitAnd, BitXor, BitXorAssign, Not};
 
-use crate::macros::{bytes_to_lanes, lanes_to_bytes};
+use crate::fixed::FixedHash;
+use crate::macros::lane_accessors;
+use crate::motorist::Motorist;
 use crate::{CyclistHash, CyclistKeyed, Permutation};
 
 /// A Cyclist hash using Keccak-f\[1600\] and `r=1088`, offering 256-bit security and a very
 /// conservative design.
 pub type KeccyakMaxHash = CyclistHash<KeccakF1600, { 1600 / 8 }, { (1600 - 512) / 8 }>;
 
+/// [`KeccyakMaxHash`] with a 512-bit digest.
+pub type Keccyak512 = FixedHash<KeccakF1600, { 1600 / 8 }, { (1600 - 512) / 8 }, 64>;
+
 /// A keyed Cyclist using Keccak-f\[1600\] and `r_absorb=1536`/`r_squeeze=1344`, offering 256-bit
 /// security and a very conservative design.
 pub type KeccyakMaxKeyed = CyclistKeyed<
@@ -42,6 +66,17 @@ pub type KeccyakMaxKeyed = CyclistKeyed<
     32,
 >;
 
+/// [`KeccyakMaxKeyed`] as an [`aead`](::aead) scheme.
+#[cfg(feature = "aead")]
+pub type KeccyakMaxAead = crate::aead::CyclistAead<
+    KeccakF1600,
+    { 1600 / 8 },
+    { (1600 - 64) / 8 },  // R_absorb=b-W
+    { (1600 - 256) / 8 }, // R_squeeze=b-c
+    32,
+    32,
+>;
+
 /// A Cyclist hash using Keccak-p\[1600,14\] and `r=1088`, offering 256-bit security and a
 /// performance-oriented design.
 pub type Keccyak256Hash = CyclistHash<KeccakP1600_14, { 1600 / 8 }, { (1600 - 512) / 8 }>;
@@ -57,6 +92,17 @@ pub type Keccyak256Keyed = CyclistKeyed<
     32,
 >;
 
+/// [`Keccyak256Keyed`] as an [`aead`](::aead) scheme.
+#[cfg(feature = "aead")]
+pub type Keccyak256Aead = crate::aead::CyclistAead<
+    KeccakP1600_14,
+    { 1600 / 8 },
+    { (1600 - 64) / 8 },  // R_absorb=b-W
+    { (1600 - 256) / 8 }, // R_squeeze=b-c
+    32,
+    32,
+>;
+
 /// A Cyclist hash using Keccak-p\[1600,12\] and `r=1344`, offering 128-bit security and a
 /// performance-oriented design.
 pub type Keccyak128Hash = CyclistHash<KeccakP1600_12, { 1600 / 8 }, { (1600 - 256) / 8 }>;
@@ -72,6 +118,22 @@ pub type Keccyak128Keyed = CyclistKeyed<
     16,
 >;
 
+/// [`Keccyak128Keyed`] as an [`aead`](::aead) scheme.
+#[cfg(feature = "aead")]
+pub type Keccyak128Aead = crate::aead::CyclistAead<
+    KeccakP1600_12,
+    { 1600 / 8 },
+    { (1600 - 32) / 8 },  // R_absorb=b-W
+    { (1600 - 192) / 8 }, // R_squeeze=b-c
+    16,
+    16,
+>;
+
+/// A four-pipe [`Motorist`] session using Keccak-p\[1600,12\] and `r=1344`, offering 128-bit
+/// security and a performance-oriented design, with pipes permuted in parallel given the
+/// `keccak-multiway` feature.
+pub type Keccyak128Motorist = Motorist<KeccakP1600_12, { 1600 / 8 }, { (1600 - 256) / 8 }, 16, 4>;
+
 /// A Cyclist hash using Keccak-p\[1600,10\] and `r=1344`, offering 128-bit security and a
 /// very performance-oriented design.
 pub type KeccyakMinHash = CyclistHash<KeccakP1600_10, { 1600 / 8 }, { (1600 - 256) / 8 }>;
@@ -87,135 +149,655 @@ pub type KeccyakMinKeyed = CyclistKeyed<
     16,
 >;
 
+/// [`KeccyakMinKeyed`] as an [`aead`](::aead) scheme.
+#[cfg(feature = "aead")]
+pub type KeccyakMinAead = crate::aead::CyclistAead<
+    KeccakP1600_10,
+    { 1600 / 8 },
+    { (1600 - 32) / 8 },  // R_absorb=b-W
+    { (1600 - 192) / 8 }, // R_squeeze=b-c
+    16,
+    16,
+>;
+
+/// A Cyclist hash using Keccak-f\[800\] and `r=544`, offering 128-bit security on 32-bit
+/// targets.
+pub type Keccyak800Hash = CyclistHash<KeccakP800, { 800 / 8 }, { (800 - 256) / 8 }>;
+
+/// A keyed Cyclist using Keccak-f\[800\] and `r_absorb=768`/`r_squeeze=608`, offering 128-bit
+/// security on 32-bit targets.
+pub type Keccyak800Keyed = CyclistKeyed<
+    KeccakP800,
+    { 800 / 8 },
+    { (800 - 32) / 8 },  // R_absorb=b-W
+    { (800 - 192) / 8 }, // R_squeeze=b-c
+    16,
+    16,
+>;
+
+/// [`Keccyak800Keyed`] as an [`aead`](::aead) scheme.
+#[cfg(feature = "aead")]
+pub type Keccyak800Aead = crate::aead::CyclistAead<
+    KeccakP800,
+    { 800 / 8 },
+    { (800 - 32) / 8 },  // R_absorb=b-W
+    { (800 - 192) / 8 }, // R_squeeze=b-c
+    16,
+    16,
+>;
+
+/// A Cyclist hash using Keccak-f\[400\] and `r=144`, offering 128-bit security with a 50-byte
+/// state.
+pub type Keccyak400Hash = CyclistHash<KeccakP400, { 400 / 8 }, { (400 - 256) / 8 }>;
+
+/// A keyed Cyclist using Keccak-f\[400\] and `r_absorb=368`/`r_squeeze=208`, offering 128-bit
+/// security with a 50-byte state.
+pub type Keccyak400Keyed = CyclistKeyed<
+    KeccakP400,
+    { 400 / 8 },
+    { (400 - 32) / 8 },  // R_absorb=b-W
+    { (400 - 192) / 8 }, // R_squeeze=b-c
+    16,
+    16,
+>;
+
+/// [`Keccyak400Keyed`] as an [`aead`](::aead) scheme.
+#[cfg(feature = "aead")]
+pub type Keccyak400Aead = crate::aead::CyclistAead<
+    KeccakP400,
+    { 400 / 8 },
+    { (400 - 32) / 8 },  // R_absorb=b-W
+    { (400 - 192) / 8 }, // R_squeeze=b-c
+    16,
+    16,
+>;
+
+/// A Cyclist hash using Keccak-f\[200\] and `r=72`, offering 64-bit security with a 25-byte
+/// state.
+pub type Keccyak200Hash = CyclistHash<KeccakP200, { 200 / 8 }, { (200 - 128) / 8 }>;
+
+/// A keyed Cyclist using Keccak-f\[200\] and `r_absorb=168`/`r_squeeze=72`, offering 64-bit
+/// security with a 25-byte state.
+///
+/// Keys must be at most 20 bytes long, so there is no [`aead`](::aead) scheme for it: a
+/// [`CyclistAead`](crate::aead::CyclistAead) key is [`KEY_LEN`](crate::aead::KEY_LEN) bytes.
+pub type Keccyak200Keyed = CyclistKeyed<
+    KeccakP200,
+    { 200 / 8 },
+    { (200 - 32) / 8 },  // R_absorb=b-W
+    { (200 - 128) / 8 }, // R_squeeze=b-c
+    8,
+    8,
+>;
+
+// All Keccyak schemes can be sent and shared between threads.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<KeccyakMaxHash>();
+    assert_send_sync::<KeccyakMaxKeyed>();
+    assert_send_sync::<Keccyak512>();
+    assert_send_sync::<Keccyak256Hash>();
+    assert_send_sync::<Keccyak256Keyed>();
+    assert_send_sync::<Keccyak128Hash>();
+    assert_send_sync::<Keccyak128Keyed>();
+    assert_send_sync::<Keccyak128Motorist>();
+    assert_send_sync::<KeccyakMinHash>();
+    assert_send_sync::<KeccyakMinKeyed>();
+    assert_send_sync::<Keccyak800Hash>();
+    assert_send_sync::<Keccyak800Keyed>();
+    assert_send_sync::<Keccyak400Hash>();
+    assert_send_sync::<Keccyak400Keyed>();
+    assert_send_sync::<Keccyak200Hash>();
+    assert_send_sync::<Keccyak200Keyed>();
+};
+
 /// The Keccak-p\[1600,10\] permutation (aka KitTen).
-#[derive(Clone, Debug)]
-#[repr(align(8))]
-pub struct KeccakP1600_10([u8; 200]);
+#[derive(Clone, Debug, Default)]
+pub struct KeccakP1600_10([u64; 25]);
 
-impl Default for KeccakP1600_10 {
-    fn default() -> Self {
-        KeccakP1600_10([0u8; 200])
+impl Permutation<200> for KeccakP1600_10 {
+    const NAME: &'static str = "Keccak-p[1600,10]";
+    const ID: u16 = 0x0101;
+
+    lane_accessors!(u64, 200);
+
+    #[inline(always)]
+    fn permute(&mut self) {
+        keccak_p1600(&mut self.0, 10, keccak_p::keccak_p1600_10);
     }
-}
 
-impl AsRef<[u8; 200]> for KeccakP1600_10 {
-    fn as_ref(&self) -> &[u8; 200] {
-        &self.0
+    #[cfg(feature = "keccak-multiway")]
+    fn permute_many(states: &mut [Self]) {
+        keccak_p1600_many(states, 10);
     }
 }
 
-impl AsMut<[u8; 200]> for KeccakP1600_10 {
-    fn as_mut(&mut self) -> &mut [u8; 200] {
-        &mut self.0
+/// The Keccak-p\[1600,12\] permutation from the KangarooTwelve XOF/hash function.
+#[derive(Clone, Debug, Default)]
+pub struct KeccakP1600_12([u64; 25]);
+
+impl Permutation<200> for KeccakP1600_12 {
+    const NAME: &'static str = "Keccak-p[1600,12]";
+    const ID: u16 = 0x0102;
+
+    lane_accessors!(u64, 200);
+
+    #[inline(always)]
+    fn permute(&mut self) {
+        keccak_p1600(&mut self.0, 12, keccak_p::keccak_p1600_12);
+    }
+
+    #[cfg(feature = "keccak-multiway")]
+    fn permute_many(states: &mut [Self]) {
+        keccak_p1600_many(states, 12);
     }
 }
 
-impl Permutation<200> for KeccakP1600_10 {
+/// The Keccak-p\[1600,14\] permutation from the MarsupilamiFourteen XOF/hash function.
+#[derive(Clone, Debug, Default)]
+pub struct KeccakP1600_14([u64; 25]);
+
+impl Permutation<200> for KeccakP1600_14 {
+    const NAME: &'static str = "Keccak-p[1600,14]";
+    const ID: u16 = 0x0103;
+
+    lane_accessors!(u64, 200);
+
     #[inline(always)]
     fn permute(&mut self) {
-        let mut lanes = [0u64; 25];
-        bytes_to_lanes!(u64, self.0, lanes);
-        keccak_p::keccak_p1600_10(&mut lanes);
-        lanes_to_bytes!(u64, lanes, self.0);
+        keccak_p1600(&mut self.0, 14, keccak_p::keccak_p1600_14);
+    }
+
+    #[cfg(feature = "keccak-multiway")]
+    fn permute_many(states: &mut [Self]) {
+        keccak_p1600_many(states, 14);
     }
 }
 
-/// The Keccak-p\[1600,12\] permutation from the KangarooTwelve XOF/hash function.
-#[derive(Clone, Debug)]
-#[repr(align(8))]
-pub struct KeccakP1600_12([u8; 200]);
+/// The Keccak-f\[1600\] permutation from the SHA-3 hash algorithm.
+#[derive(Clone, Debug, Default)]
+pub struct KeccakF1600([u64; 25]);
+
+impl Permutation<200> for KeccakF1600 {
+    const NAME: &'static str = "Keccak-f[1600]";
+    const ID: u16 = 0x0104;
+
+    lane_accessors!(u64, 200);
+
+    #[inline(always)]
+    fn permute(&mut self) {
+        keccak_p1600(&mut self.0, 24, keccak_p::keccak_f1600);
+    }
 
-impl Default for KeccakP1600_12 {
-    fn default() -> Self {
-        KeccakP1600_12([0u8; 200])
+    #[cfg(feature = "keccak-multiway")]
+    fn permute_many(states: &mut [Self]) {
+        keccak_p1600_many(states, 24);
     }
 }
 
-impl AsRef<[u8; 200]> for KeccakP1600_12 {
-    fn as_ref(&self) -> &[u8; 200] {
-        &self.0
+/// The Keccak-f\[800\] permutation (i.e. Keccak-p\[800,22\]), which uses 32-bit lanes.
+#[derive(Clone, Debug, Default)]
+pub struct KeccakP800([u32; 25]);
+
+impl Permutation<100> for KeccakP800 {
+    const NAME: &'static str = "Keccak-f[800]";
+    const ID: u16 = 0x0105;
+
+    lane_accessors!(u32, 100);
+
+    #[inline(always)]
+    fn permute(&mut self) {
+        keccak_p(&mut self.0, 22);
     }
 }
 
-impl AsMut<[u8; 200]> for KeccakP1600_12 {
-    fn as_mut(&mut self) -> &mut [u8; 200] {
-        &mut self.0
+/// The Keccak-f\[400\] permutation (i.e. Keccak-p\[400,20\]), which uses 16-bit lanes.
+#[derive(Clone, Debug, Default)]
+pub struct KeccakP400([u16; 25]);
+
+impl Permutation<50> for KeccakP400 {
+    const NAME: &'static str = "Keccak-f[400]";
+    const ID: u16 = 0x0106;
+
+    lane_accessors!(u16, 50);
+
+    #[inline(always)]
+    fn permute(&mut self) {
+        keccak_p(&mut self.0, 20);
     }
 }
 
-impl Permutation<200> for KeccakP1600_12 {
+/// The Keccak-f\[200\] permutation (i.e. Keccak-p\[200,18\]), which uses 8-bit lanes.
+#[derive(Clone, Debug, Default)]
+pub struct KeccakP200([u8; 25]);
+
+impl Permutation<25> for KeccakP200 {
+    const NAME: &'static str = "Keccak-f[200]";
+    const ID: u16 = 0x0107;
+
+    lane_accessors!(u8, 25);
+
     #[inline(always)]
     fn permute(&mut self) {
-        let mut lanes = [0u64; 25];
-        bytes_to_lanes!(u64, self.0, lanes);
-        keccak_p::keccak_p1600_12(&mut lanes);
-        lanes_to_bytes!(u64, lanes, self.0);
+        keccak_p(&mut self.0, 18);
     }
 }
 
-/// The Keccak-p\[1600,14\] permutation from the MarsupilamiFourteen XOF/hash function.
-#[derive(Clone, Debug)]
-#[repr(align(8))]
-pub struct KeccakP1600_14([u8; 200]);
+/// Applies Keccak-p\[1600\] with the given number of rounds to the given lanes.
+///
+/// On 32-bit targets, or with the `keccak-interleaved` feature enabled, this uses a bit-interleaved
+/// implementation which represents each 64-bit lane as a pair of 32-bit words, as XKCP does for
+/// 32-bit CPUs, replacing each 64-bit rotation with two 32-bit rotations. Otherwise, it uses the
+/// given lane-oriented implementation from the `keccak-p` crate.
+#[inline(always)]
+fn keccak_p1600(lanes: &mut [u64; 25], rounds: usize, lane_oriented: fn(&mut [u64; 25])) {
+    if cfg!(any(feature = "keccak-interleaved", target_pointer_width = "32")) {
+        let mut interleaved = [Interleaved::default(); 25];
+        for (i, &lane) in interleaved.iter_mut().zip(lanes.iter()) {
+            *i = Interleaved::from(lane);
+        }
+        keccak_p(&mut interleaved, rounds);
+        for (lane, &i) in lanes.iter_mut().zip(interleaved.iter()) {
+            *lane = u64::from(i);
+        }
+    } else {
+        lane_oriented(lanes);
+    }
+}
+
+/// Permutes each of the given states with Keccak-p\[1600\] with the given number of rounds, four
+/// or two at a time (or one at a time, with the `force-scalar` feature).
+///
+/// The states are transposed into lanes of `[u64; N]`, so each step of the permutation is a single
+/// operation on `N` independent states. This is portable code: the crate forbids `unsafe`, so it
+/// uses no intrinsics, and whatever vector instructions the compiler maps those operations onto
+/// depend on the build's target features (e.g. SSE2 by default on `x86_64`, or AVX2 with
+/// `-C target-feature=+avx2`). If the compiler doesn't vectorize it, this is slower than permuting
+/// the states one at a time, so compare the `permutation/Keccak-p1600-12x4` benchmark against
+/// `permutation/Keccak-p1600-12` before enabling the feature.
+#[cfg(feature = "keccak-multiway")]
+fn keccak_p1600_many<P: KeccakP1600Lanes>(states: &mut [P], rounds: usize) {
+    keccak_p1600_width(states, rounds, parallelism());
+}
+
+/// Returns the number of states [`keccak_p1600_many`] permutes at a time.
+#[cfg(feature = "keccak-multiway")]
+const fn parallelism() -> usize {
+    if cfg!(feature = "force-scalar") {
+        1
+    } else {
+        4
+    }
+}
+
+/// Permutes each of the given states with Keccak-p\[1600\] with the given number of rounds, at most
+/// `width` at a time.
+#[cfg(feature = "keccak-multiway")]
+fn keccak_p1600_width<P: KeccakP1600Lanes>(states: &mut [P], rounds: usize, width: usize) {
+    let mut rest = states;
+    if width >= 4 {
+        rest = keccak_p1600_chunks::<P, 4>(rest, rounds);
+    }
+    if width >= 2 {
+        rest = keccak_p1600_chunks::<P, 2>(rest, rounds);
+    }
+    for state in rest {
+        state.permute();
+    }
+}
 
-impl Default for KeccakP1600_14 {
-    fn default() -> Self {
-        KeccakP1600_14([0u8; 200])
+/// Permutes as many of the given states as possible `N` at a time, returning the remainder.
+#[cfg(feature = "keccak-multiway")]
+fn keccak_p1600_chunks<P: KeccakP1600Lanes, const N: usize>(
+    states: &mut [P],
+    rounds: usize,
+) -> &mut [P] {
+    let mut chunks = states.chunks_exact_mut(N);
+    for chunk in &mut chunks {
+        keccak_p1600_n::<P, N>(chunk, rounds);
     }
+    chunks.into_remainder()
 }
 
-impl AsRef<[u8; 200]> for KeccakP1600_14 {
-    fn as_ref(&self) -> &[u8; 200] {
-        &self.0
+/// Permutes exactly `N` states in parallel with Keccak-p\[1600\] with the given number of rounds.
+#[cfg(feature = "keccak-multiway")]
+#[inline(always)]
+fn keccak_p1600_n<P: KeccakP1600Lanes, const N: usize>(states: &mut [P], rounds: usize) {
+    let mut lanes = [Multi([0u64; N]); 25];
+    for (j, state) in states.iter_mut().enumerate() {
+        for (lane, &x) in lanes.iter_mut().zip(state.lanes().iter()) {
+            lane.0[j] = x;
+        }
     }
+
+    keccak_p(&mut lanes, rounds);
+
+    for (j, state) in states.iter_mut().enumerate() {
+        for (x, lane) in state.lanes().iter_mut().zip(lanes.iter()) {
+            *x = lane.0[j];
+        }
+    }
+}
+
+/// A Keccak-p\[1600\] permutation whose state is stored as 64-bit lanes.
+#[cfg(feature = "keccak-multiway")]
+trait KeccakP1600Lanes: Permutation<200> {
+    /// Returns the state's lanes.
+    fn lanes(&mut self) -> &mut [u64; 25];
 }
 
-impl AsMut<[u8; 200]> for KeccakP1600_14 {
-    fn as_mut(&mut self) -> &mut [u8; 200] {
+#[cfg(feature = "keccak-multiway")]
+impl KeccakP1600Lanes for KeccakP1600_10 {
+    fn lanes(&mut self) -> &mut [u64; 25] {
         &mut self.0
     }
 }
 
-impl Permutation<200> for KeccakP1600_14 {
+#[cfg(feature = "keccak-multiway")]
+impl KeccakP1600Lanes for KeccakP1600_12 {
+    fn lanes(&mut self) -> &mut [u64; 25] {
+        &mut self.0
+    }
+}
+
+#[cfg(feature = "keccak-multiway")]
+impl KeccakP1600Lanes for KeccakP1600_14 {
+    fn lanes(&mut self) -> &mut [u64; 25] {
+        &mut self.0
+    }
+}
+
+#[cfg(feature = "keccak-multiway")]
+impl KeccakP1600Lanes for KeccakF1600 {
+    fn lanes(&mut self) -> &mut [u64; 25] {
+        &mut self.0
+    }
+}
+
+/// `N` independent 64-bit lanes, one from each of `N` states.
+#[cfg(feature = "keccak-multiway")]
+#[derive(Clone, Copy, Debug)]
+struct Multi<const N: usize>([u64; N]);
+
+#[cfg(feature = "keccak-multiway")]
+impl<const N: usize> BitAnd for Multi<N> {
+    type Output = Self;
+
     #[inline(always)]
-    fn permute(&mut self) {
-        let mut lanes = [0u64; 25];
-        bytes_to_lanes!(u64, self.0, lanes);
-        keccak_p::keccak_p1600_14(&mut lanes);
-        lanes_to_bytes!(u64, lanes, self.0);
+    fn bitand(self, rhs: Self) -> Self {
+        Multi(core::array::from_fn(|i| self.0[i] & rhs.0[i]))
     }
 }
 
-/// The Keccak-f\[1600\] permutation from the SHA-3 hash algorithm.
-#[derive(Clone, Debug)]
-#[repr(align(8))]
-pub struct KeccakF1600([u8; 200]);
+#[cfg(feature = "keccak-multiway")]
+impl<const N: usize> BitXor for Multi<N> {
+    type Output = Self;
+
+    #[inline(always)]
+    fn bitxor(self, rhs: Self) -> Self {
+        Multi(core::array::from_fn(|i| self.0[i] ^ rhs.0[i]))
+    }
+}
 
-impl Default for KeccakF1600 {
-    fn default() -> Self {
-        KeccakF1600([0u8; 200])
+#[cfg(feature = "keccak-multiway")]
+impl<const N: usize> BitXorAssign for Multi<N> {
+    #[inline(always)]
+    fn bitxor_assign(&mut self, rhs: Self) {
+        *self = *self ^ rhs;
     }
 }
 
-impl AsRef<[u8; 200]> for KeccakF1600 {
-    fn as_ref(&self) -> &[u8; 200] {
-        &self.0
+#[cfg(feature = "keccak-multiway")]
+impl<const N: usize> Not for Multi<N> {
+    type Output = Self;
+
+    #[inline(always)]
+    fn not(self) -> Self {
+        Multi(self.0.map(|a| !a))
     }
 }
 
-impl AsMut<[u8; 200]> for KeccakF1600 {
-    fn as_mut(&mut self) -> &mut [u8; 200] {
-        &mut self.0
+#[cfg(feature = "keccak-multiway")]
+impl<const N: usize> Lane for Multi<N> {
+    const MAX_ROUNDS: usize = 24;
+
+    #[inline(always)]
+    fn truncate(rc: u64) -> Self {
+        Multi([rc; N])
+    }
+
+    #[inline(always)]
+    fn rotate(self, n: u32) -> Self {
+        Multi(self.0.map(|a| a.rotate_left(n % 64)))
     }
 }
 
-impl Permutation<200> for KeccakF1600 {
+/// A Keccak-_p_ lane, for the permutations which aren't provided by the `keccak-p` crate.
+trait Lane:
+    Copy + BitAnd<Output = Self> + BitXor<Output = Self> + BitXorAssign + Not<Output = Self>
+{
+    /// The number of rounds in Keccak-f for this lane size (i.e. `12 + 2l`).
+    const MAX_ROUNDS: usize;
+
+    /// Returns the given round constant truncated to the lane size.
+    fn truncate(rc: u64) -> Self;
+
+    /// Rotates the lane left by the given number of bits, modulo the lane size.
+    fn rotate(self, n: u32) -> Self;
+}
+
+impl Lane for u32 {
+    const MAX_ROUNDS: usize = 22;
+
     #[inline(always)]
-    fn permute(&mut self) {
-        let mut lanes = [0u64; 25];
-        bytes_to_lanes!(u64, self.0, lanes);
-        keccak_p::keccak_f1600(&mut lanes);
-        lanes_to_bytes!(u64, lanes, self.0);
+    fn truncate(rc: u64) -> Self {
+        let [a, b, c, d, ..] = rc.to_le_bytes();
+        u32::from_le_bytes([a, b, c, d])
+    }
+
+    #[inline(always)]
+    fn rotate(self, n: u32) -> Self {
+        self.rotate_left(n % u32::BITS)
+    }
+}
+
+impl Lane for u8 {
+    const MAX_ROUNDS: usize = 18;
+
+    #[inline(always)]
+    fn truncate(rc: u64) -> Self {
+        rc.to_le_bytes()[0]
+    }
+
+    #[inline(always)]
+    fn rotate(self, n: u32) -> Self {
+        self.rotate_left(n % u8::BITS)
+    }
+}
+
+impl Lane for u16 {
+    const MAX_ROUNDS: usize = 20;
+
+    #[inline(always)]
+    fn truncate(rc: u64) -> Self {
+        let [a, b, ..] = rc.to_le_bytes();
+        u16::from_le_bytes([a, b])
+    }
+
+    #[inline(always)]
+    fn rotate(self, n: u32) -> Self {
+        self.rotate_left(n % u16::BITS)
+    }
+}
+
+/// A bit-interleaved 64-bit lane: the even bits of the lane, then the odd bits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Interleaved(u32, u32);
+
+impl From<u64> for Interleaved {
+    #[inline(always)]
+    fn from(x: u64) -> Self {
+        Interleaved(compress(x), compress(x >> 1))
+    }
+}
+
+impl From<Interleaved> for u64 {
+    #[inline(always)]
+    fn from(i: Interleaved) -> Self {
+        spread(i.0) | (spread(i.1) << 1)
+    }
+}
+
+/// Returns the even bits of the given word.
+#[inline(always)]
+const fn compress(x: u64) -> u32 {
+    let x = x & 0x5555_5555_5555_5555;
+    let x = (x | (x >> 1)) & 0x3333_3333_3333_3333;
+    let x = (x | (x >> 2)) & 0x0F0F_0F0F_0F0F_0F0F;
+    let x = (x | (x >> 4)) & 0x00FF_00FF_00FF_00FF;
+    let x = (x | (x >> 8)) & 0x0000_FFFF_0000_FFFF;
+    let [a, b, c, d, ..] = (x | (x >> 16)).to_le_bytes();
+    u32::from_le_bytes([a, b, c, d])
+}
+
+/// Returns a word with the given bits in its even bits.
+#[inline(always)]
+fn spread(x: u32) -> u64 {
+    let x = u64::from(x);
+    let x = (x | (x << 16)) & 0x0000_FFFF_0000_FFFF;
+    let x = (x | (x << 8)) & 0x00FF_00FF_00FF_00FF;
+    let x = (x | (x << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    let x = (x | (x << 2)) & 0x3333_3333_3333_3333;
+    (x | (x << 1)) & 0x5555_5555_5555_5555
+}
+
+impl BitAnd for Interleaved {
+    type Output = Self;
+
+    #[inline(always)]
+    fn bitand(self, rhs: Self) -> Self {
+        Interleaved(self.0 & rhs.0, self.1 & rhs.1)
+    }
+}
+
+impl BitXor for Interleaved {
+    type Output = Self;
+
+    #[inline(always)]
+    fn bitxor(self, rhs: Self) -> Self {
+        Interleaved(self.0 ^ rhs.0, self.1 ^ rhs.1)
+    }
+}
+
+impl BitXorAssign for Interleaved {
+    #[inline(always)]
+    fn bitxor_assign(&mut self, rhs: Self) {
+        self.0 ^= rhs.0;
+        self.1 ^= rhs.1;
+    }
+}
+
+impl Not for Interleaved {
+    type Output = Self;
+
+    #[inline(always)]
+    fn not(self) -> Self {
+        Interleaved(!self.0, !self.1)
+    }
+}
+
+impl Lane for Interleaved {
+    const MAX_ROUNDS: usize = 24;
+
+    #[inline(always)]
+    fn truncate(rc: u64) -> Self {
+        Interleaved::from(rc)
+    }
+
+    #[inline(always)]
+    fn rotate(self, n: u32) -> Self {
+        // Rotating by an even amount rotates the even and odd words by half as much. Rotating by an
+        // odd amount swaps the words, rotating the new even word by one more than the odd word.
+        let n = n % 64;
+        let half = n / 2;
+        if n & 1 == 0 {
+            Interleaved(self.0.rotate_left(half), self.1.rotate_left(half))
+        } else {
+            Interleaved(self.1.rotate_left(half + 1), self.0.rotate_left(half))
+        }
+    }
+}
+
+/// The Keccak round constants for 64-bit lanes. Smaller lanes use truncated constants.
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808A,
+    0x8000000080008000,
+    0x000000000000808B,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008A,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000A,
+    0x000000008000808B,
+    0x800000000000008B,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800A,
+    0x800000008000000A,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// The rho rotation offsets, in the order lanes are visited by pi.
+const RHO: [u32; 24] =
+    [1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44];
+
+/// The pi lane permutation.
+const PI: [usize; 24] =
+    [10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1];
+
+/// Applies the last `rounds` rounds of Keccak-f to the given lanes.
+#[inline(always)]
+fn keccak_p<L: Lane>(a: &mut [L; 25], rounds: usize) {
+    for &rc in &ROUND_CONSTANTS[L::MAX_ROUNDS - rounds..L::MAX_ROUNDS] {
+        // Theta.
+        let mut c = [a[0]; 5];
+        for (x, c) in c.iter_mut().enumerate() {
+            *c = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate(1);
+            for y in (0..25).step_by(5) {
+                a[y + x] ^= d;
+            }
+        }
+
+        // Rho and pi.
+        let mut last = a[1];
+        for (&j, &r) in PI.iter().zip(RHO.iter()) {
+            let t = a[j];
+            a[j] = last.rotate(r);
+            last = t;
+        }
+
+        // Chi.
+        for y in (0..25).step_by(5) {
+            let row = [a[y], a[y + 1], a[y + 2], a[y + 3], a[y + 4]];
+            for x in 0..5 {
+                a[y + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+
+        // Iota.
+        a[0] ^= L::truncate(rc);
     }
 }
 
@@ -232,6 +814,224 @@ mod tests {
         let mut d = KeccyakMaxKeyed::new(b"ok then", b"", b"");
         let p = d.open(&c);
 
-        assert_eq!(Some(m), p);
+        assert_eq!(Ok(m), p);
+    }
+
+    #[test]
+    fn lane_accessors() {
+        fn check<P: Permutation<WIDTH>, const WIDTH: usize>() {
+            let mut bytes = [0u8; WIDTH];
+            for (i, b) in bytes.iter_mut().enumerate() {
+                *b = u8::try_from(i % 251).unwrap();
+            }
+            let state = P::from_bytes(&bytes);
+            assert_eq!(bytes, state.to_bytes());
+
+            // Add an unaligned run of bytes and a single byte, and extract an unaligned prefix.
+            let mut state = P::default();
+            state.add_bytes(&bytes[..WIDTH - 3]);
+            state.add_byte(0xFF, WIDTH - 2);
+            let mut expected = bytes;
+            expected[WIDTH - 3] = 0;
+            expected[WIDTH - 2] = 0xFF;
+            expected[WIDTH - 1] = 0;
+            assert_eq!(expected, state.to_bytes());
+
+            let mut out = [0u8; WIDTH];
+            state.extract_bytes(&mut out[..WIDTH - 1]);
+            assert_eq!(expected[..WIDTH - 1], out[..WIDTH - 1]);
+
+            state.wipe();
+            assert_eq!([0u8; WIDTH], state.to_bytes());
+        }
+
+        check::<KeccakF1600, 200>();
+        check::<KeccakP800, 100>();
+        check::<KeccakP400, 50>();
+        check::<KeccakP200, 25>();
+    }
+
+    #[test]
+    fn keccak_f800() {
+        let mut lanes = [0u32; 25];
+        let mut expected = [0u32; 25];
+        for i in 0..3 {
+            keccak_p(&mut lanes, 22);
+            keccak::f800(&mut expected);
+            assert_eq!(expected, lanes, "i={}", i);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "keccak-multiway")]
+    fn permute_many() {
+        fn check<P: KeccakP1600Lanes>() {
+            for n in 0..12 {
+                let mut states = vec![P::default(); n];
+                for (i, state) in states.iter_mut().enumerate() {
+                    state.add_byte(u8::try_from(i).unwrap(), 0);
+                }
+                let mut expected = states.clone();
+                expected.iter_mut().for_each(Permutation::permute);
+                P::permute_many(&mut states);
+                for (a, b) in states.iter().zip(expected.iter()) {
+                    assert_eq!(a.to_bytes(), b.to_bytes(), "n={}", n);
+                }
+            }
+        }
+
+        check::<KeccakP1600_10>();
+        check::<KeccakP1600_12>();
+        check::<KeccakP1600_14>();
+        check::<KeccakF1600>();
+
+        // Check every width, not just the one the feature selects.
+        for width in [1, 2, 4] {
+            let mut states = vec![KeccakF1600::default(); 11];
+            for (i, state) in states.iter_mut().enumerate() {
+                state.add_byte(u8::try_from(i).unwrap(), 0);
+            }
+            let mut expected = states.clone();
+            expected.iter_mut().for_each(Permutation::permute);
+            keccak_p1600_width(&mut states, 24, width);
+            for (a, b) in states.iter().zip(expected.iter()) {
+                assert_eq!(a.to_bytes(), b.to_bytes(), "width={}", width);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "keccak-multiway", feature = "force-scalar"))]
+    fn forced_scalar() {
+        assert_eq!(1, parallelism());
+    }
+
+    #[test]
+    fn interleaving() {
+        for x in [0, 1, 2, u64::MAX, 0x0123_4567_89AB_CDEF, 0x8000_0000_0000_0001] {
+            assert_eq!(x, u64::from(Interleaved::from(x)), "x={:x}", x);
+            for n in 0..64 {
+                assert_eq!(
+                    x.rotate_left(n),
+                    u64::from(Interleaved::from(x).rotate(n)),
+                    "x={:x} n={}",
+                    x,
+                    n
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn interleaved_keccak_p1600() {
+        for rounds in [10, 12, 14, 24] {
+            let mut lanes = [0u64; 25];
+            let mut expected = [0u64; 25];
+            for i in 0..3 {
+                let mut interleaved = [Interleaved::default(); 25];
+                for (i, &lane) in interleaved.iter_mut().zip(lanes.iter()) {
+                    *i = Interleaved::from(lane);
+                }
+                keccak_p(&mut interleaved, rounds);
+                for (lane, &i) in lanes.iter_mut().zip(interleaved.iter()) {
+                    *lane = u64::from(i);
+                }
+                keccak::keccak_p(&mut expected, rounds);
+                assert_eq!(expected, lanes, "rounds={} i={}", rounds, i);
+            }
+        }
+    }
+
+    #[test]
+    fn keccak_f200() {
+        let mut lanes = [0u8; 25];
+        let mut expected = [0u8; 25];
+        for i in 0..3 {
+            keccak_p(&mut lanes, 18);
+            keccak::f200(&mut expected);
+            assert_eq!(expected, lanes, "i={}", i);
+        }
+    }
+
+    #[test]
+    fn keccyak200_round_trip() {
+        let mut d = Keccyak200Keyed::new(b"ok then", b"", b"");
+        let m = b"it's a deal".to_vec();
+        let c = d.seal(&m);
+        assert_eq!(m.len() + 8, c.len());
+
+        let mut d = Keccyak200Keyed::new(b"ok then", b"", b"");
+        assert_eq!(Ok(m), d.open(&c));
+    }
+
+    #[test]
+    fn keccak_f400() {
+        let mut lanes = [0u16; 25];
+        let mut expected = [0u16; 25];
+        for i in 0..3 {
+            keccak_p(&mut lanes, 20);
+            keccak::f400(&mut expected);
+            assert_eq!(expected, lanes, "i={}", i);
+        }
+    }
+
+    #[test]
+    fn keccyak400_round_trip() {
+        let mut d = Keccyak400Keyed::new(b"ok then", b"", b"");
+        let m = b"it's a deal".to_vec();
+        let c = d.seal(&m);
+
+        let mut d = Keccyak400Keyed::new(b"ok then", b"", b"");
+        assert_eq!(Ok(m), d.open(&c));
+    }
+
+    #[test]
+    fn keccyak800_round_trip() {
+        let mut d = Keccyak800Keyed::new(b"ok then", b"", b"");
+        let m = b"it's a deal".to_vec();
+        let c = d.seal(&m);
+
+        let mut d = Keccyak800Keyed::new(b"ok then", b"", b"");
+        assert_eq!(Ok(m), d.open(&c));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn pinned_vectors() {
+        use std::path::Path;
+
+        use crate::conformance::{Keccyak128, Keccyak256, KeccyakMax, Scheme};
+        use crate::{kat, Rates};
+
+        fn check<S: Scheme>(digest_len: usize) {
+            let (key, nonce) = ([0x0Fu8; 32], [0xF0u8; 16]);
+            let rate = S::Keyed::ABSORB_RATE;
+            let aead = kat::aead_vectors::<S>(&key, &nonce, &[0, 1, rate - 1, rate, rate + 1]);
+            let rate = S::Hash::ABSORB_RATE;
+            let hash =
+                kat::hash_vectors::<S>(&[0, 1, rate - 1, rate, rate + 1, 2 * rate + 1], digest_len);
+
+            let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("kats/v1").join(S::NAME);
+            for (file, vectors) in [
+                (format!("LWC_AEAD_KAT_{}_{}.txt", key.len() * 8, nonce.len() * 8), aead),
+                (format!("LWC_HASH_KAT_{}.txt", digest_len * 8), hash),
+            ] {
+                let path = dir.join(file);
+                if std::env::var_os("CYCLIST_UPDATE_KATS").is_some() {
+                    std::fs::create_dir_all(&dir).expect("should create directory");
+                    std::fs::write(&path, kat::format(&vectors)).expect("should write vectors");
+                    continue;
+                }
+
+                let pinned = std::fs::read_to_string(&path).expect("should read vectors");
+                let pinned = kat::parse(&pinned).expect("should parse vectors");
+                assert_eq!(0, kat::failures::<S>(&pinned).count(), "{}", path.display());
+                assert_eq!(vectors, pinned, "{} has changed", path.display());
+            }
+        }
+
+        check::<Keccyak128>(32);
+        check::<Keccyak256>(32);
+        check::<KeccyakMax>(64);
     }
 }
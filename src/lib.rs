@@ -57,39 +57,143 @@
     clippy::semicolon_if_nothing_returned
 )]
 
+use core::fmt;
+
 use constant_time_eq::constant_time_eq;
 
+pub mod abi;
+pub mod absorb;
+pub mod absorber;
+pub mod ad_digest;
+pub mod aead;
+pub mod arbitrary;
+pub mod ascon;
+pub mod auto_ratchet;
+pub mod builder;
+pub mod bulk;
+pub mod can;
+pub mod cdc;
+pub mod chunk_order;
+pub mod conformance;
+pub mod context;
+mod counting;
+pub mod digest;
+pub mod diversify;
+pub mod epochs;
+mod examples;
+pub mod firmware;
+pub mod fixed;
 pub mod fuzzing;
+pub mod harness;
+pub mod hasher;
+pub mod id;
+pub mod io;
+pub mod kat;
+pub mod kdf;
 pub mod keccyak;
+pub mod link;
+pub mod lwc;
 mod macros;
+pub mod metrics;
+pub mod motorist;
+pub mod mqtt;
+pub mod multihash;
+pub mod nonce_sequence;
+pub mod nv;
+pub mod onion;
+pub mod outboard;
+pub mod par;
+pub mod personalization;
+pub mod portable;
+pub mod precomputed;
+pub mod protocol;
+pub mod ratchet_tree;
+pub mod replay;
+pub mod rng;
+pub mod sealed_sender;
+pub mod secure;
+pub mod serde;
+pub mod short_nonce;
+pub mod shuffle;
+pub mod siv;
+pub mod snapshot;
+pub mod strategies;
+pub mod stream;
+pub mod totp;
+pub mod trace;
+pub mod transcript;
 pub mod xoodyak;
 
+/// Overwrites a temporary buffer of secret-derived data with zeroes before it goes out of scope.
+///
+/// With the `zeroize` feature enabled, the writes are guaranteed not to be elided by the compiler.
+#[inline(always)]
+pub(crate) fn wipe(buf: &mut [u8]) {
+    #[cfg(feature = "zeroize")]
+    zeroize::Zeroize::zeroize(buf);
+
+    #[cfg(not(feature = "zeroize"))]
+    buf.fill(0);
+
+    #[cfg(test)]
+    tests::WIPED.with(|wiped| wiped.set(wiped.get() + buf.len()));
+}
+
+/// Compares two byte slices in constant time, returning `true` if they're equal.
+///
+/// The time taken depends on the slices' lengths but not their contents, so this is suitable for
+/// comparing secret values of public lengths (e.g. authentication tags). Prefer comparing [`Tag`]s,
+/// which do this automatically.
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    constant_time_eq(a, b)
+}
+
 /// A permutation bijectively maps all blocks of the given width to other blocks of the given width.
-pub trait Permutation<const WIDTH: usize>:
-    Clone + Default + AsRef<[u8; WIDTH]> + AsMut<[u8; WIDTH]>
-{
+///
+/// Implementations store their state in whatever form is fastest to permute (e.g. native lanes),
+/// and the duplex accesses it only through the byte-oriented methods below, so no conversion is
+/// needed on the hot path. The bytes those methods operate on must be the canonical byte encoding
+/// of the permutation's state (e.g. little-endian lanes) regardless of the host's byte order, so
+/// that exported states can be resumed on any platform.
+pub trait Permutation<const WIDTH: usize>: Clone + Default {
+    /// The name of the permutation, as reported in [`metrics::SessionInfo`].
+    const NAME: &'static str = "unknown";
+
+    /// The permutation's identifier in [`portable`] state exports, or zero if it has none.
+    const ID: u16 = 0;
+
     /// Adds the given byte to the state at the given offset.
-    #[inline(always)]
-    fn add_byte(&mut self, byte: u8, offset: usize) {
-        self.as_mut()[offset] ^= byte;
-    }
+    fn add_byte(&mut self, byte: u8, offset: usize);
 
     /// Adds the given bytes to the beginning of the state.
-    #[inline(always)]
-    fn add_bytes(&mut self, bytes: &[u8]) {
-        for (st_byte, byte) in self.as_mut().iter_mut().zip(bytes) {
-            *st_byte ^= byte;
-        }
-    }
+    fn add_bytes(&mut self, bytes: &[u8]);
 
-    /// Fills the given mutable slice with bytes from the state.
-    #[inline(always)]
-    fn extract_bytes(&mut self, out: &mut [u8]) {
-        out.copy_from_slice(&self.as_ref()[..out.len()]);
-    }
+    /// Fills the given mutable slice with bytes from the beginning of the state.
+    fn extract_bytes(&mut self, out: &mut [u8]);
+
+    /// Returns the state's canonical byte encoding.
+    fn to_bytes(&self) -> [u8; WIDTH];
+
+    /// Returns the state with the given canonical byte encoding.
+    fn from_bytes(bytes: &[u8; WIDTH]) -> Self;
+
+    /// Overwrites the state with zeros in a way which won't be elided by the compiler (given the
+    /// `zeroize` feature).
+    fn wipe(&mut self);
 
     /// Permutes the given state.
     fn permute(&mut self);
+
+    /// Permutes each of the given states, e.g. the leaves of a tree hash or the sessions of a batch
+    /// of messages.
+    ///
+    /// Permutations which can evaluate several instances in parallel override this (e.g. the
+    /// Keccak-p\[1600\] permutations with the `keccak-multiway` feature enabled).
+    fn permute_many(states: &mut [Self]) {
+        for state in states {
+            state.permute();
+        }
+    }
 }
 
 /// Cyclist operations which are common to both hash and keyed modes.
@@ -99,22 +203,63 @@ pub trait Cyclist {
 
     /// Extends a previous absorb operation with the given slice.
     ///
-    /// The previous absorb operation must have been done with a slice whose length is evenly
-    /// divisible by the absorb rate in order for the two operations to be commutative.
+    /// The previous absorb operation must have been done with a non-empty slice whose length is
+    /// evenly divisible by the absorb rate in order for the two operations to be commutative. In
+    /// debug builds, extending any other operation panics. To absorb chunks of arbitrary sizes, use
+    /// an [`Absorber`](absorber::Absorber).
     fn absorb_more(&mut self, bin: &[u8]);
 
+    /// Absorbs each of the given slices into the corresponding object, exactly as if each object
+    /// had absorbed its slice in turn, e.g. to hash the leaves of a tree.
+    ///
+    /// [`CyclistHash`] and [`CyclistKeyed`] permute the objects' states together with
+    /// [`Permutation::permute_many`] if the slices are all the same length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the numbers of objects and slices differ.
+    fn absorb_many(objects: &mut [Self], bins: &[&[u8]])
+    where
+        Self: Sized,
+    {
+        assert_eq!(objects.len(), bins.len(), "mismatched number of inputs");
+        for (object, bin) in objects.iter_mut().zip(bins) {
+            object.absorb(bin);
+        }
+    }
+
     /// Fill the given mutable slice with squeezed data.
     fn squeeze_mut(&mut self, out: &mut [u8]);
 
     /// Extends a previous squeeze operation with the given mutable slice.
     ///
-    /// The previous squeeze operation must have produced a number of bytes that is evenly divisible
-    /// by the squeeze rate in order for the two operations to be commutative.
+    /// The previous squeeze operation must have produced a non-zero number of bytes that is evenly
+    /// divisible by the squeeze rate in order for the two operations to be commutative. In debug
+    /// builds, extending any other operation panics.
     fn squeeze_more_mut(&mut self, out: &mut [u8]);
 
     /// Fills the given mutable slice with squeezed key data.
     fn squeeze_key_mut(&mut self, out: &mut [u8]);
 
+    /// Fills each of the given mutable slices with data squeezed from the corresponding object,
+    /// exactly as if each object had squeezed into its slice in turn.
+    ///
+    /// [`CyclistHash`] and [`CyclistKeyed`] permute the objects' states together with
+    /// [`Permutation::permute_many`] if the slices are all the same length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the numbers of objects and slices differ.
+    fn squeeze_many(objects: &mut [Self], outs: &mut [&mut [u8]])
+    where
+        Self: Sized,
+    {
+        assert_eq!(objects.len(), outs.len(), "mismatched number of outputs");
+        for (object, out) in objects.iter_mut().zip(outs.iter_mut()) {
+            object.squeeze_mut(out);
+        }
+    }
+
     /// Returns `n` bytes of squeezed data.
     #[cfg(feature = "std")]
     fn squeeze(&mut self, n: usize) -> Vec<u8> {
@@ -141,8 +286,424 @@ pub trait Cyclist {
         self.squeeze_key_mut(&mut out);
         out
     }
+
+    /// Returns `N` bytes of squeezed data.
+    fn squeeze_array<const N: usize>(&mut self) -> [u8; N]
+    where
+        Self: Sized,
+    {
+        let mut out = [0u8; N];
+        self.squeeze_mut(&mut out);
+        out
+    }
+
+    /// Returns `N` bytes of squeezed key data.
+    fn squeeze_key_array<const N: usize>(&mut self) -> [u8; N]
+    where
+        Self: Sized,
+    {
+        let mut out = [0u8; N];
+        self.squeeze_key_mut(&mut out);
+        out
+    }
+}
+
+impl<C> Cyclist for &mut C
+where
+    C: Cyclist + ?Sized,
+{
+    fn absorb(&mut self, bin: &[u8]) {
+        (**self).absorb(bin);
+    }
+
+    fn absorb_more(&mut self, bin: &[u8]) {
+        (**self).absorb_more(bin);
+    }
+
+    fn squeeze_mut(&mut self, out: &mut [u8]) {
+        (**self).squeeze_mut(out);
+    }
+
+    fn squeeze_more_mut(&mut self, out: &mut [u8]) {
+        (**self).squeeze_more_mut(out);
+    }
+
+    fn squeeze_key_mut(&mut self, out: &mut [u8]) {
+        (**self).squeeze_key_mut(out);
+    }
+}
+
+/// A Cyclist object whose rates are fixed by its type, for adapters which buffer input or output a
+/// block at a time (e.g. [`Absorber`](absorber::Absorber)).
+///
+/// This trait is sealed: it's implemented for [`CyclistHash`], [`CyclistKeyed`], and mutable
+/// references to them, and can't be implemented outside this crate.
+pub trait Rates: Cyclist + sealed::Sealed {
+    /// The number of bytes which can be absorbed before the state is permuted.
+    const ABSORB_RATE: usize;
+
+    /// The number of bytes which can be squeezed before the state is permuted.
+    const SQUEEZE_RATE: usize;
+}
+
+impl<C> Rates for &mut C
+where
+    C: Rates + ?Sized,
+{
+    const ABSORB_RATE: usize = C::ABSORB_RATE;
+    const SQUEEZE_RATE: usize = C::SQUEEZE_RATE;
+}
+
+mod sealed {
+    /// Keeps [`Rates`](super::Rates) from being implemented outside this crate.
+    pub trait Sealed {}
+
+    impl<C: Sealed + ?Sized> Sealed for &mut C {}
+}
+
+/// Cyclist operations which are specific to keyed mode.
+pub trait Keyed: Rates + Clone {
+    /// The length of an authentication tag in bytes.
+    const TAG_LEN: usize;
+
+    /// The number of bytes by which sealing expands a plaintext.
+    const OVERHEAD: usize = Self::TAG_LEN;
+
+    /// Creates a new instance with the given key, optional key ID, and optional counter.
+    fn new(key: &[u8], key_id: &[u8], counter: &[u8]) -> Self;
+
+    /// Returns the length of the ciphertext of a `plaintext_len`-byte plaintext.
+    fn sealed_len(plaintext_len: usize) -> usize {
+        plaintext_len + Self::OVERHEAD
+    }
+
+    /// Returns the length of the plaintext of a `ciphertext_len`-byte ciphertext, or `None` if the
+    /// ciphertext is too short to contain a tag.
+    fn opened_len(ciphertext_len: usize) -> Option<usize> {
+        ciphertext_len.checked_sub(Self::OVERHEAD)
+    }
+
+    /// Encrypts the given mutable slice in place.
+    fn encrypt_mut(&mut self, in_out: &mut [u8]);
+
+    /// Decrypts the given mutable slice in place.
+    fn decrypt_mut(&mut self, in_out: &mut [u8]);
+
+    /// Fills the given mutable slice with keystream. See [`CyclistKeyed::keystream`].
+    fn keystream(&mut self, out: &mut [u8]) {
+        out.fill(0);
+        self.encrypt_mut(out);
+    }
+
+    /// Ratchets the state, providing forward secrecy.
+    fn ratchet(&mut self);
+
+    /// Seals the given mutable slice in place.
+    ///
+    /// The last `TAG_LEN` bytes of the slice will be overwritten with the authentication tag.
+    fn seal_mut(&mut self, in_out: &mut [u8]);
+
+    /// Opens the given mutable slice in place. The last `TAG_LEN` bytes of the slice will be
+    /// unmodified.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::BufferTooSmall`] if the slice is shorter than `TAG_LEN` bytes, leaving the
+    /// state unmodified, and [`Error::InvalidTag`] if the input could not be authenticated, after
+    /// which implementations may return [`Error::Poisoned`] for any further input.
+    fn open_mut(&mut self, in_out: &mut [u8]) -> Result<(), Error>;
+
+    /// Returns `true` if the instance has been poisoned by a failure to authenticate an input, after
+    /// which it must not be used. See [`CyclistKeyed::is_poisoned`].
+    fn is_poisoned(&self) -> bool {
+        false
+    }
+
+    /// Absorbs the given slice of data, or returns [`Error::Poisoned`] if the instance is
+    /// poisoned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the instance is poisoned.
+    fn try_absorb(&mut self, bin: &[u8]) -> Result<(), Error> {
+        if self.is_poisoned() {
+            return Err(Error::Poisoned);
+        }
+        self.absorb(bin);
+        Ok(())
+    }
+
+    /// Fills the given mutable slice with squeezed data, or returns [`Error::Poisoned`] if the
+    /// instance is poisoned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the instance is poisoned.
+    fn try_squeeze_mut(&mut self, out: &mut [u8]) -> Result<(), Error> {
+        if self.is_poisoned() {
+            return Err(Error::Poisoned);
+        }
+        self.squeeze_mut(out);
+        Ok(())
+    }
+
+    /// Encrypts the given mutable slice in place, or returns [`Error::Poisoned`] if the instance
+    /// is poisoned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the instance is poisoned.
+    fn try_encrypt_mut(&mut self, in_out: &mut [u8]) -> Result<(), Error> {
+        if self.is_poisoned() {
+            return Err(Error::Poisoned);
+        }
+        self.encrypt_mut(in_out);
+        Ok(())
+    }
+
+    /// Decrypts the given mutable slice in place, or returns [`Error::Poisoned`] if the instance
+    /// is poisoned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the instance is poisoned.
+    fn try_decrypt_mut(&mut self, in_out: &mut [u8]) -> Result<(), Error> {
+        if self.is_poisoned() {
+            return Err(Error::Poisoned);
+        }
+        self.decrypt_mut(in_out);
+        Ok(())
+    }
+
+    /// Ratchets the state, or returns [`Error::Poisoned`] if the instance is poisoned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the instance is poisoned.
+    fn try_ratchet(&mut self) -> Result<(), Error> {
+        if self.is_poisoned() {
+            return Err(Error::Poisoned);
+        }
+        self.ratchet();
+        Ok(())
+    }
+
+    /// Seals the given mutable slice in place, or returns an error if it can't be.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the instance is poisoned and [`Error::BufferTooSmall`] if the
+    /// slice is shorter than `TAG_LEN` bytes.
+    fn try_seal_mut(&mut self, in_out: &mut [u8]) -> Result<(), Error> {
+        if self.is_poisoned() {
+            return Err(Error::Poisoned);
+        }
+        if in_out.len() < Self::TAG_LEN {
+            return Err(Error::BufferTooSmall);
+        }
+        self.seal_mut(in_out);
+        Ok(())
+    }
+
+    /// Reads plaintext from `reader` until EOF and writes it to `writer` as a sequence of sealed
+    /// frames, each holding at most `chunk_len` bytes of plaintext. Returns the number of plaintext
+    /// bytes sealed.
+    ///
+    /// See the [`stream`] module for details of the framing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`stream::Error::Poisoned`] if the instance is poisoned,
+    /// [`stream::Error::LimitExceeded`] if the plaintext requires more than [`stream::MAX_FRAMES`]
+    /// frames, and [`stream::Error::Io`] for any error encountered reading from `reader` or writing
+    /// to `writer`.
+    #[cfg(feature = "std")]
+    fn seal_stream<R, W>(
+        &mut self,
+        reader: R,
+        writer: W,
+        chunk_len: usize,
+    ) -> Result<u64, stream::Error>
+    where
+        R: std::io::Read,
+        W: std::io::Write,
+    {
+        stream::seal(self, reader, writer, chunk_len)
+    }
+
+    /// Reads a stream of sealed frames from `source` twice: once to verify every frame's tag, and
+    /// then, only if the entire stream is authentic, again to decrypt it and write the plaintext to
+    /// `sink`. Returns the number of plaintext bytes written.
+    ///
+    /// This allows for decrypting streams too large to buffer in memory without ever writing
+    /// unauthenticated plaintext. `source` must not be modified between the two passes; if it is,
+    /// the second pass will detect the modification and return an error, but plaintext from frames
+    /// preceding the modified frame will have already been written to `sink`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`stream::Error::Poisoned`] if the instance is poisoned,
+    /// [`stream::Error::Truncated`] if the stream ends mid-frame,
+    /// [`stream::Error::TagMismatch`] if any frame cannot be authenticated,
+    /// [`stream::Error::LimitExceeded`] if the stream has more than [`stream::MAX_FRAMES`] frames,
+    /// and [`stream::Error::Io`] for any error encountered reading from `source`, seeking within
+    /// `source`, or writing to `sink`.
+    #[cfg(feature = "std")]
+    fn verify_then_decrypt_stream<R, W>(
+        &mut self,
+        source: R,
+        sink: W,
+        chunk_len: usize,
+    ) -> Result<u64, stream::Error>
+    where
+        R: std::io::Read + std::io::Seek,
+        W: std::io::Write,
+    {
+        stream::verify_then_decrypt(self, source, sink, chunk_len)
+    }
+}
+
+/// An error returned by a Cyclist operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The authentication tag did not match, indicating the ciphertext or associated data was
+    /// modified or the wrong key was used.
+    InvalidTag,
+
+    /// The key was empty.
+    EmptyKey,
+
+    /// The combined length of the key and key ID was too long for the absorb rate.
+    KeyTooLong,
+
+    /// The buffer was too small for the operation (e.g. shorter than a tag).
+    BufferTooSmall,
+
+    /// The keyed duplex was poisoned by a previous failure to authenticate an input.
+    Poisoned,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidTag => write!(f, "invalid authentication tag"),
+            Error::EmptyKey => write!(f, "empty key"),
+            Error::KeyTooLong => write!(f, "key too long"),
+            Error::BufferTooSmall => write!(f, "buffer too small"),
+            Error::Poisoned => write!(f, "keyed duplex poisoned by a failed open"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// An authentication tag of `N` bytes, as returned by [`CyclistKeyed::seal_detached`].
+///
+/// Tags are compared in constant time, both with other tags and with byte slices, and are
+/// formatted as lowercase hexadecimal. With the `subtle` feature enabled, tags also implement
+/// [`subtle::ConstantTimeEq`], for protocols which combine several comparisons into one
+/// [`subtle::Choice`].
+#[derive(Clone, Copy, Debug, Eq)]
+pub struct Tag<const N: usize>([u8; N]);
+
+impl<const N: usize> Tag<N> {
+    /// Returns the tag's bytes.
+    pub const fn to_bytes(&self) -> [u8; N] {
+        self.0
+    }
+
+    /// Parses a tag from a string of `2N` hexadecimal digits in either case, returning `None` if
+    /// the string is the wrong length or contains anything else.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.as_bytes();
+        if hex.len() != 2 * N {
+            return None;
+        }
+
+        let mut tag = [0u8; N];
+        for (b, pair) in tag.iter_mut().zip(hex.chunks_exact(2)) {
+            *b = (hex_digit(pair[0])? << 4) | hex_digit(pair[1])?;
+        }
+        Some(Tag(tag))
+    }
+}
+
+/// Decodes a single hexadecimal digit.
+const fn hex_digit(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
 }
 
+impl<const N: usize> From<[u8; N]> for Tag<N> {
+    fn from(bytes: [u8; N]) -> Self {
+        Tag(bytes)
+    }
+}
+
+impl<const N: usize> TryFrom<&[u8]> for Tag<N> {
+    type Error = core::array::TryFromSliceError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Tag(bytes.try_into()?))
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for Tag<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<const N: usize> PartialEq for Tag<N> {
+    fn eq(&self, other: &Self) -> bool {
+        constant_time_eq(&self.0, &other.0)
+    }
+}
+
+impl<const N: usize> PartialEq<[u8]> for Tag<N> {
+    fn eq(&self, other: &[u8]) -> bool {
+        constant_time_eq(&self.0, other)
+    }
+}
+
+#[cfg(feature = "subtle")]
+impl<const N: usize> subtle::ConstantTimeEq for Tag<N> {
+    fn ct_eq(&self, other: &Self) -> subtle::Choice {
+        self.0[..].ct_eq(&other.0[..])
+    }
+}
+
+impl<const N: usize> fmt::LowerHex for Tag<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|b| write!(f, "{:02x}", b))
+    }
+}
+
+impl<const N: usize> fmt::Display for Tag<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(self, f)
+    }
+}
+
+/// The flag added to the last byte of a domain-separated hash's initial state. Plain hash-mode
+/// operations only ever add `0x01` to that byte.
+const DOMAIN_FLAG: u8 = 0x02;
+
+/// The domain separation label absorbed by forked children's parents.
+const FORK_LABEL: &[u8] = b"cyclist.fork";
+
+/// The maximum length of a forked child's key, in bytes.
+const FORK_KEY_LEN: usize = 32;
+
+/// The number of states [`CyclistCore::up_many`] passes to [`Permutation::permute_many`] at a time.
+pub(crate) const LOCKSTEP: usize = 4;
+
 /// The core implementation of the Cyclist mode. Parameterized with the permutation algorithm, the
 /// permutation width, whether the mode is keyed or not, the absorb rate, the squeeze rate, and the
 /// ratchet rate.
@@ -159,6 +720,31 @@ struct CyclistCore<
 {
     state: P,
     up: bool,
+    extendable: Extendable,
+    poisoned: bool,
+    stats: Stats,
+    #[cfg(feature = "trace")]
+    trace: Vec<trace::Call>,
+}
+
+/// The operation, if any, which the next call can extend as if both calls had been one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Extendable {
+    Neither,
+    Absorb,
+    Squeeze,
+    /// The state was imported, so the previous operation is unknown.
+    Unknown,
+}
+
+/// Counts of the non-secret operations performed by a Cyclist object.
+#[derive(Clone, Copy, Debug, Default)]
+struct Stats {
+    permutations: u64,
+    ratchets: u64,
+    seals: u64,
+    opens: u64,
+    failures: u64,
 }
 
 impl<
@@ -176,7 +762,15 @@ where
     fn new() -> Self {
         debug_assert!(ABSORB_RATE.max(SQUEEZE_RATE) + 2 <= WIDTH);
 
-        CyclistCore { state: P::default(), up: true }
+        CyclistCore {
+            state: P::default(),
+            up: true,
+            extendable: Extendable::Neither,
+            poisoned: false,
+            stats: Stats::default(),
+            #[cfg(feature = "trace")]
+            trace: Vec::new(),
+        }
     }
 
     /// Initiates the UP mode with an optional block of data and a domain separator.
@@ -187,6 +781,10 @@ where
             self.state.add_byte(cu, WIDTH - 1);
         }
         self.state.permute();
+        self.stats.permutations += 1;
+        self.extendable = Extendable::Neither;
+        #[cfg(feature = "trace")]
+        self.trace.push(trace::Call::Up { cu });
         self.up = true;
         if let Some(out) = out {
             self.state.extract_bytes(out);
@@ -204,10 +802,24 @@ where
             self.state.add_byte(0x01, 0);
         }
         self.state.add_byte(if KEYED { cd } else { cd & 0x01 }, WIDTH - 1);
+        #[cfg(feature = "trace")]
+        self.trace.push(trace::Call::Down { len: bin.map_or(0, <[u8]>::len), cd });
         self.up = false;
+        self.extendable = Extendable::Neither;
+    }
+
+    /// Flags the state as domain-separated, so that no sequence of plain hash-mode operations can
+    /// produce the same states as the operations which follow.
+    fn separate_domain(&mut self) {
+        self.state.add_byte(DOMAIN_FLAG, WIDTH - 1);
+    }
+
+    /// Wipes the state and marks it as poisoned, so that it can't be used again.
+    fn poison(&mut self) {
+        self.state.wipe();
+        self.poisoned = true;
     }
 
-    /// Absorbs a slice of data at the given rate with the given DOWN mode domain separator.
     #[inline]
     fn absorb_any(&mut self, bin: &[u8], rate: usize, cd: u8) {
         let mut chunks_it = bin.chunks(rate);
@@ -236,30 +848,106 @@ where
     #[inline(always)]
     fn absorb(&mut self, bin: &[u8]) {
         self.absorb_any(bin, ABSORB_RATE, 0x03);
+        if !bin.is_empty() && bin.len().is_multiple_of(ABSORB_RATE) {
+            self.extendable = Extendable::Absorb;
+        }
     }
 
     /// Extends a previous absorb with more data.
     #[inline(always)]
     fn absorb_more(&mut self, bin: &[u8]) {
+        debug_assert!(
+            matches!(self.extendable, Extendable::Absorb | Extendable::Unknown),
+            "absorb_more must follow an absorb of a whole number of blocks"
+        );
         for chunk in bin.chunks(ABSORB_RATE) {
             self.up(None, 0x00);
             self.down(Some(chunk), 0x00);
         }
+        if bin.len().is_multiple_of(ABSORB_RATE) {
+            self.extendable = Extendable::Absorb;
+        }
+    }
+
+    /// Absorbs everything the given function passes to its callback as a single absorb operation,
+    /// exactly as if it had been concatenated and passed to [`CyclistCore::absorb`].
+    ///
+    /// Input is collected a block at a time, and each block is absorbed once more input arrives,
+    /// leaving the final block to be absorbed at the end.
+    fn absorb_concat(&mut self, f: impl FnOnce(&mut dyn FnMut(&[u8]))) {
+        let mut block = [0u8; ABSORB_RATE];
+        let (mut pos, mut cd) = (0, 0x03);
+        f(&mut |mut bin: &[u8]| {
+            while !bin.is_empty() {
+                if pos == ABSORB_RATE {
+                    self.absorb_block(&block, cd);
+                    (pos, cd) = (0, 0x00);
+                }
+                let n = bin.len().min(ABSORB_RATE - pos);
+                block[pos..pos + n].copy_from_slice(&bin[..n]);
+                bin = &bin[n..];
+                pos += n;
+            }
+        });
+        self.absorb_block(&block[..pos], cd);
+        if pos == ABSORB_RATE {
+            self.extendable = Extendable::Absorb;
+        }
+    }
+
+    /// Absorbs a single block with the given DOWN mode domain separator.
+    #[inline(always)]
+    fn absorb_block(&mut self, block: &[u8], cd: u8) {
+        if !self.up {
+            self.up(None, 0x00);
+        }
+        self.down(Some(block), cd);
+    }
+
+    /// Absorbs the given items as a single absorb operation, each prefixed with its length.
+    fn absorb_items(&mut self, items: &[&[u8]]) {
+        self.absorb_concat(|absorb| {
+            for item in items {
+                absorb(&u64::try_from(item.len()).expect("unexpected overflow").to_le_bytes());
+                absorb(item);
+            }
+        });
+    }
+
+    /// Absorbs the given buffers as a single absorb operation, exactly as if they had been
+    /// concatenated.
+    #[cfg(feature = "std")]
+    fn absorb_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) {
+        self.absorb_concat(|absorb| {
+            for buf in bufs {
+                absorb(buf);
+            }
+        });
     }
 
     /// Fills the given mutable slice with squeezed data.
     #[inline(always)]
     fn squeeze_mut(&mut self, out: &mut [u8]) {
         self.squeeze_any(out, 0x40);
+        if !out.is_empty() && out.len().is_multiple_of(SQUEEZE_RATE) {
+            self.extendable = Extendable::Squeeze;
+        }
     }
 
     /// Extends a previous squeeze with more data.
     #[inline(always)]
     fn squeeze_more_mut(&mut self, out: &mut [u8]) {
+        debug_assert!(
+            matches!(self.extendable, Extendable::Squeeze | Extendable::Unknown),
+            "squeeze_more must follow a squeeze of a whole number of blocks"
+        );
         for chunk in out.chunks_mut(SQUEEZE_RATE) {
             self.down(None, 0x00);
             self.up(Some(chunk), 0x00);
         }
+        if out.len().is_multiple_of(SQUEEZE_RATE) {
+            self.extendable = Extendable::Squeeze;
+        }
     }
 
     /// Fills the given mutable slice with squeezed key data.
@@ -267,43 +955,373 @@ where
     fn squeeze_key_mut(&mut self, out: &mut [u8]) {
         self.squeeze_any(out, 0x20);
     }
-}
 
-/// A Cyclist object in hash mode. Parameterized with the permutation algorithm, the permutation
-/// width, and the hash rate.
-#[derive(Clone, Debug)]
-pub struct CyclistHash<P, const WIDTH: usize, const HASH_RATE: usize>
-where
-    P: Permutation<WIDTH>,
-{
-    core: CyclistCore<P, WIDTH, false, HASH_RATE, HASH_RATE, 0>,
-}
+    /// Initiates the UP mode on the core of each of the given objects with the given domain
+    /// separator, as [`CyclistCore::up`] does without extracting a block, permuting their states
+    /// together with [`Permutation::permute_many`].
+    fn up_many<T>(objects: &mut [T], core: fn(&mut T) -> &mut Self, cu: u8) {
+        for group in objects.chunks_mut(LOCKSTEP) {
+            // Move the states into a contiguous array, leaving empty states in their place.
+            let mut states: [P; LOCKSTEP] = Default::default();
+            for (state, object) in states.iter_mut().zip(group.iter_mut()) {
+                let core = core(object);
+                if KEYED {
+                    core.state.add_byte(cu, WIDTH - 1);
+                }
+                core::mem::swap(state, &mut core.state);
+            }
 
-impl<P, const WIDTH: usize, const HASH_RATE: usize> CyclistHash<P, WIDTH, HASH_RATE>
-where
-    P: Permutation<WIDTH>,
-{
-    /// Returns the number of bytes which can be absorbed before the state is permuted.
-    pub const fn absorb_rate() -> usize {
-        HASH_RATE
-    }
+            P::permute_many(&mut states[..group.len()]);
 
-    /// Returns the number of bytes which can be squeezed before the state is permuted.
-    pub const fn squeeze_rate() -> usize {
-        HASH_RATE
+            for (state, object) in states.iter_mut().zip(group.iter_mut()) {
+                let core = core(object);
+                core::mem::swap(state, &mut core.state);
+                core.stats.permutations += 1;
+                core.extendable = Extendable::Neither;
+                #[cfg(feature = "trace")]
+                core.trace.push(trace::Call::Up { cu });
+                core.up = true;
+            }
+        }
     }
-}
 
-impl<P, const WIDTH: usize, const HASH_RATE: usize> Default for CyclistHash<P, WIDTH, HASH_RATE>
-where
-    P: Permutation<WIDTH>,
-{
-    fn default() -> Self {
-        CyclistHash { core: CyclistCore::new() }
-    }
-}
+    /// Absorbs each of the given slices, which must all be the same length, into the core of the
+    /// corresponding object, as [`CyclistCore::absorb`] does, permuting their states together.
+    fn absorb_many<T>(objects: &mut [T], core: fn(&mut T) -> &mut Self, bins: &[&[u8]]) {
+        let len = bins.first().map_or(0, |bin| bin.len());
+        debug_assert!(bins.iter().all(|bin| bin.len() == len), "inputs must be the same length");
 
-impl<P, const WIDTH: usize, const HASH_RATE: usize> Cyclist for CyclistHash<P, WIDTH, HASH_RATE>
+        // Cores in the DOWN mode need an UP first, which is only done together if they all do.
+        let mut up = true;
+        if objects.iter_mut().all(|object| !core(object).up) {
+            up = false;
+        } else {
+            for object in objects.iter_mut() {
+                let core = core(object);
+                if !core.up {
+                    core.up(None, 0x00);
+                }
+            }
+        }
+
+        for (i, start) in (0..len.max(1)).step_by(ABSORB_RATE).enumerate() {
+            if i > 0 || !up {
+                Self::up_many(objects, core, 0x00);
+            }
+            let end = (start + ABSORB_RATE).min(len);
+            let cd = if i == 0 { 0x03 } else { 0x00 };
+            for (object, bin) in objects.iter_mut().zip(bins) {
+                core(object).down(Some(&bin[start..end]), cd);
+            }
+        }
+
+        if len > 0 && len.is_multiple_of(ABSORB_RATE) {
+            for object in objects.iter_mut() {
+                core(object).extendable = Extendable::Absorb;
+            }
+        }
+    }
+
+    /// Fills each of the given slices, which must all be the same length, with data squeezed from
+    /// the core of the corresponding object, as [`CyclistCore::squeeze_mut`] does, permuting their
+    /// states together.
+    fn squeeze_many<T>(objects: &mut [T], core: fn(&mut T) -> &mut Self, outs: &mut [&mut [u8]]) {
+        let len = outs.first().map_or(0, |out| out.len());
+        debug_assert!(outs.iter().all(|out| out.len() == len), "outputs must be the same length");
+
+        for (i, start) in (0..len.max(1)).step_by(SQUEEZE_RATE).enumerate() {
+            if i > 0 {
+                for object in objects.iter_mut() {
+                    core(object).down(None, 0x00);
+                }
+            }
+            Self::up_many(objects, core, if i == 0 { 0x40 } else { 0x00 });
+            let end = (start + SQUEEZE_RATE).min(len);
+            for (object, out) in objects.iter_mut().zip(outs.iter_mut()) {
+                core(object).state.extract_bytes(&mut out[start..end]);
+            }
+        }
+
+        if len > 0 && len.is_multiple_of(SQUEEZE_RATE) {
+            for object in objects.iter_mut() {
+                core(object).extendable = Extendable::Squeeze;
+            }
+        }
+    }
+
+    /// Encrypts (or decrypts) each of the given slices, which must all be the same length, in place
+    /// with the core of the corresponding object, as [`CyclistKeyed::encrypt_mut`] (or
+    /// [`CyclistKeyed::decrypt_mut`]) does, permuting their states together.
+    fn crypt_many<T>(
+        objects: &mut [T],
+        core: fn(&mut T) -> &mut Self,
+        in_outs: &mut [&mut [u8]],
+        decrypt: bool,
+    ) {
+        let len = in_outs.first().map_or(0, |in_out| in_out.len());
+        debug_assert!(
+            in_outs.iter().all(|in_out| in_out.len() == len),
+            "inputs must be the same length"
+        );
+
+        let mut tmp = [0u8; SQUEEZE_RATE];
+        for (i, start) in (0..len).step_by(SQUEEZE_RATE).enumerate() {
+            Self::up_many(objects, core, if i == 0 { 0x80 } else { 0x00 });
+            let end = (start + SQUEEZE_RATE).min(len);
+            for (object, in_out) in objects.iter_mut().zip(in_outs.iter_mut()) {
+                let core = core(object);
+                let block = &mut in_out[start..end];
+                let keystream = &mut tmp[..block.len()];
+                core.state.extract_bytes(keystream);
+
+                // The state absorbs the plaintext, which is the input when encrypting and the
+                // output when decrypting.
+                if !decrypt {
+                    core.down(Some(block), 0x00);
+                }
+                for (b, k) in block.iter_mut().zip(keystream.iter()) {
+                    *b ^= *k;
+                }
+                if decrypt {
+                    core.down(Some(block), 0x00);
+                }
+            }
+        }
+
+        // Wipe the keystream.
+        wipe(&mut tmp);
+    }
+
+    /// Returns the mode and phase flags of an exported state.
+    const fn flags(&self) -> u8 {
+        (if KEYED { 0x02 } else { 0x00 }) | (if self.up { 0x01 } else { 0x00 })
+    }
+
+    /// Writes the mode and phase flags followed by the permutation state to the given slice, which
+    /// must be `WIDTH + 1` bytes long.
+    fn export(&self, out: &mut [u8]) {
+        assert_eq!(out.len(), WIDTH + 1, "invalid state length");
+        out[0] = self.flags();
+        let mut state = self.state.to_bytes();
+        out[1..].copy_from_slice(&state);
+        wipe(&mut state);
+    }
+
+    /// Returns a core with the state written by [`CyclistCore::export`], or `None` if the state has
+    /// the wrong length or was exported from a different mode.
+    fn import(bytes: &[u8]) -> Option<Self> {
+        let (&flags, bytes) = bytes.split_first()?;
+        if bytes.len() != WIDTH {
+            return None;
+        }
+
+        let state = P::from_bytes(bytes.try_into().ok()?);
+        Self::from_parts(flags, state)
+    }
+
+    /// Returns a core with the given flags and permutation state, or `None` if the flags are for a
+    /// different mode.
+    fn from_parts(flags: u8, state: P) -> Option<Self> {
+        let keyed = if KEYED { 0x02 } else { 0x00 };
+        (flags & !0x01 == keyed).then(|| CyclistCore {
+            state,
+            up: flags & 0x01 == 0x01,
+            extendable: Extendable::Unknown,
+            poisoned: false,
+            stats: Stats::default(),
+            #[cfg(feature = "trace")]
+            trace: Vec::new(),
+        })
+    }
+}
+
+/// Wipes the state when a Cyclist object is dropped. In keyed mode, the state is as sensitive as the
+/// key; in hash mode, it may reveal secret inputs (e.g. passwords).
+#[cfg(feature = "zeroize")]
+impl<
+        P,
+        const WIDTH: usize,
+        const KEYED: bool,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+    > Drop for CyclistCore<P, WIDTH, KEYED, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE>
+where
+    P: Permutation<WIDTH>,
+{
+    fn drop(&mut self) {
+        self.state.wipe();
+    }
+}
+
+/// A Cyclist object in hash mode. Parameterized with the permutation algorithm, the permutation
+/// width, and the hash rate.
+///
+/// A [`CyclistHash`] is [`Send`] and [`Sync`] if its permutation is, as are all the permutations in
+/// this crate.
+#[derive(Clone, Debug)]
+pub struct CyclistHash<P, const WIDTH: usize, const HASH_RATE: usize>
+where
+    P: Permutation<WIDTH>,
+{
+    core: CyclistCore<P, WIDTH, false, HASH_RATE, HASH_RATE, 0>,
+}
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize> CyclistHash<P, WIDTH, HASH_RATE>
+where
+    P: Permutation<WIDTH>,
+{
+    /// Returns the number of bytes which can be absorbed before the state is permuted.
+    pub const fn absorb_rate() -> usize {
+        HASH_RATE
+    }
+
+    /// Absorbs the given items as a single absorb operation, each prefixed with its length as a
+    /// little-endian 64-bit integer, in the style of TupleHash.
+    ///
+    /// Concatenating items before absorbing them is ambiguous: `["ab", "c"]` and `["a", "bc"]`
+    /// both concatenate to `"abc"`. Length-prefixing each item makes every list of items absorb
+    /// differently, with fewer permutations than absorbing each item separately.
+    pub fn absorb_items(&mut self, items: &[&[u8]]) {
+        self.core.absorb_items(items);
+    }
+
+    /// Absorbs the given buffers as a single absorb operation, exactly as if they had been
+    /// concatenated, e.g. to absorb a packet whose header and body are in separate buffers.
+    #[cfg(feature = "std")]
+    pub fn absorb_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) {
+        self.core.absorb_vectored(bufs);
+    }
+
+    /// Returns a hash which has absorbed the given customization string.
+    ///
+    /// The specification's `Cyclist(K, id, counter)` ignores `id` and `counter` when `K` is empty,
+    /// and so does XKCP's `Xoodyak_Initialize`: there is no hash-mode variant which absorbs them
+    /// during initialization. Stacks which customize a hash instead absorb a customization string
+    /// first, so this is bit-for-bit compatible with XKCP's `Xoodyak_Initialize(K=ε, ID=ε,
+    /// counter=ε)` followed by `Xoodyak_Absorb(customization)`.
+    ///
+    /// Unlike [`CyclistHash::builder`], which also absorbs the output length and a salt, no other
+    /// framing is added.
+    pub fn with_customization(customization: &[u8]) -> Self {
+        let mut hash = Self::default();
+        hash.absorb(customization);
+        hash
+    }
+
+    /// Returns a hash domain-separated by the given function name and customization string, in the
+    /// style of cSHAKE.
+    ///
+    /// The function name identifies a function defined on top of the hash (e.g. a KDF or a MAC),
+    /// and the customization string identifies the application using it (e.g. `b"MyProto v1"`).
+    /// As cSHAKE pads with a different suffix than SHAKE, the initial state is flagged in its
+    /// capacity, which no plain hash-mode operation modifies, and then both strings are absorbed,
+    /// each with its own absorb operation. Domain-separated hashes are thus independent of each
+    /// other and of every plain hash, including one which absorbs the same strings (e.g. with
+    /// [`CyclistHash::with_customization`]). As with cSHAKE, if both are empty the result is
+    /// identical to [`CyclistHash::default`].
+    ///
+    /// This has no equivalent in XKCP.
+    pub fn domain_separated(function_name: &[u8], customization: &[u8]) -> Self {
+        let mut hash = Self::default();
+        if !function_name.is_empty() || !customization.is_empty() {
+            hash.core.separate_domain();
+            hash.absorb(function_name);
+            hash.absorb(customization);
+        }
+        hash
+    }
+
+    /// Returns an independent child hash derived from the current state and the given label.
+    ///
+    /// A copy of the hash absorbs a domain separation label and the label, and squeezes key data
+    /// which the child absorbs. The hash itself is unmodified, so forking the same state with the
+    /// same label always returns the same child, while children with different labels are
+    /// independent of each other and of the parent.
+    pub fn fork(&self, label: &[u8]) -> Self {
+        let mut parent = self.clone();
+        parent.absorb(FORK_LABEL);
+        parent.absorb(label);
+        let mut key = [0u8; FORK_KEY_LEN];
+        parent.squeeze_key_mut(&mut key);
+
+        let mut child = Self::default();
+        child.absorb(&key);
+        wipe(&mut key);
+        child
+    }
+
+    /// Returns a snapshot of the hash's state, which [`CyclistHash::restore`] can roll back to. See
+    /// [`snapshot`].
+    pub fn snapshot(&self) -> snapshot::Snapshot<Self> {
+        snapshot::Snapshot(self.clone())
+    }
+
+    /// Rolls the hash's state back to the given snapshot.
+    pub fn restore(&mut self, snapshot: &snapshot::Snapshot<Self>) {
+        *self = snapshot.0.clone();
+    }
+
+    /// Returns the number of bytes which can be squeezed before the state is permuted.
+    pub const fn squeeze_rate() -> usize {
+        HASH_RATE
+    }
+
+    /// Returns a [`builder::HashBuilder`] for configuring the output length, salt, and
+    /// customization string of a hasher.
+    pub const fn builder<'a>() -> builder::HashBuilder<'a, Self> {
+        builder::HashBuilder::new()
+    }
+
+    /// Returns the length of an exported state in bytes.
+    pub const fn state_len() -> usize {
+        WIDTH + 1
+    }
+
+    /// Exports the hash's state to the given slice, allowing an in-progress hash to be persisted
+    /// and later resumed with [`CyclistHash::from_bytes`]. Operation counts (see [`metrics`]) are
+    /// not included.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` is not [`CyclistHash::state_len`] bytes long.
+    pub fn to_bytes_mut(&self, out: &mut [u8]) {
+        self.core.export(out);
+    }
+
+    /// Returns the hash's exported state. See [`CyclistHash::to_bytes_mut`].
+    #[cfg(feature = "std")]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = vec![0u8; Self::state_len()];
+        self.to_bytes_mut(&mut out);
+        out
+    }
+
+    /// Returns a hash with the given exported state, or `None` if the state is not a hash state of
+    /// this width.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(CyclistHash { core: CyclistCore::import(bytes)? })
+    }
+}
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize> Default for CyclistHash<P, WIDTH, HASH_RATE>
+where
+    P: Permutation<WIDTH>,
+{
+    fn default() -> Self {
+        CyclistHash { core: CyclistCore::new() }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<P, const WIDTH: usize, const HASH_RATE: usize> zeroize::ZeroizeOnDrop
+    for CyclistHash<P, WIDTH, HASH_RATE>
+where
+    P: Permutation<WIDTH>,
+{
+}
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize> Cyclist for CyclistHash<P, WIDTH, HASH_RATE>
 where
     P: Permutation<WIDTH>,
 {
@@ -326,11 +1344,56 @@ where
     fn squeeze_key_mut(&mut self, out: &mut [u8]) {
         self.core.squeeze_key_mut(out);
     }
+
+    fn absorb_many(hashes: &mut [Self], bins: &[&[u8]]) {
+        assert_eq!(hashes.len(), bins.len(), "mismatched number of inputs");
+        if same_len(bins) {
+            CyclistCore::absorb_many(hashes, |hash| &mut hash.core, bins);
+        } else {
+            for (hash, bin) in hashes.iter_mut().zip(bins) {
+                hash.absorb(bin);
+            }
+        }
+    }
+
+    fn squeeze_many(hashes: &mut [Self], outs: &mut [&mut [u8]]) {
+        assert_eq!(hashes.len(), outs.len(), "mismatched number of outputs");
+        if same_len(outs) {
+            CyclistCore::squeeze_many(hashes, |hash| &mut hash.core, outs);
+        } else {
+            for (hash, out) in hashes.iter_mut().zip(outs.iter_mut()) {
+                hash.squeeze_mut(out);
+            }
+        }
+    }
+}
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize> Rates for CyclistHash<P, WIDTH, HASH_RATE>
+where
+    P: Permutation<WIDTH>,
+{
+    const ABSORB_RATE: usize = HASH_RATE;
+    const SQUEEZE_RATE: usize = HASH_RATE;
+}
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize> sealed::Sealed
+    for CyclistHash<P, WIDTH, HASH_RATE>
+where
+    P: Permutation<WIDTH>,
+{
+}
+
+/// Returns `true` if the given slices are all the same length.
+fn same_len<T: AsRef<[u8]>>(slices: &[T]) -> bool {
+    slices.windows(2).all(|w| w[0].as_ref().len() == w[1].as_ref().len())
 }
 
 /// A Cyclist object in keyed mode. Parameterized with the permutation algorithm, the permutation
 /// width, the absorb rate, the squeeze rate, the ratchet rate, and the length of authentication
 /// tags.
+///
+/// A [`CyclistKeyed`] is [`Send`] and [`Sync`] if its permutation is, as are all the permutations in
+/// this crate.
 #[derive(Clone, Debug)]
 pub struct CyclistKeyed<
     P,
@@ -358,15 +1421,38 @@ where
 {
     /// Creates a new [`CyclistKeyed`] instance with the given key, optional key ID, and optional
     /// counter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is empty or if the combined key and key ID length is not less than the
+    /// absorb rate. Use [`CyclistKeyed::try_new`] to handle these as errors.
     pub fn new(key: &[u8], key_id: &[u8], counter: &[u8]) -> Self {
-        let mut core =
-            CyclistCore::<P, WIDTH, true, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE>::new();
         assert!(!key.is_empty(), "key length must be > 0");
         assert!(
-            key.len() + key_id.len() <= ABSORB_RATE - 1,
+            key.len() + key_id.len() < ABSORB_RATE,
             "combined key and key ID length must be <= {}",
             ABSORB_RATE - 1,
         );
+        Self::try_new(key, key_id, counter).expect("invalid key")
+    }
+
+    /// Creates a new [`CyclistKeyed`] instance with the given key, optional key ID, and optional
+    /// counter.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::EmptyKey`] if the key is empty and [`Error::KeyTooLong`] if the combined key
+    /// and key ID length is not less than the absorb rate.
+    pub fn try_new(key: &[u8], key_id: &[u8], counter: &[u8]) -> Result<Self, Error> {
+        if key.is_empty() {
+            return Err(Error::EmptyKey);
+        }
+        if key.len() + key_id.len() >= ABSORB_RATE {
+            return Err(Error::KeyTooLong);
+        }
+
+        let mut core =
+            CyclistCore::<P, WIDTH, true, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE>::new();
 
         // Initialize and partition a buffer for the initial state.
         let mut state = [0u8; ABSORB_RATE];
@@ -382,19 +1468,62 @@ where
         // Set the last byte of the initial state to the key ID length.
         len_state[0] = key_id.len().try_into().expect("invalid key length");
 
-        // Absorb the initial state.
+        // Absorb the initial state and wipe the key from the stack.
         core.absorb_any(&state[..key.len() + key_id.len() + 1], ABSORB_RATE, 0x02);
+        wipe(&mut state);
 
         // If given a counter, trickle it in one byte at a time.
         if !counter.is_empty() {
             core.absorb_any(counter, 1, 0x00);
         }
 
-        CyclistKeyed { core }
+        Ok(CyclistKeyed { core })
+    }
+
+    /// Creates a new [`CyclistKeyed`] instance with the given key and nonce, as
+    /// `CyclistKeyed::new(key, Some(nonce), None)` did before key IDs and counters were passed as
+    /// slices.
+    ///
+    /// The nonce is used as the key ID, so this is equivalent to `CyclistKeyed::new(key, nonce,
+    /// b"")`. Note that this means the combined key and nonce length must be less than the absorb
+    /// rate; longer nonces should be absorbed after initialization instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is empty or if the combined key and nonce length is not less than the
+    /// absorb rate.
+    #[deprecated(note = "use `CyclistKeyed::new(key, nonce, b\"\")`")]
+    pub fn new_with_nonce(key: &[u8], nonce: &[u8]) -> Self {
+        Self::new(key, nonce, b"")
+    }
+
+    /// Creates a new [`CyclistKeyed`] instance with the given key, optional key ID, and optional
+    /// counter, as `CyclistKeyed::new` did before key IDs and counters were passed as slices.
+    ///
+    /// Absent values are equivalent to empty slices, so this is equivalent to
+    /// `CyclistKeyed::new(key, key_id.unwrap_or_default(), counter.unwrap_or_default())`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is empty or if the combined key and key ID length is not less than the
+    /// absorb rate.
+    #[deprecated(note = "use `CyclistKeyed::new`, passing empty slices for absent values")]
+    pub fn new_with_key_id_and_counter(
+        key: &[u8],
+        key_id: Option<&[u8]>,
+        counter: Option<&[u8]>,
+    ) -> Self {
+        Self::new(key, key_id.unwrap_or_default(), counter.unwrap_or_default())
     }
 
     /// Encrypts the given mutable slice in place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the duplex is poisoned (see [`CyclistKeyed::is_poisoned`]). Use
+    /// [`CyclistKeyed::try_encrypt_mut`] to handle this as an error.
     pub fn encrypt_mut(&mut self, in_out: &mut [u8]) {
+        self.assert_unpoisoned();
         let mut tmp = [0u8; SQUEEZE_RATE];
 
         // Start with 0x80 as the domain separator for the UP mode.
@@ -416,6 +1545,9 @@ where
                 *p ^= *k;
             }
         }
+
+        // Wipe the keystream.
+        wipe(&mut tmp);
     }
 
     /// Returns an encrypted copy of the given slice.
@@ -427,7 +1559,13 @@ where
     }
 
     /// Decrypts the given mutable slice in place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the duplex is poisoned (see [`CyclistKeyed::is_poisoned`]). Use
+    /// [`CyclistKeyed::try_decrypt_mut`] to handle this as an error.
     pub fn decrypt_mut(&mut self, in_out: &mut [u8]) {
+        self.assert_unpoisoned();
         let mut tmp = [0u8; SQUEEZE_RATE];
 
         // Start with 0x80 as the domain separator for the UP mode.
@@ -449,6 +1587,9 @@ where
             // Update the state with the plaintext.
             self.core.down(Some(ciphertext), 0x00);
         }
+
+        // Wipe the keystream.
+        wipe(&mut tmp);
     }
 
     /// Returns an decrypted copy of the given slice.
@@ -459,25 +1600,103 @@ where
         c
     }
 
+    /// Fills the given mutable slice with raw keystream, for protocols which must XOR it with the
+    /// plaintext externally (e.g. hardware offload).
+    ///
+    /// This is exactly equivalent to encrypting `out.len()` zero bytes: the state absorbs zeros,
+    /// not the plaintext, so it advances identically to `encrypt_mut(&mut [0u8; n])`, and the
+    /// transcript authenticates neither the plaintext nor the ciphertext. Absorb the plaintext or
+    /// ciphertext separately if it must be authenticated.
+    pub fn keystream(&mut self, out: &mut [u8]) {
+        out.fill(0);
+        self.encrypt_mut(out);
+    }
+
     /// Ratchets the state, providing forward secrecy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the duplex is poisoned (see [`CyclistKeyed::is_poisoned`]). Use
+    /// [`CyclistKeyed::try_ratchet`] to handle this as an error.
     pub fn ratchet(&mut self) {
+        self.assert_unpoisoned();
         let mut rolled_key = [0u8; RATCHET_RATE];
         self.core.squeeze_any(&mut rolled_key, 0x10);
         self.core.absorb_any(&rolled_key, RATCHET_RATE, 0x00);
+        wipe(&mut rolled_key);
+        self.core.stats.ratchets += 1;
+    }
+
+    /// Returns an independent child duplex derived from the current state and the given label,
+    /// e.g. to branch one shared secret into separate `client->server` and `server->client`
+    /// channels.
+    ///
+    /// A copy of the duplex absorbs a domain separation label and the label, and squeezes a key of
+    /// 32 bytes (or one less than the absorb rate, if that's shorter) with which the child is
+    /// initialized. The duplex itself is unmodified, so both ends of a protocol can fork the same
+    /// state with the same label to get the same child, while children with different labels are
+    /// independent of each other and of the parent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the duplex is poisoned (see [`CyclistKeyed::is_poisoned`]).
+    pub fn fork(&self, label: &[u8]) -> Self {
+        let mut parent = self.clone();
+        parent.absorb(FORK_LABEL);
+        parent.absorb(label);
+        let mut key = [0u8; FORK_KEY_LEN];
+        let key = &mut key[..FORK_KEY_LEN.min(ABSORB_RATE - 1)];
+        parent.squeeze_key_mut(key);
+
+        let child = Self::new(key, b"", b"");
+        wipe(key);
+        child
+    }
+
+    /// Returns a snapshot of the duplex's state, which [`CyclistKeyed::restore`] can roll back to,
+    /// e.g. to retry a trial decryption. See [`snapshot`].
+    pub fn snapshot(&self) -> snapshot::Snapshot<Self> {
+        snapshot::Snapshot(self.clone())
+    }
+
+    /// Rolls the duplex's state back to the given snapshot.
+    pub fn restore(&mut self, snapshot: &snapshot::Snapshot<Self>) {
+        *self = snapshot.0.clone();
     }
 
     /// Seals the given mutable slice in place.
     ///
     /// The last `TAG_LEN` bytes of the slice will be overwritten with the authentication tag.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slice is shorter than `TAG_LEN` bytes or if the duplex is poisoned (see
+    /// [`CyclistKeyed::is_poisoned`]). Use [`CyclistKeyed::try_seal_mut`] to handle these as
+    /// errors.
     pub fn seal_mut(&mut self, in_out: &mut [u8]) {
         // Split the buffer into plaintext and tag.
         let (plaintext, tag) = in_out.split_at_mut(in_out.len() - TAG_LEN);
 
+        // Seal the plaintext and copy the tag after it.
+        tag.copy_from_slice(self.seal_detached(plaintext).as_ref());
+    }
+
+    /// Seals the given mutable slice in place and returns the authentication tag separately, for
+    /// protocols which carry the tag elsewhere (e.g. in a header).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the duplex is poisoned (see [`CyclistKeyed::is_poisoned`]). Use
+    /// [`CyclistKeyed::try_seal_detached`] to handle this as an error.
+    pub fn seal_detached(&mut self, in_out: &mut [u8]) -> Tag<TAG_LEN> {
         // Encrypt the plaintext.
-        self.encrypt_mut(plaintext);
+        self.encrypt_mut(in_out);
 
         // Squeeze a tag.
-        self.squeeze_mut(tag);
+        let mut tag = [0u8; TAG_LEN];
+        self.squeeze_mut(&mut tag);
+        self.core.stats.seals += 1;
+        Tag(tag)
     }
 
     /// Returns a sealed copy of the given slice.
@@ -485,60 +1704,435 @@ where
     /// The returned [Vec] will be `TAG_LEN` bytes longer than `bin`.
     #[cfg(feature = "std")]
     pub fn seal(&mut self, bin: &[u8]) -> Vec<u8> {
-        let mut c = vec![0u8; bin.len() + TAG_LEN];
-        c[..bin.len()].copy_from_slice(bin);
+        let mut c = Vec::with_capacity(bin.len() + TAG_LEN);
+        self.seal_into(bin, &mut c);
+        c
+    }
+
+    /// Seals the given slice, replacing the contents of `out` with the ciphertext.
+    ///
+    /// Unlike [`CyclistKeyed::seal`], this reuses the capacity of `out`, avoiding an allocation per
+    /// message when `out` is reused across calls.
+    #[cfg(feature = "std")]
+    pub fn seal_into(&mut self, bin: &[u8], out: &mut Vec<u8>) {
+        out.clear();
+        out.extend_from_slice(bin);
+        out.resize(bin.len() + TAG_LEN, 0);
+        self.seal_mut(out);
+    }
+
+    /// Returns a sealed copy of the given buffers, exactly as if they had been concatenated and
+    /// passed to [`CyclistKeyed::seal`], e.g. to seal a packet whose header and body are in
+    /// separate buffers.
+    ///
+    /// This allocates a single buffer for the ciphertext. To seal the buffers in place instead, use
+    /// [`CyclistKeyed::seal_vectored_mut`].
+    #[cfg(feature = "std")]
+    pub fn seal_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> Vec<u8> {
+        let len = bufs.iter().map(|buf| buf.len()).sum::<usize>();
+        let mut c = Vec::with_capacity(len + TAG_LEN);
+        for buf in bufs {
+            c.extend_from_slice(buf);
+        }
+        c.resize(len + TAG_LEN, 0);
         self.seal_mut(&mut c);
         c
     }
 
-    /// Opens the given mutable slice in place. Returns `true` if the input was authenticated. The
-    /// last `TAG_LEN` bytes of the slice will be unmodified.
-    #[must_use]
-    pub fn open_mut(&mut self, in_out: &mut [u8]) -> bool {
+    /// Seals the given buffers in place and returns the authentication tag separately, exactly as
+    /// if they had been concatenated and passed to [`CyclistKeyed::seal_detached`].
+    ///
+    /// Blocks of plaintext which span buffers are encrypted a piece at a time, so nothing is copied
+    /// besides the current block.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the duplex is poisoned (see [`CyclistKeyed::is_poisoned`]).
+    #[cfg(feature = "std")]
+    pub fn seal_vectored_mut(&mut self, bufs: &mut [std::io::IoSliceMut<'_>]) -> Tag<TAG_LEN> {
+        self.assert_unpoisoned();
+        let mut tmp = [0u8; SQUEEZE_RATE];
+        let mut block = [0u8; SQUEEZE_RATE];
+        let mut pos = 0;
+
+        // Start with 0x80 as the domain separator for the UP mode.
+        let mut cu = 0x80;
+
+        for buf in bufs.iter_mut() {
+            let mut buf = &mut buf[..];
+            while !buf.is_empty() {
+                // At the start of each block, fill the temporary buffer with output from the state.
+                if pos == 0 {
+                    self.core.up(Some(&mut tmp), cu);
+                    cu = 0x00;
+                }
+
+                // Collect as much of the block's plaintext as this buffer holds, and encrypt it.
+                let n = buf.len().min(SQUEEZE_RATE - pos);
+                let (plaintext, rest) = buf.split_at_mut(n);
+                block[pos..pos + n].copy_from_slice(plaintext);
+                for (p, k) in plaintext.iter_mut().zip(&tmp[pos..]) {
+                    *p ^= *k;
+                }
+                buf = rest;
+                pos += n;
+
+                // Once the block is complete, update the state with its plaintext.
+                if pos == SQUEEZE_RATE {
+                    self.core.down(Some(&block), 0x00);
+                    pos = 0;
+                }
+            }
+        }
+
+        // Update the state with the final partial block, if any.
+        if pos > 0 {
+            self.core.down(Some(&block[..pos]), 0x00);
+        }
+
+        // Wipe the keystream and plaintext.
+        wipe(&mut tmp);
+        wipe(&mut block);
+
+        // Squeeze a tag.
+        let mut tag = [0u8; TAG_LEN];
+        self.squeeze_mut(&mut tag);
+        self.core.stats.seals += 1;
+        Tag(tag)
+    }
+
+    /// Opens the given mutable slice in place. The last `TAG_LEN` bytes of the slice will be
+    /// unmodified. If the input cannot be authenticated, the rest of the slice is zeroed out and
+    /// the duplex is poisoned (see [`CyclistKeyed::is_poisoned`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the duplex is poisoned, [`Error::BufferTooSmall`] if the slice
+    /// is shorter than `TAG_LEN` bytes, leaving the state unmodified, and [`Error::InvalidTag`] if
+    /// the input could not be authenticated.
+    pub fn open_mut(&mut self, in_out: &mut [u8]) -> Result<(), Error> {
+        self.check_unpoisoned()?;
+
+        // Reject inputs too short to contain a tag.
+        if in_out.len() < TAG_LEN {
+            self.core.stats.opens += 1;
+            self.core.stats.failures += 1;
+            return Err(Error::BufferTooSmall);
+        }
+
         // Split the buffer into ciphertext and tag.
         let (ciphertext, tag) = in_out.split_at_mut(in_out.len() - TAG_LEN);
+        let tag = Tag::try_from(&*tag).expect("invalid tag length");
+
+        self.open_detached(ciphertext, &tag)
+    }
+
+    /// Opens the given mutable slice in place, given its detached authentication tag.
+    ///
+    /// If the tag doesn't match, the slice is zeroed out and the duplex is poisoned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the duplex is poisoned and [`Error::InvalidTag`] if the input
+    /// could not be authenticated.
+    pub fn open_detached(&mut self, in_out: &mut [u8], tag: &Tag<TAG_LEN>) -> Result<(), Error> {
+        self.check_unpoisoned()?;
 
         // Decrypt the ciphertext.
-        self.decrypt_mut(ciphertext);
+        self.decrypt_mut(in_out);
 
         // Squeeze a counterfactual tag.
         let mut tag_p = [0u8; TAG_LEN];
         self.squeeze_mut(&mut tag_p);
 
-        // If the two tags are equal in constant time, the plaintext is authentic.
-        if constant_time_eq(tag, &tag_p) {
-            true
-        } else {
-            // Otherwise, the ciphertext is inauthentic and we zero out the inauthentic plaintext to
-            // avoid bugs where the caller forgets to check the return value of this function and
-            // discloses inauthentic plaintext.
-            ciphertext.fill(0);
-            false
+        // Compare the two tags in constant time and wipe the counterfactual tag, which would
+        // otherwise allow an attacker with access to the stack to forge a tag for this ciphertext.
+        let authentic = constant_time_eq(&tag.0, &tag_p);
+        wipe(&mut tag_p);
+
+        // If the two tags are equal, the plaintext is authentic. Otherwise, the ciphertext is
+        // inauthentic and we zero out the inauthentic plaintext to avoid bugs where the caller
+        // forgets to check the return value of this function and discloses inauthentic plaintext.
+        self.core.stats.opens += 1;
+        if !authentic {
+            self.reject(in_out);
+            return Err(Error::InvalidTag);
         }
+        Ok(())
     }
 
-    /// Returns an unsealed copy of the given slice, or `None` if the ciphertext cannot be
-    /// authenticated.
-    #[cfg(feature = "std")]
-    pub fn open(&mut self, bin: &[u8]) -> Option<Vec<u8>> {
-        let mut c = bin.to_vec();
-        self.open_mut(&mut c).then(|| c[..c.len() - TAG_LEN].to_vec())
+    /// Returns `true` if the duplex has been poisoned by a failure to authenticate an input.
+    ///
+    /// By then the duplex has absorbed attacker-controlled ciphertext, and its state can no longer
+    /// be relied upon, so it is wiped. Opening further inputs with a poisoned duplex returns
+    /// [`Error::Poisoned`], as do the `try_` variants of the other operations (e.g.
+    /// [`CyclistKeyed::try_seal_mut`]). The infallible operations panic instead, since continuing
+    /// with a wiped state would be far worse. To retry an open (e.g. for trial decryption), take a
+    /// [`CyclistKeyed::snapshot`] beforehand and restore it.
+    pub const fn is_poisoned(&self) -> bool {
+        self.core.poisoned
     }
 
-    /// Returns the number of bytes which can be absorbed before the state is permuted.
-    pub const fn absorb_rate() -> usize {
-        ABSORB_RATE
+    /// Returns [`Error::Poisoned`] if the duplex has been poisoned.
+    pub(crate) const fn check_unpoisoned(&self) -> Result<(), Error> {
+        if self.core.poisoned {
+            return Err(Error::Poisoned);
+        }
+        Ok(())
     }
 
-    /// Returns the number of bytes which can be squeezed before the state is permuted.
-    pub const fn squeeze_rate() -> usize {
-        SQUEEZE_RATE
+    /// Panics if the duplex has been poisoned.
+    fn assert_unpoisoned(&self) {
+        assert!(!self.core.poisoned, "keyed duplex used after a failed open");
     }
 
-    /// Returns the length of an authentication tag in bytes.
-    pub const fn tag_len() -> usize {
-        TAG_LEN
-    }
+    /// Absorbs the given slice of data. See [`Cyclist::absorb`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the duplex is poisoned.
+    pub fn try_absorb(&mut self, bin: &[u8]) -> Result<(), Error> {
+        self.check_unpoisoned()?;
+        self.core.absorb(bin);
+        Ok(())
+    }
+
+    /// Fills the given mutable slice with squeezed data. See [`Cyclist::squeeze_mut`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the duplex is poisoned.
+    pub fn try_squeeze_mut(&mut self, out: &mut [u8]) -> Result<(), Error> {
+        self.check_unpoisoned()?;
+        self.core.squeeze_mut(out);
+        Ok(())
+    }
+
+    /// Encrypts the given mutable slice in place. See [`CyclistKeyed::encrypt_mut`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the duplex is poisoned.
+    pub fn try_encrypt_mut(&mut self, in_out: &mut [u8]) -> Result<(), Error> {
+        self.check_unpoisoned()?;
+        self.encrypt_mut(in_out);
+        Ok(())
+    }
+
+    /// Decrypts the given mutable slice in place. See [`CyclistKeyed::decrypt_mut`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the duplex is poisoned.
+    pub fn try_decrypt_mut(&mut self, in_out: &mut [u8]) -> Result<(), Error> {
+        self.check_unpoisoned()?;
+        self.decrypt_mut(in_out);
+        Ok(())
+    }
+
+    /// Ratchets the state. See [`CyclistKeyed::ratchet`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the duplex is poisoned.
+    pub fn try_ratchet(&mut self) -> Result<(), Error> {
+        self.check_unpoisoned()?;
+        self.ratchet();
+        Ok(())
+    }
+
+    /// Seals the given mutable slice in place. See [`CyclistKeyed::seal_mut`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the duplex is poisoned and [`Error::BufferTooSmall`] if the
+    /// slice is shorter than `TAG_LEN` bytes, leaving the state unmodified.
+    pub fn try_seal_mut(&mut self, in_out: &mut [u8]) -> Result<(), Error> {
+        self.check_unpoisoned()?;
+        if in_out.len() < TAG_LEN {
+            return Err(Error::BufferTooSmall);
+        }
+        self.seal_mut(in_out);
+        Ok(())
+    }
+
+    /// Seals the given mutable slice in place and returns the authentication tag separately. See
+    /// [`CyclistKeyed::seal_detached`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the duplex is poisoned.
+    pub fn try_seal_detached(&mut self, in_out: &mut [u8]) -> Result<Tag<TAG_LEN>, Error> {
+        self.check_unpoisoned()?;
+        Ok(self.seal_detached(in_out))
+    }
+
+    /// Exports the session's state to the given slice. See [`CyclistKeyed::to_bytes_mut`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the duplex is poisoned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` is not [`CyclistKeyed::state_len`] bytes long.
+    pub fn try_to_bytes_mut(&self, out: &mut [u8]) -> Result<(), Error> {
+        self.check_unpoisoned()?;
+        self.core.export(out);
+        Ok(())
+    }
+
+    /// Records an authentication failure, zeroing out the inauthentic plaintext and poisoning the
+    /// duplex.
+    pub(crate) fn reject(&mut self, plaintext: &mut [u8]) {
+        self.core.stats.failures += 1;
+        plaintext.fill(0);
+        self.core.poison();
+    }
+
+    /// Returns an unsealed copy of the given slice.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the duplex is poisoned, [`Error::BufferTooSmall`] if the slice
+    /// is shorter than `TAG_LEN` bytes, and [`Error::InvalidTag`] if the ciphertext cannot be
+    /// authenticated.
+    #[cfg(feature = "std")]
+    pub fn open(&mut self, bin: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut p = Vec::with_capacity(bin.len());
+        self.open_into(bin, &mut p)?;
+        Ok(p)
+    }
+
+    /// Opens the given slice, replacing the contents of `out` with the plaintext. If the input
+    /// cannot be authenticated, `out` will be empty.
+    ///
+    /// Unlike [`CyclistKeyed::open`], this reuses the capacity of `out`, avoiding an allocation per
+    /// message when `out` is reused across calls.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the duplex is poisoned, [`Error::BufferTooSmall`] if the slice
+    /// is shorter than `TAG_LEN` bytes, and [`Error::InvalidTag`] if the ciphertext cannot be
+    /// authenticated.
+    #[cfg(feature = "std")]
+    pub fn open_into(&mut self, bin: &[u8], out: &mut Vec<u8>) -> Result<(), Error> {
+        out.clear();
+        out.extend_from_slice(bin);
+        match self.open_mut(out) {
+            Ok(()) => {
+                out.truncate(bin.len() - TAG_LEN);
+                Ok(())
+            }
+            Err(e) => {
+                out.clear();
+                Err(e)
+            }
+        }
+    }
+
+    /// Returns the number of bytes which can be absorbed before the state is permuted.
+    pub const fn absorb_rate() -> usize {
+        ABSORB_RATE
+    }
+
+    /// Absorbs the given items as a single absorb operation, each prefixed with its length as a
+    /// little-endian 64-bit integer. See [`CyclistHash::absorb_items`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the duplex is poisoned (see [`CyclistKeyed::is_poisoned`]).
+    pub fn absorb_items(&mut self, items: &[&[u8]]) {
+        self.assert_unpoisoned();
+        self.core.absorb_items(items);
+    }
+
+    /// Absorbs the given buffers as a single absorb operation, exactly as if they had been
+    /// concatenated. See [`CyclistHash::absorb_vectored`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the duplex is poisoned (see [`CyclistKeyed::is_poisoned`]).
+    #[cfg(feature = "std")]
+    pub fn absorb_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) {
+        self.assert_unpoisoned();
+        self.core.absorb_vectored(bufs);
+    }
+
+    /// Returns the number of bytes which can be squeezed before the state is permuted.
+    pub const fn squeeze_rate() -> usize {
+        SQUEEZE_RATE
+    }
+
+    /// Returns the length of an authentication tag in bytes.
+    pub const fn tag_len() -> usize {
+        TAG_LEN
+    }
+
+    /// The number of bytes by which sealing expands a plaintext.
+    pub const OVERHEAD: usize = TAG_LEN;
+
+    /// Returns the length of the ciphertext of a `plaintext_len`-byte plaintext.
+    pub const fn sealed_len(plaintext_len: usize) -> usize {
+        plaintext_len + Self::OVERHEAD
+    }
+
+    /// Returns the length of the plaintext of a `ciphertext_len`-byte ciphertext, or `None` if the
+    /// ciphertext is too short to contain a tag.
+    pub const fn opened_len(ciphertext_len: usize) -> Option<usize> {
+        ciphertext_len.checked_sub(Self::OVERHEAD)
+    }
+
+    /// Returns the length of an exported state in bytes.
+    pub const fn state_len() -> usize {
+        WIDTH + 1
+    }
+
+    /// Exports the session's state to the given slice, allowing it to be persisted or migrated to
+    /// another process and later resumed with [`CyclistKeyed::from_bytes`]. Operation counts (see
+    /// [`metrics`]) are not included.
+    ///
+    /// The exported state is as sensitive as the key: it can be used to decrypt and forge any of the
+    /// session's subsequent messages. It should be stored with the same care and wiped after use.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` is not [`CyclistKeyed::state_len`] bytes long or if the duplex is poisoned
+    /// (see [`CyclistKeyed::is_poisoned`]). Use [`CyclistKeyed::try_to_bytes_mut`] to handle the
+    /// latter as an error.
+    pub fn to_bytes_mut(&self, out: &mut [u8]) {
+        self.assert_unpoisoned();
+        self.core.export(out);
+    }
+
+    /// Returns the session's exported state. See [`CyclistKeyed::to_bytes_mut`].
+    #[cfg(feature = "std")]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = vec![0u8; Self::state_len()];
+        self.to_bytes_mut(&mut out);
+        out
+    }
+
+    /// Returns a session with the given exported state, or `None` if the state is not a keyed state
+    /// of this width.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(CyclistKeyed { core: CyclistCore::import(bytes)? })
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > zeroize::ZeroizeOnDrop
+    for CyclistKeyed<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+{
 }
 
 impl<
@@ -553,32 +2147,600 @@ where
     P: Permutation<WIDTH>,
 {
     fn absorb(&mut self, bin: &[u8]) {
+        self.assert_unpoisoned();
         self.core.absorb(bin);
     }
 
     fn absorb_more(&mut self, bin: &[u8]) {
+        self.assert_unpoisoned();
         self.core.absorb_more(bin);
     }
 
     fn squeeze_mut(&mut self, out: &mut [u8]) {
+        self.assert_unpoisoned();
         self.core.squeeze_mut(out);
     }
 
     fn squeeze_more_mut(&mut self, out: &mut [u8]) {
+        self.assert_unpoisoned();
         self.core.squeeze_more_mut(out);
     }
 
     fn squeeze_key_mut(&mut self, out: &mut [u8]) {
+        self.assert_unpoisoned();
         self.core.squeeze_key_mut(out);
     }
+
+    fn absorb_many(keyeds: &mut [Self], bins: &[&[u8]]) {
+        assert_eq!(keyeds.len(), bins.len(), "mismatched number of inputs");
+        keyeds.iter().for_each(Self::assert_unpoisoned);
+        if same_len(bins) {
+            CyclistCore::absorb_many(keyeds, |keyed| &mut keyed.core, bins);
+        } else {
+            for (keyed, bin) in keyeds.iter_mut().zip(bins) {
+                keyed.core.absorb(bin);
+            }
+        }
+    }
+
+    fn squeeze_many(keyeds: &mut [Self], outs: &mut [&mut [u8]]) {
+        assert_eq!(keyeds.len(), outs.len(), "mismatched number of outputs");
+        keyeds.iter().for_each(Self::assert_unpoisoned);
+        if same_len(outs) {
+            CyclistCore::squeeze_many(keyeds, |keyed| &mut keyed.core, outs);
+        } else {
+            for (keyed, out) in keyeds.iter_mut().zip(outs.iter_mut()) {
+                keyed.core.squeeze_mut(out);
+            }
+        }
+    }
+}
+
+impl<
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > Rates for CyclistKeyed<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+{
+    const ABSORB_RATE: usize = ABSORB_RATE;
+    const SQUEEZE_RATE: usize = SQUEEZE_RATE;
+}
+
+impl<
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > sealed::Sealed for CyclistKeyed<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+{
+}
+
+impl<
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > Keyed for CyclistKeyed<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+{
+    const TAG_LEN: usize = TAG_LEN;
+
+    fn new(key: &[u8], key_id: &[u8], counter: &[u8]) -> Self {
+        Self::new(key, key_id, counter)
+    }
+
+    fn encrypt_mut(&mut self, in_out: &mut [u8]) {
+        self.encrypt_mut(in_out);
+    }
+
+    fn decrypt_mut(&mut self, in_out: &mut [u8]) {
+        self.decrypt_mut(in_out);
+    }
+
+    fn ratchet(&mut self) {
+        self.ratchet();
+    }
+
+    fn seal_mut(&mut self, in_out: &mut [u8]) {
+        self.seal_mut(in_out);
+    }
+
+    fn open_mut(&mut self, in_out: &mut [u8]) -> Result<(), Error> {
+        self.open_mut(in_out)
+    }
+
+    fn is_poisoned(&self) -> bool {
+        self.is_poisoned()
+    }
+
+    fn try_absorb(&mut self, bin: &[u8]) -> Result<(), Error> {
+        self.try_absorb(bin)
+    }
+
+    fn try_squeeze_mut(&mut self, out: &mut [u8]) -> Result<(), Error> {
+        self.try_squeeze_mut(out)
+    }
+
+    fn try_encrypt_mut(&mut self, in_out: &mut [u8]) -> Result<(), Error> {
+        self.try_encrypt_mut(in_out)
+    }
+
+    fn try_decrypt_mut(&mut self, in_out: &mut [u8]) -> Result<(), Error> {
+        self.try_decrypt_mut(in_out)
+    }
+
+    fn try_ratchet(&mut self) -> Result<(), Error> {
+        self.try_ratchet()
+    }
+
+    fn try_seal_mut(&mut self, in_out: &mut [u8]) -> Result<(), Error> {
+        self.try_seal_mut(in_out)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::xoodyak::XoodyakHash;
+    use std::cell::Cell;
+
+    use crate::xoodyak::{XoodyakHash, XoodyakKeyed};
 
     use super::*;
 
+    thread_local! {
+        /// The number of bytes of temporary buffers wiped on this thread.
+        pub(crate) static WIPED: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// Returns the number of bytes of temporary buffers wiped by `f`.
+    fn wiped(f: impl FnOnce()) -> usize {
+        let before = WIPED.with(Cell::get);
+        f();
+        WIPED.with(Cell::get) - before
+    }
+
+    #[test]
+    fn wiping_temporaries() {
+        let rate = XoodyakKeyed::squeeze_rate();
+        let tag_len = XoodyakKeyed::tag_len();
+        let keyed = || XoodyakKeyed::new(b"ok then", b"", b"");
+        let mut c = keyed().seal(b"it's a deal");
+
+        assert_eq!(XoodyakKeyed::absorb_rate(), wiped(|| drop(keyed())));
+
+        let mut k = keyed();
+        assert_eq!(rate, wiped(|| k.encrypt_mut(&mut [0u8; 10])));
+
+        let mut k = keyed();
+        assert_eq!(rate, wiped(|| k.decrypt_mut(&mut [0u8; 10])));
+
+        let mut k = keyed();
+        assert_eq!(16, wiped(|| k.ratchet()));
+
+        let mut k = keyed();
+        assert_eq!(rate + tag_len, wiped(|| assert!(k.open_mut(&mut c.clone()).is_ok())));
+
+        c[0] ^= 1;
+        let mut k = keyed();
+        assert_eq!(rate + tag_len, wiped(|| assert!(k.open_mut(&mut c).is_err())));
+    }
+
+    #[test]
+    #[cfg(feature = "zeroize")]
+    fn zeroize_on_drop() {
+        const fn assert_zeroize_on_drop<T: zeroize::ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<XoodyakHash>();
+        assert_zeroize_on_drop::<XoodyakKeyed>();
+    }
+
+    #[test]
+    fn overhead() {
+        assert_eq!(16, XoodyakKeyed::OVERHEAD);
+        assert_eq!(27, XoodyakKeyed::sealed_len(11));
+        assert_eq!(Some(11), XoodyakKeyed::opened_len(27));
+        assert_eq!(None, XoodyakKeyed::opened_len(15));
+        assert_eq!(27, <XoodyakKeyed as Keyed>::sealed_len(11));
+        assert_eq!(None, <XoodyakKeyed as Keyed>::opened_len(15));
+
+        let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+        assert_eq!(XoodyakKeyed::sealed_len(11), d.seal(b"it's a deal").len());
+    }
+
+    #[test]
+    fn invalid_keys() {
+        assert_eq!(Some(Error::EmptyKey), XoodyakKeyed::try_new(b"", b"", b"").err());
+        let key = [0u8; 44];
+        assert_eq!(Some(Error::KeyTooLong), XoodyakKeyed::try_new(&key, b"", b"").err());
+        assert_eq!(Some(Error::KeyTooLong), XoodyakKeyed::try_new(&key[..43], &[0; 1], b"").err());
+        assert!(XoodyakKeyed::try_new(&key[..42], &[0; 1], b"").is_ok());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn compatibility_constructors() {
+        let mut expected = XoodyakKeyed::new(b"key", b"nonce", b"");
+        let mut keyed = XoodyakKeyed::new_with_nonce(b"key", b"nonce");
+        assert_eq!(expected.squeeze(16), keyed.squeeze(16));
+
+        let mut expected = XoodyakKeyed::new(b"key", b"id", b"counter");
+        let mut keyed =
+            XoodyakKeyed::new_with_key_id_and_counter(b"key", Some(b"id"), Some(b"counter"));
+        assert_eq!(expected.squeeze(16), keyed.squeeze(16));
+
+        let mut expected = XoodyakKeyed::new(b"key", b"", b"");
+        let mut keyed = XoodyakKeyed::new_with_key_id_and_counter(b"key", None, None);
+        assert_eq!(expected.squeeze(16), keyed.squeeze(16));
+    }
+
+    #[test]
+    fn domain_separated_hashes() {
+        let digest = |hash: &mut XoodyakHash| {
+            hash.absorb(b"input");
+            hash.squeeze(16)
+        };
+
+        assert_eq!(
+            digest(&mut XoodyakHash::default()),
+            digest(&mut XoodyakHash::domain_separated(b"", b""))
+        );
+
+        let mut plain = XoodyakHash::default();
+        plain.absorb(b"KDF");
+        plain.absorb(b"MyProto v1");
+
+        let digests = [
+            digest(&mut XoodyakHash::default()),
+            digest(&mut XoodyakHash::domain_separated(b"KDF", b"")),
+            digest(&mut XoodyakHash::domain_separated(b"", b"KDF")),
+            digest(&mut XoodyakHash::domain_separated(b"KDF", b"MyProto v1")),
+            digest(&mut XoodyakHash::domain_separated(b"KDF", b"MyProto v2")),
+            digest(&mut XoodyakHash::domain_separated(b"KDFM", b"yProto v1")),
+            digest(&mut XoodyakHash::with_customization(b"MyProto v1")),
+            digest(&mut plain),
+        ];
+        for (i, a) in digests.iter().enumerate() {
+            for b in &digests[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn poisoning() {
+        let mut keyed = XoodyakKeyed::new(b"key", b"", b"");
+        let c = keyed.clone().seal(b"message");
+        assert_eq!(Err(Error::BufferTooSmall), keyed.open(&c[..4]));
+        assert!(!keyed.is_poisoned());
+
+        let snapshot = keyed.snapshot();
+        assert_eq!(Err(Error::InvalidTag), keyed.open(&c[1..]));
+        assert!(keyed.is_poisoned());
+        assert_eq!(Err(Error::Poisoned), keyed.open(&c));
+        assert_eq!(Err(Error::Poisoned), keyed.open(&c[..4]));
+        assert_eq!(Err(Error::Poisoned), keyed.open_detached(&mut [], &Tag::from([0; 16])));
+
+        keyed.restore(&snapshot);
+        assert!(!keyed.is_poisoned());
+        assert_eq!(Ok(b"message".to_vec()), keyed.open(&c));
+    }
+
+    #[test]
+    fn using_poisoned_duplexes() {
+        let mut keyed = XoodyakKeyed::new(b"key", b"", b"");
+        assert_eq!(Ok(()), keyed.try_seal_mut(&mut [0u8; 16]));
+        assert_eq!(Err(Error::BufferTooSmall), keyed.try_seal_mut(&mut [0u8; 4]));
+        assert_eq!(Err(Error::InvalidTag), keyed.open(&[0u8; 16]));
+
+        let mut buf = [0u8; 32];
+        assert_eq!(Err(Error::Poisoned), keyed.try_absorb(b"ad"));
+        assert_eq!(Err(Error::Poisoned), keyed.try_squeeze_mut(&mut buf));
+        assert_eq!(Err(Error::Poisoned), keyed.try_encrypt_mut(&mut buf));
+        assert_eq!(Err(Error::Poisoned), keyed.try_decrypt_mut(&mut buf));
+        assert_eq!(Err(Error::Poisoned), keyed.try_ratchet());
+        assert_eq!(Err(Error::Poisoned), keyed.try_seal_mut(&mut buf));
+        assert_eq!(Err(Error::Poisoned), keyed.try_seal_detached(&mut buf).map(|_| ()));
+        let mut state = vec![0u8; XoodyakKeyed::state_len()];
+        assert_eq!(Err(Error::Poisoned), keyed.try_to_bytes_mut(&mut state));
+        assert_eq!([0u8; 32], buf);
+    }
+
+    #[test]
+    #[should_panic(expected = "keyed duplex used after a failed open")]
+    fn sealing_with_poisoned_duplexes() {
+        let mut keyed = XoodyakKeyed::new(b"key", b"", b"");
+        assert_eq!(Err(Error::InvalidTag), keyed.open(&[0u8; 16]));
+        keyed.seal_mut(&mut [0u8; 16]);
+    }
+
+    #[test]
+    fn vectored_operations() {
+        use std::io::{IoSlice, IoSliceMut};
+
+        let data = XoodyakHash::default().squeeze(200);
+        for splits in [vec![], vec![0], vec![0, 0], vec![1, 44], vec![16, 17, 100], vec![200]] {
+            let mut bufs = Vec::new();
+            let mut start = 0;
+            for end in splits.iter().copied().chain([data.len()]) {
+                bufs.push(IoSlice::new(&data[start..end]));
+                start = end;
+            }
+
+            let (mut a, mut b) = (XoodyakHash::default(), XoodyakHash::default());
+            a.absorb(&data);
+            b.absorb_vectored(&bufs);
+            assert_eq!(a.squeeze(16), b.squeeze(16));
+
+            let (mut a, mut b) =
+                (XoodyakKeyed::new(b"key", b"", b""), XoodyakKeyed::new(b"key", b"", b""));
+            a.absorb(&data);
+            b.absorb_vectored(&bufs);
+            let mut c = b.clone();
+            let sealed = a.seal(&data);
+            assert_eq!(sealed, b.seal_vectored(&bufs));
+
+            let mut in_out = data.clone();
+            let mut bufs = Vec::new();
+            let mut rest = &mut in_out[..];
+            let mut start = 0;
+            for end in splits.iter().copied().chain([data.len()]) {
+                let (buf, tail) = rest.split_at_mut(end - start);
+                bufs.push(IoSliceMut::new(buf));
+                rest = tail;
+                start = end;
+            }
+            let tag = c.seal_vectored_mut(&mut bufs);
+            assert_eq!(sealed, [&in_out[..], tag.as_ref()].concat());
+            assert_eq!(a.squeeze(16), c.squeeze(16));
+        }
+
+        #[cfg(feature = "keccyak")]
+        {
+            let bufs = [IoSlice::new(&data[..30]), IoSlice::new(&data[30..])];
+            let mut a = keccyak::Keccyak200Hash::default();
+            a.absorb(&data);
+            let mut b = keccyak::Keccyak200Hash::default();
+            b.absorb_vectored(&bufs);
+            assert_eq!(a.squeeze(8), b.squeeze(8));
+        }
+
+        let mut a = XoodyakKeyed::new(b"key", b"", b"");
+        let mut b = a.clone();
+        let mut c = a.clone();
+        let sealed = a.seal(b"");
+        assert_eq!(sealed, b.seal_vectored(&[]));
+        assert_eq!(sealed, c.seal_vectored_mut(&mut []).as_ref());
+        let tag = a.squeeze(16);
+        assert_eq!(tag, b.squeeze(16));
+        assert_eq!(tag, c.squeeze(16));
+    }
+
+    #[test]
+    fn forking() {
+        let mut parent = XoodyakKeyed::new(b"a shared secret", b"", b"");
+        parent.absorb(b"handshake");
+        let mut parent_p = parent.clone();
+
+        let mut c2s = parent.fork(b"client->server");
+        let mut s2c = parent.fork(b"server->client");
+        let tags = [c2s.squeeze(16), s2c.squeeze(16), parent.squeeze(16)];
+        assert_ne!(tags[0], tags[1]);
+        assert_ne!(tags[0], tags[2]);
+        assert_ne!(tags[1], tags[2]);
+        assert_eq!(tags[2], parent_p.squeeze(16), "forking modified the parent");
+
+        let mut c2s_p = parent_p.fork(b"client->server");
+        let c = parent_p.fork(b"client->server").seal(b"message");
+        assert_eq!(Ok(b"message".to_vec()), c2s_p.open(&c));
+
+        let mut hash = XoodyakHash::default();
+        hash.absorb(b"input");
+        let (mut a, mut b) = (hash.fork(b"a"), hash.fork(b"b"));
+        assert_eq!(a.squeeze(16), hash.fork(b"a").squeeze(16));
+        assert_ne!(a.squeeze(16), b.squeeze(16));
+        assert_ne!(hash.fork(b"a").squeeze(16), hash.squeeze(16));
+
+        #[cfg(feature = "keccyak")]
+        {
+            let keyed = keccyak::Keccyak200Keyed::new(b"key", b"", b"");
+            let mut child = keyed.fork(b"child");
+            assert_eq!(child.squeeze(8), keyed.fork(b"child").squeeze(8));
+        }
+    }
+
+    #[test]
+    fn absorbing_items() {
+        let digest = |items: &[&[u8]]| {
+            let mut hash = XoodyakHash::default();
+            hash.absorb_items(items);
+            hash.squeeze(16)
+        };
+
+        let mut expected = XoodyakHash::default();
+        expected.absorb(b"\x02\x00\x00\x00\x00\x00\x00\x00ab\x01\x00\x00\x00\x00\x00\x00\x00c");
+        assert_eq!(expected.squeeze(16), digest(&[b"ab", b"c"]));
+
+        let digests = [
+            digest(&[b"ab", b"c"]),
+            digest(&[b"a", b"bc"]),
+            digest(&[b"abc"]),
+            digest(&[b"abc", b""]),
+            digest(&[b""]),
+            digest(&[]),
+        ];
+        for (i, a) in digests.iter().enumerate() {
+            for b in &digests[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+
+        // Long items are streamed across blocks.
+        let long = [0xCAu8; 100];
+        let mut expected = XoodyakKeyed::new(b"ok then", b"", b"");
+        let mut encoded = 100u64.to_le_bytes().to_vec();
+        encoded.extend_from_slice(&long);
+        encoded.extend_from_slice(&0u64.to_le_bytes());
+        expected.absorb(&encoded);
+        let mut keyed = XoodyakKeyed::new(b"ok then", b"", b"");
+        keyed.absorb_items(&[&long, b""]);
+        assert_eq!(expected.squeeze(16), keyed.squeeze(16));
+
+        // Items which fill a whole number of blocks can be extended.
+        let mut expected = XoodyakHash::default();
+        expected.absorb(&[&8u64.to_le_bytes()[..], &[0xCA; 8], &[0xFE; 16]].concat());
+        let mut hash = XoodyakHash::default();
+        hash.absorb_items(&[&[0xCA; 8]]);
+        hash.absorb_more(&[0xFE; 16]);
+        assert_eq!(expected.squeeze(16), hash.squeeze(16));
+
+        #[cfg(feature = "keccyak")]
+        {
+            let mut expected = keccyak::Keccyak200Keyed::new(b"key", b"", b"");
+            expected.absorb(&encoded);
+            let mut keyed = keccyak::Keccyak200Keyed::new(b"key", b"", b"");
+            keyed.absorb_items(&[&long, b""]);
+            assert_eq!(expected.squeeze(8), keyed.squeeze(8));
+        }
+    }
+
+    #[test]
+    fn squeeze_arrays() {
+        let mut hash = XoodyakHash::default();
+        hash.absorb(b"ok");
+        let mut expected = hash.clone();
+        assert_eq!(expected.squeeze(20), hash.squeeze_array::<20>());
+
+        let mut keyed = XoodyakKeyed::new(b"ok then", b"", b"");
+        let mut expected = keyed.clone();
+        assert_eq!(expected.squeeze_key(32), keyed.squeeze_key_array::<32>());
+        assert_eq!(expected.squeeze(0), keyed.squeeze_array::<0>());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "absorb_more must follow an absorb of a whole number of blocks")]
+    fn misaligned_absorb_more() {
+        let mut hash = XoodyakHash::default();
+        hash.absorb(&[0u8; 17]);
+        hash.absorb_more(b"more");
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "absorb_more must follow an absorb of a whole number of blocks")]
+    fn absorb_more_after_squeeze() {
+        let mut keyed = XoodyakKeyed::new(b"ok then", b"", b"");
+        keyed.absorb(&[0u8; 44]);
+        keyed.squeeze(16);
+        keyed.absorb_more(b"more");
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "squeeze_more must follow a squeeze of a whole number of blocks")]
+    fn misaligned_squeeze_more() {
+        let mut hash = XoodyakHash::default();
+        hash.squeeze(15);
+        hash.squeeze_more(1);
+    }
+
+    #[test]
+    fn aligned_extensions() {
+        let mut hash = XoodyakHash::default();
+        hash.absorb(&[0u8; 32]);
+        hash.absorb_more(&[0u8; 16]);
+        hash.absorb_more(&[0u8; 3]);
+        hash.squeeze(16);
+        hash.squeeze_more(32);
+        hash.squeeze_more(1);
+
+        // Imported states can be extended, since their previous operation is unknown.
+        let mut hash = XoodyakHash::from_bytes(&hash.to_bytes()).expect("should import");
+        hash.squeeze_more(16);
+    }
+
+    #[test]
+    fn constant_time_comparisons() {
+        assert!(ct_eq(b"", b""));
+        assert!(ct_eq(b"tag", b"tag"));
+        assert!(!ct_eq(b"tag", b"tah"));
+        assert!(!ct_eq(b"tag", b"tags"));
+
+        let tag = Tag::from(*b"tag");
+        assert!(tag == *b"tag".as_slice());
+        assert!(tag != *b"tah".as_slice());
+        assert!(tag != *b"ta".as_slice());
+    }
+
+    #[test]
+    fn hex_tags() {
+        let tag = Tag::from([0x01, 0xAB, 0xFF]);
+        assert_eq!("01abff", tag.to_string());
+        assert_eq!("01abff", format!("{:x}", tag));
+        assert_eq!(Some(tag), Tag::from_hex("01abff"));
+        assert_eq!(Some(tag), Tag::from_hex("01ABfF"));
+        assert_eq!(None, Tag::<3>::from_hex("01abf"));
+        assert_eq!(None, Tag::<3>::from_hex("01abfff"));
+        assert_eq!(None, Tag::<3>::from_hex("01abfg"));
+        assert_eq!(None, Tag::<1>::from_hex("é"));
+    }
+
+    #[test]
+    #[cfg(feature = "subtle")]
+    fn subtle_tags() {
+        use subtle::ConstantTimeEq;
+
+        let tag = Tag::from([0x01, 0xAB, 0xFF]);
+        assert!(bool::from(tag.ct_eq(&Tag::from([0x01, 0xAB, 0xFF]))));
+        assert!(!bool::from(tag.ct_eq(&Tag::from([0x01, 0xAB, 0xFE]))));
+    }
+
+    #[test]
+    fn detached_tags() {
+        let mut sealer = XoodyakKeyed::new(b"ok then", b"", b"");
+        let mut c = *b"it's a deal";
+        let tag = sealer.seal_detached(&mut c);
+
+        let mut attached = XoodyakKeyed::new(b"ok then", b"", b"").seal(b"it's a deal");
+        assert_eq!(&c[..], &attached[..c.len()]);
+        assert_eq!(tag.as_ref(), &attached[c.len()..]);
+        assert_eq!(tag, Tag::try_from(&attached[c.len()..]).expect("should be a tag"));
+
+        let mut p = c;
+        let mut opener = XoodyakKeyed::new(b"ok then", b"", b"");
+        assert_eq!(Ok(()), opener.open_detached(&mut p, &tag));
+        assert_eq!(b"it's a deal", &p);
+
+        let mut bad_tag = tag.to_bytes();
+        bad_tag[0] ^= 1;
+        let mut opener = XoodyakKeyed::new(b"ok then", b"", b"");
+        assert_eq!(Err(Error::InvalidTag), opener.open_detached(&mut c, &bad_tag.into()));
+        assert_eq!([0u8; 11], c);
+
+        attached[0] ^= 1;
+        assert_eq!(
+            Err(Error::InvalidTag),
+            XoodyakKeyed::new(b"ok then", b"", b"").open_mut(&mut attached)
+        );
+    }
+
+    #[test]
+    fn wiping() {
+        let mut buf = [0xCAu8; 32];
+        wipe(&mut buf);
+        assert_eq!([0u8; 32], buf);
+    }
+
     #[test]
     fn absorbing_more() {
         let mut st = XoodyakHash::default();
@@ -595,6 +2757,26 @@ mod tests {
         assert_eq!(one, two);
     }
 
+    #[test]
+    fn reusing_buffers() {
+        let mut c = Vec::new();
+        let mut p = Vec::new();
+        for m in [&b"it's a deal"[..], b"", b"ok"] {
+            let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+            d.seal_into(m, &mut c);
+            assert_eq!(XoodyakKeyed::new(b"ok then", b"", b"").seal(m), c);
+
+            let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+            assert_eq!(Ok(()), d.open_into(&c, &mut p));
+            assert_eq!(m, p);
+        }
+
+        c[0] ^= 1;
+        let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+        assert_eq!(Err(Error::InvalidTag), d.open_into(&c, &mut p));
+        assert!(p.is_empty());
+    }
+
     #[test]
     fn squeezing_more() {
         let mut st = XoodyakHash::default();
@@ -606,4 +2788,268 @@ mod tests {
 
         assert_eq!(one, two);
     }
+
+    #[test]
+    fn exporting_and_importing() {
+        let mut hash = XoodyakHash::default();
+        hash.absorb(b"in progress");
+        let state = hash.to_bytes();
+        assert_eq!(XoodyakHash::state_len(), state.len());
+
+        let mut resumed = XoodyakHash::from_bytes(&state).expect("should import");
+        resumed.absorb(b"more");
+        hash.absorb(b"more");
+        assert_eq!(hash.squeeze(32), resumed.squeeze(32));
+
+        let mut keyed = XoodyakKeyed::new(b"ok then", b"", b"");
+        let c = keyed.seal(b"it's a deal");
+        let mut resumed = XoodyakKeyed::from_bytes(&keyed.to_bytes()).expect("should import");
+        assert_eq!(keyed.seal(b"ok"), resumed.seal(b"ok"));
+
+        // Hash and keyed states aren't interchangeable, and truncated states are rejected.
+        assert!(XoodyakKeyed::from_bytes(&state).is_none());
+        assert!(XoodyakHash::from_bytes(&keyed.to_bytes()).is_none());
+        assert!(XoodyakHash::from_bytes(&state[..state.len() - 1]).is_none());
+        assert!(XoodyakHash::from_bytes(&[]).is_none());
+
+        let opener = XoodyakKeyed::new(b"ok then", b"", b"");
+        let mut opener = XoodyakKeyed::from_bytes(&opener.to_bytes()).expect("should import");
+        assert_eq!(Ok(b"it's a deal".to_vec()), opener.open(&c));
+    }
+
+    /// Returns the lengths at which chunking bugs are most likely: empty, a single byte, either side
+    /// of the tag length, and either side of one and two multiples of each rate.
+    fn boundary_lengths(tag_len: usize, rates: &[usize]) -> Vec<usize> {
+        let mut lens = vec![0, 1, tag_len - 1, tag_len, tag_len + 1];
+        for &rate in rates {
+            lens.extend([rate - 1, rate, rate + 1, 2 * rate - 1, 2 * rate, 2 * rate + 1]);
+        }
+        lens.sort_unstable();
+        lens.dedup();
+        lens
+    }
+
+    fn check_hash_boundaries<H: Cyclist + Default>(rate: usize) {
+        for len in boundary_lengths(16, &[rate]) {
+            let input = (0..=255u8).cycle().take(len).collect::<Vec<u8>>();
+
+            let mut one = H::default();
+            one.absorb(&input);
+            let one = one.squeeze(len);
+
+            // Operations can only be extended after a whole number of blocks.
+            let split = len - len % rate;
+            if split == 0 {
+                continue;
+            }
+
+            let mut two = H::default();
+            two.absorb(&input[..split]);
+            two.absorb_more(&input[split..]);
+            let mut two_out = two.squeeze(split);
+            two_out.extend(two.squeeze_more(len - split));
+
+            assert_eq!(one, two_out, "len={}", len);
+        }
+    }
+
+    fn check_keyed_boundaries<K: Keyed>(rates: &[usize]) {
+        for len in boundary_lengths(K::TAG_LEN, rates) {
+            let m = (0..=255u8).cycle().take(len).collect::<Vec<u8>>();
+
+            // Sealing must be equivalent to encrypting and then squeezing a tag.
+            let mut c = m.clone();
+            c.resize(len + K::TAG_LEN, 0);
+            K::new(b"ok then", b"", b"").seal_mut(&mut c);
+
+            let mut d = K::new(b"ok then", b"", b"");
+            let mut c_p = m.clone();
+            d.encrypt_mut(&mut c_p);
+            c_p.extend(d.squeeze(K::TAG_LEN));
+            assert_eq!(c, c_p, "len={}", len);
+
+            // Decryption must invert encryption.
+            let mut p = c.clone();
+            K::new(b"ok then", b"", b"").decrypt_mut(&mut p[..len]);
+            assert_eq!(m, p[..len], "len={}", len);
+
+            // Opening must authenticate the sealed message.
+            let mut p = c.clone();
+            assert!(K::new(b"ok then", b"", b"").open_mut(&mut p).is_ok(), "len={}", len);
+            assert_eq!(m, p[..len], "len={}", len);
+
+            // Modifying any byte must cause opening to fail.
+            for i in [0, len / 2, len, len + K::TAG_LEN - 1] {
+                let mut p = c.clone();
+                p[i] ^= 1;
+                assert!(
+                    K::new(b"ok then", b"", b"").open_mut(&mut p).is_err(),
+                    "len={} i={}",
+                    len,
+                    i
+                );
+            }
+
+            // Inputs too short to contain a tag must never be authentic.
+            if len < K::TAG_LEN {
+                assert!(
+                    K::new(b"ok then", b"", b"").open_mut(&mut m.clone()).is_err(),
+                    "len={}",
+                    len
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn hash_boundaries() {
+        check_hash_boundaries::<XoodyakHash>(XoodyakHash::absorb_rate());
+        #[cfg(feature = "keccyak")]
+        {
+            use crate::keccyak::*;
+            check_hash_boundaries::<KeccyakMaxHash>(KeccyakMaxHash::absorb_rate());
+            check_hash_boundaries::<Keccyak256Hash>(Keccyak256Hash::absorb_rate());
+            check_hash_boundaries::<Keccyak128Hash>(Keccyak128Hash::absorb_rate());
+            check_hash_boundaries::<KeccyakMinHash>(KeccyakMinHash::absorb_rate());
+            check_hash_boundaries::<Keccyak800Hash>(Keccyak800Hash::absorb_rate());
+            check_hash_boundaries::<Keccyak400Hash>(Keccyak400Hash::absorb_rate());
+            check_hash_boundaries::<Keccyak200Hash>(Keccyak200Hash::absorb_rate());
+        }
+        #[cfg(feature = "ascon")]
+        {
+            use crate::ascon::*;
+            check_hash_boundaries::<AsconCyclistHash>(AsconCyclistHash::absorb_rate());
+        }
+    }
+
+    #[test]
+    fn keyed_boundaries() {
+        check_keyed_boundaries::<XoodyakKeyed>(&[
+            XoodyakKeyed::absorb_rate(),
+            XoodyakKeyed::squeeze_rate(),
+        ]);
+        #[cfg(feature = "keccyak")]
+        {
+            use crate::keccyak::*;
+            check_keyed_boundaries::<KeccyakMaxKeyed>(&[
+                KeccyakMaxKeyed::absorb_rate(),
+                KeccyakMaxKeyed::squeeze_rate(),
+            ]);
+            check_keyed_boundaries::<Keccyak256Keyed>(&[
+                Keccyak256Keyed::absorb_rate(),
+                Keccyak256Keyed::squeeze_rate(),
+            ]);
+            check_keyed_boundaries::<Keccyak128Keyed>(&[
+                Keccyak128Keyed::absorb_rate(),
+                Keccyak128Keyed::squeeze_rate(),
+            ]);
+            check_keyed_boundaries::<KeccyakMinKeyed>(&[
+                KeccyakMinKeyed::absorb_rate(),
+                KeccyakMinKeyed::squeeze_rate(),
+            ]);
+            check_keyed_boundaries::<Keccyak800Keyed>(&[
+                Keccyak800Keyed::absorb_rate(),
+                Keccyak800Keyed::squeeze_rate(),
+            ]);
+            check_keyed_boundaries::<Keccyak400Keyed>(&[
+                Keccyak400Keyed::absorb_rate(),
+                Keccyak400Keyed::squeeze_rate(),
+            ]);
+            check_keyed_boundaries::<Keccyak200Keyed>(&[
+                Keccyak200Keyed::absorb_rate(),
+                Keccyak200Keyed::squeeze_rate(),
+            ]);
+        }
+        #[cfg(feature = "ascon")]
+        {
+            use crate::ascon::*;
+            check_keyed_boundaries::<AsconCyclistKeyed>(&[
+                AsconCyclistKeyed::absorb_rate(),
+                AsconCyclistKeyed::squeeze_rate(),
+            ]);
+        }
+    }
+
+    #[test]
+    fn keystream() {
+        let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+        let mut e = d.clone();
+        let mut keystream = [0xCAu8; 100];
+        d.keystream(&mut keystream);
+        assert_eq!(e.encrypt(&[0u8; 100]), keystream);
+        assert_eq!(d.squeeze(16), e.squeeze(16));
+
+        // XORing the keystream externally is equivalent to decrypting, but leaves a different state.
+        let c = XoodyakKeyed::new(b"ok then", b"", b"").encrypt(b"it's a deal");
+        let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+        let mut p = [0u8; 11];
+        Keyed::keystream(&mut d, &mut p);
+        p.iter_mut().zip(&c).for_each(|(p, c)| *p ^= c);
+        assert_eq!(b"it's a deal", &p);
+    }
+
+    #[test]
+    fn many_operations() {
+        fn check<C: Cyclist + Clone>(c: &C) {
+            for n in [0u8, 1, 3, 4, 5, 9] {
+                for len in [0, 1, 16, 44, 100, 168, 400] {
+                    for mixed in [false, true] {
+                        // Prepare distinct objects, all in the same phase unless mixed.
+                        let mut objects = (0..n)
+                            .map(|i| {
+                                let mut object = c.clone();
+                                object.absorb(&[i]);
+                                if mixed && i % 2 == 0 {
+                                    object.squeeze(1);
+                                }
+                                object
+                            })
+                            .collect::<Vec<_>>();
+                        let mut expected = objects.clone();
+
+                        // Use uneven lengths when mixed, which can't be done in lockstep.
+                        let bins = (0..n)
+                            .map(|i| vec![i; if mixed { len + usize::from(i) } else { len }])
+                            .collect::<Vec<_>>();
+                        let bins = bins.iter().map(Vec::as_slice).collect::<Vec<_>>();
+                        C::absorb_many(&mut objects, &bins);
+                        for (object, bin) in expected.iter_mut().zip(&bins) {
+                            object.absorb(bin);
+                        }
+
+                        let mut outs = vec![vec![0u8; len]; usize::from(n)];
+                        C::squeeze_many(
+                            &mut objects,
+                            &mut outs.iter_mut().map(Vec::as_mut_slice).collect::<Vec<_>>(),
+                        );
+                        for (object, out) in expected.iter_mut().zip(&outs) {
+                            assert_eq!(&object.squeeze(len), out, "n={n} len={len}");
+                        }
+
+                        // The objects' states must be identical afterwards, too.
+                        for (object, expected) in objects.iter_mut().zip(expected.iter_mut()) {
+                            object.absorb(b"more");
+                            expected.absorb(b"more");
+                            assert_eq!(expected.squeeze(16), object.squeeze(16));
+                        }
+                    }
+                }
+            }
+        }
+
+        check(&XoodyakHash::default());
+        check(&XoodyakKeyed::new(b"ok then", b"", b""));
+        #[cfg(feature = "keccyak")]
+        {
+            check(&keccyak::Keccyak128Hash::default());
+            check(&keccyak::Keccyak128Keyed::new(b"ok then", b"", b""));
+        }
+    }
+
+    #[test]
+    fn short_bulk_inputs() {
+        let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+        assert_eq!(Err(Error::BufferTooSmall), d.bulk_open_mut(&mut [0u8; 15], 64));
+        assert_eq!(Err(Error::BufferTooSmall), d.open(&[0u8; 15]));
+    }
 }
@@ -0,0 +1,452 @@
+//! Secure framing for byte-oriented links between microcontrollers (e.g. UART or SPI).
+//!
+//! A [`Sender`] seals each payload into a frame with a short authentication tag and a rolling
+//! counter, then byte-stuffs it with [COBS][cobs] so that it contains no zero bytes and can be
+//! delimited by a single `0x00`. A [`Receiver`] unstuffs and opens frames in place, and a
+//! [`FrameBuffer`] collects frames from a link a byte at a time. Nothing is allocated; the memory
+//! required is fixed by the largest payload.
+//!
+//! [cobs]: https://en.wikipedia.org/wiki/Consistent_Overhead_Byte_Stuffing
+//!
+//! ```rust
+//! use cyclist::link::{max_frame_len, FrameBuffer, Receiver, Sender};
+//! use cyclist::xoodyak::XoodyakKeyed;
+//! use cyclist::Keyed;
+//!
+//! let key = XoodyakKeyed::new(b"a shared link key", b"", b"");
+//! let mut sender = Sender::<_, 4>::new(key.clone());
+//! let mut receiver = Receiver::<_, 4>::new(key);
+//!
+//! // Seal a frame into a fixed-size buffer and write it to the link.
+//! let mut frame = [0u8; max_frame_len(32, 4)];
+//! let n = sender.seal_frame(b"temperature: 21C", &mut frame);
+//!
+//! // Read bytes from the link until a whole frame has arrived, then open it.
+//! let mut buffer = FrameBuffer::<{ max_frame_len(32, 4) }>::new();
+//! for &b in &frame[..n] {
+//!     if let Some(frame) = buffer.push(b) {
+//!         let payload = receiver.open_frame(frame.expect("should fit")).expect("should open");
+//!         assert_eq!(b"temperature: 21C", payload);
+//!     }
+//! }
+//! ```
+//!
+//! # Construction
+//!
+//! Both ends of a link share a keyed duplex and a 64-bit frame counter, which starts at zero. To
+//! seal a frame, a copy of the duplex absorbs a domain separation label and the counter as a
+//! little-endian 64-bit integer, encrypts the payload, and squeezes a `TAG_LEN`-byte tag. The frame
+//! consists of the counter's least significant byte, the ciphertext, and the tag, stuffed with COBS
+//! and followed by a zero byte.
+//!
+//! A receiver reconstructs the counter as the smallest value not less than the counter of the frame
+//! after the last one it opened which has the same least significant byte. This allows up to 255
+//! consecutive frames to be lost without desynchronizing the link, while rejecting replayed and
+//! reordered frames. Tags of 4 to 8 bytes are supported, trading per-frame overhead for forgery
+//! resistance: with a 4-byte tag, an attacker who can inject frames has a 2<sup>-32</sup> chance
+//! of a forgery being accepted.
+
+use core::fmt;
+
+use constant_time_eq::constant_time_eq;
+
+use crate::{wipe, Keyed};
+
+/// The maximum length of an authentication tag, in bytes.
+pub const MAX_TAG_LEN: usize = 8;
+
+/// The minimum length of an authentication tag, in bytes.
+pub const MIN_TAG_LEN: usize = 4;
+
+/// The domain separation label for frames.
+const LINK_LABEL: &[u8] = b"cyclist.link";
+
+/// Returns the maximum length of a stuffed and delimited frame holding `payload_len` bytes of
+/// payload with a `tag_len`-byte tag.
+pub const fn max_frame_len(payload_len: usize, tag_len: usize) -> usize {
+    let raw_len = 1 + payload_len + tag_len;
+    raw_len + raw_len / 254 + 2
+}
+
+/// An error receiving a frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The frame was longer than the [`FrameBuffer`] could hold.
+    Overflow,
+
+    /// The frame was not validly stuffed, or was too short to contain a counter and a tag.
+    Malformed,
+
+    /// The frame could not be authenticated, indicating it was modified, replayed, or sent with a
+    /// different key, or that too many frames were lost.
+    Inauthentic,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Overflow => write!(f, "frame overflow"),
+            Error::Malformed => write!(f, "malformed frame"),
+            Error::Inauthentic => write!(f, "inauthentic frame"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// The sending end of a link.
+#[derive(Clone, Debug)]
+pub struct Sender<K, const TAG_LEN: usize> {
+    keyed: K,
+    counter: u64,
+}
+
+impl<K, const TAG_LEN: usize> Sender<K, TAG_LEN>
+where
+    K: Keyed,
+{
+    /// Creates a new [`Sender`] with the given keyed duplex, which must be in the same state as the
+    /// receiver's.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `TAG_LEN` is less than [`MIN_TAG_LEN`] or greater than [`MAX_TAG_LEN`].
+    pub fn new(keyed: K) -> Self {
+        assert!((MIN_TAG_LEN..=MAX_TAG_LEN).contains(&TAG_LEN), "invalid tag length");
+        Sender { keyed, counter: 0 }
+    }
+
+    /// Seals the given payload into a stuffed frame, including its trailing zero byte, at the start
+    /// of `out`. Returns the length of the frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` is shorter than [`max_frame_len`] of the payload's length.
+    pub fn seal_frame(&mut self, payload: &[u8], out: &mut [u8]) -> usize {
+        let raw_len = 1 + payload.len() + TAG_LEN;
+        let frame_len = max_frame_len(payload.len(), TAG_LEN);
+        assert!(out.len() >= frame_len, "output buffer too short");
+
+        // Write the unstuffed frame at the end of the buffer so it can be stuffed in place.
+        let start = frame_len - 1 - raw_len;
+        let raw = &mut out[start..start + raw_len];
+        let (counter, rest) = raw.split_at_mut(1);
+        let (ciphertext, tag) = rest.split_at_mut(payload.len());
+        counter[0] = self.counter.to_le_bytes()[0];
+        ciphertext.copy_from_slice(payload);
+
+        let mut keyed = frame_keyed(&self.keyed, self.counter);
+        keyed.encrypt_mut(ciphertext);
+        keyed.squeeze_mut(tag);
+        self.counter += 1;
+
+        let n = stuff(&mut out[..frame_len - 1], start);
+        out[n] = 0;
+        n + 1
+    }
+}
+
+/// The receiving end of a link.
+#[derive(Clone, Debug)]
+pub struct Receiver<K, const TAG_LEN: usize> {
+    keyed: K,
+    counter: u64,
+}
+
+impl<K, const TAG_LEN: usize> Receiver<K, TAG_LEN>
+where
+    K: Keyed,
+{
+    /// Creates a new [`Receiver`] with the given keyed duplex, which must be in the same state as
+    /// the sender's.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `TAG_LEN` is less than [`MIN_TAG_LEN`] or greater than [`MAX_TAG_LEN`].
+    pub fn new(keyed: K) -> Self {
+        assert!((MIN_TAG_LEN..=MAX_TAG_LEN).contains(&TAG_LEN), "invalid tag length");
+        Receiver { keyed, counter: 0 }
+    }
+
+    /// Unstuffs and opens the given frame in place, with or without its trailing zero byte, and
+    /// returns its payload. If the frame is not authentic, its contents are wiped and the receiver's
+    /// state is unmodified.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Malformed`] if the frame isn't validly stuffed or is too short, and
+    /// [`Error::Inauthentic`] if it cannot be authenticated.
+    pub fn open_frame<'a>(&mut self, frame: &'a mut [u8]) -> Result<&'a [u8], Error> {
+        let frame = match frame {
+            [frame @ .., 0] => frame,
+            frame => frame,
+        };
+        let raw_len = unstuff(frame).ok_or(Error::Malformed)?;
+        if raw_len < 1 + TAG_LEN {
+            return Err(Error::Malformed);
+        }
+
+        let raw = &mut frame[..raw_len];
+        let (counter, rest) = raw.split_at_mut(1);
+        let (ciphertext, tag) = rest.split_at_mut(raw_len - 1 - TAG_LEN);
+
+        // Advance to the next counter value with the frame's least significant byte.
+        let skipped = counter[0].wrapping_sub(self.counter.to_le_bytes()[0]);
+        let counter = self.counter + u64::from(skipped);
+
+        let mut keyed = frame_keyed(&self.keyed, counter);
+        keyed.decrypt_mut(ciphertext);
+        let mut tag_p = [0u8; MAX_TAG_LEN];
+        keyed.squeeze_mut(&mut tag_p[..TAG_LEN]);
+        let authentic = constant_time_eq(tag, &tag_p[..TAG_LEN]);
+        wipe(&mut tag_p);
+
+        if !authentic {
+            wipe(ciphertext);
+            return Err(Error::Inauthentic);
+        }
+
+        self.counter = counter + 1;
+        Ok(ciphertext)
+    }
+}
+
+/// A fixed-size buffer which collects stuffed frames from a link a byte at a time.
+#[derive(Clone, Debug)]
+pub struct FrameBuffer<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+    overflowed: bool,
+}
+
+impl<const N: usize> FrameBuffer<N> {
+    /// Creates a new, empty [`FrameBuffer`]. To hold frames of up to `payload_len` bytes of payload,
+    /// `N` should be at least [`max_frame_len`] of `payload_len`.
+    pub const fn new() -> Self {
+        FrameBuffer { buf: [0u8; N], len: 0, overflowed: false }
+    }
+
+    /// Adds a byte received from the link to the buffer. If the byte ends a frame, returns the
+    /// frame, ready to be passed to [`Receiver::open_frame`]. Empty frames are ignored, so a link
+    /// can be resynchronized by sending a zero byte.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Overflow`] at the end of a frame which didn't fit in the buffer.
+    pub fn push(&mut self, byte: u8) -> Option<Result<&mut [u8], Error>> {
+        if byte != 0 {
+            match self.buf.get_mut(self.len) {
+                Some(b) => {
+                    *b = byte;
+                    self.len += 1;
+                }
+                None => self.overflowed = true,
+            }
+            return None;
+        }
+
+        let len = core::mem::take(&mut self.len);
+        if core::mem::take(&mut self.overflowed) {
+            return Some(Err(Error::Overflow));
+        }
+        if len == 0 {
+            return None;
+        }
+        Some(Ok(&mut self.buf[..len]))
+    }
+}
+
+impl<const N: usize> Default for FrameBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns a copy of the keyed duplex which has absorbed the label and the given frame counter.
+fn frame_keyed<K: Keyed>(keyed: &K, counter: u64) -> K {
+    let mut keyed = keyed.clone();
+    keyed.absorb(LINK_LABEL);
+    keyed.absorb(&counter.to_le_bytes());
+    keyed
+}
+
+/// Stuffs `buf[start..]` with COBS into the start of `buf`, returning the stuffed length. To leave
+/// room for the overhead, `start` must be greater than `(buf.len() - start) / 254`.
+fn stuff(buf: &mut [u8], start: usize) -> usize {
+    debug_assert!(start > (buf.len() - start) / 254);
+
+    // The output never overtakes the input, so each byte can be read before it's overwritten.
+    let (mut code_pos, mut out_pos, mut code) = (0, 1, 1u8);
+    for i in start..buf.len() {
+        let b = buf[i];
+        if b == 0 {
+            buf[code_pos] = code;
+            (code_pos, out_pos, code) = (out_pos, out_pos + 1, 1);
+        } else {
+            buf[out_pos] = b;
+            out_pos += 1;
+            code += 1;
+            if code == 0xFF {
+                buf[code_pos] = code;
+                (code_pos, out_pos, code) = (out_pos, out_pos + 1, 1);
+            }
+        }
+    }
+    buf[code_pos] = code;
+    out_pos
+}
+
+/// Unstuffs the given COBS-encoded slice in place, returning the unstuffed length, or `None` if the
+/// slice isn't validly stuffed.
+fn unstuff(buf: &mut [u8]) -> Option<usize> {
+    let (mut read, mut write) = (0, 0);
+    while read < buf.len() {
+        let n = usize::from(buf[read]).checked_sub(1)?;
+        let block = read + 1..read + 1 + n;
+        if block.end > buf.len() || buf[block.clone()].contains(&0) {
+            return None;
+        }
+        buf.copy_within(block.clone(), write);
+        write += n;
+        read = block.end;
+
+        // Blocks of fewer than 254 bytes are followed by a zero, unless they're the last block.
+        if n < 0xFE && read < buf.len() {
+            buf[write] = 0;
+            write += 1;
+        }
+    }
+    Some(write)
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakKeyed;
+
+    use super::*;
+
+    const KEY: &[u8] = b"a shared link key";
+
+    fn link() -> (Sender<XoodyakKeyed, 4>, Receiver<XoodyakKeyed, 4>) {
+        let keyed = XoodyakKeyed::new(KEY, b"", b"");
+        (Sender::new(keyed.clone()), Receiver::new(keyed))
+    }
+
+    #[test]
+    fn stuffing() {
+        for (raw, stuffed) in [
+            (&[0x00][..], &[0x01, 0x01][..]),
+            (&[0x00, 0x00], &[0x01, 0x01, 0x01]),
+            (&[0x11, 0x22, 0x00, 0x33], &[0x03, 0x11, 0x22, 0x02, 0x33]),
+            (&[0x11, 0x00, 0x00, 0x00], &[0x02, 0x11, 0x01, 0x01, 0x01]),
+        ] {
+            let start = 1 + raw.len() / 254;
+            let mut buf = [0u8; 8];
+            buf[start..start + raw.len()].copy_from_slice(raw);
+            let n = stuff(&mut buf[..start + raw.len()], start);
+            assert_eq!(stuffed, &buf[..n]);
+            assert_eq!(Some(raw.len()), unstuff(&mut buf[..n]));
+            assert_eq!(raw, &buf[..raw.len()]);
+        }
+
+        for len in [253, 254, 255, 600] {
+            let raw = (0..len).map(|i| u8::try_from(i % 255).expect("ok") + 1).collect::<Vec<_>>();
+            let start = 1 + len / 254;
+            let mut buf = vec![0u8; start + len];
+            buf[start..].copy_from_slice(&raw);
+            let n = stuff(&mut buf, start);
+            assert!(!buf[..n].contains(&0));
+            assert_eq!(Some(len), unstuff(&mut buf[..n]));
+            assert_eq!(raw, &buf[..len]);
+        }
+
+        assert_eq!(None, unstuff(&mut [0x03, 0x11]));
+        assert_eq!(None, unstuff(&mut [0x03, 0x11, 0x00]));
+        assert_eq!(None, unstuff(&mut [0x00]));
+    }
+
+    #[test]
+    fn round_trip() {
+        let (mut sender, mut receiver) = link();
+        for payload in [&b""[..], b"ok", &[0u8; 300]] {
+            let mut frame = vec![0xFFu8; max_frame_len(payload.len(), 4)];
+            let n = sender.seal_frame(payload, &mut frame);
+            assert_eq!(Some(&0), frame[..n].last());
+            assert!(!frame[..n - 1].contains(&0));
+            assert_eq!(Ok(payload), receiver.open_frame(&mut frame[..n]));
+        }
+    }
+
+    #[test]
+    fn lost_frames() {
+        let (mut sender, mut receiver) = link();
+        let mut frame = [0u8; max_frame_len(2, 4)];
+        for i in 0..300 {
+            let n = sender.seal_frame(b"ok", &mut frame);
+            if i % 100 == 99 {
+                assert_eq!(Ok(&b"ok"[..]), receiver.open_frame(&mut frame[..n]), "i={}", i);
+            }
+        }
+
+        // Losing 256 frames in a row desynchronizes the link.
+        for _ in 0..256 {
+            sender.seal_frame(b"ok", &mut frame);
+        }
+        let n = sender.seal_frame(b"ok", &mut frame);
+        assert_eq!(Err(Error::Inauthentic), receiver.open_frame(&mut frame[..n]));
+    }
+
+    #[test]
+    fn replayed_frames() {
+        let (mut sender, mut receiver) = link();
+        let mut frame = [0u8; max_frame_len(2, 4)];
+        let n = sender.seal_frame(b"ok", &mut frame);
+        let mut replay = frame;
+        assert_eq!(Ok(&b"ok"[..]), receiver.open_frame(&mut frame[..n]));
+        assert_eq!(Err(Error::Inauthentic), receiver.open_frame(&mut replay[..n]));
+
+        // Failures don't affect the receiver's state.
+        let n = sender.seal_frame(b"ok", &mut frame);
+        assert_eq!(Ok(&b"ok"[..]), receiver.open_frame(&mut frame[..n]));
+    }
+
+    #[test]
+    fn modified_frames() {
+        let (mut sender, mut receiver) = link();
+        let mut frame = [0u8; max_frame_len(8, 4)];
+        let n = sender.seal_frame(b"payload!", &mut frame);
+        frame[3] ^= 0x01;
+        assert_eq!(Err(Error::Inauthentic), receiver.open_frame(&mut frame[..n]));
+        assert_eq!(Err(Error::Malformed), receiver.open_frame(&mut [0x03, 0x11]));
+        assert_eq!(Err(Error::Malformed), receiver.open_frame(&mut [0x03, 0x11, 0x22]));
+    }
+
+    #[test]
+    fn frame_buffers() {
+        let (mut sender, mut receiver) = link();
+        let mut buffer = FrameBuffer::<{ max_frame_len(4, 4) }>::new();
+
+        let mut frame = [0u8; max_frame_len(7, 4)];
+        let mut stream = vec![0u8];
+        for payload in [&b"one"[..], b"seven!!", b"two"] {
+            let n = sender.seal_frame(payload, &mut frame);
+            stream.extend(&frame[..n]);
+        }
+
+        let mut results = Vec::new();
+        for b in stream {
+            if let Some(frame) = buffer.push(b) {
+                results.push(frame.and_then(|f| receiver.open_frame(f).map(<[u8]>::to_vec)));
+            }
+        }
+        assert_eq!(vec![Ok(b"one".to_vec()), Err(Error::Overflow), Ok(b"two".to_vec())], results);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid tag length")]
+    fn short_tags() {
+        Sender::<_, 2>::new(XoodyakKeyed::new(KEY, b"", b""));
+    }
+}
@@ -0,0 +1,197 @@
+#![cfg(feature = "xoodyak")]
+
+//! Entry points for Xoodyak in the shape of the NIST Lightweight Cryptography (LWC) API.
+//!
+//! The LWC competition's reference implementations, and the SUPERCOP harnesses which drive them,
+//! expose Xoodyak through `crypto_aead_encrypt`, `crypto_aead_decrypt`, and `crypto_hash`. The
+//! functions here take the same inputs in the same order, as slices rather than pointers and
+//! lengths, and produce byte-for-byte identical outputs, so the crate can be checked against the
+//! LWC known-answer test files or dropped into an existing harness:
+//!
+//! ```rust
+//! use cyclist::lwc::{self, CRYPTO_ABYTES};
+//!
+//! let key = [0u8; lwc::CRYPTO_KEYBYTES];
+//! let npub = [1u8; lwc::CRYPTO_NPUBBYTES];
+//!
+//! let mut c = [0u8; 18 + CRYPTO_ABYTES];
+//! let clen = lwc::crypto_aead_encrypt(&mut c, b"this is a message!", b"ad", &npub, &key)
+//!     .expect("buffer should be large enough");
+//!
+//! let mut m = [0u8; 18];
+//! let mlen = lwc::crypto_aead_decrypt(&mut m, &c[..clen], b"ad", &npub, &key)
+//!     .expect("ciphertext should be authentic");
+//! assert_eq!(b"this is a message!", &m[..mlen]);
+//! ```
+//!
+//! The secret message number (`nsec`) of the LWC API is unused by Xoodyak and is omitted.
+//!
+//! # Construction
+//!
+//! As in the round 3 reference implementation, encryption initializes a [`XoodyakKeyed`] with the
+//! key as the key and the public nonce as the key ID, absorbs the associated data, encrypts the
+//! message, and squeezes a [`CRYPTO_ABYTES`]-byte tag, which follows the ciphertext. Hashing
+//! absorbs the message with a [`XoodyakHash`] and squeezes a [`CRYPTO_BYTES`]-byte digest.
+
+use crate::xoodyak::{XoodyakHash, XoodyakKeyed};
+use crate::{Cyclist, Error, Tag};
+
+/// The length of a key in bytes.
+pub const CRYPTO_KEYBYTES: usize = 16;
+
+/// The length of a public nonce in bytes.
+pub const CRYPTO_NPUBBYTES: usize = 16;
+
+/// The number of bytes a ciphertext is longer than its message (i.e. the length of the tag).
+pub const CRYPTO_ABYTES: usize = 16;
+
+/// The length of a digest in bytes.
+pub const CRYPTO_BYTES: usize = 32;
+
+/// Encrypts and authenticates the message `m` and associated data `ad` with the public nonce
+/// `npub` and key `k`, writing the ciphertext followed by the tag to the start of `c` and
+/// returning their combined length.
+///
+/// # Errors
+///
+/// Returns [`Error::BufferTooSmall`] if `c` is shorter than `m.len() + CRYPTO_ABYTES` bytes.
+pub fn crypto_aead_encrypt(
+    c: &mut [u8],
+    m: &[u8],
+    ad: &[u8],
+    npub: &[u8; CRYPTO_NPUBBYTES],
+    k: &[u8; CRYPTO_KEYBYTES],
+) -> Result<usize, Error> {
+    let clen = m.len() + CRYPTO_ABYTES;
+    let c = c.get_mut(..clen).ok_or(Error::BufferTooSmall)?;
+    let (ciphertext, tag) = c.split_at_mut(m.len());
+    ciphertext.copy_from_slice(m);
+    tag.copy_from_slice(keyed(npub, ad, k).seal_detached(ciphertext).as_ref());
+    Ok(clen)
+}
+
+/// Decrypts and verifies the ciphertext and tag `c` and associated data `ad` with the public
+/// nonce `npub` and key `k`, writing the message to the start of `m` and returning its length.
+///
+/// # Errors
+///
+/// Returns [`Error::BufferTooSmall`] if `c` is shorter than `CRYPTO_ABYTES` bytes or `m` is shorter
+/// than `c.len() - CRYPTO_ABYTES` bytes, and [`Error::InvalidTag`] if the ciphertext could not be
+/// authenticated, in which case the message bytes of `m` are zeroed.
+pub fn crypto_aead_decrypt(
+    m: &mut [u8],
+    c: &[u8],
+    ad: &[u8],
+    npub: &[u8; CRYPTO_NPUBBYTES],
+    k: &[u8; CRYPTO_KEYBYTES],
+) -> Result<usize, Error> {
+    let mlen = c.len().checked_sub(CRYPTO_ABYTES).ok_or(Error::BufferTooSmall)?;
+    let m = m.get_mut(..mlen).ok_or(Error::BufferTooSmall)?;
+    let (ciphertext, tag) = c.split_at(mlen);
+    let tag = Tag::try_from(tag).map_err(|_| Error::BufferTooSmall)?;
+    m.copy_from_slice(ciphertext);
+    keyed(npub, ad, k).open_detached(m, &tag)?;
+    Ok(mlen)
+}
+
+/// Hashes the message `m`, writing the digest to `out`.
+pub fn crypto_hash(out: &mut [u8; CRYPTO_BYTES], m: &[u8]) {
+    let mut hash = XoodyakHash::default();
+    hash.absorb(m);
+    hash.squeeze_mut(out);
+}
+
+/// Returns a keyed duplex for the public nonce which has absorbed the associated data.
+fn keyed(npub: &[u8; CRYPTO_NPUBBYTES], ad: &[u8], k: &[u8; CRYPTO_KEYBYTES]) -> XoodyakKeyed {
+    let mut keyed = XoodyakKeyed::new(k, npub, b"");
+    keyed.absorb(ad);
+    keyed
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; CRYPTO_KEYBYTES] = [0x0F; CRYPTO_KEYBYTES];
+    const NPUB: [u8; CRYPTO_NPUBBYTES] = [0xF0; CRYPTO_NPUBBYTES];
+
+    #[test]
+    fn matches_the_reference_sequence() {
+        let mut c = [0u8; 5 + CRYPTO_ABYTES];
+        assert_eq!(Ok(c.len()), crypto_aead_encrypt(&mut c, b"hello", b"ad", &NPUB, &KEY));
+
+        let mut keyed = XoodyakKeyed::new(&KEY, &NPUB, b"");
+        keyed.absorb(b"ad");
+        assert_eq!(keyed.seal(b"hello"), c);
+
+        let mut digest = [0u8; CRYPTO_BYTES];
+        crypto_hash(&mut digest, b"hello");
+        let mut hash = XoodyakHash::default();
+        hash.absorb(b"hello");
+        assert_eq!(hash.squeeze(CRYPTO_BYTES), digest);
+    }
+
+    #[test]
+    fn supercop_aead_round_3_test_vector() {
+        // from https://github.com/XKCP/XKCP/blob/2a8d2311a830ab3037f8c7ef2511e5c7cc032127/tests/SUPERCOP/Xoodyak_aead_round3/selftest.c
+        let key = [
+            0x5a, 0x4b, 0x3c, 0x2d, 0x1e, 0x0f, 0x00, 0xf1, 0xe2, 0xd3, 0xc4, 0xb5, 0xa6, 0x97,
+            0x88, 0x79,
+        ];
+        let npub = [
+            0x6b, 0x4c, 0x2d, 0x0e, 0xef, 0xd0, 0xb1, 0x92, 0x72, 0x53, 0x34, 0x15, 0xf6, 0xd7,
+            0xb8, 0x99,
+        ];
+        let ad = [0x32, 0xf3, 0xb4, 0x75, 0x35, 0xf6];
+        let m = [0xe4, 0x65, 0xe5, 0x66, 0xe6, 0x67, 0xe7];
+        let expected = [
+            0x6e, 0x68, 0x08, 0x1c, 0x7e, 0xac, 0xbf, 0x72, 0xe2, 0xa6, 0x77, 0xa6, 0x0e, 0x44,
+            0x27, 0x48, 0xd7, 0xa8, 0x6e, 0x78, 0x8e, 0xb9, 0xd4,
+        ];
+
+        let mut c = [0u8; 7 + CRYPTO_ABYTES];
+        assert_eq!(Ok(c.len()), crypto_aead_encrypt(&mut c, &m, &ad, &npub, &key));
+        assert_eq!(expected, c);
+
+        let mut m_p = [0u8; 7];
+        assert_eq!(Ok(m.len()), crypto_aead_decrypt(&mut m_p, &c, &ad, &npub, &key));
+        assert_eq!(m, m_p);
+    }
+
+    #[test]
+    fn round_trip() {
+        for len in [0, 1, 23, 24, 25, 100] {
+            let msg = vec![0xCA; len];
+            let mut c = vec![0u8; len + CRYPTO_ABYTES + 3];
+            let clen = crypto_aead_encrypt(&mut c, &msg, b"ad", &NPUB, &KEY).expect("encrypt");
+            assert_eq!(len + CRYPTO_ABYTES, clen);
+
+            let mut m = vec![0u8; len + 3];
+            let mlen =
+                crypto_aead_decrypt(&mut m, &c[..clen], b"ad", &NPUB, &KEY).expect("decrypt");
+            assert_eq!(msg, m[..mlen]);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let mut c = [0u8; 4 + CRYPTO_ABYTES];
+        assert_eq!(
+            Err(Error::BufferTooSmall),
+            crypto_aead_encrypt(&mut c[..19], b"abcd", b"", &NPUB, &KEY)
+        );
+        crypto_aead_encrypt(&mut c, b"abcd", b"", &NPUB, &KEY).expect("encrypt");
+
+        let mut m = [0xFFu8; 4];
+        assert_eq!(
+            Err(Error::BufferTooSmall),
+            crypto_aead_decrypt(&mut m, &c[..CRYPTO_ABYTES - 1], b"", &NPUB, &KEY)
+        );
+        assert_eq!(
+            Err(Error::BufferTooSmall),
+            crypto_aead_decrypt(&mut m[..3], &c, b"", &NPUB, &KEY)
+        );
+        assert_eq!(Err(Error::InvalidTag), crypto_aead_decrypt(&mut m, &c, b"x", &NPUB, &KEY));
+        assert_eq!([0u8; 4], m);
+    }
+}
@@ -18,5 +18,61 @@ macro_rules! lanes_to_bytes {
     };
 }
 
+/// Implements the byte-oriented methods of [`Permutation`](crate::Permutation) for a permutation
+/// whose state is a tuple struct of `$width / size_of::<$n>()` lanes of type `$n`, which are
+/// encoded as little-endian bytes.
+macro_rules! lane_accessors {
+    ($n:ty, $width:expr) => {
+        #[inline(always)]
+        #[allow(clippy::modulo_one)] // 8-bit lanes
+        fn add_byte(&mut self, byte: u8, offset: usize) {
+            const LEN: usize = core::mem::size_of::<$n>();
+            self.0[offset / LEN] ^= <$n>::from(byte) << (8 * (offset % LEN));
+        }
+
+        #[inline(always)]
+        fn add_bytes(&mut self, bytes: &[u8]) {
+            const LEN: usize = core::mem::size_of::<$n>();
+            let mut chunks = bytes.chunks_exact(LEN);
+            for (lane, chunk) in self.0.iter_mut().zip(&mut chunks) {
+                *lane ^= <$n>::from_le_bytes(chunk.try_into().unwrap());
+            }
+            let offset = bytes.len() - chunks.remainder().len();
+            for (i, &byte) in chunks.remainder().iter().enumerate() {
+                self.add_byte(byte, offset + i);
+            }
+        }
+
+        #[inline(always)]
+        fn extract_bytes(&mut self, out: &mut [u8]) {
+            const LEN: usize = core::mem::size_of::<$n>();
+            for (chunk, lane) in out.chunks_mut(LEN).zip(self.0.iter()) {
+                chunk.copy_from_slice(&lane.to_le_bytes()[..chunk.len()]);
+            }
+        }
+
+        fn to_bytes(&self) -> [u8; $width] {
+            let mut out = [0u8; $width];
+            $crate::macros::lanes_to_bytes!($n, self.0, out);
+            out
+        }
+
+        fn from_bytes(bytes: &[u8; $width]) -> Self {
+            let mut state = Self::default();
+            $crate::macros::bytes_to_lanes!($n, bytes, state.0);
+            state
+        }
+
+        fn wipe(&mut self) {
+            #[cfg(feature = "zeroize")]
+            zeroize::Zeroize::zeroize(&mut self.0);
+
+            #[cfg(not(feature = "zeroize"))]
+            self.0.fill(0);
+        }
+    };
+}
+
 pub(crate) use bytes_to_lanes;
+pub(crate) use lane_accessors;
 pub(crate) use lanes_to_bytes;
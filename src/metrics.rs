@@ -0,0 +1,304 @@
+//! Reporting of non-secret operational metadata about Cyclist sessions.
+//!
+//! Every Cyclist object keeps counts of the permutations, ratchets, seals, opens, and
+//! authentication failures it has performed. These counts, along with the algorithm and its
+//! parameters, can be retrieved as a [`SessionInfo`] and reported to a [`Metrics`] implementation,
+//! allowing operators to monitor cryptographic usage (e.g. for self-test logging) and enforce
+//! rekeying policies:
+//!
+//! ```rust
+//! use cyclist::metrics::SessionInfo;
+//! use cyclist::xoodyak::XoodyakKeyed;
+//!
+//! let mut keyed = XoodyakKeyed::new(b"This is a secret key!", b"", b"");
+//! let ciphertext = keyed.seal(b"This is the plaintext!");
+//!
+//! keyed.report(&|info: &SessionInfo| {
+//!     assert_eq!(info.algorithm, "Xoodoo[12]");
+//!     assert_eq!(info.seals, 1);
+//! });
+//! ```
+//!
+//! No key material, state, or plaintext is ever included.
+//!
+//! With the `metrics` feature enabled, [`Counters`] reports sessions to the
+//! [`metrics`](::metrics) facade, allowing e.g. Prometheus alerts on spikes in authentication
+//! failures which might indicate active tampering.
+
+use crate::{CyclistHash, CyclistKeyed, Permutation};
+
+/// Non-secret metadata about a Cyclist session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SessionInfo {
+    /// The name of the permutation.
+    pub algorithm: &'static str,
+    /// Whether or not the session is in keyed mode.
+    pub keyed: bool,
+    /// The width of the permutation in bytes.
+    pub width: usize,
+    /// The absorb rate in bytes.
+    pub absorb_rate: usize,
+    /// The squeeze rate in bytes.
+    pub squeeze_rate: usize,
+    /// The ratchet rate in bytes, or zero in hash mode.
+    pub ratchet_rate: usize,
+    /// The length of authentication tags in bytes, or zero in hash mode.
+    pub tag_len: usize,
+    /// The number of permutation calls performed.
+    pub permutations: u64,
+    /// The number of ratchets performed.
+    pub ratchets: u64,
+    /// The number of messages sealed.
+    pub seals: u64,
+    /// The number of messages opened, successfully or not.
+    pub opens: u64,
+    /// The number of messages which failed authentication.
+    pub auth_failures: u64,
+}
+
+/// A receiver of session metadata.
+pub trait Metrics {
+    /// Records the metadata of a session.
+    fn record(&self, info: &SessionInfo);
+}
+
+impl<F> Metrics for F
+where
+    F: Fn(&SessionInfo),
+{
+    fn record(&self, info: &SessionInfo) {
+        self(info);
+    }
+}
+
+/// A [`Metrics`] implementation which increments counters via the [`metrics`](::metrics) facade.
+///
+/// Each counter is labeled with the session's `algorithm`:
+///
+/// * `cyclist_permutations_total`: permutation calls
+/// * `cyclist_ratchets_total`: ratchets
+/// * `cyclist_seals_total`: messages sealed
+/// * `cyclist_opens_total`: messages successfully opened
+/// * `cyclist_auth_failures_total`: messages which failed authentication
+///
+/// Because sessions report cumulative counts, each session should be reported exactly once, when it
+/// is no longer in use.
+#[cfg(feature = "metrics")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Counters;
+
+#[cfg(feature = "metrics")]
+impl Metrics for Counters {
+    fn record(&self, info: &SessionInfo) {
+        let algorithm = info.algorithm;
+        ::metrics::counter!("cyclist_permutations_total", info.permutations, "algorithm" => algorithm);
+        ::metrics::counter!("cyclist_ratchets_total", info.ratchets, "algorithm" => algorithm);
+        ::metrics::counter!("cyclist_seals_total", info.seals, "algorithm" => algorithm);
+        ::metrics::counter!(
+            "cyclist_opens_total",
+            info.opens - info.auth_failures,
+            "algorithm" => algorithm
+        );
+        ::metrics::counter!("cyclist_auth_failures_total", info.auth_failures, "algorithm" => algorithm);
+    }
+}
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize> CyclistHash<P, WIDTH, HASH_RATE>
+where
+    P: Permutation<WIDTH>,
+{
+    /// Returns the non-secret metadata of this session.
+    pub const fn session_info(&self) -> SessionInfo {
+        SessionInfo {
+            algorithm: P::NAME,
+            keyed: false,
+            width: WIDTH,
+            absorb_rate: HASH_RATE,
+            squeeze_rate: HASH_RATE,
+            ratchet_rate: 0,
+            tag_len: 0,
+            permutations: self.core.stats.permutations,
+            ratchets: 0,
+            seals: 0,
+            opens: 0,
+            auth_failures: 0,
+        }
+    }
+
+    /// Reports the non-secret metadata of this session to the given [`Metrics`].
+    pub fn report<M>(&self, metrics: &M)
+    where
+        M: Metrics + ?Sized,
+    {
+        metrics.record(&self.session_info());
+    }
+}
+
+impl<
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > CyclistKeyed<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+{
+    /// Returns the non-secret metadata of this session.
+    pub const fn session_info(&self) -> SessionInfo {
+        let stats = &self.core.stats;
+        SessionInfo {
+            algorithm: P::NAME,
+            keyed: true,
+            width: WIDTH,
+            absorb_rate: ABSORB_RATE,
+            squeeze_rate: SQUEEZE_RATE,
+            ratchet_rate: RATCHET_RATE,
+            tag_len: TAG_LEN,
+            permutations: stats.permutations,
+            ratchets: stats.ratchets,
+            seals: stats.seals,
+            opens: stats.opens,
+            auth_failures: stats.failures,
+        }
+    }
+
+    /// Reports the non-secret metadata of this session to the given [`Metrics`].
+    pub fn report<M>(&self, metrics: &M)
+    where
+        M: Metrics + ?Sized,
+    {
+        metrics.record(&self.session_info());
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "xoodyak"))]
+mod tests {
+    use std::cell::RefCell;
+
+    use crate::xoodyak::{XoodyakHash, XoodyakKeyed};
+    use crate::Cyclist;
+
+    use super::*;
+
+    #[test]
+    fn hash_session() {
+        let mut hash = XoodyakHash::default();
+        hash.absorb(&[0u8; 32]);
+        hash.squeeze(16);
+
+        let info = hash.session_info();
+        assert_eq!("Xoodoo[12]", info.algorithm);
+        assert!(!info.keyed);
+        assert_eq!(48, info.width);
+        assert_eq!(16, info.absorb_rate);
+        assert_eq!(2, info.permutations);
+    }
+
+    #[test]
+    fn keyed_session() {
+        let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+        let mut c = d.seal(b"it's a deal");
+        d.ratchet();
+        let before = d.session_info().permutations;
+        d.bulk_seal(&[0u8; 100], 30);
+
+        let info = d.session_info();
+        assert!(info.keyed);
+        assert_eq!(16, info.tag_len);
+        assert_eq!(2, info.ratchets, "bulk mode should ratchet once");
+        assert_eq!(2, info.seals);
+        assert!(info.permutations > before + 4, "segment permutations were not counted");
+
+        c[0] ^= 1;
+        let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+        assert!(d.open_mut(&mut c).is_err());
+        let info = d.session_info();
+        assert_eq!(1, info.opens);
+        assert_eq!(1, info.auth_failures);
+    }
+
+    #[test]
+    fn reporting() {
+        let reports = RefCell::new(Vec::new());
+        let metrics = |info: &SessionInfo| reports.borrow_mut().push(*info);
+
+        let d = XoodyakKeyed::new(b"ok then", b"", b"");
+        d.report(&metrics);
+
+        assert_eq!(vec![d.session_info()], reports.into_inner());
+    }
+
+    #[test]
+    #[cfg(feature = "metrics")]
+    fn counters() {
+        use std::collections::HashMap;
+        use std::sync::{Arc, Mutex};
+
+        use ::metrics::{
+            Counter, CounterFn, Gauge, Histogram, Key, KeyName, Recorder, SharedString, Unit,
+        };
+
+        #[derive(Default)]
+        struct Totals(Mutex<HashMap<String, u64>>);
+
+        struct Handle(Arc<Totals>, String);
+
+        impl CounterFn for Handle {
+            fn increment(&self, value: u64) {
+                *self.0 .0.lock().unwrap().entry(self.1.clone()).or_default() += value;
+            }
+
+            fn absolute(&self, value: u64) {
+                self.0 .0.lock().unwrap().insert(self.1.clone(), value);
+            }
+        }
+
+        struct TestRecorder(Arc<Totals>);
+
+        impl Recorder for TestRecorder {
+            fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+            fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+            fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+            fn register_counter(&self, key: &Key) -> Counter {
+                let label = key.labels().map(|l| l.value().to_string()).collect::<String>();
+                let name = format!("{}{{{}}}", key.name(), label);
+                Counter::from_arc(Arc::new(Handle(self.0.clone(), name)))
+            }
+
+            fn register_gauge(&self, _: &Key) -> Gauge {
+                Gauge::noop()
+            }
+
+            fn register_histogram(&self, _: &Key) -> Histogram {
+                Histogram::noop()
+            }
+        }
+
+        let totals = Arc::new(Totals::default());
+        ::metrics::set_boxed_recorder(Box::new(TestRecorder(totals.clone())))
+            .expect("recorder already set");
+
+        let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+        let mut c = d.seal(b"it's a deal");
+        d.ratchet();
+        d.report(&Counters);
+
+        let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
+        assert!(d.open_mut(&mut c.clone()).is_ok());
+        c[0] ^= 1;
+        assert!(d.open_mut(&mut c).is_err());
+        d.report(&Counters);
+
+        let totals = totals.0.lock().unwrap();
+        assert_eq!(Some(&1), totals.get("cyclist_seals_total{Xoodoo[12]}"));
+        assert_eq!(Some(&1), totals.get("cyclist_ratchets_total{Xoodoo[12]}"));
+        assert_eq!(Some(&1), totals.get("cyclist_opens_total{Xoodoo[12]}"));
+        assert_eq!(Some(&1), totals.get("cyclist_auth_failures_total{Xoodoo[12]}"));
+    }
+}
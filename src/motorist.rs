@@ -0,0 +1,368 @@
+//! A multi-pipe keyed mode, after Keyak's Motorist layer.
+//!
+//! Cyclist's keyed mode is strictly serial: each block of ciphertext depends on the permutation of
+//! the preceding block. A [`Motorist`] session instead runs `PIPES` independent duplexes (pipes)
+//! side by side, striping each message's blocks across them, and permutes all of them at once with
+//! [`Permutation::permute_many`]. With a permutation which evaluates several instances in parallel
+//! (e.g. Keccak-p\[1600\] with the `keccak-multiway` feature enabled), throughput on large
+//! messages scales with the permutation's SIMD width:
+//!
+//! ```rust
+//! use cyclist::keccyak::Keccyak128Motorist;
+//!
+//! let mut sealer = Keccyak128Motorist::new(b"This is a secret key!", b"This is a nonce!");
+//! let mut message = [0xCAu8; 1024 + 16];
+//! sealer.seal_mut(b"This is authenticated data!", &mut message).expect("should seal");
+//!
+//! let mut opener = Keccyak128Motorist::new(b"This is a secret key!", b"This is a nonce!");
+//! assert!(opener.open_mut(b"This is authenticated data!", &mut message).is_ok());
+//! assert_eq!([0xCAu8; 1024], message[..1024]);
+//! ```
+//!
+//! As with [`CyclistKeyed`](crate::CyclistKeyed), a session can seal or open any number of
+//! messages in turn, and each tag authenticates every preceding message in the session. Likewise,
+//! a session which fails to open a message is poisoned: its state is wiped, and every later
+//! operation returns [`Error::Poisoned`]. The output is not interchangeable with that of Keyak or
+//! of the Cyclist keyed mode.
+//!
+//! # Construction
+//!
+//! Every block absorbed by a pipe is padded with a `0x01` byte, and its kind is added to the last
+//! byte of the state before the pipe is permuted.
+//!
+//! 1. Each pipe absorbs a header containing the length of the key, the key, the length of the
+//!    nonce, the nonce, its index, and the number of pipes.
+//! 2. To seal a message, each pipe absorbs the lengths of the associated data and plaintext as
+//!    little-endian 64-bit integers.
+//! 3. The associated data is split into blocks of `RATE` bytes, and block `i` is absorbed by pipe
+//!    `i % PIPES`. All pipes are permuted after each round of `PIPES` blocks.
+//! 4. The plaintext is split and striped in the same way, and each block is encrypted with the
+//!    pipe's state before the plaintext block is absorbed.
+//! 5. Each pipe produces a `TAG_LEN`-byte chain value, and the concatenated chain values are
+//!    absorbed by every pipe. The tag is extracted from the first pipe.
+
+use crate::{ct_eq, wipe, Error, Permutation};
+
+/// The kind of a header block.
+const HEADER: u8 = 0x01;
+
+/// The kind of a block of message lengths.
+const LENGTHS: u8 = 0x02;
+
+/// The kind of a block of associated data.
+const AD: u8 = 0x03;
+
+/// The kind of a block of plaintext.
+const PLAINTEXT: u8 = 0x04;
+
+/// The kind of a block of chain values.
+const CHAIN: u8 = 0x05;
+
+/// A keyed session of `PIPES` parallel duplexes.
+#[derive(Clone, Debug)]
+pub struct Motorist<
+    P,
+    const WIDTH: usize,
+    const RATE: usize,
+    const TAG_LEN: usize,
+    const PIPES: usize,
+> where
+    P: Permutation<WIDTH>,
+{
+    pipes: [P; PIPES],
+    poisoned: bool,
+}
+
+impl<P, const WIDTH: usize, const RATE: usize, const TAG_LEN: usize, const PIPES: usize>
+    Motorist<P, WIDTH, RATE, TAG_LEN, PIPES>
+where
+    P: Permutation<WIDTH>,
+{
+    /// Starts a new session with the given key and nonce.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is empty, or if the key and nonce are too long to fit in a single block
+    /// along with four bytes of framing.
+    pub fn new(key: &[u8], nonce: &[u8]) -> Self {
+        assert!(PIPES > 0, "must have at least one pipe");
+        assert!(RATE < WIDTH - 1, "rate must leave room for framing");
+        assert!(!key.is_empty(), "key must not be empty");
+        assert!(key.len() + nonce.len() + 4 <= RATE, "key and nonce must fit in a single block");
+
+        let mut motorist =
+            Motorist { pipes: core::array::from_fn(|_| P::default()), poisoned: false };
+        let (key_len, nonce_len, pipes) = (to_u8(key.len()), to_u8(nonce.len()), to_u8(PIPES));
+        for (i, pipe) in motorist.pipes.iter_mut().enumerate() {
+            let mut block = [0u8; RATE];
+            let header = [&[key_len][..], key, &[nonce_len], nonce, &[to_u8(i), pipes]];
+            let mut offset = 0;
+            for part in header {
+                block[offset..offset + part.len()].copy_from_slice(part);
+                offset += part.len();
+            }
+            absorb_block(pipe, &block[..offset], HEADER);
+            wipe(&mut block);
+        }
+        P::permute_many(&mut motorist.pipes);
+        motorist
+    }
+
+    /// Returns the length of authentication tags.
+    pub const fn tag_len() -> usize {
+        TAG_LEN
+    }
+
+    /// Returns `true` if the session was poisoned by a failure to open a message.
+    pub const fn is_poisoned(&self) -> bool {
+        self.poisoned
+    }
+
+    /// Seals the given mutable slice in place, authenticating it and the given associated data.
+    ///
+    /// The last `TAG_LEN` bytes of the slice will be overwritten with the authentication tag.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the session is poisoned and [`Error::BufferTooSmall`] if the
+    /// slice is shorter than `TAG_LEN` bytes, in which case the slice is unmodified.
+    pub fn seal_mut(&mut self, ad: &[u8], in_out: &mut [u8]) -> Result<(), Error> {
+        if self.poisoned {
+            return Err(Error::Poisoned);
+        }
+        if in_out.len() < TAG_LEN {
+            return Err(Error::BufferTooSmall);
+        }
+
+        let (plaintext, tag) = in_out.split_at_mut(in_out.len() - TAG_LEN);
+        self.absorb_message(ad, plaintext, true);
+        self.tag(tag);
+        Ok(())
+    }
+
+    /// Opens the given mutable slice in place, verifying it and the given associated data. The
+    /// last `TAG_LEN` bytes of the slice will be unmodified.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the session is poisoned, [`Error::BufferTooSmall`] if the
+    /// slice is shorter than `TAG_LEN` bytes, and [`Error::InvalidTag`] if the input could not be
+    /// authenticated, in which case the plaintext is zeroed and the session is poisoned.
+    pub fn open_mut(&mut self, ad: &[u8], in_out: &mut [u8]) -> Result<(), Error> {
+        if self.poisoned {
+            return Err(Error::Poisoned);
+        }
+        if in_out.len() < TAG_LEN {
+            return Err(Error::BufferTooSmall);
+        }
+
+        let (ciphertext, tag) = in_out.split_at_mut(in_out.len() - TAG_LEN);
+        self.absorb_message(ad, ciphertext, false);
+
+        let mut tag_p = [0u8; TAG_LEN];
+        self.tag(&mut tag_p);
+        let authentic = ct_eq(tag, &tag_p);
+        wipe(&mut tag_p);
+        if !authentic {
+            ciphertext.fill(0);
+            self.poison();
+            return Err(Error::InvalidTag);
+        }
+        Ok(())
+    }
+
+    /// Wipes the pipes' states and marks the session as poisoned, so that it can't be used again.
+    fn poison(&mut self) {
+        for pipe in &mut self.pipes {
+            pipe.wipe();
+        }
+        self.poisoned = true;
+    }
+
+    /// Absorbs a message's lengths and associated data, and encrypts or decrypts its text.
+    fn absorb_message(&mut self, ad: &[u8], text: &mut [u8], encrypt: bool) {
+        let mut lengths = [0u8; 16];
+        lengths[..8].copy_from_slice(&to_u64(ad.len()).to_le_bytes());
+        lengths[8..].copy_from_slice(&to_u64(text.len()).to_le_bytes());
+        for pipe in &mut self.pipes {
+            absorb_block(pipe, &lengths, LENGTHS);
+        }
+        P::permute_many(&mut self.pipes);
+
+        for round in ad.chunks(RATE * PIPES) {
+            for (pipe, block) in self.pipes.iter_mut().zip(round.chunks(RATE)) {
+                absorb_block(pipe, block, AD);
+            }
+            P::permute_many(&mut self.pipes);
+        }
+
+        let mut keystream = [0u8; RATE];
+        for round in text.chunks_mut(RATE * PIPES) {
+            for (pipe, block) in self.pipes.iter_mut().zip(round.chunks_mut(RATE)) {
+                let keystream = &mut keystream[..block.len()];
+                pipe.extract_bytes(keystream);
+                if encrypt {
+                    absorb_block(pipe, block, PLAINTEXT);
+                    xor(block, keystream);
+                } else {
+                    xor(block, keystream);
+                    absorb_block(pipe, block, PLAINTEXT);
+                }
+            }
+            P::permute_many(&mut self.pipes);
+        }
+        wipe(&mut keystream);
+    }
+
+    /// Absorbs every pipe's chain value into every pipe and extracts a tag from the first pipe.
+    fn tag(&mut self, tag: &mut [u8]) {
+        let mut chains = [[0u8; TAG_LEN]; PIPES];
+        for (pipe, chain) in self.pipes.iter_mut().zip(chains.iter_mut()) {
+            pipe.extract_bytes(chain);
+        }
+
+        let chains = chains.as_flattened_mut();
+        for block in chains.chunks(RATE) {
+            for pipe in &mut self.pipes {
+                absorb_block(pipe, block, CHAIN);
+            }
+            P::permute_many(&mut self.pipes);
+        }
+        wipe(chains);
+
+        self.pipes[0].extract_bytes(tag);
+    }
+}
+
+/// Wipes the pipes' states when a session is dropped, as each is as sensitive as the key.
+#[cfg(feature = "zeroize")]
+impl<P, const WIDTH: usize, const RATE: usize, const TAG_LEN: usize, const PIPES: usize> Drop
+    for Motorist<P, WIDTH, RATE, TAG_LEN, PIPES>
+where
+    P: Permutation<WIDTH>,
+{
+    fn drop(&mut self) {
+        for pipe in &mut self.pipes {
+            pipe.wipe();
+        }
+    }
+}
+
+/// Adds the given block, its padding, and its kind to the given pipe's state.
+fn absorb_block<P: Permutation<WIDTH>, const WIDTH: usize>(pipe: &mut P, block: &[u8], kind: u8) {
+    pipe.add_bytes(block);
+    pipe.add_byte(0x01, block.len());
+    pipe.add_byte(kind, WIDTH - 1);
+}
+
+/// XORs the given keystream into the given block.
+fn xor(block: &mut [u8], keystream: &[u8]) {
+    for (b, k) in block.iter_mut().zip(keystream) {
+        *b ^= k;
+    }
+}
+
+/// Converts a length which is known to be shorter than a block to a byte.
+fn to_u8(n: usize) -> u8 {
+    u8::try_from(n).expect("unexpected overflow")
+}
+
+/// Converts a length to a 64-bit integer.
+fn to_u64(n: usize) -> u64 {
+    u64::try_from(n).expect("unexpected overflow")
+}
+
+#[cfg(all(test, feature = "std", feature = "keccyak"))]
+mod tests {
+    use crate::keccyak::Keccyak128Motorist;
+
+    use super::*;
+
+    fn seal(ad: &[u8], m: &[u8]) -> Vec<u8> {
+        let mut c = m.to_vec();
+        c.resize(m.len() + Keccyak128Motorist::tag_len(), 0);
+        Keccyak128Motorist::new(b"key", b"nonce").seal_mut(ad, &mut c).expect("should seal");
+        c
+    }
+
+    #[test]
+    fn round_trip() {
+        for len in [0, 1, 167, 168, 169, 4 * 168, 4 * 168 + 1, 10_000] {
+            let m = vec![0xCAu8; len];
+            let mut c = seal(b"ad", &m);
+            if len > 0 {
+                assert_ne!(m, c[..len]);
+            }
+
+            let mut opener = Keccyak128Motorist::new(b"key", b"nonce");
+            assert_eq!(Ok(()), opener.open_mut(b"ad", &mut c), "len={len}");
+            assert_eq!(m, c[..len]);
+        }
+    }
+
+    #[test]
+    fn sessions() {
+        let mut sealer = Keccyak128Motorist::new(b"key", b"nonce");
+        let mut opener = Keccyak128Motorist::new(b"key", b"nonce");
+        let mut one = vec![0xCAu8; 100 + 16];
+        let mut two = one.clone();
+        sealer.seal_mut(b"", &mut one).expect("should seal");
+        sealer.seal_mut(b"", &mut two).expect("should seal");
+
+        // Each message in a session is encrypted differently.
+        assert_ne!(one, two);
+        assert_eq!(Ok(()), opener.open_mut(b"", &mut one));
+        assert_eq!(Ok(()), opener.open_mut(b"", &mut two));
+        assert_eq!(one[..100], two[..100]);
+    }
+
+    #[test]
+    fn striping_is_positional() {
+        // Swapping the blocks sent to two pipes is detected.
+        let m = vec![0xCAu8; 2 * 168];
+        let mut c = seal(b"", &m);
+        c[..2 * 168].rotate_left(168);
+        let mut opener = Keccyak128Motorist::new(b"key", b"nonce");
+        assert_eq!(Err(Error::InvalidTag), opener.open_mut(b"", &mut c));
+        assert_eq!(vec![0u8; 2 * 168], c[..2 * 168]);
+    }
+
+    #[test]
+    fn inputs_are_bound() {
+        let c = seal(b"ad", b"message");
+        assert_ne!(c, seal(b"ae", b"message"));
+        assert_ne!(c[7..], seal(b"a", b"dmessage")[8..]);
+
+        let mut bad = c.clone();
+        bad[0] ^= 1;
+        assert!(Keccyak128Motorist::new(b"key", b"nonce").open_mut(b"ad", &mut bad).is_err());
+        let mut bad = c.clone();
+        assert!(Keccyak128Motorist::new(b"key", b"nonc").open_mut(b"ad", &mut bad).is_err());
+        let mut bad = c;
+        assert!(Keccyak128Motorist::new(b"kez", b"nonce").open_mut(b"ad", &mut bad).is_err());
+
+        let mut short = [0u8; 15];
+        assert_eq!(
+            Err(Error::BufferTooSmall),
+            Keccyak128Motorist::new(b"key", b"nonce").open_mut(b"ad", &mut short)
+        );
+        assert_eq!(
+            Err(Error::BufferTooSmall),
+            Keccyak128Motorist::new(b"key", b"nonce").seal_mut(b"ad", &mut short)
+        );
+        assert_eq!([0u8; 15], short);
+    }
+
+    #[test]
+    fn poisoning() {
+        let mut c = seal(b"ad", b"message");
+        let mut opener = Keccyak128Motorist::new(b"key", b"nonce");
+        assert_eq!(Err(Error::InvalidTag), opener.open_mut(b"ae", &mut c.clone()));
+        assert!(opener.is_poisoned());
+
+        // Neither the right message nor a new one can be processed by a poisoned session.
+        assert_eq!(Err(Error::Poisoned), opener.open_mut(b"ad", &mut c));
+        assert_eq!(Err(Error::Poisoned), opener.seal_mut(b"ad", &mut [0u8; 16]));
+        assert!(!Keccyak128Motorist::new(b"key", b"nonce").is_poisoned());
+    }
+}
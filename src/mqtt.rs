@@ -0,0 +1,184 @@
+#![cfg(feature = "std")]
+
+//! Payload protection for publish/subscribe messaging (e.g. MQTT) with topic binding.
+//!
+//! A common mistake in IoT deployments is to encrypt message payloads without binding them to
+//! their topics, which allows an attacker with access to the broker to take a valid payload
+//! published to one topic (e.g. `site/door/unlock`) and replay it to another. [`seal_for_topic`]
+//! binds the topic into each envelope as associated data, and [`open_for_topic`] rejects envelopes
+//! published to any other topic:
+//!
+//! ```rust
+//! use cyclist::mqtt::{open_for_topic, seal_for_topic};
+//! use cyclist::xoodyak::XoodyakKeyed;
+//!
+//! let key = b"a broker-wide key";
+//! let envelope = seal_for_topic::<XoodyakKeyed>(key, "site/door/status", b"closed");
+//!
+//! let payload = open_for_topic::<XoodyakKeyed>(key, "site/door/status", &envelope);
+//! assert_eq!(Ok(b"closed".to_vec()), payload);
+//!
+//! assert!(open_for_topic::<XoodyakKeyed>(key, "site/door/unlock", &envelope).is_err());
+//! ```
+//!
+//! Topics are bound byte-for-byte, as MQTT compares them: `site/door` and `site/door/` are
+//! different topics.
+//!
+//! # Envelopes
+//!
+//! An envelope is `TAG_LEN` bytes longer than its payload, and consists of a synthetic IV followed
+//! by the ciphertext. Because the IV is derived from the topic and payload rather than a nonce,
+//! sealing never requires a source of randomness or a counter, but publishing the same payload to
+//! the same topic twice produces the same envelope. Applications which need to hide repeated
+//! payloads or to detect replays within a topic should include a timestamp or sequence number in
+//! each payload.
+//!
+//! # Construction
+//!
+//! A keyed duplex is initialized with the key, and absorbs a domain separation label and the topic.
+//! A copy absorbs the payload and squeezes the synthetic IV; another copy absorbs the synthetic IV
+//! and encrypts the payload. To open an envelope, the payload is decrypted and the synthetic IV is
+//! recalculated and compared in constant time.
+
+use constant_time_eq::constant_time_eq;
+
+use crate::{wipe, Error, Keyed};
+
+/// The domain separation label absorbed by every envelope.
+const MQTT_LABEL: &[u8] = b"cyclist.mqtt";
+
+/// Returns the length of an envelope holding a `payload_len`-byte payload.
+pub const fn sealed_len<K: Keyed>(payload_len: usize) -> usize {
+    K::TAG_LEN + payload_len
+}
+
+/// Returns the length of the payload of an `envelope_len`-byte envelope, or `None` if the envelope
+/// is too short.
+pub const fn opened_len<K: Keyed>(envelope_len: usize) -> Option<usize> {
+    envelope_len.checked_sub(K::TAG_LEN)
+}
+
+/// Seals `payload` for publication to `topic` with the given key, returning the envelope.
+pub fn seal_for_topic<K: Keyed>(key: &[u8], topic: &str, payload: &[u8]) -> Vec<u8> {
+    let base = topic_keyed::<K>(key, topic);
+
+    let mut envelope = vec![0u8; sealed_len::<K>(payload.len())];
+    let (siv, ciphertext) = envelope.split_at_mut(K::TAG_LEN);
+    synthetic_iv(&base, payload, siv);
+
+    ciphertext.copy_from_slice(payload);
+    let mut keyed = base;
+    keyed.absorb(siv);
+    keyed.encrypt_mut(ciphertext);
+    envelope
+}
+
+/// Opens an envelope published to `topic` with the given key, returning the payload.
+///
+/// # Errors
+///
+/// Returns [`Error::BufferTooSmall`] if the envelope is shorter than `TAG_LEN` bytes and
+/// [`Error::InvalidTag`] if it cannot be authenticated, including if it was sealed for a different
+/// topic.
+pub fn open_for_topic<K: Keyed>(
+    key: &[u8],
+    topic: &str,
+    envelope: &[u8],
+) -> Result<Vec<u8>, Error> {
+    if envelope.len() < K::TAG_LEN {
+        return Err(Error::BufferTooSmall);
+    }
+
+    let base = topic_keyed::<K>(key, topic);
+    let (siv, ciphertext) = envelope.split_at(K::TAG_LEN);
+
+    let mut payload = ciphertext.to_vec();
+    let mut keyed = base.clone();
+    keyed.absorb(siv);
+    keyed.decrypt_mut(&mut payload);
+
+    // Recalculate the synthetic IV, and never return inauthentic plaintext.
+    let mut siv_p = vec![0u8; K::TAG_LEN];
+    synthetic_iv(&base, &payload, &mut siv_p);
+    let authentic = constant_time_eq(siv, &siv_p);
+    wipe(&mut siv_p);
+    if !authentic {
+        wipe(&mut payload);
+        return Err(Error::InvalidTag);
+    }
+    Ok(payload)
+}
+
+/// Returns a keyed duplex which has absorbed the label and the topic.
+fn topic_keyed<K: Keyed>(key: &[u8], topic: &str) -> K {
+    let mut keyed = K::new(key, b"", b"");
+    keyed.absorb(MQTT_LABEL);
+    keyed.absorb(topic.as_bytes());
+    keyed
+}
+
+/// Squeezes the synthetic IV of `payload` into `siv`.
+fn synthetic_iv<K: Keyed>(base: &K, payload: &[u8], siv: &mut [u8]) {
+    let mut keyed = base.clone();
+    keyed.absorb(payload);
+    keyed.squeeze_mut(siv);
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakKeyed;
+
+    use super::*;
+
+    const KEY: &[u8] = b"a broker-wide key";
+
+    #[test]
+    fn round_trip() {
+        for len in [0, 1, 16, 100] {
+            let payload = vec![7u8; len];
+            let envelope = seal_for_topic::<XoodyakKeyed>(KEY, "a/b", &payload);
+            assert_eq!(sealed_len::<XoodyakKeyed>(len), envelope.len());
+            assert_eq!(Some(len), opened_len::<XoodyakKeyed>(envelope.len()));
+            assert_eq!(Ok(payload), open_for_topic::<XoodyakKeyed>(KEY, "a/b", &envelope));
+        }
+    }
+
+    #[test]
+    fn topic_binding() {
+        let envelope = seal_for_topic::<XoodyakKeyed>(KEY, "a/b", b"payload");
+        for topic in ["a/b/", "a/c", "", "A/b"] {
+            assert_eq!(
+                Err(Error::InvalidTag),
+                open_for_topic::<XoodyakKeyed>(KEY, topic, &envelope),
+                "topic={}",
+                topic
+            );
+        }
+        assert_ne!(envelope, seal_for_topic::<XoodyakKeyed>(KEY, "a/c", b"payload"));
+        assert_eq!(envelope, seal_for_topic::<XoodyakKeyed>(KEY, "a/b", b"payload"));
+    }
+
+    #[test]
+    fn modified_envelopes() {
+        let envelope = seal_for_topic::<XoodyakKeyed>(KEY, "a/b", b"payload");
+        for i in 0..envelope.len() {
+            let mut envelope = envelope.clone();
+            envelope[i] ^= 1;
+            assert_eq!(
+                Err(Error::InvalidTag),
+                open_for_topic::<XoodyakKeyed>(KEY, "a/b", &envelope),
+                "i={}",
+                i
+            );
+        }
+
+        assert_eq!(
+            Err(Error::BufferTooSmall),
+            open_for_topic::<XoodyakKeyed>(KEY, "a/b", &envelope[..XoodyakKeyed::TAG_LEN - 1])
+        );
+        assert_eq!(
+            Err(Error::InvalidTag),
+            open_for_topic::<XoodyakKeyed>(b"another key", "a/b", &envelope)
+        );
+    }
+}
@@ -0,0 +1,280 @@
+#![cfg(feature = "multihash")]
+
+//! Digests in the [multihash](https://multiformats.io/multihash/) format.
+//!
+//! A multihash is a self-describing digest: an unsigned varint code identifying the hash function,
+//! an unsigned varint digest length, and the digest itself. This allows content-addressing systems
+//! (e.g. IPFS) to carry Cyclist digests without custom wrappers:
+//!
+//! ```rust
+//! use cyclist::multihash::{self, MultihashCode};
+//! use cyclist::xoodyak::XoodyakHash;
+//! use cyclist::Cyclist;
+//!
+//! let mut hash = XoodyakHash::default();
+//! hash.absorb(b"This is some content!");
+//! let mh = multihash::squeeze(&mut hash, 32);
+//!
+//! let (code, digest) = multihash::decode(&mh).expect("should decode");
+//! assert_eq!(XoodyakHash::MULTIHASH_CODE, code);
+//! assert_eq!(32, digest.len());
+//! ```
+//!
+//! None of the Cyclist hashes have registered multicodec codes, so each uses a code in the
+//! multicodec table's private-use range ([`PRIVATE_USE`] and up), offset by its permutation's
+//! [`Permutation::ID`](crate::Permutation::ID). Private-use codes are only meaningful between
+//! parties which agree on them, and will be replaced by registered codes if any are assigned.
+
+use core::fmt;
+
+use crate::Cyclist;
+
+/// The first code in the multicodec table's private-use range.
+pub const PRIVATE_USE: u64 = 0x30_0000;
+
+/// The maximum length of an unsigned varint, per the multiformats specification.
+const MAX_VARINT_LEN: usize = 9;
+
+/// A hash with a multihash code.
+pub trait MultihashCode {
+    /// The hash's multihash code.
+    const MULTIHASH_CODE: u64;
+}
+
+#[cfg(feature = "xoodyak")]
+impl MultihashCode for crate::xoodyak::XoodyakHash {
+    const MULTIHASH_CODE: u64 = PRIVATE_USE + 0x0001;
+}
+
+#[cfg(feature = "keccyak")]
+impl MultihashCode for crate::keccyak::KeccyakMinHash {
+    const MULTIHASH_CODE: u64 = PRIVATE_USE + 0x0101;
+}
+
+#[cfg(feature = "keccyak")]
+impl MultihashCode for crate::keccyak::Keccyak128Hash {
+    const MULTIHASH_CODE: u64 = PRIVATE_USE + 0x0102;
+}
+
+#[cfg(feature = "keccyak")]
+impl MultihashCode for crate::keccyak::Keccyak256Hash {
+    const MULTIHASH_CODE: u64 = PRIVATE_USE + 0x0103;
+}
+
+#[cfg(feature = "keccyak")]
+impl MultihashCode for crate::keccyak::KeccyakMaxHash {
+    const MULTIHASH_CODE: u64 = PRIVATE_USE + 0x0104;
+}
+
+#[cfg(feature = "keccyak")]
+impl MultihashCode for crate::keccyak::Keccyak800Hash {
+    const MULTIHASH_CODE: u64 = PRIVATE_USE + 0x0105;
+}
+
+#[cfg(feature = "keccyak")]
+impl MultihashCode for crate::keccyak::Keccyak400Hash {
+    const MULTIHASH_CODE: u64 = PRIVATE_USE + 0x0106;
+}
+
+#[cfg(feature = "keccyak")]
+impl MultihashCode for crate::keccyak::Keccyak200Hash {
+    const MULTIHASH_CODE: u64 = PRIVATE_USE + 0x0107;
+}
+
+#[cfg(feature = "ascon")]
+impl MultihashCode for crate::ascon::AsconCyclistHash {
+    const MULTIHASH_CODE: u64 = PRIVATE_USE + 0x0201;
+}
+
+/// An error encoding or decoding a multihash.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The output buffer is too small for the multihash.
+    BufferTooSmall,
+
+    /// A varint was truncated, too long, or not minimally encoded.
+    InvalidVarint,
+
+    /// The digest's length doesn't match its encoded length.
+    LengthMismatch,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Error::BufferTooSmall => "buffer too small",
+            Error::InvalidVarint => "invalid varint",
+            Error::LengthMismatch => "digest length mismatch",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Returns the length of the multihash of a `digest_len`-byte digest with the given code.
+pub fn encoded_len(code: u64, digest_len: usize) -> usize {
+    let len = u64::try_from(digest_len).expect("unexpected overflow");
+    varint_len(code) + varint_len(len) + digest_len
+}
+
+/// Writes the multihash of the given code and digest to `out`, returning its length.
+///
+/// # Errors
+///
+/// Returns [`Error::BufferTooSmall`] if `out` is shorter than [`encoded_len`].
+pub fn encode_mut(code: u64, digest: &[u8], out: &mut [u8]) -> Result<usize, Error> {
+    let n = encoded_len(code, digest.len());
+    if out.len() < n {
+        return Err(Error::BufferTooSmall);
+    }
+
+    let i = write_varint(code, out);
+    let i =
+        i + write_varint(u64::try_from(digest.len()).expect("unexpected overflow"), &mut out[i..]);
+    out[i..n].copy_from_slice(digest);
+    Ok(n)
+}
+
+/// Returns the multihash of the given code and digest.
+#[cfg(feature = "std")]
+pub fn encode(code: u64, digest: &[u8]) -> Vec<u8> {
+    let mut out = vec![0u8; encoded_len(code, digest.len())];
+    encode_mut(code, digest, &mut out).expect("invalid length");
+    out
+}
+
+/// Squeezes a `len`-byte digest from the given hash and returns it as a multihash.
+#[cfg(feature = "std")]
+pub fn squeeze<H: Cyclist + MultihashCode>(hash: &mut H, len: usize) -> Vec<u8> {
+    let mut digest = vec![0u8; len];
+    hash.squeeze_mut(&mut digest);
+    encode(H::MULTIHASH_CODE, &digest)
+}
+
+/// Squeezes a digest from the given hash into `digest` and writes it as a multihash to `out`,
+/// returning the multihash's length.
+///
+/// # Errors
+///
+/// Returns [`Error::BufferTooSmall`] if `out` is shorter than [`encoded_len`].
+pub fn squeeze_mut<H: Cyclist + MultihashCode>(
+    hash: &mut H,
+    digest: &mut [u8],
+    out: &mut [u8],
+) -> Result<usize, Error> {
+    if out.len() < encoded_len(H::MULTIHASH_CODE, digest.len()) {
+        return Err(Error::BufferTooSmall);
+    }
+    hash.squeeze_mut(digest);
+    encode_mut(H::MULTIHASH_CODE, digest, out)
+}
+
+/// Decodes the given multihash into its code and digest.
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidVarint`] if the code or length is malformed and
+/// [`Error::LengthMismatch`] if the digest isn't exactly as long as its encoded length.
+pub fn decode(mh: &[u8]) -> Result<(u64, &[u8]), Error> {
+    let (code, rest) = read_varint(mh)?;
+    let (len, digest) = read_varint(rest)?;
+    if u64::try_from(digest.len()).ok() != Some(len) {
+        return Err(Error::LengthMismatch);
+    }
+    Ok((code, digest))
+}
+
+/// Returns the length of the given value as an unsigned varint.
+const fn varint_len(mut n: u64) -> usize {
+    let mut len = 1;
+    while n >= 0x80 {
+        n >>= 7;
+        len += 1;
+    }
+    len
+}
+
+/// Writes the given value as an unsigned varint, returning its length.
+fn write_varint(mut n: u64, out: &mut [u8]) -> usize {
+    let mut i = 0;
+    loop {
+        let [b, ..] = (n & 0x7F).to_le_bytes();
+        n >>= 7;
+        if n == 0 {
+            out[i] = b;
+            return i + 1;
+        }
+        out[i] = b | 0x80;
+        i += 1;
+    }
+}
+
+/// Reads a minimally-encoded unsigned varint, returning its value and the remaining bytes.
+fn read_varint(bytes: &[u8]) -> Result<(u64, &[u8]), Error> {
+    let mut n = 0u64;
+    for (i, &b) in bytes.iter().enumerate().take(MAX_VARINT_LEN) {
+        n |= u64::from(b & 0x7F) << (7 * i);
+        if b & 0x80 == 0 {
+            // A trailing zero byte means the varint wasn't minimally encoded.
+            if b == 0 && i > 0 {
+                return Err(Error::InvalidVarint);
+            }
+            return Ok((n, &bytes[i + 1..]));
+        }
+    }
+    Err(Error::InvalidVarint)
+}
+
+#[cfg(all(test, feature = "std", feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakHash;
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut hash = XoodyakHash::default();
+        hash.absorb(b"ok then");
+        let mut expected = hash.clone();
+        let mh = squeeze(&mut hash, 32);
+
+        // 0x300001 is 0x81 0x80 0xC0 0x01 as a varint.
+        assert_eq!([0x81, 0x80, 0xC0, 0x01, 32], mh[..5]);
+        assert_eq!(encoded_len(XoodyakHash::MULTIHASH_CODE, 32), mh.len());
+        assert_eq!(Ok((XoodyakHash::MULTIHASH_CODE, expected.squeeze(32).as_slice())), decode(&mh));
+    }
+
+    #[test]
+    fn squeeze_into_slices() {
+        let mut hash = XoodyakHash::default();
+        let mut digest = [0u8; 16];
+        let mut out = [0u8; 21];
+        assert_eq!(Err(Error::BufferTooSmall), squeeze_mut(&mut hash, &mut digest, &mut out[..20]));
+        assert_eq!(Ok(21), squeeze_mut(&mut hash, &mut digest, &mut out));
+        assert_eq!(Ok((XoodyakHash::MULTIHASH_CODE, digest.as_slice())), decode(&out));
+    }
+
+    #[test]
+    fn varints() {
+        for n in [0, 1, 0x7F, 0x80, 0x3FFF, 0x4000, PRIVATE_USE, u64::MAX >> 1] {
+            let mut buf = [0u8; MAX_VARINT_LEN];
+            let len = write_varint(n, &mut buf);
+            assert_eq!(varint_len(n), len);
+            assert_eq!(Ok((n, &[][..])), read_varint(&buf[..len]));
+        }
+
+        assert_eq!(Err(Error::InvalidVarint), read_varint(&[]));
+        assert_eq!(Err(Error::InvalidVarint), read_varint(&[0x80]));
+        assert_eq!(Err(Error::InvalidVarint), read_varint(&[0x81, 0x00]));
+        assert_eq!(Err(Error::InvalidVarint), read_varint(&[0xFF; 10]));
+    }
+
+    #[test]
+    fn invalid_multihashes() {
+        assert_eq!(Err(Error::LengthMismatch), decode(&[0x01, 0x02, 0xAA]));
+        assert_eq!(Err(Error::LengthMismatch), decode(&[0x01, 0x01, 0xAA, 0xBB]));
+        assert_eq!(Ok((1, &[0xAA][..])), decode(&[0x01, 0x01, 0xAA]));
+    }
+}
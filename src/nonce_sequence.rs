@@ -0,0 +1,157 @@
+//! Automatic nonce management for sealing many messages with a single key.
+//!
+//! Reusing a nonce with the same key is catastrophic for any duplex-based AEAD, and applications
+//! which manage nonces by hand tend to get it wrong eventually (e.g. by restoring a counter from a
+//! stale backup, or cloning the object which holds it). A [`NonceSequence`] owns a keyed duplex and
+//! a monotonically increasing 64-bit counter, and hands out a fresh keyed duplex for each message
+//! along with the counter the recipient needs to reconstruct it:
+//!
+//! ```rust
+//! use cyclist::nonce_sequence::NonceSequence;
+//! use cyclist::xoodyak::XoodyakKeyed;
+//! use cyclist::Keyed;
+//!
+//! let key = XoodyakKeyed::new(b"a shared key", b"client->server", b"");
+//! let mut sender = NonceSequence::new(key.clone());
+//! let receiver = NonceSequence::new(key);
+//!
+//! let (counter, mut keyed) = sender.next_keyed().expect("should have counters left");
+//! let ciphertext = keyed.seal(b"a message");
+//!
+//! let mut keyed = receiver.keyed_for(counter);
+//! assert_eq!(Ok(b"a message".to_vec()), keyed.open(&ciphertext));
+//! ```
+//!
+//! A [`NonceSequence`] deliberately does not implement [`Clone`], since two copies would hand out
+//! the same counters. Once every counter has been used, [`NonceSequence::next_keyed`] returns
+//! [`Error::Exhausted`] rather than wrapping around, at which point the key must be replaced.
+//!
+//! Applications which persist the counter across restarts can resume a sequence with
+//! [`NonceSequence::starting_at`], but must ensure the persisted value is never older than the
+//! last counter used.
+//!
+//! # Construction
+//!
+//! The keyed duplex absorbs a domain separation label when the sequence is created. To derive the
+//! duplex for a message, a copy absorbs the message's counter as a little-endian 64-bit integer.
+
+use core::fmt;
+
+use crate::Keyed;
+
+/// The domain separation label for nonce sequences.
+const NONCE_SEQUENCE_LABEL: &[u8] = b"cyclist.nonce-sequence";
+
+/// An error advancing a nonce sequence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// Every counter has already been used with the key.
+    Exhausted,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Exhausted => write!(f, "nonce sequence exhausted"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// A sequence of keyed duplexes, each derived from a shared key and a unique counter.
+#[derive(Debug)]
+pub struct NonceSequence<K> {
+    keyed: K,
+    counter: u64,
+}
+
+impl<K> NonceSequence<K>
+where
+    K: Keyed,
+{
+    /// Creates a new [`NonceSequence`] with the given keyed duplex, starting at zero.
+    pub fn new(keyed: K) -> Self {
+        Self::starting_at(keyed, 0)
+    }
+
+    /// Creates a new [`NonceSequence`] with the given keyed duplex, starting at the given counter,
+    /// e.g. to resume a sequence after a restart.
+    pub fn starting_at(mut keyed: K, counter: u64) -> Self {
+        keyed.absorb(NONCE_SEQUENCE_LABEL);
+        NonceSequence { keyed, counter }
+    }
+
+    /// Returns the counter which will be used for the next message.
+    pub const fn counter(&self) -> u64 {
+        self.counter
+    }
+
+    /// Returns the number of messages which can still be sealed with the key.
+    pub const fn remaining(&self) -> u64 {
+        u64::MAX - self.counter
+    }
+
+    /// Returns the next counter and a keyed duplex derived from it, and advances the sequence.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Exhausted`] if every counter has been used, in which case the sequence is
+    /// unmodified.
+    pub fn next_keyed(&mut self) -> Result<(u64, K), Error> {
+        let counter = self.counter;
+        self.counter = counter.checked_add(1).ok_or(Error::Exhausted)?;
+        Ok((counter, self.keyed_for(counter)))
+    }
+
+    /// Returns the keyed duplex for the given counter, e.g. to open a message sealed by another
+    /// sequence with the same key.
+    ///
+    /// This does not advance the sequence, and so must not be used to seal messages.
+    pub fn keyed_for(&self, counter: u64) -> K {
+        let mut keyed = self.keyed.clone();
+        keyed.absorb(&counter.to_le_bytes());
+        keyed
+    }
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakKeyed;
+    use crate::Cyclist;
+
+    use super::*;
+
+    #[test]
+    fn unique_duplexes() {
+        let mut seq = NonceSequence::new(XoodyakKeyed::new(b"key", b"", b""));
+        let (c0, mut k0) = seq.next_keyed().expect("should have counters left");
+        let (c1, mut k1) = seq.next_keyed().expect("should have counters left");
+        assert_eq!((0, 1, 2), (c0, c1, seq.counter()));
+        let (t0, t1) = (k0.squeeze(16), k1.squeeze(16));
+        assert_ne!(t0, t1);
+        assert_eq!(t1, seq.keyed_for(1).squeeze(16));
+    }
+
+    #[test]
+    fn resuming() {
+        let mut a = NonceSequence::new(XoodyakKeyed::new(b"key", b"", b""));
+        let mut b = NonceSequence::starting_at(XoodyakKeyed::new(b"key", b"", b""), 1);
+        a.next_keyed().expect("should have counters left");
+        let (_, mut k_a) = a.next_keyed().expect("should have counters left");
+        let (_, mut k_b) = b.next_keyed().expect("should have counters left");
+        assert_eq!(k_a.squeeze(16), k_b.squeeze(16));
+    }
+
+    #[test]
+    fn exhaustion() {
+        let mut seq = NonceSequence::starting_at(XoodyakKeyed::new(b"key", b"", b""), u64::MAX - 1);
+        assert_eq!(1, seq.remaining());
+        assert_eq!(u64::MAX - 1, seq.next_keyed().expect("should have one counter left").0);
+        assert_eq!(0, seq.remaining());
+        assert_eq!(Some(Error::Exhausted), seq.next_keyed().err());
+        assert_eq!(u64::MAX, seq.counter());
+    }
+}
@@ -0,0 +1,198 @@
+//! Anti-rollback sealing of versioned state for non-volatile storage.
+//!
+//! Embedded devices often persist state (e.g. counters, keys, or configuration) to flash, where an
+//! attacker with physical access may replace it with an older copy. A sealed record includes a
+//! monotonically increasing version number, which is authenticated along with the state. When a
+//! record is opened, its version is checked against a minimum version held somewhere the attacker
+//! can't roll back (e.g. a monotonic counter or one-time-programmable fuses), and older records are
+//! rejected.
+//!
+//! ```rust
+//! use cyclist::nv::{open, seal, sealed_len, HEADER_LEN};
+//! use cyclist::xoodyak::XoodyakKeyed;
+//! use cyclist::Keyed;
+//!
+//! let keyed = XoodyakKeyed::new(b"device storage key", b"", b"");
+//! let state = b"boot count: 12";
+//!
+//! let mut record = vec![0u8; sealed_len::<XoodyakKeyed>(state.len())];
+//! record[HEADER_LEN..HEADER_LEN + state.len()].copy_from_slice(state);
+//! seal(&keyed, 7, &mut record);
+//!
+//! // Open the record, requiring at least version 7.
+//! assert_eq!(Ok(7), open(&keyed, 7, &mut record));
+//! assert_eq!(state, &record[HEADER_LEN..HEADER_LEN + state.len()]);
+//! ```
+//!
+//! # Construction
+//!
+//! A record consists of an [`HEADER_LEN`]-byte header containing the version as a little-endian
+//! 64-bit integer, followed by the sealed state. The state is sealed with a copy of the given keyed
+//! duplex after it has absorbed a domain separation label and the header.
+
+use core::fmt;
+
+use crate::{wipe, Keyed};
+
+/// The length of a record's header, in bytes.
+pub const HEADER_LEN: usize = 8;
+
+/// The domain separation label for sealed records.
+const NV_LABEL: &[u8] = b"cyclist.nv";
+
+/// An error opening a sealed record.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The record was too short to contain a header and a tag.
+    Truncated,
+
+    /// The record could not be authenticated.
+    Inauthentic,
+
+    /// The record was authentic, but older than the minimum version.
+    Rollback {
+        /// The version of the record.
+        version: u64,
+        /// The minimum acceptable version.
+        min_version: u64,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Truncated => write!(f, "truncated record"),
+            Error::Inauthentic => write!(f, "inauthentic record"),
+            Error::Rollback { version, min_version } => {
+                write!(f, "rolled back record (version {} < {})", version, min_version)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Returns the length of a record holding `state_len` bytes of state.
+pub const fn sealed_len<K: Keyed>(state_len: usize) -> usize {
+    HEADER_LEN + state_len + K::TAG_LEN
+}
+
+/// Returns the length of the state held by a `record_len`-byte record, or `None` if the record is
+/// too short.
+pub const fn opened_len<K: Keyed>(record_len: usize) -> Option<usize> {
+    record_len.checked_sub(HEADER_LEN + K::TAG_LEN)
+}
+
+/// Seals a record in place with the given version. The state must be in
+/// `record[HEADER_LEN..record.len() - K::TAG_LEN]`; the header and tag will be overwritten.
+///
+/// # Panics
+///
+/// Panics if the record is shorter than [`sealed_len`] of an empty state.
+pub fn seal<K: Keyed>(keyed: &K, version: u64, record: &mut [u8]) {
+    assert!(record.len() >= sealed_len::<K>(0), "record too short");
+
+    let (header, sealed) = record.split_at_mut(HEADER_LEN);
+    header.copy_from_slice(&version.to_le_bytes());
+    start(keyed, header).seal_mut(sealed);
+}
+
+/// Opens a record in place, returning its version if it is authentic and at least `min_version`.
+/// If so, the state will be in `record[HEADER_LEN..record.len() - K::TAG_LEN]`; otherwise, it will
+/// have been wiped.
+///
+/// # Errors
+///
+/// Returns [`Error::Truncated`] if the record is too short, [`Error::Inauthentic`] if it cannot be
+/// authenticated, and [`Error::Rollback`] if its version is less than `min_version`.
+pub fn open<K: Keyed>(keyed: &K, min_version: u64, record: &mut [u8]) -> Result<u64, Error> {
+    if record.len() < sealed_len::<K>(0) {
+        return Err(Error::Truncated);
+    }
+
+    let (header, sealed) = record.split_at_mut(HEADER_LEN);
+    let version = u64::from_le_bytes(header.try_into().expect("invalid header length"));
+    if start(keyed, header).open_mut(sealed).is_err() {
+        return Err(Error::Inauthentic);
+    }
+
+    // Only report rollbacks of authentic records, and don't disclose their state.
+    if version < min_version {
+        let state_len = sealed.len() - K::TAG_LEN;
+        wipe(&mut sealed[..state_len]);
+        return Err(Error::Rollback { version, min_version });
+    }
+
+    Ok(version)
+}
+
+/// Returns a copy of the keyed duplex which has absorbed the label and the record's header.
+fn start<K: Keyed>(keyed: &K, header: &[u8]) -> K {
+    let mut keyed = keyed.clone();
+    keyed.absorb(NV_LABEL);
+    keyed.absorb(header);
+    keyed
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakKeyed;
+
+    use super::*;
+
+    const STATE: &[u8] = b"boot count: 12";
+
+    fn keyed() -> XoodyakKeyed {
+        XoodyakKeyed::new(b"device storage key", b"", b"")
+    }
+
+    fn record(version: u64) -> [u8; HEADER_LEN + STATE.len() + 16] {
+        let mut record = [0u8; HEADER_LEN + STATE.len() + 16];
+        record[HEADER_LEN..HEADER_LEN + STATE.len()].copy_from_slice(STATE);
+        seal(&keyed(), version, &mut record);
+        record
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut record = record(7);
+        assert_eq!(sealed_len::<XoodyakKeyed>(STATE.len()), record.len());
+        assert_ne!(STATE, &record[HEADER_LEN..HEADER_LEN + STATE.len()]);
+
+        assert_eq!(Ok(7), open(&keyed(), 5, &mut record));
+        assert_eq!(STATE, &record[HEADER_LEN..HEADER_LEN + STATE.len()]);
+    }
+
+    #[test]
+    fn rollbacks() {
+        let mut record = record(7);
+        assert_eq!(
+            Err(Error::Rollback { version: 7, min_version: 8 }),
+            open(&keyed(), 8, &mut record)
+        );
+        assert_eq!([0u8; STATE.len()], record[HEADER_LEN..HEADER_LEN + STATE.len()]);
+    }
+
+    #[test]
+    fn modified_versions() {
+        let mut record = record(7);
+        record[..HEADER_LEN].copy_from_slice(&8u64.to_le_bytes());
+        assert_eq!(Err(Error::Inauthentic), open(&keyed(), 8, &mut record));
+    }
+
+    #[test]
+    fn truncated_records() {
+        let mut record = record(7);
+        assert_eq!(Err(Error::Truncated), open(&keyed(), 0, &mut record[..HEADER_LEN + 15]));
+        assert_eq!(Err(Error::Inauthentic), open(&keyed(), 0, &mut record[..HEADER_LEN + 16]));
+    }
+
+    #[test]
+    fn wrong_keys() {
+        let mut record = record(7);
+        let other = XoodyakKeyed::new(b"another device key", b"", b"");
+        assert_eq!(Err(Error::Inauthentic), open(&other, 0, &mut record));
+    }
+}
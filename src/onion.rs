@@ -0,0 +1,129 @@
+#![cfg(feature = "std")]
+
+//! Layered ("onion") encryption with a separate key for each layer.
+//!
+//! [`onion_seal`] pads a payload to a fixed length and then seals it once for each key, starting
+//! with the last key, so that the holder of the first key must peel the outermost layer with
+//! [`onion_peel`], the holder of the second key the next layer, and so on. The holder of the last
+//! key recovers the original payload with [`onion_unpad`].
+//!
+//! Each layer is sealed with a fresh keyed duplex which absorbs a domain separation label and the
+//! length of the layer's plaintext, so a layer can't be confused with any other use of the same key.
+//! Layers are not sealed with a nonce, so **each key must be used for only one onion**, as is the
+//! case with per-packet keys derived from an ephemeral key exchange.
+//!
+//! # Size Normalization
+//!
+//! Payloads are padded to `padded_len` bytes, so all onions with the same number of layers have
+//! the same length regardless of their payloads. Each layer adds `TAG_LEN` bytes, so the length of
+//! an onion does reveal the number of layers remaining.
+
+use crate::Keyed;
+
+/// The domain separation label absorbed by every layer.
+pub(crate) const ONION_LABEL: &[u8] = b"cyclist.onion";
+
+/// Pads `payload` to `padded_len` bytes and seals it in layers with the given keys. The outermost
+/// layer is sealed with `keys[0]`.
+///
+/// # Panics
+///
+/// Panics if `payload` is not shorter than `padded_len`.
+pub fn onion_seal<K: Keyed>(keys: &[&[u8]], payload: &[u8], padded_len: usize) -> Vec<u8> {
+    assert!(payload.len() < padded_len, "payload must be shorter than padded length");
+
+    // Pad the payload with a single 0x80 byte followed by zeroes.
+    let mut onion = Vec::with_capacity(padded_len + keys.len() * K::TAG_LEN);
+    onion.extend_from_slice(payload);
+    onion.push(0x80);
+    onion.resize(padded_len, 0);
+
+    // Seal each layer, starting with the innermost.
+    for key in keys.iter().rev() {
+        let len = onion.len();
+        onion.resize(len + K::TAG_LEN, 0);
+        layer::<K>(key, len).seal_mut(&mut onion);
+    }
+    onion
+}
+
+/// Peels the outermost layer of `onion` with the given key, returning the next layer, or `None` if
+/// the layer cannot be authenticated.
+pub fn onion_peel<K: Keyed>(key: &[u8], onion: &[u8]) -> Option<Vec<u8>> {
+    let len = onion.len().checked_sub(K::TAG_LEN)?;
+    let mut next = onion.to_vec();
+    if layer::<K>(key, len).open_mut(&mut next).is_ok() {
+        next.truncate(len);
+        Some(next)
+    } else {
+        None
+    }
+}
+
+/// Removes the padding from a fully-peeled onion, returning the original payload, or `None` if the
+/// padding is invalid.
+pub fn onion_unpad(padded: &[u8]) -> Option<&[u8]> {
+    let end = padded.iter().rposition(|&b| b != 0)?;
+    (padded[end] == 0x80).then(|| &padded[..end])
+}
+
+/// Creates the duplex for a layer with a plaintext of `len` bytes.
+fn layer<K: Keyed>(key: &[u8], len: usize) -> K {
+    let mut keyed = K::new(key, b"", b"");
+    keyed.absorb(ONION_LABEL);
+    keyed.absorb(&u64::try_from(len).expect("unexpected overflow").to_le_bytes());
+    keyed
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakKeyed;
+
+    use super::*;
+
+    const KEYS: [&[u8]; 3] = [b"first", b"second", b"third"];
+
+    #[test]
+    fn round_trip() {
+        let mut onion = onion_seal::<XoodyakKeyed>(&KEYS, b"it's a deal", 64);
+        assert_eq!(64 + 3 * XoodyakKeyed::TAG_LEN, onion.len());
+
+        for key in KEYS {
+            onion = onion_peel::<XoodyakKeyed>(key, &onion).expect("unable to peel layer");
+        }
+
+        assert_eq!(Some(&b"it's a deal"[..]), onion_unpad(&onion));
+    }
+
+    #[test]
+    fn normalized_size() {
+        let short = onion_seal::<XoodyakKeyed>(&KEYS, b"", 64);
+        let long = onion_seal::<XoodyakKeyed>(&KEYS, &[0u8; 63], 64);
+        assert_eq!(short.len(), long.len());
+
+        let peeled = onion_peel::<XoodyakKeyed>(KEYS[0], &long).expect("unable to peel layer");
+        let peeled = onion_peel::<XoodyakKeyed>(KEYS[1], &peeled).expect("unable to peel layer");
+        let peeled = onion_peel::<XoodyakKeyed>(KEYS[2], &peeled).expect("unable to peel layer");
+        assert_eq!(Some(&[0u8; 63][..]), onion_unpad(&peeled));
+    }
+
+    #[test]
+    fn wrong_order() {
+        let onion = onion_seal::<XoodyakKeyed>(&KEYS, b"it's a deal", 64);
+        assert_eq!(None, onion_peel::<XoodyakKeyed>(KEYS[1], &onion));
+    }
+
+    #[test]
+    fn distinct_from_seal() {
+        let onion = onion_seal::<XoodyakKeyed>(&KEYS[..1], b"it's a deal", 64);
+        let mut d = XoodyakKeyed::new(KEYS[0], b"", b"");
+        assert!(d.open(&onion).is_err());
+    }
+
+    #[test]
+    fn invalid_padding() {
+        assert_eq!(None, onion_unpad(&[0u8; 16]));
+        assert_eq!(None, onion_unpad(&[1u8; 16]));
+        assert_eq!(None, onion_unpad(&[]));
+    }
+}
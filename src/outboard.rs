@@ -0,0 +1,387 @@
+#![cfg(feature = "std")]
+
+//! Verified streaming of large files via an outboard hash tree.
+//!
+//! [`encode_outboard`] hashes a file as a binary tree of [`CHUNK_LEN`]-byte chunks, returning a
+//! 32-byte root hash and an outboard encoding of the tree's interior nodes. Given the root hash and
+//! the outboard encoding, [`verify_range`] can verify any range of the file while reading only the
+//! chunks which overlap it, as determined by [`chunk_range`].
+//!
+//! ```rust
+//! use cyclist::outboard::{chunk_range, encode_outboard, verify_range};
+//! use cyclist::xoodyak::XoodyakHash;
+//!
+//! let file = vec![0xCAu8; 100_000];
+//! let (root, outboard) = encode_outboard::<XoodyakHash>(&file);
+//!
+//! // Verify bytes 50,000..50,010 of the file, reading only the chunks that contain them.
+//! let range = chunk_range(50_000, 10, file.len() as u64);
+//! let chunks = &file[range.start as usize..range.end as usize];
+//! assert!(verify_range::<XoodyakHash>(&root, &outboard, range.start, chunks));
+//! ```
+//!
+//! # Construction
+//!
+//! The tree's leaves are the file's chunks, the last of which may be shorter than [`CHUNK_LEN`]
+//! (or empty, for an empty file). Each interior node has a left subtree containing the largest
+//! power of two chunks which leaves at least one chunk for its right subtree.
+//!
+//! A leaf's hash is squeezed from a hash which has absorbed a label, the chunk's index, and the
+//! chunk. An interior node's hash is squeezed from a hash which has absorbed a label and its
+//! children's hashes. The root node additionally absorbs the length of the file before its label,
+//! which is distinct from the labels of non-root nodes.
+//!
+//! The outboard encoding consists of the length of the file as a little-endian 64-bit integer,
+//! followed by the pair of child hashes of each interior node, in pre-order.
+//!
+//! Leaves are hashed in groups of four consecutive chunks, whose hashes are run in lockstep, so a
+//! permutation which evaluates several instances in parallel (see
+//! [`Permutation::permute_many`](crate::Permutation::permute_many)) permutes their states together.
+
+use core::ops::Range;
+
+use crate::{Cyclist, LOCKSTEP};
+
+/// The length of a chunk in bytes.
+pub const CHUNK_LEN: usize = 1024;
+
+/// The length of a node hash in bytes.
+pub const HASH_LEN: usize = 32;
+
+/// A hash of a node in the tree.
+type Hash = [u8; HASH_LEN];
+
+/// The domain separation label for leaves.
+pub(crate) const LEAF_LABEL: &[u8] = b"cyclist.outboard.leaf";
+
+/// The domain separation label for interior nodes.
+pub(crate) const PARENT_LABEL: &[u8] = b"cyclist.outboard.parent";
+
+/// The domain separation label for a root leaf.
+pub(crate) const ROOT_LEAF_LABEL: &[u8] = b"cyclist.outboard.root-leaf";
+
+/// The domain separation label for a root interior node.
+pub(crate) const ROOT_PARENT_LABEL: &[u8] = b"cyclist.outboard.root-parent";
+
+/// Hashes `data` and returns the root hash and the outboard encoding of the tree.
+pub fn encode_outboard<H: Cyclist + Default>(data: &[u8]) -> (Hash, Vec<u8>) {
+    let len = u64::try_from(data.len()).expect("unexpected overflow");
+    let chunks = chunk_count(len).expect("unexpected overflow");
+    let mut outboard = Vec::with_capacity(8 + 2 * HASH_LEN * (chunks - 1));
+    outboard.extend_from_slice(&len.to_le_bytes());
+    let root = if chunks == 1 {
+        hash_leaf::<H>(data, 0, Some(len))
+    } else {
+        let leaves = data
+            .chunks(LOCKSTEP * CHUNK_LEN)
+            .enumerate()
+            .flat_map(|(i, group)| hash_leaf_group::<H>(group, i * LOCKSTEP))
+            .collect::<Vec<_>>();
+        encode_node::<H>(&leaves, 0, chunks, Some(len), &mut outboard)
+    };
+    (root, outboard)
+}
+
+/// Returns the range of bytes of a file of `file_len` bytes which must be passed to
+/// [`verify_range`] in order to verify the `len` bytes starting at `offset`. The range always
+/// includes the chunk containing `offset`, and is empty only if `offset` is past the end of the
+/// file.
+pub fn chunk_range(offset: u64, len: u64, file_len: u64) -> Range<u64> {
+    let chunk_len = u64::try_from(CHUNK_LEN).expect("unexpected overflow");
+    let start = (offset / chunk_len * chunk_len).min(file_len);
+    let end = offset.saturating_add(len.max(1)).min(file_len);
+    let end = end.div_ceil(chunk_len).saturating_mul(chunk_len).min(file_len).max(start);
+    start..end
+}
+
+/// Verifies that `data` is the range of a file starting at `offset`, given the file's root hash
+/// and outboard encoding. `offset` must be the start of a chunk, and `data` must be non-empty
+/// (unless the file is empty) and end at the end of a chunk or of the file, as returned by
+/// [`chunk_range`].
+#[must_use]
+pub fn verify_range<H: Cyclist + Default>(
+    root: &Hash,
+    outboard: &[u8],
+    offset: u64,
+    data: &[u8],
+) -> bool {
+    let Some((len, nodes)) = outboard.split_first_chunk::<8>() else {
+        return false;
+    };
+    let len = u64::from_le_bytes(*len);
+
+    // Ensure the outboard encoding has exactly one pair of hashes per interior node.
+    let Some(chunks) = chunk_count(len) else {
+        return false;
+    };
+    if nodes.len() != 2 * HASH_LEN * (chunks - 1) {
+        return false;
+    }
+
+    // Ensure the data is a whole, non-empty number of chunks within the file.
+    let data_len = u64::try_from(data.len()).expect("unexpected overflow");
+    if chunk_range(offset, data_len, len) != (offset..offset + data_len)
+        || (data.is_empty() && len > 0)
+    {
+        return false;
+    }
+
+    let first = usize::try_from(offset).expect("unexpected overflow") / CHUNK_LEN;
+    let verifier = Verifier { outboard: nodes, data, first, len };
+    verifier.verify_node::<H>(root, 0, chunks, true, &mut 0)
+}
+
+/// The state of a range verification.
+struct Verifier<'a> {
+    outboard: &'a [u8],
+    data: &'a [u8],
+    first: usize,
+    len: u64,
+}
+
+impl Verifier<'_> {
+    /// Verifies the subtree of `n` chunks starting at chunk `start` against the expected hash,
+    /// reading interior nodes from the outboard encoding starting at `cursor`.
+    fn verify_node<H: Cyclist + Default>(
+        &self,
+        expected: &Hash,
+        start: usize,
+        n: usize,
+        root: bool,
+        cursor: &mut usize,
+    ) -> bool {
+        let root_len = root.then_some(self.len);
+        let last = self.first + self.data.len().div_ceil(CHUNK_LEN).max(1);
+
+        // Skip subtrees which don't overlap the range, trusting their hashes.
+        if start + n <= self.first || last <= start {
+            *cursor += (n - 1) * 2 * HASH_LEN;
+            return true;
+        }
+
+        if n == 1 {
+            let begin = (start - self.first) * CHUNK_LEN;
+            let end = (begin + CHUNK_LEN).min(self.data.len());
+            return hash_leaf::<H>(&self.data[begin..end], start, root_len) == *expected;
+        }
+
+        let pair = &self.outboard[*cursor..*cursor + 2 * HASH_LEN];
+        *cursor += 2 * HASH_LEN;
+        let (left, right) = pair.split_at(HASH_LEN);
+        let (left, right): (Hash, Hash) =
+            (left.try_into().expect("invalid hash"), right.try_into().expect("invalid hash"));
+        if hash_parent::<H>(&left, &right, root_len) != *expected {
+            return false;
+        }
+
+        let split = left_chunks(n);
+        self.verify_node::<H>(&left, start, split, false, cursor)
+            && self.verify_node::<H>(&right, start + split, n - split, false, cursor)
+    }
+}
+
+/// Hashes the subtree of `n > 1` chunks starting at chunk `start`, given the hashes of its leaves,
+/// appending its interior nodes to the outboard encoding in pre-order.
+fn encode_node<H: Cyclist + Default>(
+    leaves: &[Hash],
+    start: usize,
+    n: usize,
+    root_len: Option<u64>,
+    outboard: &mut Vec<u8>,
+) -> Hash {
+    if n == 1 {
+        return leaves[start];
+    }
+
+    // Reserve space for this node's children so that they precede its descendants.
+    let pos = outboard.len();
+    outboard.resize(pos + 2 * HASH_LEN, 0);
+
+    let split = left_chunks(n);
+    let left = encode_node::<H>(leaves, start, split, None, outboard);
+    let right = encode_node::<H>(leaves, start + split, n - split, None, outboard);
+    outboard[pos..pos + HASH_LEN].copy_from_slice(&left);
+    outboard[pos + HASH_LEN..pos + 2 * HASH_LEN].copy_from_slice(&right);
+    hash_parent::<H>(&left, &right, root_len)
+}
+
+/// Returns the number of chunks in a file of the given length, if it is addressable.
+pub(crate) fn chunk_count(len: u64) -> Option<usize> {
+    usize::try_from(len).ok().map(|len| len.div_ceil(CHUNK_LEN).max(1))
+}
+
+/// Returns the number of chunks in the left subtree of a node with `n > 1` chunks.
+pub(crate) const fn left_chunks(n: usize) -> usize {
+    1 << (n - 1).ilog2()
+}
+
+/// Hashes a leaf, absorbing the file's length if it is the root.
+pub(crate) fn hash_leaf<H: Cyclist + Default>(
+    chunk: &[u8],
+    index: usize,
+    root_len: Option<u64>,
+) -> Hash {
+    let mut hash = start::<H>(root_len, LEAF_LABEL, ROOT_LEAF_LABEL);
+    hash.absorb(&u64::try_from(index).expect("unexpected overflow").to_le_bytes());
+    hash.absorb(chunk);
+    finish(hash)
+}
+
+/// Hashes a group of at most [`LOCKSTEP`] consecutive chunks as non-root leaves, the first of which
+/// has the given index, running their hashes in lockstep.
+pub(crate) fn hash_leaf_group<H: Cyclist + Default>(group: &[u8], first: usize) -> Vec<Hash> {
+    let chunks = group.chunks(CHUNK_LEN).collect::<Vec<_>>();
+    let indices = (first..first + chunks.len())
+        .map(|i| u64::try_from(i).expect("unexpected overflow").to_le_bytes())
+        .collect::<Vec<_>>();
+
+    let mut hashes =
+        chunks.iter().map(|_| start::<H>(None, LEAF_LABEL, ROOT_LEAF_LABEL)).collect::<Vec<H>>();
+    H::absorb_many(&mut hashes, &indices.iter().map(|index| &index[..]).collect::<Vec<_>>());
+    H::absorb_many(&mut hashes, &chunks);
+
+    let mut leaves = vec![[0u8; HASH_LEN]; chunks.len()];
+    H::squeeze_many(
+        &mut hashes,
+        &mut leaves.iter_mut().map(|leaf| &mut leaf[..]).collect::<Vec<_>>(),
+    );
+    leaves
+}
+
+/// Hashes an interior node, absorbing the file's length if it is the root.
+pub(crate) fn hash_parent<H: Cyclist + Default>(
+    left: &Hash,
+    right: &Hash,
+    root_len: Option<u64>,
+) -> Hash {
+    let mut hash = start::<H>(root_len, PARENT_LABEL, ROOT_PARENT_LABEL);
+    hash.absorb(left);
+    hash.absorb(right);
+    finish(hash)
+}
+
+/// Starts hashing a node with the appropriate label.
+fn start<H: Cyclist + Default>(root_len: Option<u64>, label: &[u8], root_label: &[u8]) -> H {
+    let mut hash = H::default();
+    match root_len {
+        Some(len) => {
+            hash.absorb(&len.to_le_bytes());
+            hash.absorb(root_label);
+        }
+        None => hash.absorb(label),
+    }
+    hash
+}
+
+/// Squeezes a node hash.
+fn finish<H: Cyclist>(mut hash: H) -> Hash {
+    let mut out = [0u8; HASH_LEN];
+    hash.squeeze_mut(&mut out);
+    out
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakHash;
+
+    use super::*;
+
+    /// Returns pseudorandom test data.
+    fn data(len: usize) -> Vec<u8> {
+        let mut hash = XoodyakHash::default();
+        hash.absorb(b"test data");
+        hash.squeeze(len)
+    }
+
+    /// Verifies the given range of the file, reading only the necessary chunks.
+    fn verify(root: &Hash, outboard: &[u8], file: &[u8], offset: usize, len: usize) -> bool {
+        let to_u64 = |n: usize| u64::try_from(n).expect("unexpected overflow");
+        let to_usize = |n: u64| usize::try_from(n).expect("unexpected overflow");
+        let range = chunk_range(to_u64(offset), to_u64(len), to_u64(file.len()));
+        let chunks = &file[to_usize(range.start)..to_usize(range.end)];
+        verify_range::<XoodyakHash>(root, outboard, range.start, chunks)
+    }
+
+    #[test]
+    fn round_trip() {
+        for len in
+            [0, 1, CHUNK_LEN, CHUNK_LEN + 1, 3 * CHUNK_LEN, 5 * CHUNK_LEN - 7, 16 * CHUNK_LEN]
+        {
+            let file = data(len);
+            let (root, outboard) = encode_outboard::<XoodyakHash>(&file);
+            assert_eq!(8 + 2 * HASH_LEN * (len.div_ceil(CHUNK_LEN).max(1) - 1), outboard.len());
+
+            for offset in (0..len.max(1)).step_by(CHUNK_LEN / 3) {
+                for range_len in [0, 1, CHUNK_LEN, 2 * CHUNK_LEN + 5] {
+                    assert!(
+                        verify(&root, &outboard, &file, offset, range_len),
+                        "len={} offset={} range_len={}",
+                        len,
+                        offset,
+                        range_len,
+                    );
+                }
+            }
+            assert!(verify_range::<XoodyakHash>(&root, &outboard, 0, &file));
+        }
+    }
+
+    #[test]
+    fn modified_data() {
+        let mut file = data(10 * CHUNK_LEN + 10);
+        let (root, outboard) = encode_outboard::<XoodyakHash>(&file);
+
+        file[4 * CHUNK_LEN + 100] ^= 1;
+        assert!(!verify(&root, &outboard, &file, 4 * CHUNK_LEN + 5, 1));
+        assert!(!verify(&root, &outboard, &file, 0, file.len()));
+
+        // Ranges which don't include the modified chunk still verify.
+        assert!(verify(&root, &outboard, &file, 0, CHUNK_LEN));
+        assert!(verify(&root, &outboard, &file, 9 * CHUNK_LEN, 20));
+    }
+
+    #[test]
+    fn modified_outboard() {
+        let file = data(10 * CHUNK_LEN + 10);
+        let (root, outboard) = encode_outboard::<XoodyakHash>(&file);
+
+        for i in 0..outboard.len() {
+            let mut bad = outboard.clone();
+            bad[i] ^= 1;
+            assert!(!verify(&root, &bad, &file, 0, file.len()), "i={}", i);
+        }
+
+        assert!(!verify(&root, &outboard[..outboard.len() - 1], &file, 0, 1));
+        assert!(!verify_range::<XoodyakHash>(&root, &[], 0, &file));
+    }
+
+    #[test]
+    fn misaligned_ranges() {
+        let file = data(4 * CHUNK_LEN);
+        let (root, outboard) = encode_outboard::<XoodyakHash>(&file);
+
+        assert!(!verify_range::<XoodyakHash>(&root, &outboard, 1, &file[1..CHUNK_LEN]));
+        assert!(!verify_range::<XoodyakHash>(&root, &outboard, 0, &file[..CHUNK_LEN - 1]));
+        assert!(!verify_range::<XoodyakHash>(&root, &outboard, 0, &[]));
+        assert!(!verify_range::<XoodyakHash>(&root, &outboard, 4 * 1024, &[]));
+    }
+
+    #[test]
+    fn chunks_are_positional() {
+        let mut file = data(2 * CHUNK_LEN);
+        let (root, _) = encode_outboard::<XoodyakHash>(&file);
+
+        file.rotate_left(CHUNK_LEN);
+        assert_ne!(root, encode_outboard::<XoodyakHash>(&file).0);
+        assert_ne!(root, encode_outboard::<XoodyakHash>(&file[..CHUNK_LEN]).0);
+    }
+
+    #[test]
+    fn left_subtrees() {
+        assert_eq!(1, left_chunks(2));
+        assert_eq!(2, left_chunks(3));
+        assert_eq!(2, left_chunks(4));
+        assert_eq!(4, left_chunks(5));
+        assert_eq!(8, left_chunks(9));
+    }
+}
@@ -0,0 +1,132 @@
+#![cfg(feature = "rayon")]
+
+//! Multi-core hashing and bulk encryption using [rayon].
+//!
+//! Cyclist's hash and keyed modes are strictly serial, so hashing or sealing a 1 GiB buffer uses a
+//! single core no matter how many are available. This module provides parallel alternatives which
+//! split the input into independent pieces and then combine the results:
+//!
+//! * [`par_hash`] hashes a buffer as the same tree of chunks as [`outboard`](crate::outboard),
+//!   hashing its leaves and subtrees in parallel. The result is identical to the root hash returned by
+//!   [`encode_outboard`](crate::outboard::encode_outboard), but not to a [`CyclistHash`] digest of
+//!   the buffer.
+//! * [`Rayon`] is a [`bulk`](crate::bulk) mode [`Executor`], which seals or opens independently
+//!   keyed segments in parallel before the session absorbs each segment's tag in order.
+//!
+//! ```rust
+//! use cyclist::par::{par_hash, Rayon};
+//! use cyclist::xoodyak::{XoodyakHash, XoodyakKeyed};
+//!
+//! let buf = vec![0xCAu8; 1 << 20];
+//! let digest = par_hash::<XoodyakHash>(&buf);
+//!
+//! let mut sealed = buf.clone();
+//! sealed.extend([0u8; 16]);
+//! let mut sealer = XoodyakKeyed::new(b"This is a secret key!", b"This is a nonce!", b"");
+//! sealer.bulk_seal_mut_with(&mut sealed, 64 * 1024, &Rayon);
+//!
+//! let mut opener = XoodyakKeyed::new(b"This is a secret key!", b"This is a nonce!", b"");
+//! assert_eq!(Ok(buf), opener.bulk_open(&sealed, 64 * 1024));
+//! ```
+//!
+//! Work is scheduled on the current rayon thread pool, which is the global pool unless the call is
+//! made from within [`rayon::ThreadPool::install`]. Applications which need to bound the CPU
+//! budget of these operations should install a dedicated pool.
+//!
+//! [`CyclistHash`]: crate::CyclistHash
+
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+use rayon::slice::ParallelSlice;
+
+use crate::bulk::Executor;
+use crate::outboard::{
+    chunk_count, hash_leaf, hash_leaf_group, hash_parent, left_chunks, CHUNK_LEN, HASH_LEN,
+};
+use crate::{Cyclist, LOCKSTEP};
+
+/// The number of chunks below which subtrees are hashed on a single thread.
+const SERIAL_CHUNKS: usize = 16;
+
+/// Hashes `data` in parallel, returning the root hash of its [`outboard`](crate::outboard) tree.
+pub fn par_hash<H: Cyclist + Default>(data: &[u8]) -> [u8; HASH_LEN] {
+    let len = u64::try_from(data.len()).expect("unexpected overflow");
+    let chunks = chunk_count(len).expect("unexpected overflow");
+    if chunks == 1 {
+        return hash_leaf::<H>(data, 0, Some(len));
+    }
+
+    let leaves = data
+        .par_chunks(LOCKSTEP * CHUNK_LEN)
+        .enumerate()
+        .flat_map_iter(|(i, group)| hash_leaf_group::<H>(group, i * LOCKSTEP))
+        .collect::<Vec<_>>();
+    hash_node::<H>(&leaves, 0, chunks, Some(len))
+}
+
+/// Hashes the subtree of `n` chunks starting at chunk `start`, given the hashes of its leaves, in
+/// parallel if it is large enough.
+fn hash_node<H: Cyclist + Default>(
+    leaves: &[[u8; HASH_LEN]],
+    start: usize,
+    n: usize,
+    root_len: Option<u64>,
+) -> [u8; HASH_LEN] {
+    if n == 1 {
+        return leaves[start];
+    }
+
+    let split = left_chunks(n);
+    let left = || hash_node::<H>(leaves, start, split, None);
+    let right = || hash_node::<H>(leaves, start + split, n - split, None);
+    let (left, right) =
+        if n < SERIAL_CHUNKS { (left(), right()) } else { rayon::join(left, right) };
+    hash_parent::<H>(&left, &right, root_len)
+}
+
+/// A bulk mode [`Executor`] which processes segments in parallel on the current rayon thread pool.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Rayon;
+
+impl Executor for Rayon {
+    fn map<T, F>(&self, segments: Vec<&mut [u8]>, f: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(usize, &mut [u8]) -> T + Sync,
+    {
+        segments.into_par_iter().enumerate().map(|(i, segment)| f(i, segment)).collect()
+    }
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::outboard::encode_outboard;
+    use crate::xoodyak::{XoodyakHash, XoodyakKeyed};
+
+    use super::*;
+
+    #[test]
+    fn par_hash_matches_outboard_root() {
+        for len in [0, 1, CHUNK_LEN, CHUNK_LEN + 1, 17 * CHUNK_LEN - 3, 100 * CHUNK_LEN] {
+            let data = vec![0xCAu8; len];
+            let (root, _) = encode_outboard::<XoodyakHash>(&data);
+            assert_eq!(root, par_hash::<XoodyakHash>(&data), "len={len}");
+        }
+    }
+
+    #[test]
+    fn bulk_seal_matches_serial() {
+        let m = vec![0xCAu8; 10_000];
+        let mut serial = XoodyakKeyed::new(b"ok then", b"", b"");
+        let c = serial.bulk_seal(&m, 64);
+
+        let mut c_p = m.clone();
+        c_p.extend([0u8; 16]);
+        let mut parallel = XoodyakKeyed::new(b"ok then", b"", b"");
+        parallel.bulk_seal_mut_with(&mut c_p, 64, &Rayon);
+        assert_eq!(c, c_p);
+
+        let mut opener = XoodyakKeyed::new(b"ok then", b"", b"");
+        assert_eq!(Ok(()), opener.bulk_open_mut_with(&mut c_p, 64, &Rayon));
+        assert_eq!(m, c_p[..m.len()]);
+    }
+}
@@ -0,0 +1,208 @@
+#![cfg(feature = "std")]
+
+//! A registry of well-known personalization labels.
+//!
+//! Protocols built on Cyclist typically absorb a personalization label (e.g. `myproto.handshake`)
+//! to separate their hashes from those of every other protocol. If two protocols accidentally use
+//! the same label, their outputs are interchangeable. The registry records which protocol owns
+//! which label, and refuses to register a label or protocol name which is already taken.
+//!
+//! ```rust
+//! use cyclist::personalization::Personalization;
+//! use cyclist::xoodyak::XoodyakHash;
+//! use cyclist::Cyclist;
+//!
+//! let p = Personalization::register("myproto", b"myproto.handshake").expect("label taken");
+//! assert_eq!(Some(p), Personalization::lookup("myproto"));
+//!
+//! // Label collisions are detected at registration.
+//! assert!(Personalization::register("yourproto", b"myproto.handshake").is_err());
+//!
+//! let mut hash = XoodyakHash::default();
+//! p.absorb_into(&mut hash);
+//! hash.absorb(b"handshake transcript");
+//! ```
+//!
+//! The registry starts with the [`WELL_KNOWN`] labels, whose identifiers are stable for a given
+//! [`REGISTRY_VERSION`]. Registrations are process-wide, and are assigned identifiers in the order
+//! they are made. Because Cyclist absorb operations are length-delimited, only identical labels
+//! collide; a label which is a prefix of another is distinct from it.
+
+use std::fmt;
+use std::sync::{PoisonError, RwLock};
+
+use crate::Cyclist;
+
+/// The version of the registry. Incremented whenever [`WELL_KNOWN`] changes.
+pub const REGISTRY_VERSION: u32 = 2;
+
+/// The well-known personalization labels used by this crate, in identifier order.
+pub const WELL_KNOWN: &[Personalization] = &[
+    Personalization::well_known(0, "cyclist.cdc.gear", crate::cdc::GEAR_LABEL),
+    Personalization::well_known(1, "cyclist.cdc.chunk", crate::cdc::CHUNK_LABEL),
+    Personalization::well_known(2, "cyclist.cdc.manifest", crate::cdc::MANIFEST_LABEL),
+    Personalization::well_known(3, "cyclist.hasher", crate::hasher::HASHER_LABEL),
+    Personalization::well_known(4, "cyclist.onion", crate::onion::ONION_LABEL),
+    Personalization::well_known(5, "cyclist.outboard.leaf", crate::outboard::LEAF_LABEL),
+    Personalization::well_known(6, "cyclist.outboard.parent", crate::outboard::PARENT_LABEL),
+    Personalization::well_known(7, "cyclist.outboard.root-leaf", crate::outboard::ROOT_LEAF_LABEL),
+    Personalization::well_known(
+        8,
+        "cyclist.outboard.root-parent",
+        crate::outboard::ROOT_PARENT_LABEL,
+    ),
+    Personalization::well_known(9, "cyclist.shuffle", crate::shuffle::SHUFFLE_LABEL),
+];
+
+/// Personalizations registered at runtime, after the [`WELL_KNOWN`] labels.
+static REGISTERED: RwLock<Vec<Personalization>> = RwLock::new(Vec::new());
+
+/// A registered personalization label and the protocol which owns it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Personalization {
+    id: u32,
+    name: &'static str,
+    label: &'static [u8],
+}
+
+impl Personalization {
+    /// Registers the given personalization label as belonging to the named protocol.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NameTaken`] if a personalization is already registered with the given name,
+    /// and [`Error::LabelTaken`] if one is already registered with the given label.
+    pub fn register(name: &'static str, label: &'static [u8]) -> Result<Personalization, Error> {
+        let mut registered = REGISTERED.write().unwrap_or_else(PoisonError::into_inner);
+        for &existing in WELL_KNOWN.iter().chain(registered.iter()) {
+            if existing.name == name {
+                return Err(Error::NameTaken(existing));
+            }
+            if existing.label == label {
+                return Err(Error::LabelTaken(existing));
+            }
+        }
+
+        let id = u32::try_from(WELL_KNOWN.len() + registered.len()).expect("unexpected overflow");
+        let p = Personalization { id, name, label };
+        registered.push(p);
+        Ok(p)
+    }
+
+    /// Returns the personalization registered with the given name, if any.
+    pub fn lookup(name: &str) -> Option<Personalization> {
+        Self::find(|p| p.name == name)
+    }
+
+    /// Returns the personalization registered with the given label, if any.
+    pub fn lookup_label(label: &[u8]) -> Option<Personalization> {
+        Self::find(|p| p.label == label)
+    }
+
+    /// Returns the personalization registered with the given identifier, if any.
+    pub fn lookup_id(id: u32) -> Option<Personalization> {
+        Self::find(|p| p.id == id)
+    }
+
+    /// Returns the personalization's identifier, a compact alternative to its name.
+    pub const fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Returns the name of the protocol which owns the personalization.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the personalization label.
+    pub const fn label(&self) -> &'static [u8] {
+        self.label
+    }
+
+    /// Absorbs the personalization label into the given Cyclist object.
+    pub fn absorb_into<C: Cyclist + ?Sized>(&self, cyclist: &mut C) {
+        cyclist.absorb(self.label);
+    }
+
+    /// Returns a well-known personalization.
+    const fn well_known(id: u32, name: &'static str, label: &'static [u8]) -> Personalization {
+        Personalization { id, name, label }
+    }
+
+    /// Returns the first registered personalization matching the predicate.
+    fn find(f: impl Fn(&Personalization) -> bool) -> Option<Personalization> {
+        let registered = REGISTERED.read().unwrap_or_else(PoisonError::into_inner);
+        WELL_KNOWN.iter().chain(registered.iter()).copied().find(f)
+    }
+}
+
+/// An error registering a personalization.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The protocol name is already registered.
+    NameTaken(Personalization),
+
+    /// The personalization label is already registered.
+    LabelTaken(Personalization),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NameTaken(p) => write!(f, "name already registered: {}", p.name),
+            Error::LabelTaken(p) => write!(f, "label already registered by {}", p.name),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn well_known_labels() {
+        for (i, p) in WELL_KNOWN.iter().enumerate() {
+            assert_eq!(i, usize::try_from(p.id()).expect("unexpected overflow"));
+            assert_eq!(p.name().as_bytes(), p.label());
+            assert_eq!(Some(*p), Personalization::lookup(p.name()));
+            assert_eq!(Some(*p), Personalization::lookup_label(p.label()));
+            assert_eq!(Some(*p), Personalization::lookup_id(p.id()));
+        }
+    }
+
+    #[test]
+    fn registration() {
+        let p = Personalization::register("test.registration", b"test.registration.label")
+            .expect("should register");
+        assert!(usize::try_from(p.id()).expect("unexpected overflow") >= WELL_KNOWN.len());
+        assert_eq!(Some(p), Personalization::lookup("test.registration"));
+        assert_eq!(Some(p), Personalization::lookup_label(b"test.registration.label"));
+        assert_eq!(Some(p), Personalization::lookup_id(p.id()));
+        assert_eq!(None, Personalization::lookup("test.unregistered"));
+    }
+
+    #[test]
+    fn collisions() {
+        let p = Personalization::register("test.collisions", b"test.collisions.label")
+            .expect("should register");
+
+        assert_eq!(
+            Err(Error::NameTaken(p)),
+            Personalization::register("test.collisions", b"test.collisions.other"),
+        );
+        assert_eq!(
+            Err(Error::LabelTaken(p)),
+            Personalization::register("test.collisions.other", b"test.collisions.label"),
+        );
+        assert_eq!(
+            Err(Error::LabelTaken(WELL_KNOWN[3])),
+            Personalization::register("test.hasher", b"cyclist.hasher"),
+        );
+
+        // Prefixes are not collisions.
+        assert!(Personalization::register("test.prefix", b"test.collisions").is_ok());
+    }
+}
@@ -0,0 +1,418 @@
+//! A portable, versioned format for exported states.
+//!
+//! [`CyclistHash::to_bytes`] and [`CyclistKeyed::to_bytes`] export only the phase and permutation
+//! state, and rely on the caller to know which scheme produced them. The portable format also
+//! identifies the format version and the scheme, and includes the session's operation counts (see
+//! [`metrics`](crate::metrics)), so a session exported by one build (e.g. on a big-endian
+//! microcontroller) can be validated and resumed by another (e.g. on an x86 server):
+//!
+//! ```rust
+//! use cyclist::xoodyak::XoodyakKeyed;
+//! use cyclist::Cyclist;
+//!
+//! let mut session = XoodyakKeyed::new(b"This is a secret key!", b"", b"");
+//! let ciphertext = session.seal(b"This is the plaintext!");
+//!
+//! let exported = session.to_portable_bytes();
+//! let mut resumed = XoodyakKeyed::from_portable_bytes(&exported).expect("should import");
+//! assert_eq!(1, resumed.session_info().seals);
+//! assert_eq!(session.seal(b"message"), resumed.seal(b"message"));
+//! ```
+//!
+//! An exported keyed session is as sensitive as its key.
+//!
+//! # Format
+//!
+//! All integers are little-endian.
+//!
+//! | Offset        | Length  | Field                                                        |
+//! |---------------|---------|--------------------------------------------------------------|
+//! | 0             | 1       | Format version ([`VERSION`])                                 |
+//! | 1             | 2       | Permutation ID ([`Permutation::ID`])                         |
+//! | 3             | 2       | Permutation width in bytes                                   |
+//! | 5             | 2       | Absorb rate in bytes                                         |
+//! | 7             | 2       | Squeeze rate in bytes                                        |
+//! | 9             | 2       | Ratchet rate in bytes (zero in hash mode)                    |
+//! | 11            | 1       | Tag length in bytes (zero in hash mode)                      |
+//! | 12            | 1       | Flags: `0x02` if keyed, `0x01` if in the up phase            |
+//! | 13            | `WIDTH` | Permutation state, in canonical (little-endian lane) order   |
+//! | 13 + `WIDTH`  | 40      | Permutations, ratchets, seals, opens, and failures (`u64`s)  |
+//!
+//! The first 12 bytes identify the scheme. A state can only be imported by a scheme with the same
+//! permutation ID and parameters.
+
+use core::fmt;
+
+use crate::{CyclistCore, CyclistHash, CyclistKeyed, Permutation, Stats};
+
+/// The current version of the portable format.
+pub const VERSION: u8 = 1;
+
+/// The length of the header (version and scheme ID) in bytes.
+const HEADER_LEN: usize = 12;
+
+/// The length of the operation counts in bytes.
+const COUNTS_LEN: usize = 5 * 8;
+
+/// An error importing a portable state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The state has the wrong length for the scheme.
+    InvalidLength,
+
+    /// The state was exported with an unsupported version of the format.
+    UnsupportedVersion,
+
+    /// The state was exported by a different scheme.
+    SchemeMismatch,
+
+    /// The state was exported in a different mode (i.e. hash or keyed).
+    ModeMismatch,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Error::InvalidLength => "invalid state length",
+            Error::UnsupportedVersion => "unsupported state version",
+            Error::SchemeMismatch => "state exported by a different scheme",
+            Error::ModeMismatch => "state exported in a different mode",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// A hash or keyed duplex which can be exported and imported in the portable format, for code which
+/// is generic over schemes (e.g. [`harness::portable`](crate::harness::portable)).
+pub trait Portable: Sized {
+    /// Returns the length of a portable exported state in bytes.
+    fn portable_len() -> usize;
+
+    /// Exports the state in the portable format to the given slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` is not [`Portable::portable_len`] bytes long.
+    fn write_portable_bytes(&self, out: &mut [u8]);
+
+    /// Returns an instance with the given state in the portable format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if the state is malformed or was exported by a different scheme.
+    fn from_portable_bytes(bytes: &[u8]) -> Result<Self, Error>;
+}
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize> Portable for CyclistHash<P, WIDTH, HASH_RATE>
+where
+    P: Permutation<WIDTH>,
+{
+    fn portable_len() -> usize {
+        Self::portable_len()
+    }
+
+    fn write_portable_bytes(&self, out: &mut [u8]) {
+        self.to_portable_bytes_mut(out);
+    }
+
+    fn from_portable_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_portable_bytes(bytes)
+    }
+}
+
+impl<
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > Portable for CyclistKeyed<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+{
+    fn portable_len() -> usize {
+        Self::portable_len()
+    }
+
+    fn write_portable_bytes(&self, out: &mut [u8]) {
+        self.to_portable_bytes_mut(out);
+    }
+
+    fn from_portable_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_portable_bytes(bytes)
+    }
+}
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize> CyclistHash<P, WIDTH, HASH_RATE>
+where
+    P: Permutation<WIDTH>,
+{
+    /// Returns the length of a portable exported state in bytes.
+    pub const fn portable_len() -> usize {
+        HEADER_LEN + 1 + WIDTH + COUNTS_LEN
+    }
+
+    /// Exports the hash's state in the portable format to the given slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` is not [`CyclistHash::portable_len`] bytes long.
+    pub fn to_portable_bytes_mut(&self, out: &mut [u8]) {
+        export(&self.core, 0, out);
+    }
+
+    /// Returns the hash's state in the portable format. See [`CyclistHash::to_portable_bytes_mut`].
+    #[cfg(feature = "std")]
+    pub fn to_portable_bytes(&self) -> Vec<u8> {
+        let mut out = vec![0u8; Self::portable_len()];
+        self.to_portable_bytes_mut(&mut out);
+        out
+    }
+
+    /// Returns a hash with the given state in the portable format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if the state is malformed or was exported by a different scheme.
+    pub fn from_portable_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(CyclistHash { core: import(bytes, 0)? })
+    }
+}
+
+impl<
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > CyclistKeyed<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+{
+    /// Returns the length of a portable exported state in bytes.
+    pub const fn portable_len() -> usize {
+        HEADER_LEN + 1 + WIDTH + COUNTS_LEN
+    }
+
+    /// Exports the session's state in the portable format to the given slice.
+    ///
+    /// The exported state is as sensitive as the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` is not [`CyclistKeyed::portable_len`] bytes long.
+    pub fn to_portable_bytes_mut(&self, out: &mut [u8]) {
+        export(&self.core, TAG_LEN, out);
+    }
+
+    /// Returns the session's state in the portable format. See
+    /// [`CyclistKeyed::to_portable_bytes_mut`].
+    #[cfg(feature = "std")]
+    pub fn to_portable_bytes(&self) -> Vec<u8> {
+        let mut out = vec![0u8; Self::portable_len()];
+        self.to_portable_bytes_mut(&mut out);
+        out
+    }
+
+    /// Returns a session with the given state in the portable format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if the state is malformed or was exported by a different scheme.
+    pub fn from_portable_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(CyclistKeyed { core: import(bytes, TAG_LEN)? })
+    }
+}
+
+/// Writes the header of the given scheme to the given array.
+fn header<
+    P,
+    const WIDTH: usize,
+    const ABSORB_RATE: usize,
+    const SQUEEZE_RATE: usize,
+    const RATCHET_RATE: usize,
+>(
+    tag_len: usize,
+) -> [u8; HEADER_LEN]
+where
+    P: Permutation<WIDTH>,
+{
+    let u16_le = |n: usize| u16::try_from(n).expect("parameter too large").to_le_bytes();
+
+    let mut header = [0u8; HEADER_LEN];
+    header[0] = VERSION;
+    header[1..3].copy_from_slice(&P::ID.to_le_bytes());
+    header[3..5].copy_from_slice(&u16_le(WIDTH));
+    header[5..7].copy_from_slice(&u16_le(ABSORB_RATE));
+    header[7..9].copy_from_slice(&u16_le(SQUEEZE_RATE));
+    header[9..11].copy_from_slice(&u16_le(RATCHET_RATE));
+    header[11] = u8::try_from(tag_len).expect("parameter too large");
+    header
+}
+
+/// Writes the given core's state in the portable format to the given slice.
+fn export<
+    P,
+    const WIDTH: usize,
+    const KEYED: bool,
+    const ABSORB_RATE: usize,
+    const SQUEEZE_RATE: usize,
+    const RATCHET_RATE: usize,
+>(
+    core: &CyclistCore<P, WIDTH, KEYED, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE>,
+    tag_len: usize,
+    out: &mut [u8],
+) where
+    P: Permutation<WIDTH>,
+{
+    assert_eq!(out.len(), HEADER_LEN + 1 + WIDTH + COUNTS_LEN, "invalid state length");
+
+    let (head, out) = out.split_at_mut(HEADER_LEN);
+    head.copy_from_slice(&header::<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE>(tag_len));
+
+    let (state, counts) = out.split_at_mut(WIDTH + 1);
+    core.export(state);
+
+    let stats = &core.stats;
+    for (b, n) in counts.chunks_mut(8).zip([
+        stats.permutations,
+        stats.ratchets,
+        stats.seals,
+        stats.opens,
+        stats.failures,
+    ]) {
+        b.copy_from_slice(&n.to_le_bytes());
+    }
+}
+
+/// Returns a core with the given state in the portable format.
+fn import<
+    P,
+    const WIDTH: usize,
+    const KEYED: bool,
+    const ABSORB_RATE: usize,
+    const SQUEEZE_RATE: usize,
+    const RATCHET_RATE: usize,
+>(
+    bytes: &[u8],
+    tag_len: usize,
+) -> Result<CyclistCore<P, WIDTH, KEYED, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE>, Error>
+where
+    P: Permutation<WIDTH>,
+{
+    let expected = header::<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE>(tag_len);
+    match bytes.first() {
+        Some(&VERSION) => {}
+        Some(_) => return Err(Error::UnsupportedVersion),
+        None => return Err(Error::InvalidLength),
+    }
+    if bytes.len() < HEADER_LEN {
+        return Err(Error::InvalidLength);
+    }
+    if bytes[..HEADER_LEN] != expected {
+        return Err(Error::SchemeMismatch);
+    }
+    if bytes.len() != HEADER_LEN + 1 + WIDTH + COUNTS_LEN {
+        return Err(Error::InvalidLength);
+    }
+
+    let (state, counts) = bytes[HEADER_LEN..].split_at(WIDTH + 1);
+    let mut core = CyclistCore::import(state).ok_or(Error::ModeMismatch)?;
+
+    let mut counts =
+        counts.chunks(8).map(|b| u64::from_le_bytes(b.try_into().expect("invalid count length")));
+    let mut next = || counts.next().expect("missing count");
+    core.stats = Stats {
+        permutations: next(),
+        ratchets: next(),
+        seals: next(),
+        opens: next(),
+        failures: next(),
+    };
+    Ok(core)
+}
+
+#[cfg(all(test, feature = "std", feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::{XoodyakHash, XoodyakKeyed};
+    use crate::Cyclist;
+
+    use super::*;
+
+    #[test]
+    fn hash_known_answer() {
+        let mut hash = XoodyakHash::default();
+        hash.absorb(b"portable");
+        hash.absorb(b"state");
+        let exported = hash.to_portable_bytes();
+        assert_eq!(XoodyakHash::portable_len(), exported.len());
+
+        // Permutation states are little-endian lanes, so this is the same on every platform.
+        assert_eq!(
+            [
+                0x01, 0x01, 0x00, 0x30, 0x00, 0x10, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04,
+                0x0e, 0x74, 0xe2, 0x7e, 0xb3, 0x9d, 0xe0, 0x67, 0x07, 0x34, 0x47, 0xf7, 0x7f, 0xfb,
+                0x78, 0xdf, 0x21, 0xe7, 0x7d, 0x0c, 0x16, 0x85, 0xf1, 0x2e, 0xe4, 0xd1, 0xfa, 0x4e,
+                0x04, 0x7c, 0x2b, 0x05, 0x24, 0x51, 0x46, 0x1f, 0x20, 0xf7, 0x50, 0xa4, 0xb7, 0xe4,
+                0xc7, 0xca, 0xd7, 0xbb, 0xf9, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x00, 0x00,
+            ],
+            exported.as_slice()
+        );
+
+        let mut resumed = XoodyakHash::from_portable_bytes(&exported).expect("should import");
+        assert_eq!(hash.squeeze(32), resumed.squeeze(32));
+        assert_eq!(hash.session_info(), resumed.session_info());
+    }
+
+    #[test]
+    fn keyed_known_answer() {
+        let mut keyed = XoodyakKeyed::new(b"ok then", b"", b"");
+        let c = keyed.seal(b"it's a deal");
+        let exported = keyed.to_portable_bytes();
+        assert_eq!(
+            [0x01, 0x01, 0x00, 0x30, 0x00, 0x2c, 0x00, 0x18, 0x00, 0x10, 0x00, 0x10, 0x03],
+            exported[..13]
+        );
+
+        let mut resumed = XoodyakKeyed::from_portable_bytes(&exported).expect("should import");
+        assert_eq!(keyed.session_info(), resumed.session_info());
+        assert_eq!(keyed.seal(b"ok"), resumed.seal(b"ok"));
+
+        let opener = XoodyakKeyed::new(b"ok then", b"", b"");
+        let mut opener =
+            XoodyakKeyed::from_portable_bytes(&opener.to_portable_bytes()).expect("should import");
+        assert_eq!(Ok(b"it's a deal".to_vec()), opener.open(&c));
+    }
+
+    #[test]
+    fn invalid_states() {
+        let hash = XoodyakHash::default().to_portable_bytes();
+        let keyed = XoodyakKeyed::new(b"ok then", b"", b"").to_portable_bytes();
+
+        assert_eq!(Err(Error::InvalidLength), XoodyakHash::from_portable_bytes(&[]).map(drop));
+        assert_eq!(
+            Err(Error::InvalidLength),
+            XoodyakHash::from_portable_bytes(&hash[..hash.len() - 1]).map(drop)
+        );
+        assert_eq!(Err(Error::SchemeMismatch), XoodyakHash::from_portable_bytes(&keyed).map(drop));
+
+        let mut bad = hash.clone();
+        bad[0] = 2;
+        assert_eq!(
+            Err(Error::UnsupportedVersion),
+            XoodyakHash::from_portable_bytes(&bad).map(drop)
+        );
+
+        let mut bad = hash;
+        bad[12] = 0x02;
+        assert_eq!(Err(Error::ModeMismatch), XoodyakHash::from_portable_bytes(&bad).map(drop));
+    }
+}
@@ -0,0 +1,132 @@
+//! Precomputed keys for sealing many messages with the same key.
+//!
+//! [`CyclistKeyed::new`] absorbs the key every time it's called, which costs at least one permutation
+//! (and one per byte of counter). Servers which seal millions of small records with the same key
+//! can instead absorb the key once into a [`PrecomputedKey`], and cheaply clone its state for each
+//! message:
+//!
+//! ```rust
+//! use cyclist::xoodyak::{XoodyakKey, XoodyakKeyed};
+//! use cyclist::Cyclist;
+//!
+//! let key = XoodyakKey::new(b"This is a secret key!", b"");
+//!
+//! let mut sealer = key.with_nonce(b"This is a nonce!");
+//! let ciphertext = sealer.seal(b"This is the plaintext!");
+//!
+//! // It's equivalent to absorbing the key and the nonce for each message.
+//! let mut opener = XoodyakKeyed::new(b"This is a secret key!", b"", b"");
+//! opener.absorb(b"This is a nonce!");
+//! assert_eq!(Ok(b"This is the plaintext!".to_vec()), opener.open(&ciphertext));
+//! ```
+
+use crate::{CyclistKeyed, Error, Permutation};
+
+/// A key which has been absorbed into a keyed duplex, ready to be cloned for each message.
+#[derive(Clone, Debug)]
+pub struct PrecomputedKey<
+    P,
+    const WIDTH: usize,
+    const ABSORB_RATE: usize,
+    const SQUEEZE_RATE: usize,
+    const RATCHET_RATE: usize,
+    const TAG_LEN: usize,
+> where
+    P: Permutation<WIDTH>,
+{
+    keyed: CyclistKeyed<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>,
+}
+
+impl<
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > PrecomputedKey<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+{
+    /// Absorbs the given key and optional key ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is empty or the combined key and key ID length is not less than the absorb
+    /// rate.
+    pub fn new(key: &[u8], key_id: &[u8]) -> Self {
+        PrecomputedKey { keyed: CyclistKeyed::new(key, key_id, b"") }
+    }
+
+    /// Absorbs the given key and optional key ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::EmptyKey`] if the key is empty and [`Error::KeyTooLong`] if the combined key
+    /// and key ID length is not less than the absorb rate.
+    pub fn try_new(key: &[u8], key_id: &[u8]) -> Result<Self, Error> {
+        Ok(PrecomputedKey { keyed: CyclistKeyed::try_new(key, key_id, b"")? })
+    }
+
+    /// Returns a keyed duplex equivalent to one created with [`CyclistKeyed::new`] with the key,
+    /// key ID, and no counter.
+    pub fn keyed(
+        &self,
+    ) -> CyclistKeyed<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN> {
+        self.keyed.clone()
+    }
+
+    /// Returns a keyed duplex equivalent to one created with [`CyclistKeyed::new`] with the key,
+    /// key ID, and the given counter.
+    pub fn with_counter(
+        &self,
+        counter: &[u8],
+    ) -> CyclistKeyed<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN> {
+        let mut keyed = self.keyed.clone();
+        if !counter.is_empty() {
+            keyed.core.absorb_any(counter, 1, 0x00);
+        }
+        keyed
+    }
+
+    /// Returns a keyed duplex equivalent to one created with [`CyclistKeyed::new`] with the key,
+    /// key ID, and no counter, which has absorbed the given nonce.
+    pub fn with_nonce(
+        &self,
+        nonce: &[u8],
+    ) -> CyclistKeyed<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN> {
+        let mut keyed = self.keyed.clone();
+        keyed.core.absorb(nonce);
+        keyed
+    }
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::{XoodyakKey, XoodyakKeyed};
+    use crate::Cyclist;
+
+    #[test]
+    fn equivalence() {
+        let key = XoodyakKey::new(b"ok then", b"id");
+
+        let mut a = key.keyed();
+        let mut b = XoodyakKeyed::new(b"ok then", b"id", b"");
+        assert_eq!(a.squeeze(16), b.squeeze(16));
+
+        let mut a = key.with_counter(b"counter");
+        let mut b = XoodyakKeyed::new(b"ok then", b"id", b"counter");
+        assert_eq!(a.squeeze(16), b.squeeze(16));
+
+        let mut a = key.with_nonce(b"nonce");
+        let mut b = XoodyakKeyed::new(b"ok then", b"id", b"");
+        b.absorb(b"nonce");
+        assert_eq!(a.squeeze(16), b.squeeze(16));
+    }
+
+    #[test]
+    fn invalid_keys() {
+        assert_eq!(Some(crate::Error::EmptyKey), XoodyakKey::try_new(b"", b"").err());
+        assert_eq!(Some(crate::Error::KeyTooLong), XoodyakKey::try_new(&[0; 44], b"").err());
+    }
+}
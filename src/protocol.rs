@@ -0,0 +1,304 @@
+//! Strobe-style protocol operations over a keyed duplex.
+//!
+//! A [`Protocol`] records every message two parties exchange in a shared transcript, so that
+//! encryption keys and MACs depend on everything which came before them. Each operation absorbs a
+//! distinct operation code before its data, so (for example) data sent in the clear can never be
+//! confused with associated data or a key. This allows handshakes written against `strobe-rs` to be
+//! ported operation-for-operation:
+//!
+//! ```rust
+//! use cyclist::protocol::Protocol;
+//! use cyclist::xoodyak::XoodyakKeyed;
+//!
+//! let mut client = Protocol::<XoodyakKeyed>::new(b"my handshake v1");
+//! let mut server = Protocol::<XoodyakKeyed>::new(b"my handshake v1");
+//!
+//! // Both parties mix in a shared secret (e.g. from a key exchange).
+//! client.key(b"a shared secret");
+//! server.key(b"a shared secret");
+//!
+//! // The client sends a nonce in the clear, then an encrypted message and a MAC.
+//! client.send_clr(b"a nonce");
+//! let mut message = *b"hello";
+//! client.send_enc(&mut message);
+//! let mac = client.send_mac::<16>();
+//!
+//! server.recv_clr(b"a nonce");
+//! server.recv_enc(&mut message);
+//! assert!(server.recv_mac(&mac).is_ok());
+//! assert_eq!(b"hello", &message);
+//! ```
+//!
+//! The send and receive forms of each operation record the same transcript, so two parties' states
+//! stay in sync as long as every message sent by one is received by the other, in order. Unlike
+//! Strobe, a protocol's transcript doesn't record which party sent each message.
+//!
+//! # Construction
+//!
+//! A protocol is a keyed duplex initialized with a fixed domain separation label, which absorbs the
+//! protocol's label. Each operation absorbs a one-byte operation code, followed by:
+//!
+//! * `ad`, `key`, `send_clr`, `recv_clr`: absorbing the data.
+//! * `send_enc`, `recv_enc`: encrypting or decrypting the data.
+//! * `send_mac`, `recv_mac`: squeezing the MAC.
+//! * `prf`: squeezing the output.
+//! * `ratchet`: ratcheting the state.
+//! * `bind_negotiation`: absorbing the offered and chosen lists, each as its length (as a
+//!   little-endian 64-bit integer) followed by each of its identifiers (see
+//!   [`Absorb`](crate::absorb::Absorb)).
+//!
+//! # Downgrade Protection
+//!
+//! Protocols which negotiate algorithms (or versions, or any other security parameters) in the
+//! clear are vulnerable to an attacker who strips the stronger options from an offer. Binding the
+//! negotiation to the transcript with [`Protocol::bind_negotiation`] makes such a downgrade fail
+//! key confirmation, since each party binds the offer it actually sent or received:
+//!
+//! ```rust
+//! use cyclist::protocol::Protocol;
+//! use cyclist::xoodyak::XoodyakKeyed;
+//!
+//! let mut client = Protocol::<XoodyakKeyed>::new(b"my handshake v1");
+//! let mut server = Protocol::<XoodyakKeyed>::new(b"my handshake v1");
+//!
+//! // An attacker strips the client's strongest option.
+//! client.bind_negotiation(&[b"keccyak-256", b"xoodyak"], &[b"xoodyak"]);
+//! server.bind_negotiation(&[b"xoodyak"], &[b"xoodyak"]);
+//!
+//! client.key(b"a shared secret");
+//! server.key(b"a shared secret");
+//! assert!(server.recv_mac(&client.send_mac::<16>()).is_err());
+//! ```
+
+use crate::absorb::Absorb;
+use crate::{Error, Keyed, Tag};
+
+/// The key used to initialize a protocol's keyed duplex.
+const PROTOCOL_LABEL: &[u8] = b"cyclist.protocol";
+
+/// The operation code for initialization.
+const OP_INIT: u8 = 0x01;
+
+/// The operation code for associated data.
+const OP_AD: u8 = 0x02;
+
+/// The operation code for keys.
+const OP_KEY: u8 = 0x03;
+
+/// The operation code for cleartext messages.
+const OP_CLR: u8 = 0x04;
+
+/// The operation code for encrypted messages.
+const OP_ENC: u8 = 0x05;
+
+/// The operation code for MACs.
+const OP_MAC: u8 = 0x06;
+
+/// The operation code for PRF output.
+const OP_PRF: u8 = 0x07;
+
+/// The operation code for ratchets.
+const OP_RATCHET: u8 = 0x08;
+
+/// The operation code for negotiations.
+const OP_NEGOTIATION: u8 = 0x09;
+
+/// A transcript of a protocol between two parties.
+#[derive(Clone, Debug)]
+pub struct Protocol<K> {
+    keyed: K,
+}
+
+impl<K> Protocol<K>
+where
+    K: Keyed,
+{
+    /// Creates a new [`Protocol`] with the given label, which should uniquely identify the protocol
+    /// and its version.
+    pub fn new(label: &[u8]) -> Self {
+        let mut keyed = K::new(PROTOCOL_LABEL, b"", b"");
+        keyed.absorb(&[OP_INIT]);
+        keyed.absorb(label);
+        Protocol { keyed }
+    }
+
+    /// Adds associated data (e.g. protocol metadata or a public key) to the transcript.
+    pub fn ad(&mut self, data: &[u8]) {
+        self.op(OP_AD);
+        self.keyed.absorb(data);
+    }
+
+    /// Adds secret key material (e.g. the output of a key exchange) to the transcript.
+    pub fn key(&mut self, key: &[u8]) {
+        self.op(OP_KEY);
+        self.keyed.absorb(key);
+    }
+
+    /// Adds a message to the transcript which is being sent in the clear.
+    pub fn send_clr(&mut self, data: &[u8]) {
+        self.op(OP_CLR);
+        self.keyed.absorb(data);
+    }
+
+    /// Adds a message to the transcript which was received in the clear.
+    pub fn recv_clr(&mut self, data: &[u8]) {
+        self.op(OP_CLR);
+        self.keyed.absorb(data);
+    }
+
+    /// Encrypts a message to be sent in place. The ciphertext is only confidential if the transcript
+    /// includes a secret key.
+    pub fn send_enc(&mut self, in_out: &mut [u8]) {
+        self.op(OP_ENC);
+        self.keyed.encrypt_mut(in_out);
+    }
+
+    /// Decrypts a received message in place. The plaintext is not authenticated until a subsequent
+    /// call to [`Protocol::recv_mac`] succeeds.
+    pub fn recv_enc(&mut self, in_out: &mut [u8]) {
+        self.op(OP_ENC);
+        self.keyed.decrypt_mut(in_out);
+    }
+
+    /// Returns a MAC of the transcript to be sent.
+    pub fn send_mac<const N: usize>(&mut self) -> Tag<N> {
+        self.op(OP_MAC);
+        let mut mac = [0u8; N];
+        self.keyed.squeeze_mut(&mut mac);
+        Tag(mac)
+    }
+
+    /// Compares a received MAC to a MAC of the transcript in constant time.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidTag`] if the MACs don't match, indicating the two parties'
+    /// transcripts differ.
+    pub fn recv_mac<const N: usize>(&mut self, mac: &Tag<N>) -> Result<(), Error> {
+        if self.send_mac::<N>() == *mac {
+            Ok(())
+        } else {
+            Err(Error::InvalidTag)
+        }
+    }
+
+    /// Fills `out` with pseudorandom output (e.g. a session key) which depends on the transcript.
+    pub fn prf(&mut self, out: &mut [u8]) {
+        self.op(OP_PRF);
+        self.keyed.squeeze_mut(out);
+    }
+
+    /// Ratchets the transcript's state, preventing the recovery of earlier keys or messages if the
+    /// state is later compromised.
+    pub fn ratchet(&mut self) {
+        self.op(OP_RATCHET);
+        self.keyed.ratchet();
+    }
+
+    /// Adds the identifiers of the algorithms one party offered and the other chose to the
+    /// transcript, so that any tampering with the negotiation causes the parties' MACs to differ.
+    ///
+    /// Both parties must call this with the offer and choice as they sent or received them, in the
+    /// order they were sent, before confirming keys with [`Protocol::send_mac`] and
+    /// [`Protocol::recv_mac`].
+    pub fn bind_negotiation(&mut self, offered: &[&[u8]], chosen: &[&[u8]]) {
+        self.op(OP_NEGOTIATION);
+        offered.absorb_into(&mut self.keyed);
+        chosen.absorb_into(&mut self.keyed);
+    }
+
+    /// Absorbs an operation code.
+    fn op(&mut self, op: u8) {
+        self.keyed.absorb(&[op]);
+    }
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakKeyed;
+
+    use super::*;
+
+    fn protocol() -> Protocol<XoodyakKeyed> {
+        let mut protocol = Protocol::new(b"test protocol");
+        protocol.key(b"a shared secret");
+        protocol
+    }
+
+    #[test]
+    fn round_trip() {
+        let (mut a, mut b) = (protocol(), protocol());
+
+        a.ad(b"metadata");
+        b.ad(b"metadata");
+
+        let mut message = *b"a message";
+        a.send_enc(&mut message);
+        assert_ne!(b"a message", &message);
+        b.recv_enc(&mut message);
+        assert_eq!(b"a message", &message);
+        assert_eq!(Ok(()), b.recv_mac(&a.send_mac::<16>()));
+
+        a.ratchet();
+        b.ratchet();
+        let (mut ka, mut kb) = ([0u8; 32], [0u8; 32]);
+        a.prf(&mut ka);
+        b.prf(&mut kb);
+        assert_eq!(ka, kb);
+    }
+
+    #[test]
+    fn diverging_transcripts() {
+        let (mut a, mut b) = (protocol(), protocol());
+        a.send_clr(b"hello");
+        b.recv_clr(b"hellp");
+        assert_eq!(Err(Error::InvalidTag), b.recv_mac(&a.send_mac::<16>()));
+
+        let mut c = Protocol::<XoodyakKeyed>::new(b"another protocol");
+        c.key(b"a shared secret");
+        assert_eq!(Err(Error::InvalidTag), c.recv_mac(&protocol().send_mac::<16>()));
+    }
+
+    #[test]
+    fn operations_are_domain_separated() {
+        let ops: [fn(&mut Protocol<XoodyakKeyed>); 5] = [
+            |p| p.ad(b"data"),
+            |p| p.key(b"data"),
+            |p| p.send_clr(b"data"),
+            |p| p.send_enc(&mut b"data".to_owned()),
+            |p| p.bind_negotiation(&[b"data"], &[]),
+        ];
+        let outputs = ops.map(|op| {
+            let mut p = protocol();
+            op(&mut p);
+            p.send_mac::<16>()
+        });
+        for (i, a) in outputs.iter().enumerate() {
+            for b in &outputs[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn negotiation_binding() {
+        let offered: &[&[u8]] = &[b"keccyak-256", b"xoodyak"];
+        let (mut a, mut b) = (protocol(), protocol());
+        a.bind_negotiation(offered, &[b"keccyak-256"]);
+        b.bind_negotiation(offered, &[b"keccyak-256"]);
+        assert_eq!(Ok(()), b.recv_mac(&a.send_mac::<16>()));
+
+        // Stripped offers, changed choices, and shuffled lists are all detected.
+        for (offered_p, chosen_p) in [
+            (&offered[1..], &[b"xoodyak".as_slice()][..]),
+            (offered, &[b"xoodyak".as_slice()][..]),
+            (&[b"xoodyak".as_slice(), b"keccyak-256"][..], &[b"keccyak-256".as_slice()][..]),
+            (&[b"keccyak-256xoodyak".as_slice()][..], &[b"keccyak-256".as_slice()][..]),
+        ] {
+            let (mut a, mut b) = (protocol(), protocol());
+            a.bind_negotiation(offered, &[b"keccyak-256"]);
+            b.bind_negotiation(offered_p, chosen_p);
+            assert_eq!(Err(Error::InvalidTag), b.recv_mac(&a.send_mac::<16>()));
+        }
+    }
+}
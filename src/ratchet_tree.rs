@@ -0,0 +1,320 @@
+#![cfg(feature = "std")]
+
+//! A symmetric ratchet tree for deriving forward-secure group epoch keys.
+//!
+//! A [`RatchetTree`] is a complete binary tree whose leaves hold the secrets of group members.
+//! Each interior node's secret is derived from the secrets of its children, so the root secret
+//! depends on every member's secret. Changing a single member's secret (e.g. to heal a compromised
+//! member) only requires re-deriving the `log2(n)` nodes on its path to the root.
+//!
+//! The group proceeds in epochs. Each epoch has an epoch secret, derived from the previous epoch's
+//! secret and the current root secret, from which the epoch's key is derived. Every change to the
+//! tree advances the epoch, as does [`RatchetTree::advance`]. Because epoch secrets are ratcheted
+//! forward, compromising an epoch's secret reveals nothing about previous epochs, and because they
+//! incorporate the root secret, updating a compromised member's secret heals future epochs.
+//!
+//! # Derivations
+//!
+//! Every derivation initializes a keyed duplex with a secret as the key and a label as the key ID,
+//! absorbs any additional inputs, ratchets, and squeezes [`SECRET_LEN`] bytes of key data. If a
+//! secret and label don't fit in the keyed scheme's first block, as much of the secret as fits is
+//! used as the key and the rest is absorbed before any other inputs:
+//!
+//! * Leaves are derived from member secrets with the label `leaf` and the leaf's index.
+//! * Interior nodes with two children are derived from the left child with the label `node` and
+//!   the right child as input. Interior nodes with a single child are derived from that child with
+//!   the label `left` or `right`. Interior nodes with no children are blank.
+//! * The first epoch secret is derived from the root with the label `init`. Subsequent epoch
+//!   secrets are derived from the previous epoch secret with the label `advance` and the root as
+//!   input.
+//! * Epoch keys are derived from the epoch secret with the label `epoch` and the epoch number.
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use crate::{wipe, Keyed};
+
+/// The length of all secrets in a [`RatchetTree`], in bytes.
+pub const SECRET_LEN: usize = 32;
+
+/// A secret held by a node of a [`RatchetTree`].
+pub type Secret = [u8; SECRET_LEN];
+
+/// A binary tree of symmetric ratchet states for a group.
+pub struct RatchetTree<K> {
+    nodes: Vec<Option<Secret>>,
+    epoch: u64,
+    epoch_secret: Secret,
+    _keyed: PhantomData<K>,
+}
+
+impl<K> RatchetTree<K>
+where
+    K: Keyed,
+{
+    /// Creates a new [`RatchetTree`] with the given member secrets, starting at epoch zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no member secrets are given.
+    pub fn new(members: &[&[u8]]) -> Self {
+        assert!(!members.is_empty(), "a group must have at least one member");
+
+        let leaves = members
+            .iter()
+            .enumerate()
+            .map(|(i, secret)| Some(leaf::<K>(secret, i)))
+            .collect::<Vec<_>>();
+        let nodes = build::<K>(leaves);
+        let epoch_secret = derive::<K>(nodes[0].as_ref().expect("empty tree"), b"init", &[]);
+        RatchetTree { nodes, epoch: 0, epoch_secret, _keyed: PhantomData }
+    }
+
+    /// Returns the current epoch.
+    pub const fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Returns the key for the current epoch.
+    pub fn epoch_key(&self) -> Secret {
+        derive::<K>(&self.epoch_secret, b"epoch", &[&self.epoch.to_le_bytes()])
+    }
+
+    /// Returns the number of leaves in the tree, including blank leaves.
+    pub const fn capacity(&self) -> usize {
+        self.nodes.len() / 2 + 1
+    }
+
+    /// Returns the number of members in the group.
+    pub fn members(&self) -> usize {
+        self.nodes[self.capacity() - 1..].iter().filter(|n| n.is_some()).count()
+    }
+
+    /// Adds a member with the given secret to the group, advancing the epoch and returning the
+    /// member's index. The member occupies the first blank leaf, if any, or else the tree doubles
+    /// in capacity.
+    pub fn add(&mut self, secret: &[u8]) -> usize {
+        let cap = self.capacity();
+        if let Some(index) = self.nodes[cap - 1..].iter().position(Option::is_none) {
+            self.update(index, secret);
+            return index;
+        }
+
+        let mut leaves = self.nodes.split_off(cap - 1);
+        leaves.push(Some(leaf::<K>(secret, cap)));
+        self.wipe_nodes();
+        self.nodes = build::<K>(leaves);
+        self.advance();
+        cap
+    }
+
+    /// Replaces the secret of the member at the given index, e.g. to heal a compromised member,
+    /// and advances the epoch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not a leaf of the tree.
+    pub fn update(&mut self, index: usize, secret: &[u8]) {
+        self.set_leaf(index, Some(leaf::<K>(secret, index)));
+    }
+
+    /// Removes the member at the given index from the group and advances the epoch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not a leaf of the tree or if it is the group's last member.
+    pub fn remove(&mut self, index: usize) {
+        assert!(self.members() > 1, "a group must have at least one member");
+        self.set_leaf(index, None);
+    }
+
+    /// Advances the epoch without changing the group's membership.
+    pub fn advance(&mut self) {
+        let root = self.nodes[0].as_ref().expect("empty tree");
+        let mut epoch_secret = derive::<K>(&self.epoch_secret, b"advance", &[root]);
+        core::mem::swap(&mut self.epoch_secret, &mut epoch_secret);
+        wipe(&mut epoch_secret);
+        self.epoch += 1;
+    }
+
+    /// Replaces the leaf at the given index and re-derives its path to the root.
+    fn set_leaf(&mut self, index: usize, secret: Option<Secret>) {
+        assert!(index < self.capacity(), "invalid leaf index");
+
+        let mut node = self.capacity() - 1 + index;
+        self.replace(node, secret);
+        while node > 0 {
+            node = (node - 1) / 2;
+            let parent = parent::<K>(&self.nodes[2 * node + 1], &self.nodes[2 * node + 2]);
+            self.replace(node, parent);
+        }
+        self.advance();
+    }
+
+    /// Replaces the secret of the given node, wiping its previous secret.
+    fn replace(&mut self, node: usize, secret: Option<Secret>) {
+        if let Some(old) = &mut self.nodes[node] {
+            wipe(old);
+        }
+        self.nodes[node] = secret;
+    }
+}
+
+impl<K> RatchetTree<K> {
+    /// Wipes the secrets of all nodes.
+    fn wipe_nodes(&mut self) {
+        for secret in self.nodes.iter_mut().flatten() {
+            wipe(secret);
+        }
+    }
+}
+
+impl<K> Drop for RatchetTree<K> {
+    fn drop(&mut self) {
+        self.wipe_nodes();
+        wipe(&mut self.epoch_secret);
+    }
+}
+
+impl<K> fmt::Debug for RatchetTree<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RatchetTree")
+            .field("nodes", &self.nodes.len())
+            .field("epoch", &self.epoch)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Builds a complete tree from the given leaves, padding them with blank leaves to a power of two.
+fn build<K: Keyed>(mut leaves: Vec<Option<Secret>>) -> Vec<Option<Secret>> {
+    leaves.resize(leaves.len().next_power_of_two(), None);
+    let interior = leaves.len() - 1;
+    let mut nodes = vec![None; interior];
+    nodes.extend(leaves);
+    for node in (0..interior).rev() {
+        nodes[node] = parent::<K>(&nodes[2 * node + 1], &nodes[2 * node + 2]);
+    }
+    nodes
+}
+
+/// Derives a leaf from a member's secret and index.
+fn leaf<K: Keyed>(secret: &[u8], index: usize) -> Secret {
+    let index = u64::try_from(index).expect("unexpected overflow").to_le_bytes();
+    derive::<K>(secret, b"leaf", &[&index])
+}
+
+/// Derives a parent node from its children.
+fn parent<K: Keyed>(left: &Option<Secret>, right: &Option<Secret>) -> Option<Secret> {
+    match (left, right) {
+        (Some(left), Some(right)) => Some(derive::<K>(left, b"node", &[right])),
+        (Some(left), None) => Some(derive::<K>(left, b"left", &[])),
+        (None, Some(right)) => Some(derive::<K>(right, b"right", &[])),
+        (None, None) => None,
+    }
+}
+
+/// Derives a secret from the given key, label, and inputs.
+fn derive<K: Keyed>(key: &[u8], label: &[u8], inputs: &[&[u8]]) -> Secret {
+    let (key, rest) = key.split_at(key.len().min(K::ABSORB_RATE - 1 - label.len()));
+    let mut keyed = K::new(key, label, b"");
+    if !rest.is_empty() {
+        keyed.absorb(rest);
+    }
+    for input in inputs {
+        keyed.absorb(input);
+    }
+    keyed.ratchet();
+    let mut out = [0u8; SECRET_LEN];
+    keyed.squeeze_key_mut(&mut out);
+    out
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakKeyed;
+
+    use super::*;
+
+    type Tree = RatchetTree<XoodyakKeyed>;
+
+    #[test]
+    fn deterministic() {
+        let one = Tree::new(&[b"alice", b"bob", b"carol"]);
+        let two = Tree::new(&[b"alice", b"bob", b"carol"]);
+        assert_eq!(one.epoch_key(), two.epoch_key());
+        assert_eq!(4, one.capacity());
+        assert_eq!(3, one.members());
+
+        let three = Tree::new(&[b"alice", b"carol", b"bob"]);
+        assert_ne!(one.epoch_key(), three.epoch_key());
+    }
+
+    #[test]
+    fn epochs() {
+        let mut tree = Tree::new(&[b"alice", b"bob"]);
+        let zero = tree.epoch_key();
+        tree.advance();
+        assert_eq!(1, tree.epoch());
+        assert_ne!(zero, tree.epoch_key());
+    }
+
+    #[test]
+    fn healing() {
+        let mut one = Tree::new(&[b"alice", b"bob", b"carol"]);
+        let mut two = Tree::new(&[b"alice", b"bob", b"carol"]);
+        one.update(1, b"bob again");
+        two.update(1, b"mallory");
+        assert_eq!(1, one.epoch());
+        assert_ne!(one.epoch_key(), two.epoch_key());
+
+        let mut three = Tree::new(&[b"alice", b"bob", b"carol"]);
+        three.advance();
+        assert_ne!(one.epoch_key(), three.epoch_key());
+    }
+
+    #[test]
+    fn membership() {
+        let mut tree = Tree::new(&[b"alice", b"bob"]);
+        assert_eq!(2, tree.add(b"carol"));
+        assert_eq!(4, tree.capacity());
+        assert_eq!(3, tree.members());
+
+        tree.remove(0);
+        assert_eq!(2, tree.members());
+        assert_eq!(0, tree.add(b"dave"));
+        assert_eq!(3, tree.epoch());
+
+        // A group's epoch keys depend on its history, not just its membership.
+        let fresh = Tree::new(&[b"dave", b"bob", b"carol"]);
+        assert_ne!(fresh.epoch_key(), tree.epoch_key());
+    }
+
+    #[test]
+    fn long_secrets() {
+        let long = [0xCAu8; 100];
+        let mut tree = Tree::new(&[&long, b"bob"]);
+        let key = tree.epoch_key();
+        tree.update(0, &long[..99]);
+        assert_ne!(key, tree.epoch_key());
+    }
+
+    #[test]
+    #[cfg(feature = "keccyak")]
+    fn small_states() {
+        use crate::keccyak::Keccyak200Keyed;
+
+        let mut one = RatchetTree::<Keccyak200Keyed>::new(&[b"alice", b"bob", b"carol"]);
+        let mut two = RatchetTree::<Keccyak200Keyed>::new(&[b"alice", b"bob", b"carol"]);
+        assert_eq!(one.epoch_key(), two.epoch_key());
+        one.add(b"dave");
+        two.update(1, b"mallory");
+        assert_ne!(one.epoch_key(), two.epoch_key());
+    }
+
+    #[test]
+    #[should_panic(expected = "a group must have at least one member")]
+    fn removing_last_member() {
+        let mut tree = Tree::new(&[b"alice"]);
+        tree.remove(0);
+    }
+}
@@ -0,0 +1,305 @@
+//! Bounded-memory replay caches.
+//!
+//! Transports which accept messages out of order (e.g. over datagrams) can't simply reject messages
+//! whose sequence numbers aren't greater than the last one seen. Instead, they keep track of which
+//! recent sequence numbers have been seen with a [`ReplayCache`], which checks a message's sequence
+//! number before it's opened and records it after it's been authenticated:
+//!
+//! ```rust
+//! use cyclist::replay::{BitmapWindow, ReplayCache};
+//!
+//! let mut cache = BitmapWindow::<1>::default();
+//! assert!(cache.is_fresh(10));
+//! cache.mark_seen(10);
+//! cache.mark_seen(8);
+//!
+//! assert!(!cache.is_fresh(10));
+//! assert!(cache.is_fresh(9));
+//! assert!(!cache.is_fresh(8));
+//! ```
+//!
+//! Three policies are provided:
+//!
+//! * [`BitmapWindow`] tracks the `64 × N` sequence numbers below the highest one seen, and rejects
+//!   anything older. It suits mostly-ordered traffic (e.g. IPsec or DTLS).
+//! * [`FixedCache`] remembers the `N` highest sequence numbers seen, and rejects anything at or
+//!   below the highest one it has forgotten. It suits sparse or widely-reordered sequence numbers.
+//! * [`TimeBuckets`] remembers every sequence number seen within a time window, with a fixed
+//!   capacity per bucket of time. It suits transports which carry timestamps and reject messages
+//!   older than the window, and requires the `std` feature.
+//!
+//! All three fail closed: a sequence number a cache can't vouch for is never fresh.
+
+/// A record of which sequence numbers have been seen, used to reject replayed messages.
+pub trait ReplayCache {
+    /// Returns `true` if the given sequence number hasn't been seen and can be accepted.
+    fn is_fresh(&self, seq: u64) -> bool;
+
+    /// Records the given sequence number as seen.
+    ///
+    /// This should only be called after the message with the sequence number has been
+    /// authenticated, lest an attacker fill the cache with forged sequence numbers.
+    fn mark_seen(&mut self, seq: u64);
+}
+
+/// A sliding window of the `64 × N` sequence numbers below the highest one seen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BitmapWindow<const N: usize> {
+    highest: Option<u64>,
+    seen: [u64; N],
+}
+
+impl<const N: usize> BitmapWindow<N> {
+    /// The number of sequence numbers the window keeps track of.
+    pub const LEN: usize = 64 * N;
+
+    /// Returns the distance between the highest sequence number seen and the given one, if it's
+    /// within the window.
+    fn offset(&self, seq: u64) -> Option<usize> {
+        let d = self.highest? - seq;
+        usize::try_from(d).ok().filter(|&d| d < Self::LEN)
+    }
+
+    /// Shifts the window's bitmap up by the given number of sequence numbers.
+    fn shift(&mut self, n: u64) {
+        let n = match usize::try_from(n) {
+            Ok(n) if n < Self::LEN => n,
+            _ => {
+                self.seen = [0; N];
+                return;
+            }
+        };
+
+        let (words, bits) = (n / 64, n % 64);
+        for i in (0..N).rev() {
+            let hi = if i >= words { self.seen[i - words] << bits } else { 0 };
+            let lo =
+                if bits > 0 && i > words { self.seen[i - words - 1] >> (64 - bits) } else { 0 };
+            self.seen[i] = hi | lo;
+        }
+    }
+}
+
+impl<const N: usize> Default for BitmapWindow<N> {
+    fn default() -> Self {
+        BitmapWindow { highest: None, seen: [0; N] }
+    }
+}
+
+impl<const N: usize> ReplayCache for BitmapWindow<N> {
+    fn is_fresh(&self, seq: u64) -> bool {
+        match self.highest {
+            None => true,
+            Some(highest) if seq > highest => true,
+            Some(_) => match self.offset(seq) {
+                Some(d) => self.seen[d / 64] & (1 << (d % 64)) == 0,
+                None => false,
+            },
+        }
+    }
+
+    fn mark_seen(&mut self, seq: u64) {
+        match self.highest {
+            Some(highest) if seq <= highest => {}
+            Some(highest) => {
+                self.shift(seq - highest);
+                self.highest = Some(seq);
+            }
+            None => self.highest = Some(seq),
+        }
+
+        if let Some(d) = self.offset(seq) {
+            self.seen[d / 64] |= 1 << (d % 64);
+        }
+    }
+}
+
+/// A fixed-size set of the `N` highest sequence numbers seen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedCache<const N: usize> {
+    seen: [u64; N],
+    len: usize,
+    floor: Option<u64>,
+}
+
+impl<const N: usize> Default for FixedCache<N> {
+    fn default() -> Self {
+        FixedCache { seen: [0; N], len: 0, floor: None }
+    }
+}
+
+impl<const N: usize> ReplayCache for FixedCache<N> {
+    fn is_fresh(&self, seq: u64) -> bool {
+        self.floor.is_none_or(|floor| seq > floor) && !self.seen[..self.len].contains(&seq)
+    }
+
+    fn mark_seen(&mut self, seq: u64) {
+        if self.seen[..self.len].contains(&seq) {
+            return;
+        }
+
+        if self.len < N {
+            self.seen[self.len] = seq;
+            self.len += 1;
+            return;
+        }
+
+        // Forget the lowest sequence number, which may be the new one.
+        let lowest = self.seen.iter_mut().min_by_key(|s| **s);
+        let forgotten = match lowest {
+            Some(lowest) if *lowest < seq => core::mem::replace(lowest, seq),
+            _ => seq,
+        };
+        self.floor = Some(self.floor.map_or(forgotten, |floor| floor.max(forgotten)));
+    }
+}
+
+/// A record of the sequence numbers seen within a window of time, divided into buckets.
+///
+/// The window is `bucket_len × buckets` long, and a sequence number is forgotten once the bucket
+/// it was seen in falls out of the window. Each bucket holds up to `capacity` sequence numbers;
+/// once the current bucket is full, no sequence number is fresh until time advances into the next
+/// bucket. Because forgotten sequence numbers are fresh again, transports must reject messages
+/// which are older than the window by their own timestamps.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub struct TimeBuckets {
+    bucket_len: u64,
+    buckets: usize,
+    capacity: usize,
+    now: u64,
+    seen: std::collections::VecDeque<(u64, std::collections::HashSet<u64>)>,
+}
+
+#[cfg(feature = "std")]
+impl TimeBuckets {
+    /// Creates a new [`TimeBuckets`] with the given number of buckets, each covering `bucket_len`
+    /// units of time (e.g. seconds) and holding up to `capacity` sequence numbers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bucket_len` or `buckets` is zero.
+    pub fn new(bucket_len: u64, buckets: usize, capacity: usize) -> TimeBuckets {
+        assert!(bucket_len > 0 && buckets > 0, "invalid time buckets");
+        TimeBuckets { bucket_len, buckets, capacity, now: 0, seen: Default::default() }
+    }
+
+    /// Advances the cache's clock to the given time, forgetting any buckets which have fallen out
+    /// of the window. The clock never moves backwards.
+    pub fn advance(&mut self, now: u64) {
+        self.now = self.now.max(now / self.bucket_len);
+        let buckets = u64::try_from(self.buckets).expect("unexpected overflow");
+        let oldest = self.now.saturating_sub(buckets - 1);
+        self.seen.retain(|(bucket, _)| *bucket >= oldest);
+    }
+}
+
+#[cfg(feature = "std")]
+impl ReplayCache for TimeBuckets {
+    fn is_fresh(&self, seq: u64) -> bool {
+        let full = self
+            .seen
+            .back()
+            .is_some_and(|(bucket, seen)| *bucket == self.now && seen.len() >= self.capacity);
+        !full && self.seen.iter().all(|(_, seen)| !seen.contains(&seq))
+    }
+
+    fn mark_seen(&mut self, seq: u64) {
+        if !self.is_fresh(seq) {
+            return;
+        }
+
+        match self.seen.back_mut() {
+            Some((bucket, seen)) if *bucket == self.now => {
+                seen.insert(seq);
+            }
+            _ => self.seen.push_back((self.now, [seq].into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bitmap_window() {
+        let mut window = BitmapWindow::<2>::default();
+        assert_eq!(128, BitmapWindow::<2>::LEN);
+        assert!(window.is_fresh(0));
+
+        for seq in [5, 3, 70, 200] {
+            assert!(window.is_fresh(seq));
+            window.mark_seen(seq);
+            assert!(!window.is_fresh(seq));
+        }
+
+        // 5 and 3 have fallen out of the window, 70 hasn't.
+        assert!(!window.is_fresh(3));
+        assert!(!window.is_fresh(5));
+        assert!(!window.is_fresh(70));
+        assert!(!window.is_fresh(72));
+        assert!(window.is_fresh(73));
+        assert!(window.is_fresh(199));
+        assert!(window.is_fresh(201));
+
+        // Shifting by less than a word carries bits across words.
+        window.mark_seen(150);
+        window.mark_seen(230);
+        assert!(!window.is_fresh(150));
+        assert!(window.is_fresh(151));
+        assert!(!window.is_fresh(200));
+        assert!(!window.is_fresh(102));
+        assert!(window.is_fresh(103));
+    }
+
+    #[test]
+    fn fixed_cache() {
+        let mut cache = FixedCache::<3>::default();
+        for seq in [10, 2, 7] {
+            assert!(cache.is_fresh(seq));
+            cache.mark_seen(seq);
+        }
+        assert!(cache.is_fresh(5));
+
+        // Seeing a fourth forgets the lowest, and rejects everything below it.
+        cache.mark_seen(12);
+        assert!(!cache.is_fresh(2));
+        assert!(!cache.is_fresh(1));
+        assert!(cache.is_fresh(5));
+        assert!(!cache.is_fresh(7));
+
+        // Seeing one below the lowest raises the floor without forgetting anything else.
+        cache.mark_seen(5);
+        assert!(!cache.is_fresh(4));
+        assert!(cache.is_fresh(6));
+        assert!(!cache.is_fresh(7));
+        assert!(!cache.is_fresh(12));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn time_buckets() {
+        let mut cache = TimeBuckets::new(10, 3, 2);
+        cache.advance(100);
+        cache.mark_seen(1);
+        cache.mark_seen(2);
+        assert!(!cache.is_fresh(1));
+        assert!(!cache.is_fresh(3), "full buckets should fail closed");
+
+        cache.advance(115);
+        assert!(cache.is_fresh(3));
+        cache.mark_seen(3);
+        assert!(!cache.is_fresh(1));
+
+        // The clock never moves backwards.
+        cache.advance(0);
+        assert!(!cache.is_fresh(3));
+
+        cache.advance(130);
+        assert!(cache.is_fresh(1));
+        assert!(!cache.is_fresh(3));
+        cache.advance(140);
+        assert!(cache.is_fresh(3));
+    }
+}
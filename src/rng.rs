@@ -0,0 +1,169 @@
+#![cfg(feature = "rand_core")]
+
+//! A deterministic, cryptographically secure random number generator.
+//!
+//! A [`CyclistRng`] absorbs its seed into a keyed Cyclist object at construction and squeezes its
+//! output, allowing a single keyed scheme to serve as the only cryptographic primitive in, e.g., an
+//! embedded firmware image. It implements [`RngCore`] and [`CryptoRng`]:
+//!
+//! ```rust
+//! use cyclist::rng::CyclistRng;
+//! use cyclist::xoodyak::XoodyakKeyed;
+//! use rand_core::RngCore;
+//!
+//! let mut rng = CyclistRng::<XoodyakKeyed>::new(b"a seed from an entropy source");
+//! let mut key = [0u8; 32];
+//! rng.fill_bytes(&mut key);
+//!
+//! // Ratchet the generator's state so that a later compromise can't reveal the key.
+//! rng.ratchet();
+//! ```
+//!
+//! The generator is deterministic: two generators with the same seed produce the same output. To
+//! provide forward secrecy, call [`CyclistRng::ratchet`] after generating secrets; to incorporate
+//! additional entropy, call [`CyclistRng::reseed`].
+
+use rand_core::{CryptoRng, RngCore, SeedableRng};
+
+use crate::Keyed;
+
+/// The domain separation label absorbed by generators.
+const RNG_LABEL: &[u8] = b"cyclist.rng";
+
+/// A deterministic random number generator which squeezes output from a keyed Cyclist object.
+#[derive(Clone, Debug)]
+pub struct CyclistRng<K> {
+    keyed: K,
+}
+
+impl<K> CyclistRng<K>
+where
+    K: Keyed,
+{
+    /// Creates a new [`CyclistRng`] with the given seed, which must be kept secret and should have
+    /// at least as much entropy as the keyed scheme's security level.
+    ///
+    /// Seeds of any length are accepted. As much of the seed as the keyed scheme allows is used as
+    /// its key, and the rest is absorbed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the seed is empty.
+    pub fn new(seed: &[u8]) -> Self {
+        let (key, rest) = seed.split_at(seed.len().min(K::ABSORB_RATE - 1));
+        let mut keyed = K::new(key, b"", b"");
+        if !rest.is_empty() {
+            keyed.absorb(rest);
+        }
+        keyed.absorb(RNG_LABEL);
+        CyclistRng { keyed }
+    }
+
+    /// Absorbs additional entropy into the generator's state.
+    pub fn reseed(&mut self, entropy: &[u8]) {
+        self.keyed.absorb(entropy);
+    }
+
+    /// Ratchets the generator's state, preventing the recovery of previous outputs from it.
+    pub fn ratchet(&mut self) {
+        self.keyed.ratchet();
+    }
+}
+
+impl<K> RngCore for CyclistRng<K>
+where
+    K: Keyed,
+{
+    fn next_u32(&mut self) -> u32 {
+        let mut b = [0u8; 4];
+        self.fill_bytes(&mut b);
+        u32::from_le_bytes(b)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut b = [0u8; 8];
+        self.fill_bytes(&mut b);
+        u64::from_le_bytes(b)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.keyed.squeeze_mut(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl<K> CryptoRng for CyclistRng<K> where K: Keyed {}
+
+impl<K> SeedableRng for CyclistRng<K>
+where
+    K: Keyed,
+{
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        CyclistRng::new(&seed)
+    }
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakKeyed;
+
+    use super::*;
+
+    type Rng = CyclistRng<XoodyakKeyed>;
+
+    #[test]
+    fn deterministic() {
+        let mut one = Rng::new(b"seed");
+        let mut two = Rng::new(b"seed");
+        assert_eq!(one.next_u64(), two.next_u64());
+        assert_ne!(one.next_u64(), Rng::new(b"other seed").next_u64());
+
+        let mut three = Rng::from_seed([7u8; 32]);
+        let mut four = Rng::new(&[7u8; 32]);
+        assert_eq!(three.next_u32(), four.next_u32());
+    }
+
+    #[test]
+    #[cfg(feature = "keccyak")]
+    fn small_states() {
+        use crate::keccyak::Keccyak200Keyed;
+
+        type Rng = CyclistRng<Keccyak200Keyed>;
+
+        let mut seed = [7u8; 32];
+        let one = Rng::from_seed(seed).next_u64();
+        seed[31] ^= 1;
+        assert_ne!(one, Rng::from_seed(seed).next_u64());
+        assert_ne!(one, Rng::new(&seed[..20]).next_u64());
+        assert_ne!(Rng::new(&seed[..20]).next_u64(), Rng::new(&seed[..21]).next_u64());
+    }
+
+    #[test]
+    fn outputs_differ() {
+        let mut rng = Rng::new(b"seed");
+        let (mut one, mut two) = ([0u8; 32], [0u8; 32]);
+        rng.fill_bytes(&mut one);
+        rng.fill_bytes(&mut two);
+        assert_ne!(one, two);
+    }
+
+    #[test]
+    fn reseeding_and_ratcheting() {
+        let mut one = Rng::new(b"seed");
+        let mut two = one.clone();
+        let mut three = one.clone();
+        two.reseed(b"entropy");
+        three.ratchet();
+
+        let outputs = [one.next_u64(), two.next_u64(), three.next_u64()];
+        assert_ne!(outputs[0], outputs[1]);
+        assert_ne!(outputs[0], outputs[2]);
+        assert_ne!(outputs[1], outputs[2]);
+    }
+}
@@ -0,0 +1,289 @@
+#![cfg(feature = "std")]
+
+//! Sender-anonymous ("sealed sender") envelopes.
+//!
+//! A conventional envelope reveals its sender to anyone who can see it (e.g. a relay server),
+//! either explicitly or through the key used to seal it. A sealed sender envelope exposes only an
+//! ephemeral key ID; the sender's identity is encrypted inside, and the recipient learns and
+//! authenticates it only after opening the envelope:
+//!
+//! ```rust
+//! use cyclist::sealed_sender::{open, peek_key_id, seal};
+//! use cyclist::xoodyak::XoodyakKeyed;
+//!
+//! // An ephemeral key and key ID (e.g. from an ephemeral key exchange with the recipient), and the
+//! // key shared by the sender and the recipient.
+//! let (ephemeral_key, ephemeral_key_id) = (b"an ephemeral key", b"ephemeral key 1");
+//! let sender_key = b"alice and bob's key";
+//!
+//! let envelope =
+//!     seal::<XoodyakKeyed>(ephemeral_key, ephemeral_key_id, b"alice", sender_key, b"hi bob");
+//!
+//! // The recipient finds the ephemeral key by its ID, then looks up the sender's key by identity.
+//! assert_eq!(Ok(&ephemeral_key_id[..]), peek_key_id(&envelope));
+//! let opened = open::<XoodyakKeyed, _, _>(ephemeral_key, &envelope, |sender_id| {
+//!     (sender_id == b"alice").then_some(sender_key)
+//! })
+//! .expect("should open");
+//! assert_eq!(b"alice", opened.sender_id.as_slice());
+//! assert_eq!(b"hi bob", opened.payload.as_slice());
+//! ```
+//!
+//! This crate does not provide the key exchange. **Each ephemeral key must be used to seal only one
+//! envelope**, as with keys derived from an ephemeral Diffie-Hellman exchange, and each envelope
+//! must have a unique ephemeral key ID. Because the sender's key is shared with the recipient, the
+//! recipient can forge envelopes from the sender, so the sender's authorship is deniable to third
+//! parties.
+//!
+//! # Construction
+//!
+//! An envelope consists of a one-byte length and the ephemeral key ID, followed by the outer
+//! ciphertext.
+//!
+//! The inner layer is sealed with a keyed duplex initialized with the sender's key, which absorbs a
+//! domain separation label, the ephemeral key ID (as a nonce), and the sender's identity, and then
+//! seals the payload. The outer layer's plaintext is a one-byte length and the sender's identity,
+//! followed by the inner ciphertext. It's sealed with a keyed duplex initialized with the ephemeral
+//! key, which absorbs a distinct domain separation label and the ephemeral key ID.
+
+use std::fmt;
+
+use crate::Keyed;
+
+/// The longest supported ephemeral key ID or sender identity, in bytes.
+pub const MAX_ID_LEN: usize = 255;
+
+/// The domain separation label for the outer layer.
+const OUTER_LABEL: &[u8] = b"cyclist.sealed_sender.outer";
+
+/// The domain separation label for the inner layer.
+const INNER_LABEL: &[u8] = b"cyclist.sealed_sender.inner";
+
+/// An error opening a sealed sender envelope.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The envelope was too short or its lengths were invalid.
+    Malformed,
+
+    /// The envelope could not be authenticated with the ephemeral key.
+    Inauthentic,
+
+    /// The sender's key could not be found.
+    UnknownSender,
+
+    /// The envelope could not be authenticated with the sender's key, indicating the sender's
+    /// identity was forged.
+    ForgedSender,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Malformed => write!(f, "malformed envelope"),
+            Error::Inauthentic => write!(f, "inauthentic envelope"),
+            Error::UnknownSender => write!(f, "unknown sender"),
+            Error::ForgedSender => write!(f, "forged sender"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// The contents of an opened envelope.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Opened {
+    /// The authenticated identity of the sender.
+    pub sender_id: Vec<u8>,
+
+    /// The payload.
+    pub payload: Vec<u8>,
+}
+
+/// Returns the length of an envelope with a `key_id_len`-byte ephemeral key ID and a
+/// `sender_id_len`-byte sender identity holding a `payload_len`-byte payload.
+pub const fn sealed_len<K: Keyed>(
+    key_id_len: usize,
+    sender_id_len: usize,
+    payload_len: usize,
+) -> usize {
+    2 + key_id_len + sender_id_len + payload_len + 2 * K::TAG_LEN
+}
+
+/// Seals `payload` from the given sender, hiding the sender's identity inside an envelope sealed
+/// with the ephemeral key.
+///
+/// # Panics
+///
+/// Panics if the ephemeral key ID or the sender's identity is longer than [`MAX_ID_LEN`].
+pub fn seal<K: Keyed>(
+    ephemeral_key: &[u8],
+    ephemeral_key_id: &[u8],
+    sender_id: &[u8],
+    sender_key: &[u8],
+    payload: &[u8],
+) -> Vec<u8> {
+    let key_id_len = u8::try_from(ephemeral_key_id.len()).expect("ephemeral key ID too long");
+    let sender_id_len = u8::try_from(sender_id.len()).expect("sender ID too long");
+
+    // Lay out the envelope and the outer layer's plaintext.
+    let mut envelope =
+        Vec::with_capacity(sealed_len::<K>(ephemeral_key_id.len(), sender_id.len(), payload.len()));
+    envelope.push(key_id_len);
+    envelope.extend_from_slice(ephemeral_key_id);
+    let outer_start = envelope.len();
+    envelope.push(sender_id_len);
+    envelope.extend_from_slice(sender_id);
+    let inner_start = envelope.len();
+    envelope.extend_from_slice(payload);
+    envelope.resize(envelope.len() + 2 * K::TAG_LEN, 0);
+
+    // Seal the inner layer, then the outer layer around it.
+    let len = envelope.len();
+    inner::<K>(sender_key, ephemeral_key_id, sender_id)
+        .seal_mut(&mut envelope[inner_start..len - K::TAG_LEN]);
+    outer::<K>(ephemeral_key, ephemeral_key_id).seal_mut(&mut envelope[outer_start..]);
+    envelope
+}
+
+/// Returns the ephemeral key ID of an envelope, with which the recipient can find the ephemeral key.
+///
+/// # Errors
+///
+/// Returns [`Error::Malformed`] if the envelope is too short to contain a key ID.
+pub fn peek_key_id(envelope: &[u8]) -> Result<&[u8], Error> {
+    let (&len, rest) = envelope.split_first().ok_or(Error::Malformed)?;
+    rest.get(..usize::from(len)).ok_or(Error::Malformed)
+}
+
+/// Opens an envelope with the ephemeral key, then authenticates the sender with the key returned
+/// by `sender_key` for the sender's identity.
+///
+/// # Errors
+///
+/// Returns [`Error::Malformed`] if the envelope's lengths are invalid, [`Error::Inauthentic`] if it
+/// cannot be authenticated with the ephemeral key, [`Error::UnknownSender`] if `sender_key` returns
+/// `None`, and [`Error::ForgedSender`] if it cannot be authenticated with the sender's key.
+pub fn open<K, F, S>(ephemeral_key: &[u8], envelope: &[u8], sender_key: F) -> Result<Opened, Error>
+where
+    K: Keyed,
+    F: FnOnce(&[u8]) -> Option<S>,
+    S: AsRef<[u8]>,
+{
+    let key_id = peek_key_id(envelope)?;
+    let mut outer_p = envelope[1 + key_id.len()..].to_vec();
+    if outer_p.len() < 1 + 2 * K::TAG_LEN {
+        return Err(Error::Malformed);
+    }
+
+    // Open the outer layer.
+    outer::<K>(ephemeral_key, key_id).open_mut(&mut outer_p).map_err(|_| Error::Inauthentic)?;
+    outer_p.truncate(outer_p.len() - K::TAG_LEN);
+
+    // Split the outer plaintext into the sender's identity and the inner layer.
+    let sender_id_len = usize::from(outer_p[0]);
+    if outer_p.len() < 1 + sender_id_len + K::TAG_LEN {
+        return Err(Error::Malformed);
+    }
+    let mut payload = outer_p.split_off(1 + sender_id_len);
+    let sender_id = outer_p.split_off(1);
+
+    // Open the inner layer with the sender's key.
+    let sender_key = sender_key(&sender_id).ok_or(Error::UnknownSender)?;
+    inner::<K>(sender_key.as_ref(), key_id, &sender_id)
+        .open_mut(&mut payload)
+        .map_err(|_| Error::ForgedSender)?;
+    payload.truncate(payload.len() - K::TAG_LEN);
+
+    Ok(Opened { sender_id, payload })
+}
+
+/// Returns the keyed duplex for an envelope's outer layer.
+fn outer<K: Keyed>(ephemeral_key: &[u8], ephemeral_key_id: &[u8]) -> K {
+    let mut keyed = K::new(ephemeral_key, b"", b"");
+    keyed.absorb(OUTER_LABEL);
+    keyed.absorb(ephemeral_key_id);
+    keyed
+}
+
+/// Returns the keyed duplex for an envelope's inner layer.
+fn inner<K: Keyed>(sender_key: &[u8], ephemeral_key_id: &[u8], sender_id: &[u8]) -> K {
+    let mut keyed = K::new(sender_key, b"", b"");
+    keyed.absorb(INNER_LABEL);
+    keyed.absorb(ephemeral_key_id);
+    keyed.absorb(sender_id);
+    keyed
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakKeyed;
+
+    use super::*;
+
+    const EPHEMERAL_KEY: &[u8] = b"an ephemeral key";
+    const KEY_ID: &[u8] = b"key 1";
+    const ALICE_KEY: &[u8] = b"alice's key";
+    const MALLORY_KEY: &[u8] = b"mallory's key";
+
+    fn keys(sender_id: &[u8]) -> Option<&'static [u8]> {
+        match sender_id {
+            b"alice" => Some(ALICE_KEY),
+            b"mallory" => Some(MALLORY_KEY),
+            _ => None,
+        }
+    }
+
+    fn open_envelope(envelope: &[u8]) -> Result<Opened, Error> {
+        open::<XoodyakKeyed, _, _>(EPHEMERAL_KEY, envelope, keys)
+    }
+
+    #[test]
+    fn round_trip() {
+        let envelope =
+            seal::<XoodyakKeyed>(EPHEMERAL_KEY, KEY_ID, b"alice", ALICE_KEY, b"a message");
+        assert_eq!(sealed_len::<XoodyakKeyed>(KEY_ID.len(), 5, 9), envelope.len());
+        assert_eq!(Ok(KEY_ID), peek_key_id(&envelope));
+        assert!(!envelope.windows(5).any(|w| w == b"alice"), "sender ID should be hidden");
+        assert_eq!(
+            Ok(Opened { sender_id: b"alice".to_vec(), payload: b"a message".to_vec() }),
+            open_envelope(&envelope)
+        );
+
+        let envelope = seal::<XoodyakKeyed>(EPHEMERAL_KEY, b"", b"alice", ALICE_KEY, b"");
+        assert_eq!(
+            Ok(Opened { sender_id: b"alice".to_vec(), payload: vec![] }),
+            open_envelope(&envelope)
+        );
+    }
+
+    #[test]
+    fn forged_senders() {
+        // Mallory claims to be Alice, but doesn't have Alice's key.
+        let envelope =
+            seal::<XoodyakKeyed>(EPHEMERAL_KEY, KEY_ID, b"alice", MALLORY_KEY, b"a message");
+        assert_eq!(Err(Error::ForgedSender), open_envelope(&envelope));
+
+        let envelope =
+            seal::<XoodyakKeyed>(EPHEMERAL_KEY, KEY_ID, b"eve", MALLORY_KEY, b"a message");
+        assert_eq!(Err(Error::UnknownSender), open_envelope(&envelope));
+    }
+
+    #[test]
+    fn modified_envelopes() {
+        let envelope =
+            seal::<XoodyakKeyed>(EPHEMERAL_KEY, KEY_ID, b"alice", ALICE_KEY, b"a message");
+        for i in 1..envelope.len() {
+            let mut envelope = envelope.clone();
+            envelope[i] ^= 1;
+            assert_eq!(Err(Error::Inauthentic), open_envelope(&envelope), "i={}", i);
+        }
+
+        assert_eq!(Err(Error::Malformed), open_envelope(&[]));
+        assert_eq!(Err(Error::Malformed), open_envelope(&envelope[..20]));
+        assert_eq!(
+            Err(Error::Inauthentic),
+            open::<XoodyakKeyed, _, _>(b"another key", &envelope, keys)
+        );
+    }
+}
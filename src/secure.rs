@@ -0,0 +1,119 @@
+//! Delegation of key absorption to hardware secure elements.
+//!
+//! A [`SecureState`] implementation (e.g. a handle to an HSM, TPM, or secure element) holds a key
+//! and performs only the initial key absorption of a keyed Cyclist session, returning an opaque
+//! state which the rest of the duplex then uses in software. The key itself never enters
+//! application memory.
+//!
+//! Immediately after the key is absorbed the state has not yet been permuted, so the secure element
+//! also ratchets the state before returning it. Because ratcheting zeroes part of the state, the
+//! returned state cannot be inverted to recover the key. A session initialized this way is
+//! equivalent to one created via [`CyclistKeyed::new`] followed by [`CyclistKeyed::ratchet`].
+
+use crate::{wipe, CyclistCore, CyclistKeyed, Extendable, Permutation, Stats};
+
+/// A handle to a key which is held by a secure element.
+pub trait SecureState<const WIDTH: usize> {
+    /// The type of error returned if the secure element fails.
+    type Error;
+
+    /// Initializes a keyed Cyclist state with the held key and the given key ID and counter,
+    /// ratchets it, and returns the resulting state. Implementations running inside a secure
+    /// element should use [`CyclistKeyed::secure_state`] to do so.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the secure element fails.
+    fn initialize(&self, key_id: &[u8], counter: &[u8]) -> Result<[u8; WIDTH], Self::Error>;
+}
+
+impl<
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > CyclistKeyed<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+{
+    /// Creates a new [`CyclistKeyed`] instance with a state initialized by the given secure
+    /// element with the given key ID and counter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the secure element fails.
+    pub fn from_secure_state<S>(
+        element: &S,
+        key_id: &[u8],
+        counter: &[u8],
+    ) -> Result<Self, S::Error>
+    where
+        S: SecureState<WIDTH>,
+    {
+        let mut bytes = element.initialize(key_id, counter)?;
+        let state = P::from_bytes(&bytes);
+        wipe(&mut bytes);
+        Ok(CyclistKeyed {
+            core: CyclistCore {
+                state,
+                up: false,
+                extendable: Extendable::Neither,
+                poisoned: false,
+                stats: Stats::default(),
+                #[cfg(feature = "trace")]
+                trace: Vec::new(),
+            },
+        })
+    }
+
+    /// Returns the ratcheted state of a [`CyclistKeyed`] instance initialized with the given key,
+    /// key ID, and counter. This is the computation a [`SecureState`] implementation performs.
+    pub fn secure_state(key: &[u8], key_id: &[u8], counter: &[u8]) -> [u8; WIDTH] {
+        let mut keyed = Self::new(key, key_id, counter);
+        keyed.ratchet();
+        debug_assert!(!keyed.core.up);
+        keyed.core.state.to_bytes()
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakKeyed;
+    use crate::Cyclist;
+
+    use super::*;
+
+    struct Element(Vec<u8>);
+
+    impl SecureState<48> for Element {
+        type Error = ();
+
+        fn initialize(&self, key_id: &[u8], counter: &[u8]) -> Result<[u8; 48], Self::Error> {
+            Ok(XoodyakKeyed::secure_state(&self.0, key_id, counter))
+        }
+    }
+
+    #[test]
+    fn equivalent_to_ratcheted_session() {
+        let element = Element(b"ok then".to_vec());
+        let mut d = XoodyakKeyed::from_secure_state(&element, b"key id", b"counter")
+            .expect("invalid state");
+        d.absorb(b"ad");
+        let one = d.seal(b"it's a deal");
+
+        let mut d = XoodyakKeyed::new(b"ok then", b"key id", b"counter");
+        d.ratchet();
+        d.absorb(b"ad");
+        let two = d.seal(b"it's a deal");
+
+        assert_eq!(one, two);
+    }
+
+    #[test]
+    fn key_not_in_state() {
+        let state = XoodyakKeyed::secure_state(b"ok then", b"", b"");
+        assert!(!state.windows(7).any(|w| w == b"ok then"));
+    }
+}
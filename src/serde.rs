@@ -0,0 +1,220 @@
+#![cfg(feature = "serde")]
+
+//! Implementations of the [`serde`](::serde) traits for [`CyclistHash`] and [`CyclistKeyed`].
+//!
+//! An in-progress hash or keyed session is serialized as a tuple of the bytes returned by
+//! [`CyclistHash::to_bytes`] or [`CyclistKeyed::to_bytes`], and can be deserialized and resumed in
+//! another process:
+//!
+//! ```rust
+//! use cyclist::xoodyak::XoodyakKeyed;
+//! use cyclist::Cyclist;
+//!
+//! let mut session = XoodyakKeyed::new(b"This is a secret key!", b"", b"");
+//! session.absorb(b"This is a header!");
+//!
+//! let json = serde_json::to_string(&session).expect("should serialize");
+//! let mut resumed: XoodyakKeyed = serde_json::from_str(&json).expect("should deserialize");
+//! assert_eq!(session.seal(b"message"), resumed.seal(b"message"));
+//! ```
+//!
+//! A serialized keyed session is as sensitive as its key. A keyed session which has been poisoned
+//! by a failed open (see [`CyclistKeyed::is_poisoned`]) can't be serialized.
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use ::serde::de::{self, SeqAccess, Unexpected, Visitor};
+use ::serde::ser::{self, SerializeTuple};
+use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{wipe, CyclistCore, CyclistHash, CyclistKeyed, Permutation};
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize> Serialize for CyclistHash<P, WIDTH, HASH_RATE>
+where
+    P: Permutation<WIDTH>,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_core(&self.core, serializer)
+    }
+}
+
+impl<'de, P, const WIDTH: usize, const HASH_RATE: usize> Deserialize<'de>
+    for CyclistHash<P, WIDTH, HASH_RATE>
+where
+    P: Permutation<WIDTH>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(CyclistHash { core: deserialize_core(deserializer)? })
+    }
+}
+
+impl<
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > Serialize for CyclistKeyed<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.check_unpoisoned().map_err(ser::Error::custom)?;
+        serialize_core(&self.core, serializer)
+    }
+}
+
+impl<
+        'de,
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > Deserialize<'de> for CyclistKeyed<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(CyclistKeyed { core: deserialize_core(deserializer)? })
+    }
+}
+
+/// Serializes the flags and state of the given core as a tuple of bytes.
+fn serialize_core<
+    P,
+    S,
+    const WIDTH: usize,
+    const KEYED: bool,
+    const ABSORB_RATE: usize,
+    const SQUEEZE_RATE: usize,
+    const RATCHET_RATE: usize,
+>(
+    core: &CyclistCore<P, WIDTH, KEYED, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    P: Permutation<WIDTH>,
+    S: Serializer,
+{
+    let mut tuple = serializer.serialize_tuple(WIDTH + 1)?;
+    tuple.serialize_element(&core.flags())?;
+    let mut state = core.state.to_bytes();
+    let result = state.iter().try_for_each(|b| tuple.serialize_element(b));
+    wipe(&mut state);
+    result?;
+    tuple.end()
+}
+
+/// Deserializes a core from a tuple of bytes.
+fn deserialize_core<
+    'de,
+    P,
+    D,
+    const WIDTH: usize,
+    const KEYED: bool,
+    const ABSORB_RATE: usize,
+    const SQUEEZE_RATE: usize,
+    const RATCHET_RATE: usize,
+>(
+    deserializer: D,
+) -> Result<CyclistCore<P, WIDTH, KEYED, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE>, D::Error>
+where
+    P: Permutation<WIDTH>,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_tuple(WIDTH + 1, CoreVisitor(PhantomData))
+}
+
+/// A [`Visitor`] which reads a core's flags and state from a sequence or a byte slice.
+struct CoreVisitor<
+    P,
+    const WIDTH: usize,
+    const KEYED: bool,
+    const ABSORB_RATE: usize,
+    const SQUEEZE_RATE: usize,
+    const RATCHET_RATE: usize,
+>(PhantomData<P>);
+
+impl<
+        'de,
+        P,
+        const WIDTH: usize,
+        const KEYED: bool,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+    > Visitor<'de> for CoreVisitor<P, WIDTH, KEYED, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE>
+where
+    P: Permutation<WIDTH>,
+{
+    type Value = CyclistCore<P, WIDTH, KEYED, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bytes of {} Cyclist state", WIDTH + 1, if KEYED { "keyed" } else { "hash" })
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let flags: u8 = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let mut bytes = [0u8; WIDTH];
+        for (i, b) in bytes.iter_mut().enumerate() {
+            match seq.next_element()? {
+                Some(x) => *b = x,
+                None => {
+                    wipe(&mut bytes);
+                    return Err(de::Error::invalid_length(i + 1, &self));
+                }
+            }
+        }
+        let state = P::from_bytes(&bytes);
+        wipe(&mut bytes);
+        CyclistCore::from_parts(flags, state)
+            .ok_or_else(|| de::Error::invalid_value(Unexpected::Unsigned(u64::from(flags)), &self))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        // Don't include the state in errors, as it may be secret.
+        match v.first() {
+            Some(&flags) if v.len() == WIDTH + 1 => CyclistCore::import(v)
+                .ok_or_else(|| E::invalid_value(Unexpected::Unsigned(u64::from(flags)), &self)),
+            _ => Err(E::invalid_length(v.len(), &self)),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::{XoodyakHash, XoodyakKeyed};
+    use crate::Cyclist;
+
+    #[test]
+    fn round_trip() {
+        let mut hash = XoodyakHash::default();
+        hash.absorb(b"in progress");
+        let json = serde_json::to_string(&hash).expect("should serialize");
+        assert_eq!(serde_json::to_string(&hash.to_bytes()).expect("should serialize"), json);
+
+        let mut resumed: XoodyakHash = serde_json::from_str(&json).expect("should deserialize");
+        assert_eq!(hash.squeeze(32), resumed.squeeze(32));
+    }
+
+    #[test]
+    fn mismatched_modes() {
+        let keyed = XoodyakKeyed::new(b"key", b"", b"");
+        let json = serde_json::to_string(&keyed).expect("should serialize");
+        assert!(serde_json::from_str::<XoodyakHash>(&json).is_err());
+        assert!(serde_json::from_str::<XoodyakKeyed>(&json).is_ok());
+        assert!(serde_json::from_str::<XoodyakKeyed>("[2, 0, 0]").is_err());
+    }
+
+    #[test]
+    fn poisoned_sessions() {
+        let mut keyed = XoodyakKeyed::new(b"key", b"", b"");
+        assert!(keyed.open(&[0u8; 16]).is_err());
+        let err = serde_json::to_string(&keyed).expect_err("serialized a poisoned session");
+        assert_eq!("keyed duplex poisoned by a failed open", err.to_string());
+    }
+}
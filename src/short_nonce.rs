@@ -0,0 +1,355 @@
+//! A short-nonce profile for constrained radio links (e.g. BLE or IEEE 802.15.4).
+//!
+//! Radio stacks typically have room for only a few bytes of nonce and tag in each packet. This
+//! profile prefixes each packet with a [`NONCE_LEN`]-byte explicit nonce holding the sender's
+//! message counter, and appends a `TAG_LEN`-byte tag of between [`MIN_TAG_LEN`] and
+//! [`MAX_TAG_LEN`] bytes. Both ends enforce the limits of a key instead of silently overrunning
+//! them: a [`Sealer`] refuses to seal more than [`MAX_MESSAGES`] packets, and an [`Opener`] refuses
+//! to open any more packets once [`max_failures`] packets have failed authentication, at which
+//! point the key must be replaced.
+//!
+//! ```rust
+//! use cyclist::short_nonce::{sealed_len, Opener, Sealer, NONCE_LEN};
+//! use cyclist::xoodyak::XoodyakKeyed;
+//! use cyclist::Keyed;
+//!
+//! let key = XoodyakKeyed::new(b"a link key", b"central->peripheral", b"");
+//! let mut sealer = Sealer::<_, 4>::new(key.clone());
+//! let mut opener = Opener::<_, 4>::new(key);
+//!
+//! let mut packet = [0u8; sealed_len(3, 4)];
+//! packet[NONCE_LEN..NONCE_LEN + 3].copy_from_slice(b"on!");
+//! sealer.seal(&mut packet).expect("should be under the limit");
+//!
+//! assert_eq!(Ok(0), opener.open(&mut packet));
+//! assert_eq!(b"on!", &packet[NONCE_LEN..NONCE_LEN + 3]);
+//! ```
+//!
+//! Because the nonce is a counter which starts at zero, each direction of a link must use a
+//! distinct key or key ID.
+//!
+//! # Construction
+//!
+//! To seal a packet, a copy of the keyed duplex absorbs a domain separation label and the packet's
+//! nonce, encrypts the payload, and squeezes a `TAG_LEN`-byte tag. The nonce is the message counter
+//! as a little-endian 40-bit integer. An opener only accepts nonces greater than that of the last
+//! packet it opened, so replayed and reordered packets are rejected, but lost packets are not.
+//!
+//! The limit on authentication failures keeps the probability of an attacker forging a packet
+//! below 2<sup>-4 × `TAG_LEN`</sup>, i.e. it allows as many failures as the square root of the
+//! number of possible tags.
+
+use core::fmt;
+
+use constant_time_eq::constant_time_eq;
+
+use crate::{wipe, Keyed};
+
+/// The length of a packet's explicit nonce, in bytes.
+pub const NONCE_LEN: usize = 5;
+
+/// The minimum length of an authentication tag, in bytes.
+pub const MIN_TAG_LEN: usize = 4;
+
+/// The maximum length of an authentication tag, in bytes.
+pub const MAX_TAG_LEN: usize = 16;
+
+/// The maximum number of packets which can be sealed with a single key.
+pub const MAX_MESSAGES: u64 = 1 << (8 * NONCE_LEN);
+
+/// The domain separation label for packets.
+const SHORT_NONCE_LABEL: &[u8] = b"cyclist.short-nonce";
+
+/// Returns the maximum number of packets with `tag_len`-byte tags which can fail authentication
+/// before an [`Opener`] refuses to open any more.
+pub const fn max_failures(tag_len: usize) -> u64 {
+    let bits = 4 * tag_len;
+    if bits >= 64 {
+        u64::MAX
+    } else {
+        1 << bits
+    }
+}
+
+/// Returns the length of a packet holding `payload_len` bytes of payload with a `tag_len`-byte
+/// tag.
+pub const fn sealed_len(payload_len: usize, tag_len: usize) -> usize {
+    NONCE_LEN + payload_len + tag_len
+}
+
+/// Returns the length of the payload of a `packet_len`-byte packet with a `tag_len`-byte tag, or
+/// `None` if the packet is too short.
+pub const fn opened_len(packet_len: usize, tag_len: usize) -> Option<usize> {
+    packet_len.checked_sub(NONCE_LEN + tag_len)
+}
+
+/// An error sealing or opening a packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The packet was too short to contain a nonce and a tag.
+    Truncated,
+
+    /// The packet's nonce was not greater than that of the last packet opened.
+    Replayed {
+        /// The packet's message counter.
+        counter: u64,
+    },
+
+    /// The packet could not be authenticated.
+    Inauthentic,
+
+    /// [`MAX_MESSAGES`] packets have already been sealed with the key.
+    MessageLimit,
+
+    /// [`max_failures`] packets have already failed authentication with the key.
+    FailureLimit,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Truncated => write!(f, "truncated packet"),
+            Error::Replayed { counter } => write!(f, "replayed packet (counter {})", counter),
+            Error::Inauthentic => write!(f, "inauthentic packet"),
+            Error::MessageLimit => write!(f, "message limit reached"),
+            Error::FailureLimit => write!(f, "authentication failure limit reached"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// The sealing end of a short-nonce link.
+#[derive(Clone, Debug)]
+pub struct Sealer<K, const TAG_LEN: usize> {
+    keyed: K,
+    counter: u64,
+}
+
+impl<K, const TAG_LEN: usize> Sealer<K, TAG_LEN>
+where
+    K: Keyed,
+{
+    /// Creates a new [`Sealer`] with the given keyed duplex.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `TAG_LEN` is less than [`MIN_TAG_LEN`] or greater than [`MAX_TAG_LEN`].
+    pub fn new(keyed: K) -> Self {
+        assert!((MIN_TAG_LEN..=MAX_TAG_LEN).contains(&TAG_LEN), "invalid tag length");
+        Sealer { keyed, counter: 0 }
+    }
+
+    /// Returns the number of packets which can still be sealed with the key.
+    pub const fn remaining(&self) -> u64 {
+        MAX_MESSAGES - self.counter
+    }
+
+    /// Seals a packet in place, returning its message counter. The payload must be in
+    /// `packet[NONCE_LEN..packet.len() - TAG_LEN]`; the nonce and tag will be overwritten.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MessageLimit`] if [`MAX_MESSAGES`] packets have already been sealed, in
+    /// which case the packet is unmodified.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the packet is shorter than [`sealed_len`] of an empty payload.
+    pub fn seal(&mut self, packet: &mut [u8]) -> Result<u64, Error> {
+        assert!(packet.len() >= sealed_len(0, TAG_LEN), "packet too short");
+        if self.counter == MAX_MESSAGES {
+            return Err(Error::MessageLimit);
+        }
+
+        let counter = self.counter;
+        let (nonce, rest) = packet.split_at_mut(NONCE_LEN);
+        let (plaintext, tag) = rest.split_at_mut(rest.len() - TAG_LEN);
+        nonce.copy_from_slice(&counter.to_le_bytes()[..NONCE_LEN]);
+
+        let mut keyed = packet_keyed(&self.keyed, nonce);
+        keyed.encrypt_mut(plaintext);
+        keyed.squeeze_mut(tag);
+        self.counter += 1;
+        Ok(counter)
+    }
+}
+
+/// The opening end of a short-nonce link.
+#[derive(Clone, Debug)]
+pub struct Opener<K, const TAG_LEN: usize> {
+    keyed: K,
+    next_counter: u64,
+    failures: u64,
+}
+
+impl<K, const TAG_LEN: usize> Opener<K, TAG_LEN>
+where
+    K: Keyed,
+{
+    /// Creates a new [`Opener`] with the given keyed duplex.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `TAG_LEN` is less than [`MIN_TAG_LEN`] or greater than [`MAX_TAG_LEN`].
+    pub fn new(keyed: K) -> Self {
+        assert!((MIN_TAG_LEN..=MAX_TAG_LEN).contains(&TAG_LEN), "invalid tag length");
+        Opener { keyed, next_counter: 0, failures: 0 }
+    }
+
+    /// Returns the number of packets which have failed authentication with the key.
+    pub const fn failures(&self) -> u64 {
+        self.failures
+    }
+
+    /// Opens a packet in place, returning its message counter. If the packet is authentic, the
+    /// payload will be in `packet[NONCE_LEN..packet.len() - TAG_LEN]`; otherwise, it will have been
+    /// wiped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::FailureLimit`] if [`max_failures`] packets have already failed
+    /// authentication, [`Error::Truncated`] if the packet is too short, [`Error::Replayed`] if its
+    /// nonce isn't greater than that of the last packet opened, and [`Error::Inauthentic`] if it
+    /// cannot be authenticated.
+    pub fn open(&mut self, packet: &mut [u8]) -> Result<u64, Error> {
+        if self.failures >= max_failures(TAG_LEN) {
+            return Err(Error::FailureLimit);
+        }
+
+        if packet.len() < sealed_len(0, TAG_LEN) {
+            return Err(Error::Truncated);
+        }
+
+        let (nonce, rest) = packet.split_at_mut(NONCE_LEN);
+        let (ciphertext, tag) = rest.split_at_mut(rest.len() - TAG_LEN);
+        let mut counter = [0u8; 8];
+        counter[..NONCE_LEN].copy_from_slice(nonce);
+        let counter = u64::from_le_bytes(counter);
+        if counter < self.next_counter {
+            return Err(Error::Replayed { counter });
+        }
+
+        let mut keyed = packet_keyed(&self.keyed, nonce);
+        keyed.decrypt_mut(ciphertext);
+        let mut tag_p = [0u8; MAX_TAG_LEN];
+        keyed.squeeze_mut(&mut tag_p[..TAG_LEN]);
+        let authentic = constant_time_eq(tag, &tag_p[..TAG_LEN]);
+        wipe(&mut tag_p);
+
+        if !authentic {
+            wipe(ciphertext);
+            self.failures += 1;
+            return Err(Error::Inauthentic);
+        }
+
+        self.next_counter = counter + 1;
+        Ok(counter)
+    }
+}
+
+/// Returns a copy of the keyed duplex which has absorbed the label and the given nonce.
+fn packet_keyed<K: Keyed>(keyed: &K, nonce: &[u8]) -> K {
+    let mut keyed = keyed.clone();
+    keyed.absorb(SHORT_NONCE_LABEL);
+    keyed.absorb(nonce);
+    keyed
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakKeyed;
+
+    use super::*;
+
+    const PAYLOAD: &[u8] = b"on!";
+
+    fn keyed() -> XoodyakKeyed {
+        XoodyakKeyed::new(b"a link key", b"", b"")
+    }
+
+    fn packet(sealer: &mut Sealer<XoodyakKeyed, 4>) -> [u8; sealed_len(PAYLOAD.len(), 4)] {
+        let mut packet = [0u8; sealed_len(PAYLOAD.len(), 4)];
+        packet[NONCE_LEN..NONCE_LEN + PAYLOAD.len()].copy_from_slice(PAYLOAD);
+        sealer.seal(&mut packet).expect("should seal");
+        packet
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut sealer = Sealer::<_, 4>::new(keyed());
+        let mut opener = Opener::<_, 4>::new(keyed());
+        for i in 0..3u64 {
+            let mut packet = packet(&mut sealer);
+            assert_eq!(&i.to_le_bytes()[..NONCE_LEN], &packet[..NONCE_LEN]);
+            assert_eq!(Ok(i), opener.open(&mut packet));
+            assert_eq!(PAYLOAD, &packet[NONCE_LEN..NONCE_LEN + PAYLOAD.len()]);
+        }
+    }
+
+    #[test]
+    fn lost_and_replayed_packets() {
+        let mut sealer = Sealer::<_, 4>::new(keyed());
+        let mut opener = Opener::<_, 4>::new(keyed());
+        let mut first = packet(&mut sealer);
+        let mut second = packet(&mut sealer);
+        let mut third = packet(&mut sealer);
+
+        assert_eq!(Ok(1), opener.open(&mut second));
+        assert_eq!(Err(Error::Replayed { counter: 0 }), opener.open(&mut first));
+        assert_eq!(Err(Error::Replayed { counter: 1 }), opener.open(&mut second));
+        assert_eq!(Ok(2), opener.open(&mut third));
+        assert_eq!(0, opener.failures());
+    }
+
+    #[test]
+    fn modified_packets() {
+        let mut sealer = Sealer::<_, 4>::new(keyed());
+        let mut opener = Opener::<_, 4>::new(keyed());
+        let mut packet = packet(&mut sealer);
+        packet[NONCE_LEN] ^= 1;
+        assert_eq!(Err(Error::Inauthentic), opener.open(&mut packet));
+        assert_eq!([0u8; 3], packet[NONCE_LEN..NONCE_LEN + PAYLOAD.len()]);
+        assert_eq!(1, opener.failures());
+
+        assert_eq!(Err(Error::Truncated), opener.open(&mut [0u8; NONCE_LEN + 3]));
+    }
+
+    #[test]
+    fn message_limit() {
+        let mut sealer = Sealer::<_, 4>::new(keyed());
+        sealer.counter = MAX_MESSAGES - 1;
+        assert_eq!(1, sealer.remaining());
+
+        let mut opener = Opener::<_, 4>::new(keyed());
+        let mut packet = packet(&mut sealer);
+        assert_eq!([0xFF; NONCE_LEN], packet[..NONCE_LEN]);
+        assert_eq!(Ok(MAX_MESSAGES - 1), opener.open(&mut packet));
+
+        assert_eq!(Err(Error::MessageLimit), sealer.seal(&mut packet));
+        assert_eq!(0, sealer.remaining());
+    }
+
+    #[test]
+    fn failure_limit() {
+        assert_eq!(1 << 16, max_failures(4));
+        assert_eq!(1 << 32, max_failures(8));
+        assert_eq!(u64::MAX, max_failures(16));
+
+        let mut sealer = Sealer::<_, 4>::new(keyed());
+        let mut opener = Opener::<_, 4>::new(keyed());
+        opener.failures = max_failures(4) - 1;
+
+        let mut forged = [0u8; sealed_len(PAYLOAD.len(), 4)];
+        assert_eq!(Err(Error::Inauthentic), opener.open(&mut forged));
+        assert_eq!(Err(Error::FailureLimit), opener.open(&mut packet(&mut sealer)));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid tag length")]
+    fn long_tags() {
+        Opener::<_, 17>::new(keyed());
+    }
+}
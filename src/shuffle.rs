@@ -0,0 +1,184 @@
+//! Deterministic shuffling and sampling driven by squeezed output.
+//!
+//! Both [`shuffle`] and [`sample_k`] derive all of their randomness from a hash which has absorbed a
+//! domain separation label and a seed, so the same seed always produces the same result. This makes
+//! them useful for selecting committees from a shared random beacon, or for seeding property tests
+//! reproducibly.
+//!
+//! ```rust
+//! use cyclist::shuffle::{sample_k, shuffle};
+//! use cyclist::xoodyak::XoodyakHash;
+//!
+//! let mut members = ["alice", "bob", "carol", "dave"];
+//! shuffle::<XoodyakHash, _>(b"epoch 12", &mut members);
+//!
+//! let committee = sample_k::<XoodyakHash>(b"epoch 12", 100, 5);
+//! assert_eq!(5, committee.len());
+//! ```
+//!
+//! # Unbiasedness
+//!
+//! Indexes are chosen by squeezing 64-bit integers and rejecting any which fall in the final,
+//! partial multiple of the bound, so every index in `0..bound` is exactly equally likely (assuming
+//! the squeezed output is indistinguishable from random). [`shuffle`] is a Fisher–Yates shuffle
+//! using these indexes, so every permutation of the slice is equally likely, and [`sample_k`] is a
+//! partial Fisher–Yates shuffle, so every ordered selection of `k` distinct indexes is equally
+//! likely.
+
+use crate::Cyclist;
+
+/// The domain separation label for shuffling and sampling.
+pub(crate) const SHUFFLE_LABEL: &[u8] = b"cyclist.shuffle";
+
+/// Shuffles the given slice in place, deterministically derived from `seed`.
+pub fn shuffle<H: Cyclist + Default, T>(seed: &[u8], slice: &mut [T]) {
+    let mut rng = Indexes::<H>::new(seed);
+    for i in (1..slice.len()).rev() {
+        slice.swap(i, rng.below(i + 1));
+    }
+}
+
+/// Returns `k` distinct indexes from `0..n` in the order they were selected, deterministically
+/// derived from `seed`.
+///
+/// # Panics
+///
+/// Panics if `k` is greater than `n`.
+#[cfg(feature = "std")]
+pub fn sample_k<H: Cyclist + Default>(seed: &[u8], n: usize, k: usize) -> Vec<usize> {
+    assert!(k <= n, "cannot sample more than n indexes");
+
+    // Run the first k steps of a Fisher–Yates shuffle of 0..n, storing only the swapped entries.
+    let mut rng = Indexes::<H>::new(seed);
+    let mut swapped = std::collections::HashMap::with_capacity(k);
+    (0..k)
+        .map(|i| {
+            let j = i + rng.below(n - i);
+            let at_j = swapped.get(&j).copied().unwrap_or(j);
+            let at_i = swapped.get(&i).copied().unwrap_or(i);
+            swapped.insert(j, at_i);
+            at_j
+        })
+        .collect()
+}
+
+/// A source of uniformly distributed indexes squeezed from a hash.
+pub(crate) struct Indexes<H> {
+    hash: H,
+    buf: [u8; 64],
+    buf_pos: usize,
+}
+
+impl<H> Indexes<H>
+where
+    H: Cyclist + Default,
+{
+    /// Creates a new source of indexes from the given seed.
+    fn new(seed: &[u8]) -> Self {
+        let mut hash = H::default();
+        hash.absorb(SHUFFLE_LABEL);
+        hash.absorb(seed);
+        Indexes::from_duplex(hash)
+    }
+}
+
+impl<H> Indexes<H>
+where
+    H: Cyclist,
+{
+    /// Creates a new source of indexes squeezed from the given duplex, which should already have
+    /// absorbed a domain separation label.
+    pub(crate) const fn from_duplex(hash: H) -> Self {
+        Indexes { hash, buf: [0u8; 64], buf_pos: 64 }
+    }
+
+    /// Returns a uniformly distributed index in `0..bound`.
+    pub(crate) fn below(&mut self, bound: usize) -> usize {
+        let bound = u64::try_from(bound).expect("unexpected overflow");
+        // Reject values in the final, partial multiple of the bound.
+        let limit = u64::MAX - (u64::MAX % bound + 1) % bound;
+        loop {
+            let x = self.next_u64();
+            if x <= limit {
+                return usize::try_from(x % bound).expect("unexpected overflow");
+            }
+        }
+    }
+
+    /// Returns the next squeezed 64-bit integer.
+    fn next_u64(&mut self) -> u64 {
+        if self.buf_pos == self.buf.len() {
+            self.hash.squeeze_mut(&mut self.buf);
+            self.buf_pos = 0;
+        }
+        let x = &self.buf[self.buf_pos..self.buf_pos + 8];
+        self.buf_pos += 8;
+        u64::from_le_bytes(x.try_into().expect("invalid length"))
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakHash;
+
+    use super::*;
+
+    #[test]
+    fn shuffling() {
+        let mut one = (0..100).collect::<Vec<_>>();
+        shuffle::<XoodyakHash, _>(b"seed", &mut one);
+        assert_ne!((0..100).collect::<Vec<_>>(), one);
+
+        let mut sorted = one.clone();
+        sorted.sort_unstable();
+        assert_eq!((0..100).collect::<Vec<_>>(), sorted);
+
+        let mut two = (0..100).collect::<Vec<_>>();
+        shuffle::<XoodyakHash, _>(b"seed", &mut two);
+        assert_eq!(one, two);
+
+        let mut three = (0..100).collect::<Vec<_>>();
+        shuffle::<XoodyakHash, _>(b"other seed", &mut three);
+        assert_ne!(one, three);
+    }
+
+    #[test]
+    fn sampling() {
+        let sample = sample_k::<XoodyakHash>(b"seed", 1000, 20);
+        assert_eq!(20, sample.len());
+        assert!(sample.iter().all(|&i| i < 1000));
+
+        let mut unique = sample.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(20, unique.len());
+
+        assert_eq!(sample, sample_k::<XoodyakHash>(b"seed", 1000, 20));
+        assert_ne!(sample, sample_k::<XoodyakHash>(b"other seed", 1000, 20));
+
+        // Sampling every index is a permutation.
+        let mut sampled = sample_k::<XoodyakHash>(b"seed", 50, 50);
+        assert_ne!((0..50).collect::<Vec<_>>(), sampled);
+        sampled.sort_unstable();
+        assert_eq!((0..50).collect::<Vec<_>>(), sampled);
+        assert!(sample_k::<XoodyakHash>(b"seed", 0, 0).is_empty());
+    }
+
+    #[test]
+    fn uniformity() {
+        let mut counts = [0usize; 6];
+        let mut rng = Indexes::<XoodyakHash>::new(b"seed");
+        for _ in 0..60_000 {
+            counts[rng.below(6)] += 1;
+        }
+        for count in counts {
+            assert!((9_000..11_000).contains(&count), "skewed counts: {:?}", counts);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot sample more than n indexes")]
+    fn oversampling() {
+        sample_k::<XoodyakHash>(b"seed", 3, 4);
+    }
+}
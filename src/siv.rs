@@ -0,0 +1,215 @@
+//! Nonce-misuse-resistant sealing with a synthetic IV (SIV).
+//!
+//! [`CyclistKeyed::seal_mut`] is a single pass: each byte of plaintext is encrypted as soon as it
+//! is absorbed, so if a key and nonce are ever reused, the two messages' keystreams will be
+//! identical up to the first byte at which they differ, revealing the XOR of their plaintexts.
+//!
+//! SIV mode makes two passes over the plaintext. The first derives the tag from the whole
+//! plaintext, and the second uses the tag as a synthetic nonce for encryption, so any change to the
+//! plaintext changes the entire keystream. If a key and nonce are reused, SIV mode reveals only
+//! whether two messages (with the same associated data) are identical, which is the best possible
+//! for a deterministic scheme (i.e. DAE security):
+//!
+//! ```rust
+//! use cyclist::Cyclist;
+//! use cyclist::xoodyak::XoodyakKeyed;
+//!
+//! let mut sealer = XoodyakKeyed::new(b"This is a secret key!", b"This is a nonce!", b"");
+//! sealer.absorb(b"This is authenticated data!");
+//! let ciphertext = sealer.seal_siv(b"This is the plaintext!");
+//!
+//! let mut opener = XoodyakKeyed::new(b"This is a secret key!", b"This is a nonce!", b"");
+//! opener.absorb(b"This is authenticated data!");
+//! assert_eq!(Ok(b"This is the plaintext!".to_vec()), opener.open_siv(&ciphertext));
+//! ```
+//!
+//! The price is that sealing can't begin producing ciphertext until the whole plaintext is
+//! available, and opening can't authenticate the plaintext until it's been fully decrypted. The
+//! output of SIV mode is not interchangeable with the output of [`CyclistKeyed::seal_mut`].
+//!
+//! # Construction
+//!
+//! To seal, a copy of the session absorbs a domain separation label and the plaintext, and then
+//! squeezes the tag. The session absorbs the tag and encrypts the plaintext. To open, the session
+//! absorbs the tag and decrypts the ciphertext, and the tag is recalculated from a copy of the
+//! session made before the tag was absorbed and compared in constant time.
+
+use constant_time_eq::constant_time_eq;
+
+use crate::{wipe, Cyclist, CyclistKeyed, Error, Permutation, Tag};
+
+/// The domain separation label absorbed when deriving a synthetic IV.
+const SIV_LABEL: &[u8] = b"cyclist.siv";
+
+impl<
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > CyclistKeyed<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+{
+    /// Seals the given mutable slice in place using SIV mode.
+    ///
+    /// The last `TAG_LEN` bytes of the slice will be overwritten with the authentication tag.
+    pub fn seal_siv_mut(&mut self, in_out: &mut [u8]) {
+        let (plaintext, tag) = in_out.split_at_mut(in_out.len() - TAG_LEN);
+        tag.copy_from_slice(self.seal_siv_detached(plaintext).as_ref());
+    }
+
+    /// Seals the given mutable slice in place using SIV mode and returns the authentication tag
+    /// separately.
+    pub fn seal_siv_detached(&mut self, in_out: &mut [u8]) -> Tag<TAG_LEN> {
+        let tag = self.synthetic_iv(in_out);
+        self.absorb(&tag);
+        self.encrypt_mut(in_out);
+        self.core.stats.seals += 1;
+        Tag(tag)
+    }
+
+    /// Returns a copy of the given slice sealed using SIV mode.
+    ///
+    /// The returned [Vec] will be `TAG_LEN` bytes longer than `bin`.
+    #[cfg(feature = "std")]
+    pub fn seal_siv(&mut self, bin: &[u8]) -> Vec<u8> {
+        let mut c = vec![0u8; bin.len() + TAG_LEN];
+        c[..bin.len()].copy_from_slice(bin);
+        self.seal_siv_mut(&mut c);
+        c
+    }
+
+    /// Opens the given mutable slice in place using SIV mode. The last `TAG_LEN` bytes of the slice
+    /// will be unmodified. If the input cannot be authenticated, the rest of the slice is zeroed
+    /// out.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the duplex is poisoned, [`Error::BufferTooSmall`] if the slice
+    /// is shorter than `TAG_LEN` bytes, leaving the state unmodified, and [`Error::InvalidTag`] if
+    /// the input could not be authenticated.
+    pub fn open_siv_mut(&mut self, in_out: &mut [u8]) -> Result<(), Error> {
+        self.check_unpoisoned()?;
+        if in_out.len() < TAG_LEN {
+            self.core.stats.opens += 1;
+            self.core.stats.failures += 1;
+            return Err(Error::BufferTooSmall);
+        }
+
+        let (ciphertext, tag) = in_out.split_at_mut(in_out.len() - TAG_LEN);
+        let tag = Tag::try_from(&*tag).expect("invalid tag length");
+        self.open_siv_detached(ciphertext, &tag)
+    }
+
+    /// Opens the given mutable slice in place using SIV mode, given its detached authentication
+    /// tag. If the tag doesn't match, the slice is zeroed out.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the duplex is poisoned and [`Error::InvalidTag`] if the input
+    /// could not be authenticated.
+    pub fn open_siv_detached(
+        &mut self,
+        in_out: &mut [u8],
+        tag: &Tag<TAG_LEN>,
+    ) -> Result<(), Error> {
+        self.check_unpoisoned()?;
+
+        // Keep a copy of the session from before the tag is absorbed, from which the synthetic IV is
+        // recalculated.
+        let base = self.clone();
+        self.absorb(&tag.0);
+        self.decrypt_mut(in_out);
+
+        let mut tag_p = base.synthetic_iv(in_out);
+        let authentic = constant_time_eq(&tag.0, &tag_p);
+        wipe(&mut tag_p);
+
+        self.core.stats.opens += 1;
+        if !authentic {
+            self.reject(in_out);
+            return Err(Error::InvalidTag);
+        }
+        Ok(())
+    }
+
+    /// Returns an unsealed copy of the given slice, which was sealed using SIV mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Poisoned`] if the duplex is poisoned, [`Error::BufferTooSmall`] if the slice
+    /// is shorter than `TAG_LEN` bytes, and [`Error::InvalidTag`] if the ciphertext cannot be
+    /// authenticated.
+    #[cfg(feature = "std")]
+    pub fn open_siv(&mut self, bin: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut p = bin.to_vec();
+        self.open_siv_mut(&mut p)?;
+        p.truncate(bin.len() - TAG_LEN);
+        Ok(p)
+    }
+
+    /// Derives the synthetic IV of the given plaintext from a copy of the session.
+    fn synthetic_iv(&self, plaintext: &[u8]) -> [u8; TAG_LEN] {
+        let mut keyed = self.clone();
+        keyed.absorb(SIV_LABEL);
+        keyed.absorb(plaintext);
+        let mut tag = [0u8; TAG_LEN];
+        keyed.squeeze_mut(&mut tag);
+        tag
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakKeyed;
+    use crate::Keyed;
+
+    use super::*;
+
+    fn keyed() -> XoodyakKeyed {
+        XoodyakKeyed::new(b"ok then", b"a nonce", b"")
+    }
+
+    #[test]
+    fn round_trip() {
+        for len in [0, 1, 23, 24, 25, 100] {
+            let m = vec![7u8; len];
+            let mut d = keyed();
+            let c = d.seal_siv(&m);
+            assert_eq!(len + XoodyakKeyed::TAG_LEN, c.len());
+
+            let mut o = keyed();
+            assert_eq!(Ok(m), o.open_siv(&c));
+            assert_eq!(d.squeeze(8), o.squeeze(8), "len={}", len);
+        }
+    }
+
+    #[test]
+    fn nonce_reuse() {
+        // With SIV mode, a one-byte change at the end of the plaintext changes all the ciphertext.
+        let one = keyed().seal_siv(b"attack at dawn");
+        let two = keyed().seal_siv(b"attack at dusk");
+        assert_ne!(one[..10], two[..10]);
+        assert_eq!(one, keyed().seal_siv(b"attack at dawn"));
+        assert_ne!(one, keyed().seal(b"attack at dawn"));
+    }
+
+    #[test]
+    fn inauthentic() {
+        let c = keyed().seal_siv(b"it's a deal");
+        for i in 0..c.len() {
+            let mut c_p = c.clone();
+            c_p[i] ^= 1;
+            assert_eq!(Err(Error::InvalidTag), keyed().open_siv(&c_p), "i={}", i);
+        }
+
+        let mut d = keyed();
+        assert_eq!(Err(Error::BufferTooSmall), d.open_siv(&c[..XoodyakKeyed::TAG_LEN - 1]));
+        let mut c_p = c.clone();
+        assert_eq!(Err(Error::InvalidTag), d.open_siv_mut(&mut c_p[1..]));
+        assert!(c_p[1..c.len() - XoodyakKeyed::TAG_LEN].iter().all(|&b| b == 0));
+        assert_eq!(2, d.session_info().auth_failures);
+    }
+}
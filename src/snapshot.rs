@@ -0,0 +1,78 @@
+//! Explicit snapshots of Cyclist states, for rolling back speculative operations.
+//!
+//! Cloning a Cyclist object is the simplest way to try an operation and undo it, but clones are
+//! indistinguishable from objects which are meant to be used, which makes it hard to audit a
+//! protocol for state which is accidentally forked and reused. A [`Snapshot`] captures a state
+//! which can only be restored, not used directly:
+//!
+//! ```rust
+//! use cyclist::xoodyak::XoodyakKeyed;
+//! use cyclist::Cyclist;
+//!
+//! let mut sender = XoodyakKeyed::new(b"a key", b"", b"");
+//! sender.absorb(b"header");
+//! let c = sender.seal(b"message");
+//!
+//! let mut receiver = XoodyakKeyed::new(b"a key", b"", b"");
+//! receiver.absorb(b"header");
+//! let snapshot = receiver.snapshot();
+//!
+//! // Trial decryption of a candidate ciphertext fails, so roll back and try another.
+//! assert!(receiver.open(b"not the right ciphertext").is_err());
+//! receiver.restore(&snapshot);
+//! assert_eq!(Ok(b"message".to_vec()), receiver.open(&c));
+//! ```
+//!
+//! A snapshot includes the object's operation counts (see [`metrics`](crate::metrics)), so
+//! restoring one also rolls them back. With the `zeroize` feature, snapshots wipe their state when
+//! dropped, as Cyclist objects do, and their [`Debug`](fmt::Debug) implementation never reveals
+//! the state.
+
+use core::fmt;
+
+/// A snapshot of a Cyclist object's state, which can be restored with the object's `restore`
+/// method.
+pub struct Snapshot<C>(pub(crate) C);
+
+impl<C> fmt::Debug for Snapshot<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Snapshot").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<C> zeroize::ZeroizeOnDrop for Snapshot<C> where C: zeroize::ZeroizeOnDrop {}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::{XoodyakHash, XoodyakKeyed};
+    use crate::Cyclist;
+
+    #[test]
+    fn rollback() {
+        let mut hash = XoodyakHash::default();
+        hash.absorb(b"prefix");
+        let snapshot = hash.snapshot();
+        let a = hash.squeeze(16);
+        hash.absorb(b"speculative");
+        hash.restore(&snapshot);
+        assert_eq!(a, hash.squeeze(16));
+
+        let mut keyed = XoodyakKeyed::new(b"key", b"", b"");
+        let snapshot = keyed.snapshot();
+        let c = keyed.seal(b"message");
+        keyed.ratchet();
+        keyed.restore(&snapshot);
+        assert_eq!(c, keyed.seal(b"message"));
+        assert_eq!(1, keyed.session_info().seals);
+        assert_eq!("Snapshot { .. }", format!("{:?}", snapshot));
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn zeroize_on_drop() {
+        const fn assert_zeroize_on_drop<T: zeroize::ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<super::Snapshot<XoodyakHash>>();
+        assert_zeroize_on_drop::<super::Snapshot<XoodyakKeyed>>();
+    }
+}
@@ -0,0 +1,90 @@
+#![cfg(all(feature = "std", any(test, feature = "proptest")))]
+
+//! [Proptest](::proptest) strategies for the crate's public types.
+//!
+//! With the `proptest` feature, downstream crates can property-test protocols which compose Cyclist
+//! objects using the same strategies the crate uses for its own transcript tests:
+//!
+//! ```rust
+//! use cyclist::fuzzing;
+//! use cyclist::strategies::keyed_transcript;
+//! use cyclist::xoodyak::XoodyakKeyed;
+//! use proptest::prelude::*;
+//!
+//! proptest! {
+//!     fn symmetry(t in keyed_transcript()) {
+//!         prop_assert!(fuzzing::keyed_transcript_symmetry::<XoodyakKeyed>(&t));
+//!     }
+//! }
+//!
+//! symmetry();
+//! ```
+//!
+//! Keys are 1 to 15 bytes long, and key IDs and counters up to 15 bytes, which are valid for every
+//! scheme in the crate except the Keccyak\[200\] schemes. The `proptest` feature enables the
+//! `fuzzing` feature.
+
+use ::proptest::collection::vec;
+use ::proptest::prelude::*;
+
+use crate::fuzzing::{HashOp, HashTranscript, KeyedOp, KeyedTranscript};
+use crate::Tag;
+
+/// An arbitrary byte string with length 0..200.
+pub fn data() -> impl Strategy<Value = Vec<u8>> {
+    vec(any::<u8>(), 0..200)
+}
+
+/// An arbitrary key with length 1..16.
+pub fn key() -> impl Strategy<Value = Vec<u8>> {
+    vec(any::<u8>(), 1..16)
+}
+
+/// An arbitrary key ID, nonce, or counter with length 0..16.
+pub fn nonce() -> impl Strategy<Value = Vec<u8>> {
+    vec(any::<u8>(), 0..16)
+}
+
+/// An arbitrary tag.
+pub fn tag<const N: usize>() -> impl Strategy<Value = Tag<N>> {
+    vec(any::<u8>(), N).prop_map(|b| Tag::try_from(b.as_slice()).expect("invalid tag length"))
+}
+
+/// An arbitrary hash mode operation.
+pub fn hash_op() -> impl Strategy<Value = HashOp> {
+    prop_oneof![(1usize..256).prop_map(HashOp::Squeeze), data().prop_map(HashOp::Absorb),]
+}
+
+/// An arbitrary keyed mode operation.
+pub fn keyed_op() -> impl Strategy<Value = KeyedOp> {
+    prop_oneof![
+        Just(KeyedOp::Ratchet),
+        (1usize..256).prop_map(KeyedOp::Squeeze),
+        data().prop_map(KeyedOp::Absorb),
+        data().prop_map(KeyedOp::Encrypt),
+        data().prop_map(KeyedOp::Decrypt),
+    ]
+}
+
+prop_compose! {
+    /// A transcript of 0..62 arbitrary hash operations terminated with a `Squeeze(16)` operation to
+    /// capture the duplex's final state.
+    pub fn hash_transcript()(mut ops in vec(hash_op(), 0..62)) -> HashTranscript {
+        ops.push(HashOp::Squeeze(16));
+        HashTranscript { ops }
+    }
+}
+
+prop_compose! {
+    /// A transcript of 0..62 arbitrary keyed operations terminated with a `Squeeze(16)` operation
+    /// to capture the duplex's final state.
+    pub fn keyed_transcript()(
+        key in key(),
+        key_id in nonce(),
+        counter in nonce(),
+        mut ops in vec(keyed_op(), 0..62),
+    ) -> KeyedTranscript {
+        ops.push(KeyedOp::Squeeze(16));
+        KeyedTranscript{ key, key_id, counter, ops }
+    }
+}
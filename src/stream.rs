@@ -0,0 +1,600 @@
+#![cfg(feature = "std")]
+
+//! Sealing and opening of streams too large to hold in memory.
+//!
+//! A sealed stream is a sequence of frames, each of which consists of a chunk of ciphertext
+//! followed by a `TAG_LEN`-byte authentication tag. Every frame but the last holds exactly
+//! `chunk_len` bytes of ciphertext; the last frame holds fewer than `chunk_len` bytes (possibly
+//! zero). Before each frame is sealed, a single byte indicating whether or not it is the final frame
+//! is absorbed, which prevents an attacker from truncating the stream at a frame boundary.
+//!
+//! Because each frame is sealed with the same duplex, each tag authenticates not only its own frame
+//! but all previous frames as well. A stream may contain at most [`MAX_FRAMES`] frames.
+//!
+//! Failures are reported as an [`Error`] which includes the index of the frame at which the
+//! failure occurred and that frame's byte offset in the sealed stream, but never any plaintext.
+//!
+//! # Messages
+//!
+//! For streams of discrete messages rather than bytes, [`Push`] and [`Pull`] follow the semantics
+//! of libsodium's `crypto_secretstream`: each message is sealed with an encrypted [`MessageTag`]
+//! which marks it as an ordinary message, the end of a set of messages, a point at which both
+//! sides rekey, or the end of the stream:
+//!
+//! ```rust
+//! use cyclist::stream::{MessageTag, Pull, Push};
+//! use cyclist::xoodyak::XoodyakKeyed;
+//!
+//! let mut push = Push::new(XoodyakKeyed::new(b"This is a secret key!", b"This is a nonce!", b""));
+//! let one = push.push(b"first", b"", MessageTag::Message);
+//! let two = push.push(b"second", b"", MessageTag::Rekey);
+//! let three = push.push(b"last", b"", MessageTag::Final);
+//!
+//! let mut pull = Pull::new(XoodyakKeyed::new(b"This is a secret key!", b"This is a nonce!", b""));
+//! assert_eq!(Ok((b"first".to_vec(), MessageTag::Message)), pull.pull(&one, b""));
+//! assert_eq!(Ok((b"second".to_vec(), MessageTag::Rekey)), pull.pull(&two, b""));
+//! assert_eq!(Ok((b"last".to_vec(), MessageTag::Final)), pull.pull(&three, b""));
+//! assert!(pull.is_finished());
+//! ```
+//!
+//! Each message absorbs its associated data and is then sealed with its tag byte prepended to the
+//! plaintext, adding [`message_overhead`] bytes. Because every message is sealed with the same
+//! duplex, messages can't be reordered, dropped, or replayed without detection. After a
+//! [`MessageTag::Rekey`] message, or a call to [`Push::rekey`]/[`Pull::rekey`], both sides ratchet
+//! their duplexes, so a later compromise of the state doesn't reveal earlier messages.
+
+use std::fmt;
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
+
+use crate::Keyed;
+
+/// The maximum number of frames in a sealed stream.
+pub const MAX_FRAMES: u64 = 1 << 32;
+
+/// Returns the length of a sealed stream of `plaintext_len` bytes with `chunk_len`-byte chunks.
+///
+/// # Panics
+///
+/// Panics if `chunk_len` is zero.
+pub const fn sealed_len<K: Keyed>(plaintext_len: usize, chunk_len: usize) -> usize {
+    assert!(chunk_len > 0, "chunk length must be > 0");
+    plaintext_len + (plaintext_len / chunk_len + 1) * K::TAG_LEN
+}
+
+/// Returns the length of the plaintext of a `sealed_len`-byte sealed stream with `chunk_len`-byte
+/// chunks, or `None` if the stream would be truncated.
+///
+/// # Panics
+///
+/// Panics if `chunk_len` is zero.
+pub const fn opened_len<K: Keyed>(sealed_len: usize, chunk_len: usize) -> Option<usize> {
+    assert!(chunk_len > 0, "chunk length must be > 0");
+    let frame_len = chunk_len + K::TAG_LEN;
+    let last = sealed_len % frame_len;
+    if last < K::TAG_LEN {
+        return None;
+    }
+    Some(sealed_len / frame_len * chunk_len + last - K::TAG_LEN)
+}
+
+/// An error sealing or opening a stream.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// An I/O error occurred reading or writing the stream.
+    Io(io::Error),
+
+    /// The sealed stream ended in the middle of a frame.
+    Truncated {
+        /// The index of the incomplete frame.
+        frame: u64,
+        /// The byte offset of the incomplete frame in the sealed stream.
+        offset: u64,
+    },
+
+    /// A frame's tag did not match, indicating the stream was modified, truncated at a frame
+    /// boundary, or opened with the wrong key or chunk length.
+    TagMismatch {
+        /// The index of the inauthentic frame.
+        frame: u64,
+        /// The byte offset of the inauthentic frame in the sealed stream.
+        offset: u64,
+    },
+
+    /// The stream has more than [`MAX_FRAMES`] frames.
+    LimitExceeded {
+        /// The index of the first frame past the limit.
+        frame: u64,
+        /// The byte offset of the first frame past the limit in the sealed stream.
+        offset: u64,
+    },
+
+    /// The keyed duplex was poisoned by a previous failure to authenticate an input.
+    Poisoned,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "i/o error: {}", e),
+            Error::Truncated { frame, offset } => {
+                write!(f, "truncated stream at frame {} (offset {})", frame, offset)
+            }
+            Error::TagMismatch { frame, offset } => {
+                write!(f, "inauthentic frame {} (offset {})", frame, offset)
+            }
+            Error::LimitExceeded { frame, offset } => {
+                write!(f, "too many frames at frame {} (offset {})", frame, offset)
+            }
+            Error::Poisoned => write!(f, "keyed duplex poisoned by a failed open"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::Io(e) => e,
+            e => io::Error::new(ErrorKind::InvalidData, e),
+        }
+    }
+}
+
+/// The number of bytes [`Push::push`] adds to each message with a keyed duplex `K`.
+pub const fn message_overhead<K: Keyed>() -> usize {
+    1 + K::OVERHEAD
+}
+
+/// The type of a message in a [`Push`]/[`Pull`] stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MessageTag {
+    /// An ordinary message.
+    Message,
+
+    /// The last message of a set of messages, but not the end of the stream.
+    Push,
+
+    /// A message after which both sides rekey.
+    Rekey,
+
+    /// The last message of the stream.
+    Final,
+}
+
+impl MessageTag {
+    const fn to_byte(self) -> u8 {
+        match self {
+            MessageTag::Message => 0x00,
+            MessageTag::Push => 0x01,
+            MessageTag::Rekey => 0x02,
+            MessageTag::Final => 0x03,
+        }
+    }
+
+    const fn from_byte(b: u8) -> Option<MessageTag> {
+        match b {
+            0x00 => Some(MessageTag::Message),
+            0x01 => Some(MessageTag::Push),
+            0x02 => Some(MessageTag::Rekey),
+            0x03 => Some(MessageTag::Final),
+            _ => None,
+        }
+    }
+}
+
+/// The sending side of a stream of tagged messages.
+#[derive(Clone, Debug)]
+pub struct Push<K: Keyed> {
+    keyed: K,
+    finished: bool,
+}
+
+impl<K: Keyed> Push<K> {
+    /// Returns a new sender using the given keyed duplex, which should have a unique nonce.
+    pub const fn new(keyed: K) -> Self {
+        Push { keyed, finished: false }
+    }
+
+    /// Seals the given message and associated data with the given tag.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a [`MessageTag::Final`] message has already been pushed.
+    pub fn push(&mut self, message: &[u8], ad: &[u8], tag: MessageTag) -> Vec<u8> {
+        assert!(!self.finished, "stream already finalized");
+
+        let mut out = vec![0u8; message.len() + message_overhead::<K>()];
+        out[0] = tag.to_byte();
+        out[1..=message.len()].copy_from_slice(message);
+        self.keyed.absorb(ad);
+        self.keyed.seal_mut(&mut out);
+
+        match tag {
+            MessageTag::Rekey => self.keyed.ratchet(),
+            MessageTag::Final => self.finished = true,
+            _ => {}
+        }
+        out
+    }
+
+    /// Ratchets the sender's state. The receiver must call [`Pull::rekey`] at the same point.
+    pub fn rekey(&mut self) {
+        self.keyed.ratchet();
+    }
+
+    /// Returns `true` if a [`MessageTag::Final`] message has been pushed.
+    pub const fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+/// The receiving side of a stream of tagged messages.
+#[derive(Clone, Debug)]
+pub struct Pull<K: Keyed> {
+    keyed: K,
+    finished: bool,
+}
+
+impl<K: Keyed> Pull<K> {
+    /// Returns a new receiver using the given keyed duplex, which must be in the same state as the
+    /// sender's.
+    pub const fn new(keyed: K) -> Self {
+        Pull { keyed, finished: false }
+    }
+
+    /// Opens the given sealed message with the given associated data, returning the message and
+    /// its tag.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error::BufferTooSmall`] if `sealed` is shorter than
+    /// [`message_overhead`], and [`crate::Error::InvalidTag`] if the message cannot be
+    /// authenticated or follows a [`MessageTag::Final`] message. After a message fails to
+    /// authenticate, the receiver is poisoned and returns [`crate::Error::Poisoned`] for every
+    /// further message.
+    pub fn pull(
+        &mut self,
+        sealed: &[u8],
+        ad: &[u8],
+    ) -> Result<(Vec<u8>, MessageTag), crate::Error> {
+        if self.finished {
+            return Err(crate::Error::InvalidTag);
+        }
+        if sealed.len() < message_overhead::<K>() {
+            return Err(crate::Error::BufferTooSmall);
+        }
+
+        let mut out = sealed.to_vec();
+        self.keyed.try_absorb(ad)?;
+        self.keyed.open_mut(&mut out)?;
+        let tag = MessageTag::from_byte(out[0]).ok_or(crate::Error::InvalidTag)?;
+        out.truncate(sealed.len() - K::OVERHEAD);
+        out.remove(0);
+
+        match tag {
+            MessageTag::Rekey => self.keyed.ratchet(),
+            MessageTag::Final => self.finished = true,
+            _ => {}
+        }
+        Ok((out, tag))
+    }
+
+    /// Ratchets the receiver's state, at the same point the sender called [`Push::rekey`]. Does
+    /// nothing if the receiver is poisoned, since it can't open any more messages.
+    pub fn rekey(&mut self) {
+        let _ = self.keyed.try_ratchet();
+    }
+
+    /// Returns `true` if a [`MessageTag::Final`] message has been pulled.
+    pub const fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+/// Seals the contents of `reader` as a stream of frames written to `writer`.
+pub(crate) fn seal<K, R, W>(
+    keyed: &mut K,
+    mut reader: R,
+    mut writer: W,
+    chunk_len: usize,
+) -> Result<u64, Error>
+where
+    K: Keyed,
+    R: Read,
+    W: Write,
+{
+    assert!(chunk_len > 0, "chunk length must be > 0");
+
+    let mut frame = vec![0u8; chunk_len + K::TAG_LEN];
+    let mut written = 0u64;
+    for index in 0.. {
+        if index == MAX_FRAMES {
+            return Err(Error::LimitExceeded {
+                frame: index,
+                offset: offset::<K>(index, chunk_len),
+            });
+        }
+
+        // Fill the frame with as much plaintext as is available. A partial frame is the final frame.
+        let n = read_full(&mut reader, &mut frame[..chunk_len])?;
+        let last = n < chunk_len;
+        let frame = &mut frame[..n + K::TAG_LEN];
+
+        // Indicate whether or not this is the final frame and seal it.
+        keyed.try_absorb(&[u8::from(last)]).map_err(|_| Error::Poisoned)?;
+        keyed.seal_mut(frame);
+        writer.write_all(frame)?;
+        written += u64::try_from(n).expect("unexpected overflow");
+
+        if last {
+            break;
+        }
+    }
+    Ok(written)
+}
+
+/// Verifies a stream of frames from `source` and then decrypts it to `sink`.
+pub(crate) fn verify_then_decrypt<K, R, W>(
+    keyed: &mut K,
+    mut source: R,
+    sink: W,
+    chunk_len: usize,
+) -> Result<u64, Error>
+where
+    K: Keyed,
+    R: Read + Seek,
+    W: Write,
+{
+    assert!(chunk_len > 0, "chunk length must be > 0");
+
+    // Verify the stream using a copy of the duplex, discarding the plaintext.
+    let start = source.stream_position()?;
+    open(&mut keyed.clone(), &mut source, io::sink(), chunk_len)?;
+
+    // Rewind the stream and decrypt it for real.
+    source.seek(SeekFrom::Start(start))?;
+    open(keyed, &mut source, sink, chunk_len)
+}
+
+/// Opens a stream of frames from `source`, writing the plaintext of each authenticated frame to
+/// `sink`.
+fn open<K, R, W>(keyed: &mut K, mut source: R, mut sink: W, chunk_len: usize) -> Result<u64, Error>
+where
+    K: Keyed,
+    R: Read,
+    W: Write,
+{
+    let mut frame = vec![0u8; chunk_len + K::TAG_LEN];
+    let mut written = 0u64;
+    for index in 0.. {
+        let offset = offset::<K>(index, chunk_len);
+        if index == MAX_FRAMES {
+            return Err(Error::LimitExceeded { frame: index, offset });
+        }
+
+        // Read a full frame. A partial frame is the final frame, but it must contain a tag.
+        let n = read_full(&mut source, &mut frame)?;
+        if n < K::TAG_LEN {
+            return Err(Error::Truncated { frame: index, offset });
+        }
+        let last = n < frame.len();
+        let frame = &mut frame[..n];
+
+        // Check whether or not this is the final frame and open it.
+        keyed.try_absorb(&[u8::from(last)]).map_err(|_| Error::Poisoned)?;
+        if keyed.open_mut(frame).is_err() {
+            return Err(Error::TagMismatch { frame: index, offset });
+        }
+        let plaintext = &frame[..n - K::TAG_LEN];
+        sink.write_all(plaintext)?;
+        written += u64::try_from(plaintext.len()).expect("unexpected overflow");
+
+        if last {
+            break;
+        }
+    }
+    Ok(written)
+}
+
+/// Returns the byte offset of the frame with the given index in a sealed stream.
+fn offset<K: Keyed>(index: u64, chunk_len: usize) -> u64 {
+    let frame_len = u64::try_from(chunk_len + K::TAG_LEN).expect("unexpected overflow");
+    index.saturating_mul(frame_len)
+}
+
+/// Reads from `reader` until `buf` is full or EOF is reached. Returns the number of bytes read.
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut n = 0;
+    while n < buf.len() {
+        match reader.read(&mut buf[n..]) {
+            Ok(0) => break,
+            Ok(m) => n += m,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(n)
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::xoodyak::XoodyakKeyed;
+    use crate::Keyed;
+
+    use super::*;
+
+    fn seal(plaintext: &[u8]) -> Vec<u8> {
+        let mut sealer = XoodyakKeyed::new(b"ok then", b"nonce", b"");
+        let mut ciphertext = Vec::new();
+        let n = sealer.seal_stream(plaintext, &mut ciphertext, 64).expect("error sealing");
+        assert_eq!(u64::try_from(plaintext.len()).unwrap(), n);
+        ciphertext
+    }
+
+    #[test]
+    fn round_trip() {
+        for len in [0, 1, 63, 64, 65, 128, 1000] {
+            let plaintext = vec![0xCA; len];
+            let ciphertext = seal(&plaintext);
+            assert_eq!(sealed_len::<XoodyakKeyed>(len, 64), ciphertext.len());
+            assert_eq!(Some(len), opened_len::<XoodyakKeyed>(ciphertext.len(), 64));
+
+            let mut opener = XoodyakKeyed::new(b"ok then", b"nonce", b"");
+            let mut plaintext_p = Vec::new();
+            let n = opener
+                .verify_then_decrypt_stream(Cursor::new(ciphertext), &mut plaintext_p, 64)
+                .expect("error opening");
+
+            assert_eq!(u64::try_from(len).unwrap(), n);
+            assert_eq!(plaintext, plaintext_p);
+        }
+    }
+
+    #[test]
+    fn modified_frame() {
+        let mut ciphertext = seal(&[0xCA; 1000]);
+        let last = ciphertext.len() - 1;
+        ciphertext[last] ^= 1;
+
+        let mut opener = XoodyakKeyed::new(b"ok then", b"nonce", b"");
+        let mut plaintext = Vec::new();
+        let err = opener
+            .verify_then_decrypt_stream(Cursor::new(ciphertext), &mut plaintext, 64)
+            .expect_err("opened modified stream");
+        assert!(
+            matches!(err, Error::TagMismatch { frame: 15, offset: 1200 }),
+            "unexpected error: {:?}",
+            err
+        );
+        assert!(plaintext.is_empty(), "wrote unauthenticated plaintext");
+    }
+
+    #[test]
+    fn truncated_stream() {
+        let mut ciphertext = seal(&[0xCA; 1000]);
+        ciphertext.truncate(2 * (64 + XoodyakKeyed::TAG_LEN));
+        assert_eq!(None, opened_len::<XoodyakKeyed>(ciphertext.len(), 64));
+
+        let mut opener = XoodyakKeyed::new(b"ok then", b"nonce", b"");
+        let mut plaintext = Vec::new();
+        let err = opener
+            .verify_then_decrypt_stream(Cursor::new(ciphertext), &mut plaintext, 64)
+            .expect_err("opened truncated stream");
+        assert!(
+            matches!(err, Error::Truncated { frame: 2, offset: 160 }),
+            "unexpected error: {:?}",
+            err
+        );
+        assert!(plaintext.is_empty(), "wrote unauthenticated plaintext");
+    }
+
+    #[test]
+    fn io_errors() {
+        let err = Error::TagMismatch { frame: 1, offset: 80 };
+        assert_eq!("inauthentic frame 1 (offset 80)", err.to_string());
+        assert_eq!(ErrorKind::InvalidData, io::Error::from(err).kind());
+
+        let err = Error::from(io::Error::from(ErrorKind::UnexpectedEof));
+        assert_eq!(ErrorKind::UnexpectedEof, io::Error::from(err).kind());
+    }
+
+    fn messages() -> (Push<XoodyakKeyed>, Pull<XoodyakKeyed>) {
+        (
+            Push::new(XoodyakKeyed::new(b"ok then", b"nonce", b"")),
+            Pull::new(XoodyakKeyed::new(b"ok then", b"nonce", b"")),
+        )
+    }
+
+    #[test]
+    fn message_tags() {
+        let (mut push, mut pull) = messages();
+        let tags = [MessageTag::Message, MessageTag::Push, MessageTag::Rekey, MessageTag::Final];
+        for (i, &tag) in tags.iter().enumerate() {
+            let m = vec![0xCA; i * 10];
+            let c = push.push(&m, b"ad", tag);
+            assert_eq!(m.len() + message_overhead::<XoodyakKeyed>(), c.len());
+            assert_eq!(Ok((m, tag)), pull.pull(&c, b"ad"));
+        }
+        assert!(push.is_finished());
+        assert!(pull.is_finished());
+    }
+
+    #[test]
+    fn manual_rekeys() {
+        let (mut push, mut pull) = messages();
+        push.rekey();
+        let c = push.push(b"ok", b"", MessageTag::Message);
+        assert_eq!(Err(crate::Error::InvalidTag), pull.clone().pull(&c, b""));
+        pull.rekey();
+        assert_eq!(Ok((b"ok".to_vec(), MessageTag::Message)), pull.pull(&c, b""));
+    }
+
+    #[test]
+    fn invalid_messages() {
+        let (mut push, pull) = messages();
+        let one = push.push(b"one", b"", MessageTag::Message);
+        let two = push.push(b"two", b"", MessageTag::Final);
+
+        // Reordered, modified, and short messages and mismatched associated data are rejected.
+        assert_eq!(Err(crate::Error::InvalidTag), pull.clone().pull(&two, b""));
+        assert_eq!(Err(crate::Error::InvalidTag), pull.clone().pull(&one, b"ad"));
+        let mut bad = one.clone();
+        bad[0] ^= 1;
+        assert_eq!(Err(crate::Error::InvalidTag), pull.clone().pull(&bad, b""));
+        assert_eq!(Err(crate::Error::BufferTooSmall), pull.clone().pull(&one[..16], b""));
+
+        // Nothing follows a final message.
+        let mut pull = pull;
+        assert!(pull.pull(&one, b"").is_ok());
+        assert!(pull.pull(&two, b"").is_ok());
+        assert_eq!(Err(crate::Error::InvalidTag), pull.pull(&one, b""));
+    }
+
+    #[test]
+    fn poisoned_receiver() {
+        let (mut push, mut pull) = messages();
+        let mut bad = push.push(b"one", b"", MessageTag::Message);
+        let two = push.push(b"two", b"", MessageTag::Message);
+        bad[0] ^= 1;
+
+        assert_eq!(Err(crate::Error::InvalidTag), pull.pull(&bad, b""));
+        assert_eq!(Err(crate::Error::Poisoned), pull.pull(&two, b""));
+        pull.rekey();
+        assert_eq!(Err(crate::Error::Poisoned), pull.pull(&two, b""));
+
+        let mut ciphertext = Vec::new();
+        let mut keyed = pull.keyed;
+        let err = keyed.seal_stream(&b"data"[..], &mut ciphertext, 64).expect_err("sealed");
+        assert!(matches!(err, Error::Poisoned), "unexpected error: {:?}", err);
+        let err = keyed
+            .verify_then_decrypt_stream(Cursor::new(seal(b"data")), &mut Vec::new(), 64)
+            .expect_err("opened");
+        assert!(matches!(err, Error::Poisoned), "unexpected error: {:?}", err);
+    }
+
+    #[test]
+    #[should_panic(expected = "stream already finalized")]
+    fn push_after_final() {
+        let (mut push, _) = messages();
+        push.push(b"", b"", MessageTag::Final);
+        push.push(b"", b"", MessageTag::Message);
+    }
+}
@@ -0,0 +1,141 @@
+//! Time-based one-time passwords in the shape of RFC 6238 (TOTP), keyed with Cyclist.
+//!
+//! [`totp`] maps a key and a Unix timestamp to a short decimal code which changes every `step`
+//! seconds, and [`verify`] checks a code in constant time, accepting codes from a small window of
+//! adjacent time steps to tolerate clock skew:
+//!
+//! ```rust
+//! use cyclist::totp::{totp, verify};
+//! use cyclist::xoodyak::XoodyakKeyed;
+//!
+//! let key = b"a provisioned key";
+//! let code = totp::<XoodyakKeyed>(key, 1_700_000_000, 30, 6);
+//! println!("{:06}", code);
+//!
+//! // The code is still accepted a step later.
+//! let counter = verify::<XoodyakKeyed>(key, code, 1_700_000_030, 30, 6, 1);
+//! assert_eq!(Ok(1_700_000_000 / 30), counter);
+//! ```
+//!
+//! Codes are not compatible with RFC 6238 authenticator apps, which use HMAC. To prevent a code
+//! from being used twice, a verifier should record the counter returned by [`verify`] and reject
+//! codes with counters less than or equal to it.
+//!
+//! # Construction
+//!
+//! A keyed duplex is initialized with the key, and absorbs a domain separation label, the number of
+//! digits as a byte, and the time step counter (i.e. `unix_time / step`) as a little-endian 64-bit
+//! integer. Eight bytes are squeezed and, as a little-endian integer, reduced modulo `10^digits`.
+
+use constant_time_eq::constant_time_eq;
+
+use crate::{Error, Keyed};
+
+/// The smallest supported number of digits.
+pub const MIN_DIGITS: u32 = 6;
+
+/// The largest supported number of digits.
+pub const MAX_DIGITS: u32 = 9;
+
+/// The domain separation label absorbed by every code.
+const TOTP_LABEL: &[u8] = b"cyclist.totp";
+
+/// Returns the `digits`-digit code for the given key at the given Unix time, with a new code every
+/// `step` seconds.
+///
+/// # Panics
+///
+/// Panics if `step` is zero or if `digits` is not between [`MIN_DIGITS`] and [`MAX_DIGITS`].
+pub fn totp<K: Keyed>(key: &[u8], unix_time: u64, step: u64, digits: u32) -> u32 {
+    assert!(step > 0, "step must be > 0");
+    counter_code::<K>(key, unix_time / step, digits)
+}
+
+/// Verifies a `digits`-digit code for the given key at the given Unix time, accepting codes from up
+/// to `skew` steps before or after the current step. Returns the counter of the matching step.
+///
+/// All codes in the window are compared in constant time.
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidTag`] if the code doesn't match any step in the window.
+///
+/// # Panics
+///
+/// Panics if `step` is zero or if `digits` is not between [`MIN_DIGITS`] and [`MAX_DIGITS`].
+pub fn verify<K: Keyed>(
+    key: &[u8],
+    code: u32,
+    unix_time: u64,
+    step: u64,
+    digits: u32,
+    skew: u64,
+) -> Result<u64, Error> {
+    assert!(step > 0, "step must be > 0");
+    let current = unix_time / step;
+
+    // Check every step in the window, rather than stopping at the first match.
+    let mut matched = None;
+    for counter in current.saturating_sub(skew)..=current.saturating_add(skew) {
+        let candidate = counter_code::<K>(key, counter, digits);
+        if constant_time_eq(&candidate.to_le_bytes(), &code.to_le_bytes()) {
+            matched = Some(counter);
+        }
+    }
+    matched.ok_or(Error::InvalidTag)
+}
+
+/// Returns the code for the given counter.
+fn counter_code<K: Keyed>(key: &[u8], counter: u64, digits: u32) -> u32 {
+    assert!((MIN_DIGITS..=MAX_DIGITS).contains(&digits), "invalid number of digits");
+
+    let mut keyed = K::new(key, b"", b"");
+    keyed.absorb(TOTP_LABEL);
+    keyed.absorb(&[u8::try_from(digits).expect("unexpected overflow")]);
+    keyed.absorb(&counter.to_le_bytes());
+
+    let mut out = [0u8; 8];
+    keyed.squeeze_mut(&mut out);
+    let code = u64::from_le_bytes(out) % 10u64.pow(digits);
+    u32::try_from(code).expect("unexpected overflow")
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakKeyed;
+
+    use super::*;
+
+    const KEY: &[u8] = b"a provisioned key";
+
+    #[test]
+    fn codes() {
+        let code = totp::<XoodyakKeyed>(KEY, 1_000, 30, 6);
+        assert!(code < 1_000_000);
+        assert_eq!(code, totp::<XoodyakKeyed>(KEY, 1_019, 30, 6));
+        assert_ne!(code, totp::<XoodyakKeyed>(KEY, 1_020, 30, 6));
+        assert_ne!(code, totp::<XoodyakKeyed>(b"another key", 1_000, 30, 6));
+        assert_ne!(code, totp::<XoodyakKeyed>(KEY, 1_000, 30, 8) % 1_000_000);
+        assert!(totp::<XoodyakKeyed>(KEY, 1_000, 30, 9) < 1_000_000_000);
+    }
+
+    #[test]
+    fn skew_windows() {
+        let code = totp::<XoodyakKeyed>(KEY, 3_000, 30, 6);
+        assert_eq!(Ok(100), verify::<XoodyakKeyed>(KEY, code, 3_000, 30, 6, 0));
+        assert_eq!(Ok(100), verify::<XoodyakKeyed>(KEY, code, 2_970, 30, 6, 1));
+        assert_eq!(Ok(100), verify::<XoodyakKeyed>(KEY, code, 3_059, 30, 6, 1));
+        assert_eq!(Err(Error::InvalidTag), verify::<XoodyakKeyed>(KEY, code, 3_060, 30, 6, 1));
+        assert_eq!(Err(Error::InvalidTag), verify::<XoodyakKeyed>(KEY, code, 2_969, 30, 6, 1));
+        assert_eq!(Err(Error::InvalidTag), verify::<XoodyakKeyed>(KEY, code + 1, 3_000, 30, 6, 0));
+
+        let code = totp::<XoodyakKeyed>(KEY, 0, 30, 6);
+        assert_eq!(Ok(0), verify::<XoodyakKeyed>(KEY, code, 0, 30, 6, 5));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid number of digits")]
+    fn too_many_digits() {
+        totp::<XoodyakKeyed>(KEY, 0, 30, 10);
+    }
+}
@@ -0,0 +1,151 @@
+#![cfg(feature = "trace")]
+
+//! Observable traces of duplex calls, for testing.
+//!
+//! With the `trace` feature enabled, every Cyclist object records the sequence of `Up` and `Down`
+//! calls it makes, along with their domain separators and the length of any absorbed block, so that
+//! tests can assert that a mode-level operation makes exactly the calls the Xoodyak specification
+//! describes. No data is recorded, but recording allocates on every call, so this feature is only
+//! intended for tests:
+//!
+//! ```rust
+//! use cyclist::trace::Call;
+//! use cyclist::xoodyak::XoodyakKeyed;
+//! use cyclist::Cyclist;
+//!
+//! let mut keyed = XoodyakKeyed::new(b"key", b"", b"");
+//! keyed.clear_trace();
+//! keyed.absorb(b"ad");
+//!
+//! // Absorb(X) = AbsorbAny(X, R_absorb, 0x03)
+//! assert_eq!(&[Call::Up { cu: 0x00 }, Call::Down { len: 2, cd: 0x03 }], keyed.trace());
+//! ```
+//!
+//! Calls are recorded with the domain separators they're made with, even in hash mode, where the
+//! specification ignores `Cu` and all but the lowest bit of `Cd`.
+
+use crate::{CyclistHash, CyclistKeyed, Permutation};
+
+/// A call to one of the duplex's two primitive operations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Call {
+    /// `Up(Yi, Cu)`: the state was permuted after adding the given domain separator.
+    Up {
+        /// The domain separator.
+        cu: u8,
+    },
+
+    /// `Down(Xi, Cd)`: a block of the given length was absorbed with the given domain separator.
+    Down {
+        /// The length of the absorbed block.
+        len: usize,
+        /// The domain separator.
+        cd: u8,
+    },
+}
+
+impl<P, const WIDTH: usize, const HASH_RATE: usize> CyclistHash<P, WIDTH, HASH_RATE>
+where
+    P: Permutation<WIDTH>,
+{
+    /// Returns the duplex calls made since the object was created or its trace was cleared.
+    pub fn trace(&self) -> &[Call] {
+        &self.core.trace
+    }
+
+    /// Clears the object's trace.
+    pub fn clear_trace(&mut self) {
+        self.core.trace.clear();
+    }
+}
+
+impl<
+        P,
+        const WIDTH: usize,
+        const ABSORB_RATE: usize,
+        const SQUEEZE_RATE: usize,
+        const RATCHET_RATE: usize,
+        const TAG_LEN: usize,
+    > CyclistKeyed<P, WIDTH, ABSORB_RATE, SQUEEZE_RATE, RATCHET_RATE, TAG_LEN>
+where
+    P: Permutation<WIDTH>,
+{
+    /// Returns the duplex calls made since the object was created or its trace was cleared.
+    pub fn trace(&self) -> &[Call] {
+        &self.core.trace
+    }
+
+    /// Clears the object's trace.
+    pub fn clear_trace(&mut self) {
+        self.core.trace.clear();
+    }
+}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::{XoodyakHash, XoodyakKeyed};
+    use crate::Cyclist;
+
+    use super::*;
+
+    const fn up(cu: u8) -> Call {
+        Call::Up { cu }
+    }
+
+    const fn down(len: usize, cd: u8) -> Call {
+        Call::Down { len, cd }
+    }
+
+    #[test]
+    fn hash() {
+        let mut hash = XoodyakHash::default();
+        hash.absorb(&[0u8; 20]);
+        hash.squeeze(40);
+
+        // Absorb(X) = AbsorbAny(X, R_hash, 0x03), Squeeze(l) = SqueezeAny(l, 0x40)
+        assert_eq!(
+            &[
+                down(16, 0x03),
+                up(0x00),
+                down(4, 0x00),
+                up(0x40),
+                down(0, 0x00),
+                up(0x00),
+                down(0, 0x00),
+                up(0x00),
+            ],
+            hash.trace()
+        );
+    }
+
+    #[test]
+    fn keyed_initialization() {
+        // AbsorbAny(K || ID || |ID|, R_absorb, 0x02), AbsorbAny(counter, 1, 0x00)
+        let keyed = XoodyakKeyed::new(b"key", b"id", b"ab");
+        assert_eq!(
+            &[down(6, 0x02), up(0x00), down(1, 0x00), up(0x00), down(1, 0x00)],
+            keyed.trace()
+        );
+    }
+
+    #[test]
+    fn keyed_operations() {
+        let mut keyed = XoodyakKeyed::new(b"key", b"", b"");
+        keyed.clear_trace();
+
+        // Encrypt(P) = Crypt(P, false), followed by Squeeze(TAG_LEN)
+        keyed.seal(b"msg");
+        assert_eq!(&[up(0x80), down(3, 0x00), up(0x40)], keyed.trace());
+        keyed.clear_trace();
+
+        // Ratchet() = AbsorbAny(SqueezeAny(l_ratchet, 0x10), R_absorb, 0x00)
+        keyed.ratchet();
+        assert_eq!(&[up(0x10), down(16, 0x00)], keyed.trace());
+        keyed.clear_trace();
+
+        // SqueezeKey(l) = SqueezeAny(l, 0x20)
+        keyed.absorb(b"");
+        keyed.squeeze_key(16);
+        assert_eq!(&[up(0x00), down(0, 0x03), up(0x20)], keyed.trace());
+    }
+}
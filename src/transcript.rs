@@ -0,0 +1,236 @@
+//! Merlin-style transcripts for public-coin protocols.
+//!
+//! A [`Transcript`] applies the Fiat-Shamir transform to an interactive proof: the prover and the
+//! verifier each append the protocol's messages to their own transcript, and derive the verifier's
+//! challenges from it. This mirrors the API of [Merlin](https://merlin.cool), so Schnorr- or
+//! Bulletproof-style protocols can be ported to any hash-mode Cyclist scheme:
+//!
+//! ```rust
+//! use cyclist::transcript::Transcript;
+//! use cyclist::xoodyak::XoodyakHash;
+//!
+//! let mut prover = Transcript::<XoodyakHash>::new(b"my proof system");
+//! prover.append_message(b"commitment", b"a commitment");
+//! let mut challenge = [0u8; 32];
+//! prover.challenge_bytes(b"challenge", &mut challenge);
+//!
+//! let mut verifier = Transcript::<XoodyakHash>::new(b"my proof system");
+//! verifier.append_message(b"commitment", b"a commitment");
+//! let mut challenge_p = [0u8; 32];
+//! verifier.challenge_bytes(b"challenge", &mut challenge_p);
+//!
+//! assert_eq!(challenge, challenge_p);
+//! ```
+//!
+//! # Witness-Based Randomness
+//!
+//! With the `rand_core` feature, a prover can generate its secret nonces with
+//! [`Transcript::build_rng`]. As with Merlin, the resulting [`TranscriptRng`] depends on the
+//! transcript, the prover's witness, and an external RNG, so the nonces remain secret if any one of
+//! the witness or the external RNG is sound, and are never reused across different transcripts.
+//!
+//! # Construction
+//!
+//! A transcript is a Cyclist object which absorbs a domain separation label and the transcript's
+//! label. Appending a message absorbs its label and then the message. Generating a challenge absorbs
+//! its label and its length as a little-endian 64-bit integer, then squeezes the challenge. Each
+//! absorb operation is framed by the Cyclist mode, so labels and messages can't be confused for one
+//! another.
+//!
+//! Building an RNG clones the transcript, absorbs a distinct label followed by each witness's label
+//! and value, and finally 32 bytes from the external RNG. The RNG's output is then squeezed from the
+//! clone.
+
+#[cfg(feature = "rand_core")]
+use rand_core::{CryptoRng, RngCore};
+
+use crate::Cyclist;
+
+/// The domain separation label absorbed by every transcript.
+const TRANSCRIPT_LABEL: &[u8] = b"cyclist.transcript";
+
+/// The domain separation label absorbed when building an RNG.
+#[cfg(feature = "rand_core")]
+const RNG_LABEL: &[u8] = b"cyclist.transcript.rng";
+
+/// A transcript of a public-coin protocol.
+#[derive(Clone, Debug)]
+pub struct Transcript<H> {
+    hash: H,
+}
+
+impl<H> Transcript<H>
+where
+    H: Cyclist + Clone + Default,
+{
+    /// Creates a new [`Transcript`] with the given label, which should uniquely identify the
+    /// protocol.
+    pub fn new(label: &[u8]) -> Self {
+        let mut hash = H::default();
+        hash.absorb(TRANSCRIPT_LABEL);
+        hash.absorb(label);
+        Transcript { hash }
+    }
+
+    /// Appends a message with the given label to the transcript.
+    pub fn append_message(&mut self, label: &[u8], message: &[u8]) {
+        self.hash.absorb(label);
+        self.hash.absorb(message);
+    }
+
+    /// Appends a 64-bit integer with the given label to the transcript, encoded in little-endian
+    /// order.
+    pub fn append_u64(&mut self, label: &[u8], x: u64) {
+        self.append_message(label, &x.to_le_bytes());
+    }
+
+    /// Fills `dest` with a challenge with the given label, which depends on every message appended
+    /// so far.
+    pub fn challenge_bytes(&mut self, label: &[u8], dest: &mut [u8]) {
+        self.hash.absorb(label);
+        self.hash.absorb(&u64::try_from(dest.len()).expect("unexpected overflow").to_le_bytes());
+        self.hash.squeeze_mut(dest);
+    }
+
+    /// Returns a [`TranscriptRngBuilder`] for generating the prover's secret randomness.
+    #[cfg(feature = "rand_core")]
+    pub fn build_rng(&self) -> TranscriptRngBuilder<H> {
+        let mut hash = self.hash.clone();
+        hash.absorb(RNG_LABEL);
+        TranscriptRngBuilder { hash }
+    }
+}
+
+/// Builds a [`TranscriptRng`] from a transcript, the prover's witnesses, and an external RNG.
+#[cfg(feature = "rand_core")]
+#[derive(Clone, Debug)]
+pub struct TranscriptRngBuilder<H> {
+    hash: H,
+}
+
+#[cfg(feature = "rand_core")]
+impl<H> TranscriptRngBuilder<H>
+where
+    H: Cyclist,
+{
+    /// Rekeys the RNG with a secret witness with the given label.
+    #[must_use]
+    pub fn rekey_with_witness_bytes(mut self, label: &[u8], witness: &[u8]) -> Self {
+        self.hash.absorb(label);
+        self.hash.absorb(witness);
+        self
+    }
+
+    /// Rekeys the RNG with 32 bytes from the given external RNG and returns it.
+    pub fn finalize<R>(mut self, rng: &mut R) -> TranscriptRng<H>
+    where
+        R: RngCore + CryptoRng,
+    {
+        let mut entropy = [0u8; 32];
+        rng.fill_bytes(&mut entropy);
+        self.hash.absorb(&entropy);
+        crate::wipe(&mut entropy);
+        TranscriptRng { hash: self.hash }
+    }
+}
+
+/// An RNG whose output depends on a transcript, the prover's witnesses, and an external RNG.
+#[cfg(feature = "rand_core")]
+#[derive(Clone, Debug)]
+pub struct TranscriptRng<H> {
+    hash: H,
+}
+
+#[cfg(feature = "rand_core")]
+impl<H> RngCore for TranscriptRng<H>
+where
+    H: Cyclist,
+{
+    fn next_u32(&mut self) -> u32 {
+        let mut b = [0u8; 4];
+        self.fill_bytes(&mut b);
+        u32::from_le_bytes(b)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut b = [0u8; 8];
+        self.fill_bytes(&mut b);
+        u64::from_le_bytes(b)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.hash.squeeze_mut(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rand_core")]
+impl<H> CryptoRng for TranscriptRng<H> where H: Cyclist {}
+
+#[cfg(all(test, feature = "xoodyak"))]
+mod tests {
+    use crate::xoodyak::XoodyakHash;
+
+    use super::*;
+
+    fn challenge(t: &mut Transcript<XoodyakHash>) -> [u8; 16] {
+        let mut c = [0u8; 16];
+        t.challenge_bytes(b"challenge", &mut c);
+        c
+    }
+
+    #[test]
+    fn challenges() {
+        let mut a = Transcript::<XoodyakHash>::new(b"test");
+        let mut b = Transcript::<XoodyakHash>::new(b"test");
+        a.append_message(b"msg", b"hello");
+        b.append_message(b"msg", b"hello");
+        assert_eq!(challenge(&mut a), challenge(&mut b));
+
+        // Challenges change the transcript.
+        assert_ne!(challenge(&mut a.clone()), challenge(&mut Transcript::new(b"test")));
+        let first = challenge(&mut a);
+        b.challenge_bytes(b"challenge", &mut [0u8; 16]);
+        assert_ne!(first, challenge(&mut b));
+    }
+
+    #[test]
+    fn framing() {
+        let mut a = Transcript::<XoodyakHash>::new(b"test");
+        let mut b = Transcript::<XoodyakHash>::new(b"test");
+        a.append_message(b"label", b"message");
+        b.append_message(b"labelmes", b"sage");
+        assert_ne!(challenge(&mut a), challenge(&mut b));
+
+        let mut c = Transcript::<XoodyakHash>::new(b"test");
+        let mut d = Transcript::<XoodyakHash>::new(b"test");
+        c.append_u64(b"x", 1);
+        d.append_message(b"x", &1u64.to_be_bytes());
+        assert_ne!(challenge(&mut c), challenge(&mut d));
+    }
+
+    #[test]
+    #[cfg(feature = "rand_core")]
+    fn witness_rngs() {
+        use crate::rng::CyclistRng;
+        use crate::xoodyak::XoodyakKeyed;
+
+        let t = Transcript::<XoodyakHash>::new(b"test");
+        let rng = |t: &Transcript<XoodyakHash>, witness: &[u8], seed: &[u8]| {
+            t.build_rng()
+                .rekey_with_witness_bytes(b"witness", witness)
+                .finalize(&mut CyclistRng::<XoodyakKeyed>::new(seed))
+                .next_u64()
+        };
+
+        let out = rng(&t, b"secret", b"seed");
+        assert_eq!(out, rng(&t, b"secret", b"seed"));
+        assert_ne!(out, rng(&t, b"another secret", b"seed"));
+        assert_ne!(out, rng(&t, b"secret", b"another seed"));
+        assert_ne!(out, rng(&Transcript::new(b"another test"), b"secret", b"seed"));
+    }
+}
@@ -3,13 +3,39 @@
 //! Xoodyak, the official Cyclist selection.
 //!
 //! Uses the [`Xoodoo`] permutation to provide ~128-bit security.
+//!
+//! # XKCP Compatibility
+//!
+//! [`XoodyakHash`] and [`XoodyakKeyed`] are bit-for-bit compatible with XKCP's Xoodyak:
+//!
+//! | XKCP                                              | This crate                                   |
+//! |---------------------------------------------------|----------------------------------------------|
+//! | `Xoodyak_Initialize(ε, ε, ε)`                     | [`XoodyakHash::default`]                     |
+//! | `Xoodyak_Initialize(ε, ε, ε)`, `Xoodyak_Absorb(C)` | [`XoodyakHash::with_customization`]          |
+//! | `Xoodyak_Initialize(K, ID, counter)`              | [`XoodyakKeyed::new`]                        |
+//!
+//! With an empty key, XKCP ignores the ID and counter and initializes a plain hash, so there is no
+//! hash-mode variant with a non-empty ID or counter to reproduce.
+//!
+//! # Plane-Oriented Xoodoo
+//!
+//! With the `xoodoo-planes` feature enabled, [`Xoodoo`] uses a plane-oriented implementation in
+//! which each of the state's three 128-bit planes is permuted with whole-plane operations. This is
+//! portable code: the crate forbids `unsafe`, so it uses no intrinsics, and whether the plane
+//! operations become 128-bit vector instructions (e.g. NEON on `aarch64`, or SSE2 on `x86_64`) is
+//! up to the compiler. Compare the `permutation/Xoodoo` benchmark with and without the feature
+//! before enabling it. The feature has no effect with the `force-scalar` feature.
 
-use crate::macros::{bytes_to_lanes, lanes_to_bytes};
+use crate::fixed::FixedHash;
+use crate::precomputed::PrecomputedKey;
 use crate::{CyclistHash, CyclistKeyed, Permutation};
 
 /// Xoodyak in hash mode.
 pub type XoodyakHash = CyclistHash<Xoodoo, { 384 / 8 }, { (384 - 256) / 8 }>;
 
+/// Xoodyak in hash mode with a 256-bit digest.
+pub type Xoodyak256 = FixedHash<Xoodoo, { 384 / 8 }, { (384 - 256) / 8 }, 32>;
+
 /// Xoodyak in keyed mode.
 pub type XoodyakKeyed = CyclistKeyed<
     Xoodoo,
@@ -20,36 +46,152 @@ pub type XoodyakKeyed = CyclistKeyed<
     16,
 >;
 
+/// A key absorbed into [`XoodyakKeyed`] once, for reuse across messages.
+pub type XoodyakKey = PrecomputedKey<
+    Xoodoo,
+    { 384 / 8 },
+    { (384 - 32) / 8 },  // R_absorb=b-W
+    { (384 - 192) / 8 }, // R_squeeze=b-c
+    16,
+    16,
+>;
+
+/// [`XoodyakKeyed`] as an [`aead`](::aead) scheme.
+#[cfg(feature = "aead")]
+pub type XoodyakAead = crate::aead::CyclistAead<
+    Xoodoo,
+    { 384 / 8 },
+    { (384 - 32) / 8 },  // R_absorb=b-W
+    { (384 - 192) / 8 }, // R_squeeze=b-c
+    16,
+    16,
+>;
+
+// All Xoodyak schemes can be sent and shared between threads.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<XoodyakHash>();
+    assert_send_sync::<Xoodyak256>();
+    assert_send_sync::<XoodyakKeyed>();
+    assert_send_sync::<XoodyakKey>();
+};
+
 /// The standard Xoodoo\[12\] permutation.
-#[derive(Clone, Debug)]
-#[repr(align(4))]
-pub struct Xoodoo([u8; 48]);
+#[derive(Clone, Debug, Default)]
+pub struct Xoodoo([u32; 12]);
 
-impl Default for Xoodoo {
-    fn default() -> Self {
-        Xoodoo([0u8; 48])
+impl Permutation<48> for Xoodoo {
+    const NAME: &'static str = "Xoodoo[12]";
+    const ID: u16 = 0x0001;
+
+    crate::macros::lane_accessors!(u32, 48);
+
+    #[inline(always)]
+    fn permute(&mut self) {
+        xoodoo(&mut self.0);
     }
 }
 
-impl AsRef<[u8; 48]> for Xoodoo {
-    fn as_ref(&self) -> &[u8; 48] {
-        &self.0
+/// Applies Xoodoo\[12\] to the given lanes, using the plane-oriented implementation if selected.
+#[inline(always)]
+fn xoodoo(lanes: &mut [u32; 12]) {
+    #[cfg(feature = "xoodoo-planes")]
+    if !cfg!(feature = "force-scalar") {
+        xoodoo_planes(lanes);
+        return;
     }
+    xoodoo_p::xoodoo::<{ xoodoo_p::MAX_ROUNDS }>(lanes);
 }
 
-impl AsMut<[u8; 48]> for Xoodoo {
-    fn as_mut(&mut self) -> &mut [u8; 48] {
-        &mut self.0
+/// The round constants for Xoodoo\[12\].
+#[cfg(feature = "xoodoo-planes")]
+const ROUND_CONSTANTS: [u32; 12] =
+    [0x058, 0x038, 0x3C0, 0x0D0, 0x120, 0x014, 0x060, 0x02C, 0x380, 0x0F0, 0x1A0, 0x012];
+
+/// Applies Xoodoo\[12\] to the given lanes, one 128-bit plane at a time.
+#[cfg(feature = "xoodoo-planes")]
+#[inline(always)]
+fn xoodoo_planes(lanes: &mut [u32; 12]) {
+    let mut a = [Plane::default(); 3];
+    for (plane, chunk) in a.iter_mut().zip(lanes.chunks_exact(4)) {
+        plane.0.copy_from_slice(chunk);
+    }
+
+    for &rc in &ROUND_CONSTANTS {
+        // θ
+        let p = a[0] ^ a[1] ^ a[2];
+        let e = p.shift(1).rotate_left(5) ^ p.shift(1).rotate_left(14);
+        a[0] = a[0] ^ e;
+        a[1] = a[1] ^ e;
+        a[2] = a[2] ^ e;
+
+        // ρ-west
+        a[1] = a[1].shift(1);
+        a[2] = a[2].rotate_left(11);
+
+        // ι
+        a[0].0[0] ^= rc;
+
+        // χ
+        let b0 = a[2].and_not(a[1]);
+        let b1 = a[0].and_not(a[2]);
+        let b2 = a[1].and_not(a[0]);
+        a[0] = a[0] ^ b0;
+        a[1] = a[1] ^ b1;
+        a[2] = a[2] ^ b2;
+
+        // ρ-east
+        a[1] = a[1].rotate_left(1);
+        a[2] = a[2].shift(2).rotate_left(8);
+    }
+
+    for (plane, chunk) in a.iter().zip(lanes.chunks_exact_mut(4)) {
+        chunk.copy_from_slice(&plane.0);
     }
 }
 
-impl Permutation<48> for Xoodoo {
+/// One of the Xoodoo state's three planes of four 32-bit lanes.
+#[cfg(feature = "xoodoo-planes")]
+#[derive(Clone, Copy, Debug, Default)]
+struct Plane([u32; 4]);
+
+#[cfg(feature = "xoodoo-planes")]
+impl Plane {
+    /// Cyclically shifts the plane's lanes by `t` positions along the x axis.
     #[inline(always)]
-    fn permute(&mut self) {
-        let mut lanes = [0u32; 12];
-        bytes_to_lanes!(u32, self.0, lanes);
-        xoodoo_p::xoodoo::<{ xoodoo_p::MAX_ROUNDS }>(&mut lanes);
-        lanes_to_bytes!(u32, lanes, self.0);
+    const fn shift(self, t: usize) -> Plane {
+        let a = self.0;
+        Plane([a[(4 - t) % 4], a[(5 - t) % 4], a[(6 - t) % 4], a[(7 - t) % 4]])
+    }
+
+    /// Rotates each of the plane's lanes left by `v` bits.
+    #[inline(always)]
+    fn rotate_left(self, v: u32) -> Plane {
+        Plane(self.0.map(|x| x.rotate_left(v)))
+    }
+
+    /// Returns `self & !other`.
+    #[inline(always)]
+    fn and_not(self, other: Plane) -> Plane {
+        let mut out = self.0;
+        for (a, b) in out.iter_mut().zip(other.0) {
+            *a &= !b;
+        }
+        Plane(out)
+    }
+}
+
+#[cfg(feature = "xoodoo-planes")]
+impl core::ops::BitXor for Plane {
+    type Output = Plane;
+
+    #[inline(always)]
+    fn bitxor(self, rhs: Plane) -> Plane {
+        let mut out = self.0;
+        for (a, b) in out.iter_mut().zip(rhs.0) {
+            *a ^= b;
+        }
+        Plane(out)
     }
 }
 
@@ -85,7 +227,23 @@ mod tests {
         let mut x = XoodyakKeyed::new(&key, &key_id, b"");
         x.absorb(&ad);
         let plaintext_p = x.open(&ciphertext);
-        assert_eq!(Some(plaintext.to_vec()), plaintext_p);
+        assert_eq!(Ok(plaintext.to_vec()), plaintext_p);
+    }
+
+    #[test]
+    #[cfg(feature = "xoodoo-planes")]
+    fn plane_oriented_xoodoo() {
+        let mut lanes = [0u32; 12];
+        for (i, lane) in lanes.iter_mut().enumerate() {
+            *lane = u32::try_from(i).expect("should fit").wrapping_mul(0x9E37_79B9);
+        }
+
+        for _ in 0..4 {
+            let mut expected = lanes;
+            xoodoo_p::xoodoo::<{ xoodoo_p::MAX_ROUNDS }>(&mut expected);
+            xoodoo_planes(&mut lanes);
+            assert_eq!(expected, lanes);
+        }
     }
 
     #[test]
@@ -105,6 +263,22 @@ mod tests {
         assert_eq!(&digest, digest_p.as_slice());
     }
 
+    #[test]
+    fn supercop_hash_test_vector_customization() {
+        // The same vector, treating the message as a customization string. With an empty key,
+        // XKCP's Xoodyak_Initialize ignores the ID and counter, so this is the only hash-mode
+        // customization knob.
+        let mut x = XoodyakHash::with_customization(&[0x11, 0x97, 0x13, 0xCC, 0x83, 0xEE, 0xEF]);
+        assert_eq!(
+            vec![
+                0x99, 0x9d, 0x58, 0x65, 0xb0, 0xdd, 0x9f, 0xa3, 0x09, 0x73, 0x36, 0x5f, 0xec, 0xf0,
+                0x41, 0x77, 0x8d, 0x04, 0x49, 0xa1, 0xb0, 0xc5, 0x5b, 0x74, 0x36, 0x60, 0x83, 0x1a,
+                0x7d, 0x50, 0x25, 0xee,
+            ],
+            x.squeeze(32)
+        );
+    }
+
     #[test]
     fn rust_xoodyak_interop() {
         // from https://github.com/jedisct1/rust-xoodyak/blob/868bb8892df7b9e5fa93fe30320ca81ed5bb4556/src/test.rs#L146-L165
@@ -127,6 +301,6 @@ mod tests {
         let mut d = XoodyakKeyed::new(b"ok then", b"", b"");
         let p = d.open(&c);
 
-        assert_eq!(Some(m), p);
+        assert_eq!(Ok(m), p);
     }
 }
@@ -0,0 +1,133 @@
+//! WebAssembly bindings for Xoodyak, built on the [cyclist] crate.
+//!
+//! This crate exports two JavaScript classes via [wasm-bindgen](https://rustwasm.github.io/wasm-bindgen/),
+//! `XoodyakHash` and `XoodyakAead`, whose methods take and return `Uint8Array`s:
+//!
+//! ```js
+//! import { XoodyakHash, XoodyakAead } from "cyclist-wasm";
+//!
+//! const hash = new XoodyakHash();
+//! hash.absorb(message);
+//! const digest = hash.squeeze(32);
+//!
+//! const aead = new XoodyakAead(key, nonce);
+//! aead.absorb(ad);
+//! const ciphertext = aead.seal(plaintext);
+//! ```
+//!
+//! Build it with `wasm-pack build wasm`. Failures (e.g. an invalid key or tag) are thrown as
+//! JavaScript `Error`s.
+//!
+//! The [cyclist] crate itself forbids `unsafe` code, so the glue code generated by wasm-bindgen
+//! lives in this separate crate.
+
+use cyclist::xoodyak::{XoodyakHash, XoodyakKeyed};
+use cyclist::{Cyclist, Error};
+use wasm_bindgen::prelude::*;
+
+/// A Xoodyak hash.
+#[wasm_bindgen(js_name = XoodyakHash)]
+#[derive(Clone, Debug, Default)]
+pub struct Hash(XoodyakHash);
+
+#[wasm_bindgen(js_class = XoodyakHash)]
+impl Hash {
+    /// Creates a new, empty hash.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Hash {
+        Hash::default()
+    }
+
+    /// Absorbs the given data.
+    pub fn absorb(&mut self, data: &[u8]) {
+        self.0.absorb(data);
+    }
+
+    /// Squeezes `n` bytes of output.
+    pub fn squeeze(&mut self, n: usize) -> Vec<u8> {
+        self.0.squeeze(n)
+    }
+}
+
+/// A Xoodyak keyed duplex, used as an AEAD.
+#[wasm_bindgen(js_name = XoodyakAead)]
+#[derive(Clone, Debug)]
+pub struct Aead(XoodyakKeyed);
+
+#[wasm_bindgen(js_class = XoodyakAead)]
+impl Aead {
+    /// Creates a new AEAD with the given key and nonce.
+    ///
+    /// Throws if the key is empty or the key and nonce are too long.
+    #[wasm_bindgen(constructor)]
+    pub fn new(key: &[u8], nonce: &[u8]) -> Result<Aead, JsError> {
+        XoodyakKeyed::try_new(key, nonce, b"").map(Aead).map_err(js_error)
+    }
+
+    /// Absorbs the given associated data.
+    ///
+    /// Throws if the AEAD was poisoned by a failed open.
+    pub fn absorb(&mut self, data: &[u8]) -> Result<(), JsError> {
+        self.0.try_absorb(data).map_err(js_error)
+    }
+
+    /// Encrypts and authenticates the given plaintext, returning the ciphertext and tag.
+    ///
+    /// Throws if the AEAD was poisoned by a failed open.
+    pub fn seal(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, JsError> {
+        let mut c = vec![0u8; XoodyakKeyed::sealed_len(plaintext.len())];
+        c[..plaintext.len()].copy_from_slice(plaintext);
+        self.0.try_seal_mut(&mut c).map_err(js_error)?;
+        Ok(c)
+    }
+
+    /// Decrypts and verifies the given ciphertext and tag, returning the plaintext.
+    ///
+    /// Throws if the ciphertext could not be authenticated, after which the AEAD is poisoned and
+    /// every other method throws as well.
+    pub fn open(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>, JsError> {
+        self.0.open(ciphertext).map_err(js_error)
+    }
+
+    /// Ratchets the state for forward secrecy.
+    ///
+    /// Throws if the AEAD was poisoned by a failed open.
+    pub fn ratchet(&mut self) -> Result<(), JsError> {
+        self.0.try_ratchet().map_err(js_error)
+    }
+}
+
+/// Converts a Cyclist error to a JS error.
+fn js_error(e: Error) -> JsError {
+    JsError::new(&e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Errors are JS values, which can only be constructed on wasm targets, so these tests cover
+    // the successful paths.
+
+    #[test]
+    fn hash_matches_cyclist() {
+        let mut hash = Hash::new();
+        hash.absorb(b"message");
+
+        let mut expected = XoodyakHash::default();
+        expected.absorb(b"message");
+        assert_eq!(expected.squeeze(32), hash.squeeze(32));
+    }
+
+    #[test]
+    fn aead_round_trip() {
+        let mut sender = Aead::new(b"key", b"nonce").expect("invalid key");
+        sender.absorb(b"ad").expect("poisoned");
+        let c = sender.seal(b"message").expect("poisoned");
+        assert_eq!(b"message".len() + 16, c.len());
+
+        let mut receiver = Aead::new(b"key", b"nonce").expect("invalid key");
+        receiver.absorb(b"ad").expect("poisoned");
+        assert_eq!(b"message".to_vec(), receiver.open(&c).expect("invalid tag"));
+    }
+}
@@ -15,15 +15,48 @@ struct XTask {
 enum Command {
     /// Format, build, test, and lint.
     CI,
+
+    /// Generate the C header for the FFI crate with cbindgen.
+    Headers,
+
+    /// Regenerate the pinned Keccyak known-answer test files.
+    Kats,
 }
 
 fn main() -> Result<()> {
-    let _ = XTask::parse();
+    let task = XTask::parse();
 
     let sh = Shell::new()?;
     sh.change_dir(project_root());
 
+    match task.cmd.unwrap_or(Command::CI) {
+        Command::CI => ci(&sh),
+        Command::Headers => headers(&sh),
+        Command::Kats => kats(&sh),
+    }
+}
+
+/// Feature sets which select code paths that `--all-features` doesn't, each of which is built,
+/// tested, and linted on its own. `--all-features` enables `force-scalar`, which disables the
+/// multi-way Keccak and plane-oriented Xoodoo paths.
+const FEATURE_SETS: &[&[&str]] =
+    &[&[], &["--features", "keccak-interleaved"], &["--features", "keccak-multiway,xoodoo-planes"]];
+
+/// The features of a `no_std` build. The tests require `std`, so only the library is built.
+const NO_STD_FEATURES: &str = "xoodyak,keccyak";
+
+fn ci(sh: &Shell) -> Result<()> {
     cmd!(sh, "cargo fmt --check").run()?;
+
+    for &features in FEATURE_SETS {
+        cmd!(sh, "cargo build -p cyclist --all-targets {features...}").run()?;
+        cmd!(sh, "cargo test -p cyclist {features...}").run()?;
+        cmd!(sh, "cargo clippy -p cyclist --tests --benches {features...}").run()?;
+    }
+
+    cmd!(sh, "cargo build -p cyclist --no-default-features --features {NO_STD_FEATURES}").run()?;
+    cmd!(sh, "cargo clippy -p cyclist --no-default-features --features {NO_STD_FEATURES}").run()?;
+
     cmd!(sh, "cargo build --all-targets --all-features").run()?;
     cmd!(sh, "cargo test --all-features").run()?;
     cmd!(sh, "cargo clippy --all-features --tests --benches").run()?;
@@ -31,6 +64,24 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+fn headers(sh: &Shell) -> Result<()> {
+    cmd!(
+        sh,
+        "cbindgen --config ffi/cbindgen.toml --crate cyclist-ffi --output ffi/include/cyclist.h"
+    )
+    .run()?;
+
+    Ok(())
+}
+
+fn kats(sh: &Shell) -> Result<()> {
+    cmd!(sh, "cargo test --lib keccyak::tests::pinned_vectors")
+        .env("CYCLIST_UPDATE_KATS", "1")
+        .run()?;
+
+    Ok(())
+}
+
 fn project_root() -> PathBuf {
     Path::new(
         &env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| env!("CARGO_MANIFEST_DIR").to_owned()),